@@ -0,0 +1,28 @@
+// build.rs
+
+use std::env;
+use std::process::Command;
+
+/// Embeds the current git commit as `GIT_COMMIT` for the `/version` endpoint.
+///
+/// A `GIT_COMMIT` environment variable takes precedence (e.g. in Docker builds
+/// where `.git` is unavailable); otherwise `git rev-parse` is used.
+fn main() {
+    let commit = env::var("GIT_COMMIT")
+        .ok()
+        .filter(|commit| !commit.trim().is_empty())
+        .or_else(|| {
+            Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    println!("cargo:rustc-env=GIT_COMMIT={}", commit);
+    println!("cargo:rerun-if-env-changed=GIT_COMMIT");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+}
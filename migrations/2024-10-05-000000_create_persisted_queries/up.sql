@@ -0,0 +1,6 @@
+CREATE TABLE persisted_queries (
+    hash VARCHAR(64) PRIMARY KEY,
+    query TEXT NOT NULL,
+    operation_name TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
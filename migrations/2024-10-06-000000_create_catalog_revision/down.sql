@@ -0,0 +1,9 @@
+DROP TRIGGER IF EXISTS storage_specs_bump_catalog_revision ON storage_specs;
+DROP TRIGGER IF EXISTS memory_specs_bump_catalog_revision ON memory_specs;
+DROP TRIGGER IF EXISTS gpu_specs_bump_catalog_revision ON gpu_specs;
+DROP TRIGGER IF EXISTS cpu_specs_bump_catalog_revision ON cpu_specs;
+DROP TRIGGER IF EXISTS parts_bump_catalog_revision ON parts;
+DROP TRIGGER IF EXISTS categories_bump_catalog_revision ON categories;
+DROP TRIGGER IF EXISTS manufacturers_bump_catalog_revision ON manufacturers;
+DROP FUNCTION IF EXISTS bump_catalog_revision();
+DROP TABLE IF EXISTS catalog_revision;
@@ -0,0 +1,43 @@
+-- Single-row counter bumped whenever catalog data changes, used to invalidate cached responses
+CREATE TABLE catalog_revision (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    revision BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO catalog_revision DEFAULT VALUES;
+
+CREATE FUNCTION bump_catalog_revision() RETURNS TRIGGER AS $$
+BEGIN
+    UPDATE catalog_revision SET revision = revision + 1, updated_at = NOW();
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER manufacturers_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON manufacturers
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE TRIGGER categories_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON categories
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE TRIGGER parts_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON parts
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE TRIGGER cpu_specs_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON cpu_specs
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE TRIGGER gpu_specs_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON gpu_specs
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE TRIGGER memory_specs_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON memory_specs
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE TRIGGER storage_specs_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON storage_specs
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
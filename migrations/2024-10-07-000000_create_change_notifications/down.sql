@@ -0,0 +1,5 @@
+DROP TRIGGER IF EXISTS configuration_parts_notify_updated ON configuration_parts;
+DROP TRIGGER IF EXISTS configurations_notify_updated ON configurations;
+DROP FUNCTION IF EXISTS notify_configuration_updated();
+DROP TRIGGER IF EXISTS parts_notify_price_changed ON parts;
+DROP FUNCTION IF EXISTS notify_part_price_changed();
@@ -0,0 +1,50 @@
+-- Publish changes over LISTEN/NOTIFY so GraphQL subscriptions on every instance see them
+CREATE FUNCTION notify_part_price_changed() RETURNS TRIGGER AS $$
+BEGIN
+    PERFORM pg_notify(
+        'part_price_changed',
+        json_build_object(
+            'partId', NEW.id,
+            'oldPrice', OLD.price::TEXT,
+            'price', NEW.price::TEXT
+        )::TEXT
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER parts_notify_price_changed
+    AFTER UPDATE OF price ON parts
+    FOR EACH ROW
+    WHEN (OLD.price IS DISTINCT FROM NEW.price)
+    EXECUTE FUNCTION notify_part_price_changed();
+
+CREATE FUNCTION notify_configuration_updated() RETURNS TRIGGER AS $$
+DECLARE
+    changed_id INTEGER;
+    action TEXT := 'UPDATED';
+BEGIN
+    IF TG_TABLE_NAME = 'configurations' THEN
+        changed_id := CASE WHEN TG_OP = 'DELETE' THEN OLD.id ELSE NEW.id END;
+        IF TG_OP = 'DELETE' THEN
+            action := 'DELETED';
+        END IF;
+    ELSE
+        changed_id := CASE WHEN TG_OP = 'DELETE' THEN OLD.configuration_id ELSE NEW.configuration_id END;
+    END IF;
+
+    PERFORM pg_notify(
+        'configuration_updated',
+        json_build_object('configurationId', changed_id, 'action', action)::TEXT
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER configurations_notify_updated
+    AFTER UPDATE OR DELETE ON configurations
+    FOR EACH ROW EXECUTE FUNCTION notify_configuration_updated();
+
+CREATE TRIGGER configuration_parts_notify_updated
+    AFTER INSERT OR UPDATE OR DELETE ON configuration_parts
+    FOR EACH ROW EXECUTE FUNCTION notify_configuration_updated();
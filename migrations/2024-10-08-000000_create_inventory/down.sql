@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS inventory_notify_stock_changed ON inventory;
+DROP FUNCTION IF EXISTS notify_part_stock_changed();
+DROP TRIGGER IF EXISTS inventory_bump_catalog_revision ON inventory;
+DROP TABLE IF EXISTS inventory;
@@ -0,0 +1,46 @@
+-- Stock levels per part; parts without a row are treated as out of stock
+CREATE TABLE inventory (
+    part_id INTEGER PRIMARY KEY REFERENCES parts(id) ON DELETE CASCADE,
+    quantity_on_hand INTEGER NOT NULL DEFAULT 0 CHECK (quantity_on_hand >= 0),
+    quantity_reserved INTEGER NOT NULL DEFAULT 0 CHECK (quantity_reserved >= 0),
+    reorder_threshold INTEGER NOT NULL DEFAULT 0 CHECK (reorder_threshold >= 0),
+    supplier_sku VARCHAR(100),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (quantity_reserved <= quantity_on_hand)
+);
+
+CREATE INDEX idx_inventory_available ON inventory ((quantity_on_hand - quantity_reserved));
+
+-- Stock is part of cached catalog responses
+CREATE TRIGGER inventory_bump_catalog_revision
+    AFTER INSERT OR UPDATE OR DELETE OR TRUNCATE ON inventory
+    FOR EACH STATEMENT EXECUTE FUNCTION bump_catalog_revision();
+
+CREATE FUNCTION notify_part_stock_changed() RETURNS TRIGGER AS $$
+DECLARE
+    old_available INTEGER := 0;
+    new_available INTEGER := 0;
+    changed_id INTEGER;
+BEGIN
+    IF TG_OP <> 'INSERT' THEN
+        old_available := OLD.quantity_on_hand - OLD.quantity_reserved;
+        changed_id := OLD.part_id;
+    END IF;
+    IF TG_OP <> 'DELETE' THEN
+        new_available := NEW.quantity_on_hand - NEW.quantity_reserved;
+        changed_id := NEW.part_id;
+    END IF;
+
+    IF old_available <> new_available THEN
+        PERFORM pg_notify(
+            'part_stock_changed',
+            json_build_object('partId', changed_id, 'availableQuantity', new_available)::TEXT
+        );
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER inventory_notify_stock_changed
+    AFTER INSERT OR UPDATE OR DELETE ON inventory
+    FOR EACH ROW EXECUTE FUNCTION notify_part_stock_changed();
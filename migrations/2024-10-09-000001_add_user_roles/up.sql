@@ -0,0 +1,3 @@
+-- Staff can manage orders of every customer
+ALTER TABLE users
+    ADD COLUMN role VARCHAR(20) NOT NULL DEFAULT 'customer' CHECK (role IN ('customer', 'staff'));
@@ -0,0 +1,22 @@
+-- Requests for a quote on one of the advertised services
+CREATE TABLE quote_requests (
+    id SERIAL PRIMARY KEY,
+    -- Anonymous visitors can ask for a quote too
+    user_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    service_slug VARCHAR(50) NOT NULL,
+    configuration_id INTEGER REFERENCES configurations(id) ON DELETE SET NULL,
+    contact_name VARCHAR(200) NOT NULL,
+    contact_email VARCHAR(320) NOT NULL,
+    contact_phone VARCHAR(50),
+    company VARCHAR(200),
+    requirements TEXT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'new'
+        CHECK (status IN ('new', 'contacted', 'quoted', 'won', 'lost')),
+    assigned_to INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    staff_notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_quote_requests_status ON quote_requests (status);
+CREATE INDEX idx_quote_requests_assigned_to ON quote_requests (assigned_to);
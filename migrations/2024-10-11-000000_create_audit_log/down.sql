@@ -0,0 +1,13 @@
+DROP FUNCTION IF EXISTS revert_audit_entry(INTEGER);
+DROP TRIGGER IF EXISTS storage_specs_audit ON storage_specs;
+DROP TRIGGER IF EXISTS memory_specs_audit ON memory_specs;
+DROP TRIGGER IF EXISTS gpu_specs_audit ON gpu_specs;
+DROP TRIGGER IF EXISTS cpu_specs_audit ON cpu_specs;
+DROP TRIGGER IF EXISTS parts_audit ON parts;
+DROP TRIGGER IF EXISTS categories_audit ON categories;
+DROP TRIGGER IF EXISTS manufacturers_audit ON manufacturers;
+DROP FUNCTION IF EXISTS record_audit_log();
+DROP TABLE IF EXISTS audit_log;
+ALTER TABLE categories DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE manufacturers DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE parts DROP COLUMN IF EXISTS deleted_at;
@@ -0,0 +1,106 @@
+-- Discontinued catalog rows stay referenced by old configurations and orders
+ALTER TABLE parts ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE manufacturers ADD COLUMN deleted_at TIMESTAMPTZ;
+ALTER TABLE categories ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- Every write to catalog tables, with the row before and after the change
+CREATE TABLE audit_log (
+    id SERIAL PRIMARY KEY,
+    -- NULL for changes made outside a signed-in request, e.g. the data import
+    actor_id INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    table_name VARCHAR(63) NOT NULL,
+    -- Every audited table has a single integer key: `id`, or `part_id` for specs
+    row_key INTEGER NOT NULL,
+    action VARCHAR(10) NOT NULL CHECK (action IN ('INSERT', 'UPDATE', 'DELETE')),
+    before JSONB,
+    after JSONB,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_row ON audit_log (table_name, row_key);
+CREATE INDEX idx_audit_log_actor_id ON audit_log (actor_id);
+
+-- The application names the actor with `SET LOCAL app.actor_id`; the key
+-- column is passed as the trigger argument
+CREATE FUNCTION record_audit_log() RETURNS TRIGGER AS $$
+DECLARE
+    before_row JSONB := CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE to_jsonb(OLD) END;
+    after_row JSONB := CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE to_jsonb(NEW) END;
+BEGIN
+    IF TG_OP = 'UPDATE' AND before_row = after_row THEN
+        RETURN NULL;
+    END IF;
+
+    INSERT INTO audit_log (actor_id, table_name, row_key, action, before, after)
+    VALUES (
+        NULLIF(current_setting('app.actor_id', true), '')::INTEGER,
+        TG_TABLE_NAME,
+        (COALESCE(after_row, before_row) ->> TG_ARGV[0])::INTEGER,
+        TG_OP,
+        before_row,
+        after_row
+    );
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER manufacturers_audit
+    AFTER INSERT OR UPDATE OR DELETE ON manufacturers
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('id');
+
+CREATE TRIGGER categories_audit
+    AFTER INSERT OR UPDATE OR DELETE ON categories
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('id');
+
+CREATE TRIGGER parts_audit
+    AFTER INSERT OR UPDATE OR DELETE ON parts
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('id');
+
+CREATE TRIGGER cpu_specs_audit
+    AFTER INSERT OR UPDATE OR DELETE ON cpu_specs
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('part_id');
+
+CREATE TRIGGER gpu_specs_audit
+    AFTER INSERT OR UPDATE OR DELETE ON gpu_specs
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('part_id');
+
+CREATE TRIGGER memory_specs_audit
+    AFTER INSERT OR UPDATE OR DELETE ON memory_specs
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('part_id');
+
+CREATE TRIGGER storage_specs_audit
+    AFTER INSERT OR UPDATE OR DELETE ON storage_specs
+    FOR EACH ROW EXECUTE FUNCTION record_audit_log('part_id');
+
+-- Puts a row back the way an audit entry found it: updates are undone by
+-- restoring the old values and deletes by re-inserting the row
+CREATE FUNCTION revert_audit_entry(entry_id INTEGER) RETURNS VOID AS $$
+DECLARE
+    entry audit_log%ROWTYPE;
+    key_column TEXT;
+    columns TEXT;
+BEGIN
+    SELECT * INTO entry FROM audit_log WHERE id = entry_id;
+    IF NOT FOUND THEN
+        RAISE EXCEPTION 'audit entry % does not exist', entry_id;
+    END IF;
+
+    key_column := CASE WHEN entry.table_name LIKE '%\_specs' THEN 'part_id' ELSE 'id' END;
+    SELECT string_agg(format('%I', key), ', ') INTO columns
+    FROM jsonb_object_keys(entry.before) AS key;
+
+    IF entry.action = 'UPDATE' THEN
+        EXECUTE format(
+            'UPDATE %I SET (%s) = (SELECT %s FROM jsonb_populate_record(NULL::%I, $1)) WHERE %I = $2',
+            entry.table_name, columns, columns, entry.table_name, key_column
+        ) USING entry.before, entry.row_key;
+    ELSIF entry.action = 'DELETE' THEN
+        EXECUTE format(
+            'INSERT INTO %I (%s) SELECT %s FROM jsonb_populate_record(NULL::%I, $1)',
+            entry.table_name, columns, columns, entry.table_name
+        ) USING entry.before;
+    ELSE
+        RAISE EXCEPTION 'audit entry % records an insert and cannot be reverted', entry_id;
+    END IF;
+END;
+$$ LANGUAGE plpgsql;
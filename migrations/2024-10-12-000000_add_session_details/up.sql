@@ -0,0 +1,9 @@
+ALTER TABLE sessions
+    ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    ADD COLUMN last_seen_at TIMESTAMPTZ,
+    ADD COLUMN user_agent VARCHAR(512),
+    ADD COLUMN ip_address VARCHAR(45);
+
+CREATE INDEX sessions_user_id_idx ON sessions ("userId");
+CREATE INDEX sessions_expires_idx ON sessions (expires);
+CREATE INDEX verification_tokens_expires_idx ON verification_tokens (expires);
@@ -0,0 +1,22 @@
+-- Keys let integrators and internal services call the API without a browser
+-- session. They act on behalf of their owner, limited to their scopes.
+CREATE TABLE api_keys (
+    id SERIAL PRIMARY KEY,
+    owner_id INTEGER NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    -- Start of the secret, shown so that keys can be told apart
+    prefix VARCHAR(12) NOT NULL,
+    -- SHA-256 of the secret, which is only shown when the key is issued or rotated
+    secret_hash VARCHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL
+        CHECK (cardinality(scopes) > 0
+            AND scopes <@ ARRAY['catalog:read', 'catalog:write', 'orders:read']),
+    expires_at TIMESTAMPTZ,
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_by INTEGER REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_api_keys_owner_id ON api_keys (owner_id);
@@ -0,0 +1,12 @@
+-- Token buckets of the Postgres rate limit store, shared by every instance.
+-- A bucket is removed once it has refilled, since a missing bucket is full.
+CREATE TABLE rate_limit_buckets (
+    -- Limit and client the bucket belongs to, e.g. 'default:user:42'
+    key TEXT PRIMARY KEY,
+    tokens DOUBLE PRECISION NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    -- When the bucket is full again if no more requests arrive
+    full_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_rate_limit_buckets_full_at ON rate_limit_buckets (full_at);
@@ -0,0 +1 @@
+ALTER TABLE configurations DROP COLUMN IF EXISTS share_slug;
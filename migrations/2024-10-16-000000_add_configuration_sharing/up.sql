@@ -0,0 +1,2 @@
+-- Unguessable slug of a configuration's read-only share link; NULL while not shared
+ALTER TABLE configurations ADD COLUMN share_slug VARCHAR(64) UNIQUE;
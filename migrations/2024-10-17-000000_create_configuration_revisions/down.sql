@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS configuration_revision_parts;
+DROP TABLE IF EXISTS configuration_revisions;
+DROP FUNCTION IF EXISTS reject_revision_update();
@@ -0,0 +1,46 @@
+-- Snapshots of a configuration taken after each change, numbered from 1
+CREATE TABLE configuration_revisions (
+    id SERIAL PRIMARY KEY,
+    configuration_id INTEGER NOT NULL REFERENCES configurations(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    name VARCHAR NOT NULL,
+    description TEXT,
+    -- The revision this one was restored from, if it was made by a restore
+    restored_from INTEGER,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (configuration_id, revision)
+);
+
+CREATE TABLE configuration_revision_parts (
+    revision_id INTEGER NOT NULL REFERENCES configuration_revisions(id) ON DELETE CASCADE,
+    part_id INTEGER NOT NULL REFERENCES parts(id),
+    quantity INTEGER NOT NULL,
+    PRIMARY KEY (revision_id, part_id)
+);
+
+CREATE INDEX idx_configuration_revision_parts_part_id ON configuration_revision_parts (part_id);
+
+-- Revisions are history; they go away only with their configuration
+CREATE FUNCTION reject_revision_update() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Configuration revisions cannot be changed';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER configuration_revisions_immutable
+    BEFORE UPDATE ON configuration_revisions
+    FOR EACH ROW EXECUTE FUNCTION reject_revision_update();
+
+CREATE TRIGGER configuration_revision_parts_immutable
+    BEFORE UPDATE ON configuration_revision_parts
+    FOR EACH ROW EXECUTE FUNCTION reject_revision_update();
+
+-- Existing configurations start their history at their current state
+INSERT INTO configuration_revisions (configuration_id, revision, name, description, created_at)
+SELECT id, 1, name, description, updated_at FROM configurations;
+
+INSERT INTO configuration_revision_parts (revision_id, part_id, quantity)
+SELECT configuration_revisions.id, configuration_parts.part_id, configuration_parts.quantity
+FROM configuration_parts
+JOIN configuration_revisions
+    ON configuration_revisions.configuration_id = configuration_parts.configuration_id;
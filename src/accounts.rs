@@ -0,0 +1,61 @@
+// src/accounts.rs
+
+use crate::diesel_schema::users::{accounts, users};
+use crate::models::auth::LinkedAccount;
+use crate::types::errors::AccountError;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+
+/// Fetches the OAuth accounts linked to a user, ordered by provider.
+pub fn linked_accounts(
+    conn: &mut PgConnection,
+    user_id: i32,
+) -> Result<Vec<LinkedAccount>, AccountError> {
+    Ok(accounts::table
+        .filter(accounts::userId.eq(user_id))
+        .order((accounts::provider, accounts::id))
+        .select(LinkedAccount::as_select())
+        .load(conn)?)
+}
+
+/// Removes the user's accounts for `provider` and returns the remaining ones.
+///
+/// A verified email address counts as a way to sign in, so the last linked
+/// account can only be removed once the user's email is verified.
+pub fn unlink(
+    conn: &mut PgConnection,
+    user_id: i32,
+    provider: &str,
+) -> Result<Vec<LinkedAccount>, AccountError> {
+    conn.transaction(|conn| {
+        // Serialises concurrent unlinks so they cannot remove every method between them
+        let email_verified = users::table
+            .find(user_id)
+            .select(users::emailVerified)
+            .for_update()
+            .first::<Option<DateTime<Utc>>>(conn)?;
+
+        let accounts = linked_accounts(conn, user_id)?;
+        if !accounts.iter().any(|account| account.provider == provider) {
+            return Err(AccountError::NotLinked(provider.to_string()));
+        }
+        let remaining = accounts
+            .into_iter()
+            .filter(|account| account.provider != provider)
+            .collect::<Vec<_>>();
+        if remaining.is_empty() && email_verified.is_none() {
+            return Err(AccountError::LastLoginMethod);
+        }
+
+        diesel::delete(
+            accounts::table
+                .filter(accounts::userId.eq(user_id))
+                .filter(accounts::provider.eq(provider)),
+        )
+        .execute(conn)?;
+        info!("User {} unlinked their {} account", user_id, provider);
+        Ok(remaining)
+    })
+}
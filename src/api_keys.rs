@@ -0,0 +1,224 @@
+// src/api_keys.rs
+
+use crate::diesel_schema::api_keys::api_keys;
+use crate::diesel_schema::users::users;
+use crate::models::api_keys::{ApiKey, ApiKeyScope, NewApiKey};
+use crate::models::auth::User;
+use crate::types::errors::ApiKeyError;
+use crate::verification::{hash_token, new_token};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+
+/// Marks secrets as API keys, so that they are recognizable when leaked.
+pub const SECRET_PREFIX: &str = "pcb_";
+
+/// Characters of the secret stored in the clear to tell keys apart.
+const DISPLAY_PREFIX_LENGTH: usize = 12;
+
+/// Longest name accepted for a key.
+const MAX_NAME_LENGTH: usize = 100;
+
+/// How stale `last_used_at` may get before a request updates it, so that
+/// busy keys do not cause a write on every request.
+const LAST_USED_RESOLUTION_MINUTES: i64 = 5;
+
+/// API key the current request was authenticated with, stored in the request
+/// extensions by the authentication middleware next to its owner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyPrincipal {
+    pub key_id: i32,
+    pub scopes: Vec<ApiKeyScope>,
+}
+
+impl ApiKeyPrincipal {
+    /// Returns `true` if the key grants `scope`.
+    pub fn has_scope(&self, scope: ApiKeyScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+}
+
+impl From<&ApiKey> for ApiKeyPrincipal {
+    fn from(key: &ApiKey) -> Self {
+        ApiKeyPrincipal {
+            key_id: key.id,
+            scopes: key.scopes.clone(),
+        }
+    }
+}
+
+/// Issues a key for `owner_id` and returns it with its secret, which is not
+/// stored and cannot be retrieved later.
+pub fn issue(
+    conn: &mut PgConnection,
+    created_by: i32,
+    owner_id: i32,
+    name: &str,
+    scopes: &[ApiKeyScope],
+    expires_at: Option<DateTime<Utc>>,
+) -> Result<(ApiKey, String), ApiKeyError> {
+    let name = validate_name(name)?;
+    let scopes = validate_scopes(scopes)?;
+    if expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ApiKeyError::Validation(
+            "Expiry must be in the future".to_string(),
+        ));
+    }
+    let owner_exists = diesel::select(diesel::dsl::exists(users::table.find(owner_id)))
+        .get_result::<bool>(conn)?;
+    if !owner_exists {
+        return Err(ApiKeyError::OwnerNotFound(owner_id));
+    }
+
+    let secret = new_secret();
+    let key = diesel::insert_into(api_keys::table)
+        .values(NewApiKey {
+            owner_id,
+            name,
+            prefix: display_prefix(&secret),
+            secret_hash: hash_token(&secret),
+            scopes,
+            expires_at,
+            created_by: Some(created_by),
+        })
+        .returning(ApiKey::as_returning())
+        .get_result(conn)?;
+    info!(
+        "API key {} issued for user {} by user {}",
+        key.id, owner_id, created_by
+    );
+    Ok((key, secret))
+}
+
+/// Replaces the secret of a key, keeping its owner, scopes and expiry, and
+/// returns the key with its new secret. The old secret stops working at once.
+pub fn rotate(conn: &mut PgConnection, key_id: i32) -> Result<(ApiKey, String), ApiKeyError> {
+    let key = find(conn, key_id)?;
+    if key.revoked_at.is_some() {
+        return Err(ApiKeyError::Revoked(key_id));
+    }
+    let secret = new_secret();
+    let key = diesel::update(api_keys::table.find(key_id))
+        .set((
+            api_keys::prefix.eq(display_prefix(&secret)),
+            api_keys::secret_hash.eq(hash_token(&secret)),
+            api_keys::rotated_at.eq(Utc::now()),
+        ))
+        .returning(ApiKey::as_returning())
+        .get_result(conn)?;
+    info!("API key {} rotated", key_id);
+    Ok((key, secret))
+}
+
+/// Revokes a key. Revoking a revoked key leaves it unchanged.
+pub fn revoke(conn: &mut PgConnection, key_id: i32) -> Result<ApiKey, ApiKeyError> {
+    let revoked = diesel::update(api_keys::table.find(key_id))
+        .filter(api_keys::revoked_at.is_null())
+        .set(api_keys::revoked_at.eq(Utc::now()))
+        .execute(conn)?;
+    if revoked > 0 {
+        info!("API key {} revoked", key_id);
+    }
+    find(conn, key_id)
+}
+
+/// Fetches a key by ID.
+pub fn find(conn: &mut PgConnection, key_id: i32) -> Result<ApiKey, ApiKeyError> {
+    api_keys::table
+        .find(key_id)
+        .select(ApiKey::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(ApiKeyError::NotFound(key_id))
+}
+
+/// Fetches keys, newest first, optionally of one owner. Revoked keys are
+/// left out unless `include_revoked` is set.
+pub fn list(
+    conn: &mut PgConnection,
+    owner_id: Option<i32>,
+    include_revoked: bool,
+) -> Result<Vec<ApiKey>, ApiKeyError> {
+    let mut query = api_keys::table.select(ApiKey::as_select()).into_boxed();
+    if let Some(owner_id) = owner_id {
+        query = query.filter(api_keys::owner_id.eq(owner_id));
+    }
+    if !include_revoked {
+        query = query.filter(api_keys::revoked_at.is_null());
+    }
+    Ok(query
+        .order((api_keys::created_at.desc(), api_keys::id.desc()))
+        .load(conn)?)
+}
+
+/// Looks up the unexpired, unrevoked key with `secret` and its owner, and
+/// records that the key was used.
+pub fn authenticate(
+    conn: &mut PgConnection,
+    secret: &str,
+) -> Result<Option<(User, ApiKey)>, ApiKeyError> {
+    let now = Utc::now();
+    let found = api_keys::table
+        .inner_join(users::table)
+        .filter(api_keys::secret_hash.eq(hash_token(secret)))
+        .filter(api_keys::revoked_at.is_null())
+        .filter(
+            api_keys::expires_at
+                .is_null()
+                .or(api_keys::expires_at.gt(now)),
+        )
+        .select((User::as_select(), ApiKey::as_select()))
+        .first::<(User, ApiKey)>(conn)
+        .optional()?;
+    if let Some((_, key)) = &found {
+        let stale = now - ChronoDuration::minutes(LAST_USED_RESOLUTION_MINUTES);
+        diesel::update(api_keys::table.find(key.id))
+            .filter(
+                api_keys::last_used_at
+                    .is_null()
+                    .or(api_keys::last_used_at.lt(stale)),
+            )
+            .set(api_keys::last_used_at.eq(now))
+            .execute(conn)?;
+    }
+    Ok(found)
+}
+
+fn validate_name(name: &str) -> Result<String, ApiKeyError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiKeyError::Validation("Name is required".to_string()));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(ApiKeyError::Validation(format!(
+            "Name must be at most {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Removes duplicate scopes, keeping their order.
+fn validate_scopes(scopes: &[ApiKeyScope]) -> Result<Vec<ApiKeyScope>, ApiKeyError> {
+    let mut unique = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        if !unique.contains(scope) {
+            unique.push(*scope);
+        }
+    }
+    if unique.is_empty() {
+        return Err(ApiKeyError::Validation(
+            "At least one scope is required".to_string(),
+        ));
+    }
+    Ok(unique)
+}
+
+fn new_secret() -> String {
+    format!("{}{}", SECRET_PREFIX, new_token())
+}
+
+fn display_prefix(secret: &str) -> String {
+    secret.chars().take(DISPLAY_PREFIX_LENGTH).collect()
+}
@@ -0,0 +1,245 @@
+// src/catalog.rs
+
+use crate::diesel_schema::audit_log::audit_log;
+use crate::diesel_schema::parts::{categories, manufacturers, parts};
+use crate::models::audit_log::AuditLogEntry;
+use crate::models::parts::category::Category;
+use crate::models::parts::manufacturer::Manufacturer;
+use crate::models::parts::part::Part;
+use crate::types::errors::CatalogError;
+use chrono::{DateTime, Utc};
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::result::Error as DieselError;
+use diesel::sql_types::{Integer, Text};
+use diesel::PgConnection;
+use log::info;
+
+/// Runs `f` in a transaction whose catalog writes are attributed to `actor_id`
+/// in the audit log.
+///
+/// Writes made outside this function are recorded without an actor.
+pub fn with_actor<T, E>(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    f: impl FnOnce(&mut PgConnection) -> Result<T, E>,
+) -> Result<T, E>
+where
+    E: From<DieselError>,
+{
+    conn.transaction(|conn| {
+        // `is_local` keeps the setting from leaking into the pooled connection
+        diesel::sql_query("SELECT set_config('app.actor_id', $1, true)")
+            .bind::<Text, _>(actor_id.to_string())
+            .execute(conn)?;
+        f(conn)
+    })
+}
+
+/// Discontinues a part: it disappears from listings and can no longer be
+/// ordered, but stays referenced by existing configurations and orders.
+pub fn delete_part(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    part_id: i32,
+) -> Result<Part, CatalogError> {
+    let part = with_actor(conn, actor_id, |conn| {
+        let discontinued = diesel::update(
+            parts::table
+                .find(part_id)
+                .filter(parts::deleted_at.is_null()),
+        )
+        .set(parts::deleted_at.eq(now))
+        .returning(Part::as_returning())
+        .get_result(conn)
+        .optional()?;
+        match discontinued {
+            Some(part) => Ok(Some(part)),
+            // Already discontinued, or missing
+            None => parts::table
+                .find(part_id)
+                .select(Part::as_select())
+                .first(conn)
+                .optional(),
+        }
+    })?
+    .ok_or(CatalogError::PartNotFound(part_id))?;
+    info!("Part {} discontinued by user {}", part_id, actor_id);
+    Ok(part)
+}
+
+/// Brings a discontinued part back into listings.
+pub fn restore_part(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    part_id: i32,
+) -> Result<Part, CatalogError> {
+    let part = with_actor(conn, actor_id, |conn| {
+        diesel::update(parts::table.find(part_id))
+            .set(parts::deleted_at.eq(None::<DateTime<Utc>>))
+            .returning(Part::as_returning())
+            .get_result(conn)
+            .optional()
+    })?
+    .ok_or(CatalogError::PartNotFound(part_id))?;
+    info!("Part {} restored by user {}", part_id, actor_id);
+    Ok(part)
+}
+
+/// Discontinues a manufacturer: it disappears from listings and lookups by
+/// ID, but parts keep referring to it.
+pub fn delete_manufacturer(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    manufacturer_id: i32,
+) -> Result<Manufacturer, CatalogError> {
+    let manufacturer = with_actor(conn, actor_id, |conn| {
+        let discontinued = diesel::update(
+            manufacturers::table
+                .find(manufacturer_id)
+                .filter(manufacturers::deleted_at.is_null()),
+        )
+        .set(manufacturers::deleted_at.eq(now))
+        .get_result::<Manufacturer>(conn)
+        .optional()?;
+        match discontinued {
+            Some(manufacturer) => Ok(Some(manufacturer)),
+            // Already discontinued, or missing
+            None => manufacturers::table
+                .find(manufacturer_id)
+                .first::<Manufacturer>(conn)
+                .optional(),
+        }
+    })?
+    .ok_or(CatalogError::ManufacturerNotFound(manufacturer_id))?;
+    info!(
+        "Manufacturer {} discontinued by user {}",
+        manufacturer_id, actor_id
+    );
+    Ok(manufacturer)
+}
+
+/// Brings a discontinued manufacturer back into listings.
+pub fn restore_manufacturer(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    manufacturer_id: i32,
+) -> Result<Manufacturer, CatalogError> {
+    let manufacturer = with_actor(conn, actor_id, |conn| {
+        diesel::update(manufacturers::table.find(manufacturer_id))
+            .set(manufacturers::deleted_at.eq(None::<DateTime<Utc>>))
+            .get_result::<Manufacturer>(conn)
+            .optional()
+    })?
+    .ok_or(CatalogError::ManufacturerNotFound(manufacturer_id))?;
+    info!(
+        "Manufacturer {} restored by user {}",
+        manufacturer_id, actor_id
+    );
+    Ok(manufacturer)
+}
+
+/// Discontinues a category: it disappears from listings and lookups by ID,
+/// but parts keep referring to it.
+pub fn delete_category(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    category_id: i32,
+) -> Result<Category, CatalogError> {
+    let category = with_actor(conn, actor_id, |conn| {
+        let discontinued = diesel::update(
+            categories::table
+                .find(category_id)
+                .filter(categories::deleted_at.is_null()),
+        )
+        .set(categories::deleted_at.eq(now))
+        .get_result::<Category>(conn)
+        .optional()?;
+        match discontinued {
+            Some(category) => Ok(Some(category)),
+            // Already discontinued, or missing
+            None => categories::table
+                .find(category_id)
+                .first::<Category>(conn)
+                .optional(),
+        }
+    })?
+    .ok_or(CatalogError::CategoryNotFound(category_id))?;
+    info!("Category {} discontinued by user {}", category_id, actor_id);
+    Ok(category)
+}
+
+/// Brings a discontinued category back into listings.
+pub fn restore_category(
+    conn: &mut PgConnection,
+    actor_id: i32,
+    category_id: i32,
+) -> Result<Category, CatalogError> {
+    let category = with_actor(conn, actor_id, |conn| {
+        diesel::update(categories::table.find(category_id))
+            .set(categories::deleted_at.eq(None::<DateTime<Utc>>))
+            .get_result::<Category>(conn)
+            .optional()
+    })?
+    .ok_or(CatalogError::CategoryNotFound(category_id))?;
+    info!("Category {} restored by user {}", category_id, actor_id);
+    Ok(category)
+}
+
+/// Undoes the change recorded by an audit log entry: updates are reverted to
+/// the previous values and deleted rows are re-inserted. The revert is itself
+/// recorded in the audit log.
+pub fn revert(conn: &mut PgConnection, actor_id: i32, entry_id: i32) -> Result<(), CatalogError> {
+    with_actor(conn, actor_id, |conn| {
+        let entry = audit_log::table
+            .find(entry_id)
+            .select(AuditLogEntry::as_select())
+            .first(conn)
+            .optional()?
+            .ok_or(CatalogError::AuditEntryNotFound(entry_id))?;
+        if entry.action == "INSERT" {
+            return Err(CatalogError::IrreversibleAuditEntry(entry_id));
+        }
+
+        diesel::sql_query("SELECT revert_audit_entry($1)")
+            .bind::<Integer, _>(entry_id)
+            .execute(conn)?;
+        info!(
+            "Audit log entry {} ({} of {} {}) reverted by user {}",
+            entry_id, entry.action, entry.table_name, entry.row_key, actor_id
+        );
+        Ok(())
+    })
+}
+
+/// Fetches audit log entries, newest first, optionally only those for one
+/// table, one row of it or one actor.
+pub fn audit_entries(
+    conn: &mut PgConnection,
+    table_name: Option<&str>,
+    row_key: Option<i32>,
+    actor_id: Option<i32>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<AuditLogEntry>, CatalogError> {
+    let mut query = audit_log::table
+        .order(audit_log::id.desc())
+        .select(AuditLogEntry::as_select())
+        .into_boxed();
+    if let Some(table_name) = table_name {
+        query = query.filter(audit_log::table_name.eq(table_name));
+    }
+    if let Some(row_key) = row_key {
+        query = query.filter(audit_log::row_key.eq(row_key));
+    }
+    if let Some(actor_id) = actor_id {
+        query = query.filter(audit_log::actor_id.eq(actor_id));
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = offset {
+        query = query.offset(offset);
+    }
+    Ok(query.load(conn)?)
+}
@@ -0,0 +1,292 @@
+// src/comparisons.rs
+
+use crate::configurations::{ConfigurationDetails, ConfiguredPart};
+use crate::diesel_schema::parts::{categories, cpu_specs, gpu_specs, memory_specs, storage_specs};
+use crate::models::configurations::Configuration;
+use crate::types::errors::ConfigurationError;
+use bigdecimal::BigDecimal;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use std::collections::{BTreeMap, HashMap};
+
+/// Two configurations side by side, with the components that differ.
+pub struct Comparison {
+    pub a: Configuration,
+    pub b: Configuration,
+    pub a_totals: SpecTotals,
+    pub b_totals: SpecTotals,
+    /// Differences going from `a` to `b`, grouped by category in order of
+    /// category ID with uncategorized parts last.
+    pub changes: Vec<ComponentChange>,
+}
+
+/// Totals over a configuration's parts, each counted as many times as the
+/// build uses it. The integer totals saturate instead of overflowing, since
+/// quantities are not capped.
+#[derive(Default)]
+pub struct SpecTotals {
+    /// Sum of part prices; parts without a price count as zero.
+    pub price: BigDecimal,
+    /// CPU cores.
+    pub cores: i32,
+    /// GPU memory, in GB.
+    pub vram: BigDecimal,
+    /// Memory capacity, in GB.
+    pub ram_capacity: i32,
+    /// Storage capacity, in GB.
+    pub storage_capacity: i32,
+    /// Power draw of CPUs and GPUs, taken from their TDP, in watts.
+    pub power_draw: i32,
+}
+
+impl SpecTotals {
+    /// Returns `self` minus `other`, field by field.
+    pub fn minus(&self, other: &SpecTotals) -> SpecTotals {
+        SpecTotals {
+            price: &self.price - &other.price,
+            cores: self.cores.saturating_sub(other.cores),
+            vram: &self.vram - &other.vram,
+            ram_capacity: self.ram_capacity.saturating_sub(other.ram_capacity),
+            storage_capacity: self.storage_capacity.saturating_sub(other.storage_capacity),
+            power_draw: self.power_draw.saturating_sub(other.power_draw),
+        }
+    }
+}
+
+/// How a component differs between two configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// Only in the second configuration.
+    Added,
+    /// Only in the first configuration.
+    Removed,
+    /// Swapped for another part of the same category, or used in a different
+    /// quantity.
+    Changed,
+}
+
+/// A component that differs between two configurations.
+pub struct ComponentChange {
+    /// Name of the category the parts belong to, if any.
+    pub category: Option<String>,
+    pub kind: ChangeKind,
+    /// The part in the first configuration; `None` if it was added.
+    pub before: Option<ConfiguredPart>,
+    /// The part in the second configuration; `None` if it was removed.
+    pub after: Option<ConfiguredPart>,
+    /// Price of `after` minus price of `before`, counting quantities.
+    pub price_delta: BigDecimal,
+}
+
+/// The specs of a part that comparisons total up.
+#[derive(Default)]
+struct PartSpecs {
+    cores: Option<i32>,
+    cpu_tdp: Option<i32>,
+    vram: Option<BigDecimal>,
+    gpu_tdp: Option<i32>,
+    ram_capacity: Option<i32>,
+    storage_capacity: Option<i32>,
+}
+
+/// Compares configuration `a` with configuration `b`.
+///
+/// Parts are aligned by category. Within a category, a part in both builds
+/// is reported only if its quantity differs; the remaining parts are paired
+/// up in order of part ID as swapped components, and any left over are
+/// reported as added or removed.
+pub fn compare(
+    conn: &mut PgConnection,
+    a: ConfigurationDetails,
+    b: ConfigurationDetails,
+) -> Result<Comparison, ConfigurationError> {
+    let part_ids = a
+        .parts
+        .iter()
+        .chain(&b.parts)
+        .map(|configured| configured.part.id)
+        .collect::<Vec<_>>();
+    let specs = load_specs(conn, &part_ids)?;
+    let category_ids = a
+        .parts
+        .iter()
+        .chain(&b.parts)
+        .filter_map(|configured| configured.part.category_id)
+        .collect::<Vec<_>>();
+    let category_names = categories::table
+        .filter(categories::id.eq_any(&category_ids))
+        .select((categories::id, categories::name))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+
+    let a_totals = totals(&a.parts, &specs);
+    let b_totals = totals(&b.parts, &specs);
+
+    // Keyed so that uncategorized parts sort last
+    let mut by_category = BTreeMap::<(bool, Option<i32>), (Vec<_>, Vec<_>)>::new();
+    for configured in a.parts {
+        let category_id = configured.part.category_id;
+        by_category
+            .entry((category_id.is_none(), category_id))
+            .or_default()
+            .0
+            .push(configured);
+    }
+    for configured in b.parts {
+        let category_id = configured.part.category_id;
+        by_category
+            .entry((category_id.is_none(), category_id))
+            .or_default()
+            .1
+            .push(configured);
+    }
+
+    let mut changes = Vec::new();
+    for ((_, category_id), (before, after)) in by_category {
+        let category = category_id.and_then(|id| category_names.get(&id).cloned());
+        changes.extend(compare_category(category, before, after));
+    }
+
+    Ok(Comparison {
+        a: a.configuration,
+        b: b.configuration,
+        a_totals,
+        b_totals,
+        changes,
+    })
+}
+
+/// Compares the parts of one category, each list ordered by part ID.
+fn compare_category(
+    category: Option<String>,
+    before: Vec<ConfiguredPart>,
+    mut after: Vec<ConfiguredPart>,
+) -> Vec<ComponentChange> {
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+    for previous in before {
+        match after
+            .iter()
+            .position(|configured| configured.part.id == previous.part.id)
+        {
+            Some(index) => {
+                let current = after.remove(index);
+                if current.quantity != previous.quantity {
+                    changes.push(change(&category, Some(previous), Some(current)));
+                }
+            }
+            None => removed.push(previous),
+        }
+    }
+
+    // What is left of each list is paired up in order, as swapped components
+    let mut added = after.into_iter();
+    for previous in removed {
+        let current = added.next();
+        changes.push(change(&category, Some(previous), current));
+    }
+    changes.extend(added.map(|current| change(&category, None, Some(current))));
+    changes
+}
+
+fn change(
+    category: &Option<String>,
+    before: Option<ConfiguredPart>,
+    after: Option<ConfiguredPart>,
+) -> ComponentChange {
+    let kind = match (&before, &after) {
+        (None, _) => ChangeKind::Added,
+        (_, None) => ChangeKind::Removed,
+        _ => ChangeKind::Changed,
+    };
+    let price_delta = after.as_ref().map(line_price).unwrap_or_default()
+        - before.as_ref().map(line_price).unwrap_or_default();
+    ComponentChange {
+        category: category.clone(),
+        kind,
+        before,
+        after,
+        price_delta,
+    }
+}
+
+/// Price of a part times its quantity, or zero if the part has no price.
+fn line_price(configured: &ConfiguredPart) -> BigDecimal {
+    configured
+        .part
+        .price
+        .as_ref()
+        .map(|price| price * BigDecimal::from(configured.quantity))
+        .unwrap_or_default()
+}
+
+fn totals(parts: &[ConfiguredPart], specs: &HashMap<i32, PartSpecs>) -> SpecTotals {
+    let mut totals = SpecTotals::default();
+    for configured in parts {
+        let quantity = configured.quantity;
+        totals.price += line_price(configured);
+        let Some(spec) = specs.get(&configured.part.id) else {
+            continue;
+        };
+        add_times(&mut totals.cores, spec.cores, quantity);
+        if let Some(vram) = &spec.vram {
+            totals.vram += vram * BigDecimal::from(quantity);
+        }
+        add_times(&mut totals.ram_capacity, spec.ram_capacity, quantity);
+        add_times(
+            &mut totals.storage_capacity,
+            spec.storage_capacity,
+            quantity,
+        );
+        add_times(&mut totals.power_draw, spec.cpu_tdp, quantity);
+        add_times(&mut totals.power_draw, spec.gpu_tdp, quantity);
+    }
+    totals
+}
+
+/// Adds `value` times `quantity` to `total`, saturating on overflow.
+fn add_times(total: &mut i32, value: Option<i32>, quantity: i32) {
+    *total = total.saturating_add(value.unwrap_or(0).saturating_mul(quantity));
+}
+
+/// Loads the compared specs of the parts from the spec tables.
+fn load_specs(
+    conn: &mut PgConnection,
+    part_ids: &[i32],
+) -> Result<HashMap<i32, PartSpecs>, ConfigurationError> {
+    let mut specs = HashMap::<i32, PartSpecs>::new();
+    for (part_id, cores, tdp) in cpu_specs::table
+        .filter(cpu_specs::part_id.eq_any(part_ids))
+        .select((cpu_specs::part_id, cpu_specs::cores, cpu_specs::tdp))
+        .load::<(i32, Option<i32>, Option<i32>)>(conn)?
+    {
+        let spec = specs.entry(part_id).or_default();
+        spec.cores = cores;
+        spec.cpu_tdp = tdp;
+    }
+    for (part_id, vram, tdp) in gpu_specs::table
+        .filter(gpu_specs::part_id.eq_any(part_ids))
+        .select((gpu_specs::part_id, gpu_specs::vram_size, gpu_specs::tdp))
+        .load::<(i32, Option<BigDecimal>, Option<i32>)>(conn)?
+    {
+        let spec = specs.entry(part_id).or_default();
+        spec.vram = vram;
+        spec.gpu_tdp = tdp;
+    }
+    for (part_id, capacity) in memory_specs::table
+        .filter(memory_specs::part_id.eq_any(part_ids))
+        .select((memory_specs::part_id, memory_specs::capacity))
+        .load::<(i32, Option<i32>)>(conn)?
+    {
+        specs.entry(part_id).or_default().ram_capacity = capacity;
+    }
+    for (part_id, capacity) in storage_specs::table
+        .filter(storage_specs::part_id.eq_any(part_ids))
+        .select((storage_specs::part_id, storage_specs::capacity))
+        .load::<(i32, Option<i32>)>(conn)?
+    {
+        specs.entry(part_id).or_default().storage_capacity = capacity;
+    }
+    Ok(specs)
+}
@@ -0,0 +1,616 @@
+// src/configurations.rs
+
+use crate::diesel_schema::configurations::{
+    configuration_revision_parts, configuration_revisions, configurations,
+};
+use crate::diesel_schema::parts::{configuration_parts, parts};
+use crate::models::configurations::{
+    Configuration, ConfigurationRevision, NewConfiguration, RevisionPart,
+};
+use crate::models::parts::part::Part;
+use crate::types::errors::ConfigurationError;
+use crate::verification::new_token;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+use std::collections::BTreeMap;
+
+/// A configuration together with its parts.
+pub struct ConfigurationDetails {
+    pub configuration: Configuration,
+    pub parts: Vec<ConfiguredPart>,
+}
+
+/// A part of a configuration and how many of it the build uses.
+pub struct ConfiguredPart {
+    pub part: Part,
+    pub quantity: i32,
+}
+
+/// A revision of a configuration together with its parts, ordered by part ID.
+pub struct RevisionDetails {
+    pub revision: ConfigurationRevision,
+    pub parts: Vec<ConfiguredPart>,
+}
+
+/// Changes to a configuration; `None` leaves a field unchanged.
+pub struct ConfigurationChanges {
+    pub name: Option<String>,
+    /// `Some(None)` clears the description.
+    pub description: Option<Option<String>>,
+    /// The new parts list, as part IDs and quantities.
+    pub parts: Option<Vec<(i32, i32)>>,
+}
+
+/// What changed between two revisions of a configuration.
+pub struct RevisionDiff {
+    pub from: i32,
+    pub to: i32,
+    /// The names before and after, if the name changed.
+    pub name: Option<(String, String)>,
+    /// The descriptions before and after, if the description changed.
+    pub description: Option<(Option<String>, Option<String>)>,
+    /// Parts only in the later revision, ordered by part ID.
+    pub added: Vec<ConfiguredPart>,
+    /// Parts only in the earlier revision, ordered by part ID.
+    pub removed: Vec<ConfiguredPart>,
+    /// Parts in both revisions with different quantities, ordered by part ID.
+    pub changed: Vec<QuantityChange>,
+}
+
+/// A part whose quantity differs between two revisions.
+pub struct QuantityChange {
+    pub part: Part,
+    pub from: i32,
+    pub to: i32,
+}
+
+/// The state of a configuration that revisions capture.
+#[derive(PartialEq)]
+struct Snapshot {
+    name: String,
+    description: Option<String>,
+    /// Part IDs and quantities, ordered by part ID.
+    parts: Vec<(i32, i32)>,
+}
+
+/// Creates a configuration with `parts`, given as part IDs and quantities.
+pub fn create(
+    conn: &mut PgConnection,
+    new_configuration: NewConfiguration,
+    parts: &[(i32, i32)],
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    let configuration = conn.transaction(|conn| {
+        let configuration = diesel::insert_into(configurations::table)
+            .values(&new_configuration)
+            .returning(Configuration::as_returning())
+            .get_result(conn)?;
+        let rows = parts
+            .iter()
+            .map(|&(part_id, quantity)| {
+                (
+                    configuration_parts::configuration_id.eq(configuration.id),
+                    configuration_parts::part_id.eq(part_id),
+                    configuration_parts::quantity.eq(quantity),
+                )
+            })
+            .collect::<Vec<_>>();
+        diesel::insert_into(configuration_parts::table)
+            .values(&rows)
+            .execute(conn)?;
+        record_revision(conn, configuration.id, None)?;
+        Ok::<_, ConfigurationError>(configuration)
+    })?;
+    info!(
+        "Created configuration {} with {} parts",
+        configuration.id,
+        parts.len()
+    );
+    find(conn, configuration.id)
+}
+
+/// Fetches a configuration and its parts, ordered by part ID.
+pub fn find(
+    conn: &mut PgConnection,
+    configuration_id: i32,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    let configuration = configurations::table
+        .find(configuration_id)
+        .select(Configuration::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(ConfigurationError::NotFound(configuration_id))?;
+    let parts = configuration_parts::table
+        .inner_join(parts::table)
+        .filter(configuration_parts::configuration_id.eq(configuration_id))
+        .order(parts::id)
+        .select((Part::as_select(), configuration_parts::quantity))
+        .load::<(Part, i32)>(conn)?
+        .into_iter()
+        .map(|(part, quantity)| ConfiguredPart { part, quantity })
+        .collect();
+    Ok(ConfigurationDetails {
+        configuration,
+        parts,
+    })
+}
+
+/// Gives one of the user's configurations a share slug, keeping the one it
+/// already has so links handed out earlier keep working.
+pub fn share(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    find_owned(conn, user_id, configuration_id)?;
+    // Only set a slug where there is none, so concurrent calls agree on one
+    let shared = diesel::update(
+        configurations::table
+            .find(configuration_id)
+            .filter(configurations::share_slug.is_null()),
+    )
+    .set(configurations::share_slug.eq(new_token()))
+    .execute(conn)?;
+    if shared > 0 {
+        info!("Shared configuration {}", configuration_id);
+    }
+    find(conn, configuration_id)
+}
+
+/// Removes the share slug of one of the user's configurations, so its link
+/// stops working. Sharing it again gives it a new slug.
+pub fn revoke_share(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    find_owned(conn, user_id, configuration_id)?;
+    let revoked = diesel::update(
+        configurations::table
+            .find(configuration_id)
+            .filter(configurations::share_slug.is_not_null()),
+    )
+    .set(configurations::share_slug.eq(None::<String>))
+    .execute(conn)?;
+    if revoked > 0 {
+        info!("Revoked share link of configuration {}", configuration_id);
+    }
+    find(conn, configuration_id)
+}
+
+/// Fetches the configuration shared under `slug`, if any.
+pub fn find_shared(
+    conn: &mut PgConnection,
+    slug: &str,
+) -> Result<Option<ConfigurationDetails>, ConfigurationError> {
+    let configuration_id = configurations::table
+        .filter(configurations::share_slug.eq(slug))
+        .select(configurations::id)
+        .first::<i32>(conn)
+        .optional()?;
+    configuration_id.map(|id| find(conn, id)).transpose()
+}
+
+/// Copies the configuration shared under `slug` into the user's account, with
+/// the same parts and quantities. The copy is named `name` or after the
+/// original, and is not shared.
+pub fn fork(
+    conn: &mut PgConnection,
+    user_id: i32,
+    slug: &str,
+    name: Option<&str>,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    let original = find_shared(conn, slug)?.ok_or(ConfigurationError::ShareNotFound)?;
+    let name = match name {
+        Some(name) => validate_name(name)?,
+        None => original.configuration.name.clone(),
+    };
+    let parts = original
+        .parts
+        .iter()
+        .map(|configured| (configured.part.id, configured.quantity))
+        .collect::<Vec<_>>();
+
+    let fork = create(
+        conn,
+        NewConfiguration {
+            user_id: Some(user_id),
+            name,
+            description: original.configuration.description,
+        },
+        &parts,
+    )?;
+    info!(
+        "Forked configuration {} into configuration {} for user {}",
+        original.configuration.id, fork.configuration.id, user_id
+    );
+    Ok(fork)
+}
+
+/// Fetches one of the user's configurations with its parts.
+pub fn find_for_user(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    find_owned(conn, user_id, configuration_id)?;
+    find(conn, configuration_id)
+}
+
+/// Applies `changes` to one of the user's configurations and records a
+/// revision, unless nothing changed.
+pub fn update(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+    changes: ConfigurationChanges,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    let name = changes.name.as_deref().map(validate_name).transpose()?;
+    let parts = changes.parts.map(validate_parts).transpose()?;
+    conn.transaction(|conn| {
+        let current = lock_owned(conn, user_id, configuration_id)?;
+        let updated = Snapshot {
+            name: name.unwrap_or_else(|| current.name.clone()),
+            description: changes
+                .description
+                .unwrap_or_else(|| current.description.clone()),
+            parts: parts.unwrap_or_else(|| current.parts.clone()),
+        };
+        if updated == current {
+            return Ok(());
+        }
+        if updated.parts != current.parts {
+            check_available(conn, &updated.parts)?;
+        }
+
+        // Configurations created outside the application have no history yet
+        let recorded = diesel::select(diesel::dsl::exists(
+            configuration_revisions::table
+                .filter(configuration_revisions::configuration_id.eq(configuration_id)),
+        ))
+        .get_result::<bool>(conn)?;
+        if !recorded {
+            record_revision(conn, configuration_id, None)?;
+        }
+        overwrite(conn, configuration_id, &updated)?;
+        let revision = record_revision(conn, configuration_id, None)?;
+        info!(
+            "Updated configuration {} to revision {}",
+            configuration_id, revision
+        );
+        Ok::<_, ConfigurationError>(())
+    })?;
+    find(conn, configuration_id)
+}
+
+/// Fetches the revisions of one of the user's configurations, newest first,
+/// keeping at most `limit`.
+pub fn revisions(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+    limit: Option<i64>,
+) -> Result<Vec<RevisionDetails>, ConfigurationError> {
+    find_owned(conn, user_id, configuration_id)?;
+    let mut query = configuration_revisions::table
+        .filter(configuration_revisions::configuration_id.eq(configuration_id))
+        .order(configuration_revisions::revision.desc())
+        .select(ConfigurationRevision::as_select())
+        .into_boxed();
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    let revisions = query.load(conn)?;
+    with_parts(conn, revisions)
+}
+
+/// Compares two revisions of one of the user's configurations, reporting the
+/// changes from `from` to `to`.
+pub fn diff_revisions(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+    from: i32,
+    to: i32,
+) -> Result<RevisionDiff, ConfigurationError> {
+    find_owned(conn, user_id, configuration_id)?;
+    let before = find_revision(conn, configuration_id, from)?;
+    let after = find_revision(conn, configuration_id, to)?;
+
+    let mut before_parts = before
+        .parts
+        .into_iter()
+        .map(|configured| (configured.part.id, configured))
+        .collect::<BTreeMap<_, _>>();
+    let mut added = Vec::new();
+    let mut changed = Vec::new();
+    for configured in after.parts {
+        match before_parts.remove(&configured.part.id) {
+            None => added.push(configured),
+            Some(previous) if previous.quantity != configured.quantity => {
+                changed.push(QuantityChange {
+                    part: configured.part,
+                    from: previous.quantity,
+                    to: configured.quantity,
+                })
+            }
+            Some(_) => {}
+        }
+    }
+
+    let (before, after) = (before.revision, after.revision);
+    Ok(RevisionDiff {
+        from,
+        to,
+        name: (before.name != after.name).then_some((before.name, after.name)),
+        description: (before.description != after.description)
+            .then_some((before.description, after.description)),
+        added,
+        removed: before_parts.into_values().collect(),
+        changed,
+    })
+}
+
+/// Sets one of the user's configurations back to an earlier revision. The
+/// restore is recorded as a new revision, so later revisions are kept. Like
+/// `update`, it refuses parts that have since been discontinued.
+pub fn restore_revision(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+    revision: i32,
+) -> Result<ConfigurationDetails, ConfigurationError> {
+    conn.transaction(|conn| {
+        let current = lock_owned(conn, user_id, configuration_id)?;
+        let target = find_revision(conn, configuration_id, revision)?;
+        let restored = Snapshot {
+            name: target.revision.name,
+            description: target.revision.description,
+            parts: target
+                .parts
+                .iter()
+                .map(|configured| (configured.part.id, configured.quantity))
+                .collect(),
+        };
+        if restored == current {
+            return Ok(());
+        }
+        if restored.parts != current.parts {
+            check_available(conn, &restored.parts)?;
+        }
+
+        overwrite(conn, configuration_id, &restored)?;
+        let new_revision = record_revision(conn, configuration_id, Some(revision))?;
+        info!(
+            "Restored configuration {} to revision {} as revision {}",
+            configuration_id, revision, new_revision
+        );
+        Ok::<_, ConfigurationError>(())
+    })?;
+    find(conn, configuration_id)
+}
+
+/// Fetches one of the user's configurations. Anonymous configurations belong
+/// to no one.
+fn find_owned(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+) -> Result<Configuration, ConfigurationError> {
+    configurations::table
+        .find(configuration_id)
+        .filter(configurations::user_id.eq(user_id))
+        .select(Configuration::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(ConfigurationError::NotFound(configuration_id))
+}
+
+/// Locks one of the user's configurations for a change and returns its
+/// current state.
+fn lock_owned(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+) -> Result<Snapshot, ConfigurationError> {
+    // The lock also keeps concurrent changes from taking the same revision number
+    let configuration = configurations::table
+        .find(configuration_id)
+        .filter(configurations::user_id.eq(user_id))
+        .select(Configuration::as_select())
+        .for_update()
+        .first(conn)
+        .optional()?
+        .ok_or(ConfigurationError::NotFound(configuration_id))?;
+    Ok(Snapshot {
+        name: configuration.name,
+        description: configuration.description,
+        parts: current_parts(conn, configuration_id)?,
+    })
+}
+
+fn current_parts(
+    conn: &mut PgConnection,
+    configuration_id: i32,
+) -> Result<Vec<(i32, i32)>, ConfigurationError> {
+    Ok(configuration_parts::table
+        .filter(configuration_parts::configuration_id.eq(configuration_id))
+        .order(configuration_parts::part_id)
+        .select((configuration_parts::part_id, configuration_parts::quantity))
+        .load(conn)?)
+}
+
+/// Replaces the name, description and parts of a configuration.
+fn overwrite(
+    conn: &mut PgConnection,
+    configuration_id: i32,
+    snapshot: &Snapshot,
+) -> Result<(), ConfigurationError> {
+    diesel::update(configurations::table.find(configuration_id))
+        .set((
+            configurations::name.eq(&snapshot.name),
+            configurations::description.eq(&snapshot.description),
+            configurations::updated_at.eq(now),
+        ))
+        .execute(conn)?;
+    diesel::delete(
+        configuration_parts::table
+            .filter(configuration_parts::configuration_id.eq(configuration_id)),
+    )
+    .execute(conn)?;
+    let rows = snapshot
+        .parts
+        .iter()
+        .map(|&(part_id, quantity)| {
+            (
+                configuration_parts::configuration_id.eq(configuration_id),
+                configuration_parts::part_id.eq(part_id),
+                configuration_parts::quantity.eq(quantity),
+            )
+        })
+        .collect::<Vec<_>>();
+    diesel::insert_into(configuration_parts::table)
+        .values(&rows)
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Records the current state of a configuration as its next revision and
+/// returns the revision number.
+fn record_revision(
+    conn: &mut PgConnection,
+    configuration_id: i32,
+    restored_from: Option<i32>,
+) -> Result<i32, ConfigurationError> {
+    let configuration = configurations::table
+        .find(configuration_id)
+        .select(Configuration::as_select())
+        .first(conn)?;
+    let revision = configuration_revisions::table
+        .filter(configuration_revisions::configuration_id.eq(configuration_id))
+        .order(configuration_revisions::revision.desc())
+        .select(configuration_revisions::revision)
+        .first::<i32>(conn)
+        .optional()?
+        .unwrap_or(0)
+        + 1;
+    let revision_id = diesel::insert_into(configuration_revisions::table)
+        .values((
+            configuration_revisions::configuration_id.eq(configuration_id),
+            configuration_revisions::revision.eq(revision),
+            configuration_revisions::name.eq(&configuration.name),
+            configuration_revisions::description.eq(&configuration.description),
+            configuration_revisions::restored_from.eq(restored_from),
+        ))
+        .returning(configuration_revisions::id)
+        .get_result::<i32>(conn)?;
+    let rows = current_parts(conn, configuration_id)?
+        .into_iter()
+        .map(|(part_id, quantity)| {
+            (
+                configuration_revision_parts::revision_id.eq(revision_id),
+                configuration_revision_parts::part_id.eq(part_id),
+                configuration_revision_parts::quantity.eq(quantity),
+            )
+        })
+        .collect::<Vec<_>>();
+    diesel::insert_into(configuration_revision_parts::table)
+        .values(&rows)
+        .execute(conn)?;
+    Ok(revision)
+}
+
+fn find_revision(
+    conn: &mut PgConnection,
+    configuration_id: i32,
+    revision: i32,
+) -> Result<RevisionDetails, ConfigurationError> {
+    let found = configuration_revisions::table
+        .filter(configuration_revisions::configuration_id.eq(configuration_id))
+        .filter(configuration_revisions::revision.eq(revision))
+        .select(ConfigurationRevision::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(ConfigurationError::RevisionNotFound(
+            configuration_id,
+            revision,
+        ))?;
+    let mut details = with_parts(conn, vec![found])?;
+    Ok(details.remove(0))
+}
+
+/// Loads the parts of each revision, keeping the order of `revisions`.
+fn with_parts(
+    conn: &mut PgConnection,
+    revisions: Vec<ConfigurationRevision>,
+) -> Result<Vec<RevisionDetails>, ConfigurationError> {
+    let revision_parts = RevisionPart::belonging_to(&revisions)
+        .inner_join(parts::table)
+        .order(parts::id)
+        .select((RevisionPart::as_select(), Part::as_select()))
+        .load::<(RevisionPart, Part)>(conn)?;
+    Ok(revision_parts
+        .grouped_by(&revisions)
+        .into_iter()
+        .zip(revisions)
+        .map(|(parts, revision)| RevisionDetails {
+            revision,
+            parts: parts
+                .into_iter()
+                .map(|(revision_part, part)| ConfiguredPart {
+                    part,
+                    quantity: revision_part.quantity,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+/// Fails unless every part exists and is not discontinued.
+fn check_available(
+    conn: &mut PgConnection,
+    parts: &[(i32, i32)],
+) -> Result<(), ConfigurationError> {
+    let part_ids = parts
+        .iter()
+        .map(|&(part_id, _)| part_id)
+        .collect::<Vec<_>>();
+    let available = parts::table
+        .filter(parts::id.eq_any(&part_ids))
+        .filter(parts::deleted_at.is_null())
+        .select(parts::id)
+        .load::<i32>(conn)?;
+    match part_ids.into_iter().find(|id| !available.contains(id)) {
+        Some(missing) => Err(ConfigurationError::PartNotFound(missing)),
+        None => Ok(()),
+    }
+}
+
+fn validate_name(name: &str) -> Result<String, ConfigurationError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ConfigurationError::Validation(
+            "Name is required".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Checks a parts list and orders it by part ID.
+fn validate_parts(mut parts: Vec<(i32, i32)>) -> Result<Vec<(i32, i32)>, ConfigurationError> {
+    if let Some(&(part_id, _)) = parts.iter().find(|&&(_, quantity)| quantity < 1) {
+        return Err(ConfigurationError::Validation(format!(
+            "Quantity of part {} must be at least 1",
+            part_id
+        )));
+    }
+    parts.sort_unstable();
+    if let Some(pair) = parts.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(ConfigurationError::Validation(format!(
+            "Part {} is listed more than once",
+            pair[0].0
+        )));
+    }
+    Ok(parts)
+}
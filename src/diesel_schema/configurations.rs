@@ -8,6 +8,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    configuration_revision_parts (revision_id, part_id) {
+        revision_id -> Int4,
+        part_id -> Int4,
+        quantity -> Int4,
+    }
+}
+
+diesel::table! {
+    configuration_revisions (id) {
+        id -> Int4,
+        configuration_id -> Int4,
+        revision -> Int4,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        restored_from -> Nullable<Int4>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     configurations (id) {
         id -> Int4,
@@ -16,9 +36,22 @@ diesel::table! {
         description -> Nullable<Text>,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        #[max_length = 64]
+        share_slug -> Nullable<Varchar>,
     }
 }
 
+use super::parts::parts;
+
 diesel::joinable!(configuration_parts -> configurations (configuration_id));
+diesel::joinable!(configuration_revision_parts -> parts (part_id));
+diesel::joinable!(configuration_revision_parts -> configuration_revisions (revision_id));
+diesel::joinable!(configuration_revisions -> configurations (configuration_id));
 
-diesel::allow_tables_to_appear_in_same_query!(configuration_parts, configurations,);
+diesel::allow_tables_to_appear_in_same_query!(
+    configuration_parts,
+    configuration_revision_parts,
+    configuration_revisions,
+    configurations,
+    parts,
+);
@@ -1,3 +1,10 @@
+pub mod api_keys;
+pub mod audit_log;
 pub mod configurations;
+pub mod orders;
 pub mod parts;
+pub mod persisted_queries;
+pub mod quote_requests;
+pub mod rate_limits;
 pub mod users;
+pub mod wishlists;
@@ -1,10 +1,19 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    catalog_revision (id) {
+        id -> Bool,
+        revision -> Int8,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     categories (id) {
         id -> Int4,
         name -> Varchar,
         description -> Nullable<Text>,
+        deleted_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -46,11 +55,24 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    inventory (part_id) {
+        part_id -> Int4,
+        quantity_on_hand -> Int4,
+        quantity_reserved -> Int4,
+        reorder_threshold -> Int4,
+        #[max_length = 100]
+        supplier_sku -> Nullable<Varchar>,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     manufacturers (id) {
         id -> Int4,
         name -> Varchar,
         website -> Nullable<Varchar>,
+        deleted_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -80,6 +102,7 @@ diesel::table! {
         model -> Varchar,
         price -> Nullable<Numeric>,
         common_specifications -> Nullable<Jsonb>,
+        deleted_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -101,16 +124,19 @@ diesel::table! {
 diesel::joinable!(configuration_parts -> parts (part_id));
 diesel::joinable!(cpu_specs -> parts (part_id));
 diesel::joinable!(gpu_specs -> parts (part_id));
+diesel::joinable!(inventory -> parts (part_id));
 diesel::joinable!(memory_specs -> parts (part_id));
 diesel::joinable!(parts -> categories (category_id));
 diesel::joinable!(parts -> manufacturers (manufacturer_id));
 diesel::joinable!(storage_specs -> parts (part_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    catalog_revision,
     categories,
     configuration_parts,
     cpu_specs,
     gpu_specs,
+    inventory,
     manufacturers,
     memory_specs,
     parts,
@@ -28,6 +28,12 @@ diesel::table! {
         expires -> Timestamptz,
         #[max_length = 255]
         sessionToken -> Varchar,
+        created_at -> Timestamptz,
+        last_seen_at -> Nullable<Timestamptz>,
+        #[max_length = 512]
+        user_agent -> Nullable<Varchar>,
+        #[max_length = 45]
+        ip_address -> Nullable<Varchar>,
     }
 }
 
@@ -41,6 +47,8 @@ diesel::table! {
         emailVerified -> Nullable<Timestamptz>,
         image -> Nullable<Text>,
         bio -> Nullable<Text>,
+        #[max_length = 20]
+        role -> Varchar,
     }
 }
 
@@ -1,25 +1,515 @@
 // src/graphql_handler.rs
 
+use crate::api_keys::ApiKeyPrincipal;
+use crate::graphql_schema::query_limits::{
+    analyze_query, check_query_limits, summarize_operation, OperationSummary,
+};
 use crate::graphql_schema::{context::Context, schema::Schema};
+use crate::logger;
+use crate::metrics;
+use crate::middleware::request_id::CorrelationId;
 use crate::models::auth::User;
+use crate::persisted_queries::{resolve_query, PersistedQueryExtension, PersistedQueryStore};
+use crate::rate_limit::{RateLimitClient, RateLimitDecision, RateLimiter};
+use crate::response_cache::{CacheScope, CachedResponse, ResponseCache};
+use crate::sessions::{ClientInfo, CurrentSession};
+use crate::settings::{GraphQLSettings, PersistedQuerySettings};
+use crate::types::errors::{GraphQLRequestError, PersistedQueryError};
+use actix_web::http::header;
 use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
-use juniper::http::GraphQLRequest;
+use juniper::http::{GraphQLRequest, GraphQLResponse};
+use juniper::{
+    graphql_value, DefaultScalarValue, FieldError, InputValue, IntoFieldError, OperationType,
+    Variables,
+};
+use log::{error, warn};
+use serde::Deserialize;
+use serde_json::Value;
 use std::sync::Arc;
+use std::time::Instant;
 
-/// Handles GraphQL requests by executing the query and returning the response as JSON.
+/// JSON body of a GraphQL POST request.
+#[derive(Debug, Deserialize)]
+pub struct GraphQLBody {
+    /// Query text; may be omitted when a persisted query hash is sent.
+    pub query: Option<String>,
+    #[serde(rename = "operationName")]
+    pub operation_name: Option<String>,
+    pub variables: Option<InputValue>,
+    pub extensions: Option<RequestExtensions>,
+}
+
+/// Protocol extensions sent alongside a GraphQL request.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestExtensions {
+    pub persisted_query: Option<PersistedQueryExtension>,
+}
+
+impl GraphQLBody {
+    /// Returns the request variables keyed by name.
+    pub fn variables(&self) -> Variables {
+        self.variables
+            .as_ref()
+            .and_then(|vars| vars.to_object_value())
+            .map(|vars| {
+                vars.into_iter()
+                    .map(|(name, value)| (name.to_string(), value.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// A POST body holding either one operation or an array of operations.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum GraphQLPayload {
+    Single(GraphQLBody),
+    Batch(Vec<GraphQLBody>),
+}
+
+/// Query string of a GraphQL GET request; `variables` and `extensions` are JSON-encoded.
+#[derive(Debug, Deserialize)]
+pub struct GraphQLQueryParams {
+    pub query: Option<String>,
+    #[serde(rename = "operationName")]
+    pub operation_name: Option<String>,
+    pub variables: Option<String>,
+    pub extensions: Option<String>,
+}
+
+impl GraphQLQueryParams {
+    /// Decodes the JSON-encoded parameters into a request body.
+    pub fn into_body(self) -> Result<GraphQLBody, GraphQLRequestError> {
+        Ok(GraphQLBody {
+            query: self.query,
+            operation_name: self.operation_name,
+            variables: parse_json_param("variables", self.variables)?,
+            extensions: parse_json_param("extensions", self.extensions)?,
+        })
+    }
+}
+
+/// Shared state needed to execute an operation.
+#[derive(Clone)]
+pub(crate) struct Executor {
+    pub(crate) schema: web::Data<Arc<Schema>>,
+    context: web::Data<Context>,
+    graphql_settings: web::Data<GraphQLSettings>,
+    persisted_query_store: web::Data<PersistedQueryStore>,
+    persisted_query_settings: web::Data<PersistedQuerySettings>,
+    response_cache: web::Data<ResponseCache>,
+    rate_limiter: web::Data<RateLimiter>,
+    user: Option<User>,
+    session_id: Option<i32>,
+    api_key: Option<ApiKeyPrincipal>,
+    client: ClientInfo,
+    rate_limit_client: RateLimitClient,
+    request_id: Option<String>,
+}
+
+/// A query that passed persisted query resolution and the query limits.
+pub(crate) struct PreparedOperation {
+    pub(crate) query: String,
+    pub(crate) operation: Option<OperationSummary>,
+}
+
+/// The serialized response to one operation.
+enum OperationResponse {
+    /// A response that must not be cached.
+    Uncached(Value),
+    /// A response served from or stored in the response cache.
+    Cached {
+        response: CachedResponse,
+        scope: CacheScope,
+    },
+}
+
+impl OperationResponse {
+    fn into_body(self) -> Value {
+        match self {
+            OperationResponse::Uncached(body) => body,
+            OperationResponse::Cached { response, .. } => (*response.body).clone(),
+        }
+    }
+}
+
+impl Executor {
+    /// Collects the shared application state and the request's user and correlation ID.
+    pub(crate) fn from_request(req: &HttpRequest) -> Result<Self, actix_web::Error> {
+        // Extract the authenticated user and correlation ID from the request extensions
+        let user = req.extensions().get::<User>().cloned();
+        let session_id = req
+            .extensions()
+            .get::<CurrentSession>()
+            .map(|session| session.0);
+        let api_key = req.extensions().get::<ApiKeyPrincipal>().cloned();
+        let request_id = req
+            .extensions()
+            .get::<CorrelationId>()
+            .map(|id| id.0.clone());
+        logger::set_user_id(user.as_ref().map(|u| u.id));
+        let rate_limiter = app_data::<RateLimiter>(req)?;
+
+        Ok(Executor {
+            schema: app_data(req)?,
+            context: app_data(req)?,
+            graphql_settings: app_data(req)?,
+            persisted_query_store: app_data(req)?,
+            persisted_query_settings: app_data(req)?,
+            response_cache: app_data(req)?,
+            rate_limit_client: rate_limiter.client(req),
+            client: ClientInfo::from_request(req, rate_limiter.trust_proxy_headers()),
+            rate_limiter,
+            user,
+            session_id,
+            api_key,
+            request_id,
+        })
+    }
+
+    /// Creates the GraphQL context for an operation run on behalf of the request.
+    pub(crate) fn context(&self) -> Context {
+        Context::new(self.context.db.clone(), self.user.clone())
+            .with_session(self.session_id)
+            .with_api_key(self.api_key.clone())
+            .with_client(self.client.clone())
+            .with_request_id(self.request_id.clone())
+            .with_reference_cache(self.context.reference_cache.clone())
+            .with_response_cache(Some(Arc::clone(&self.response_cache)))
+            .with_notifications(self.context.notifications.clone())
+            .with_email_links(self.context.email_links.clone())
+    }
+
+    /// Resolves the query text, enforcing the persisted query allowlist and the
+    /// depth and complexity limits. Rejections are returned as a response body.
+    pub(crate) async fn prepare(&self, body: &GraphQLBody) -> Result<PreparedOperation, Value> {
+        // Resolve persisted query hashes and enforce the allowlist, which may hit the database
+        let persisted_query = body
+            .extensions
+            .as_ref()
+            .and_then(|ext| ext.persisted_query.clone());
+        let query = body.query.clone();
+        let store = self.persisted_query_store.clone();
+        let settings = self.persisted_query_settings.clone();
+        let resolved =
+            web::block(move || resolve_query(&store, &settings, query, persisted_query.as_ref()))
+                .await;
+        let query = match resolved {
+            Ok(Ok(query)) => query,
+            Ok(Err(e)) => {
+                match e {
+                    PersistedQueryError::DatabaseError(_) | PersistedQueryError::PoolError(_) => {
+                        error!("Failed to resolve persisted query: {}", e)
+                    }
+                    _ => warn!("Rejected GraphQL request: {}", e),
+                }
+                return Err(error_body(e));
+            }
+            Err(e) => {
+                error!("Failed to resolve persisted query: {}", e);
+                return Err(error_body(FieldError::<DefaultScalarValue>::new(
+                    "Failed to load persisted query",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                )));
+            }
+        };
+
+        // Reject queries that are too deep or too costly before running any resolver
+        let operation_name = body.operation_name.as_deref();
+        if let Some(cost) = analyze_query(
+            &self.schema,
+            &query,
+            operation_name,
+            &body.variables(),
+            &self.graphql_settings,
+        ) {
+            if let Err(e) = check_query_limits(cost, &self.graphql_settings) {
+                warn!(
+                    depth = cost.depth, complexity = cost.complexity;
+                    "Rejected GraphQL query: {}", e
+                );
+                return Err(error_body(e));
+            }
+        }
+
+        let operation = summarize_operation(&self.schema, &query, operation_name);
+        Ok(PreparedOperation { query, operation })
+    }
+
+    /// Takes a token for each root field of the operation that has a rate
+    /// limit of its own, failing with [`GraphQLRequestError::RateLimited`] for
+    /// the first field over its limit.
+    pub(crate) async fn check_rate_limit(
+        &self,
+        operation: Option<&OperationSummary>,
+    ) -> Result<(), GraphQLRequestError> {
+        let Some(op) = operation else {
+            return Ok(());
+        };
+        let limited = self
+            .rate_limiter
+            .check_operation(&op.root_fields, &self.rate_limit_client)
+            .await;
+        match limited {
+            Some((field, decision)) => Err(GraphQLRequestError::RateLimited {
+                field,
+                limit: decision.limit,
+                reset: decision.reset,
+                retry_after: decision.retry_after.unwrap_or_default(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Executes one operation and returns its serialized response.
+    ///
+    /// With `queries_only`, mutations and subscriptions are refused with
+    /// [`GraphQLRequestError::MethodNotAllowed`]. Operations with a root field
+    /// over its rate limit are refused with [`GraphQLRequestError::RateLimited`].
+    async fn execute(
+        &self,
+        body: GraphQLBody,
+        queries_only: bool,
+    ) -> Result<OperationResponse, GraphQLRequestError> {
+        // Attach the operation name to the structured logs emitted while executing
+        logger::set_operation_name(body.operation_name.as_deref());
+
+        let PreparedOperation { query, operation } = match self.prepare(&body).await {
+            Ok(prepared) => prepared,
+            Err(error_body) => return Ok(OperationResponse::Uncached(error_body)),
+        };
+        if queries_only
+            && matches!(&operation, Some(op) if op.operation_type != OperationType::Query)
+        {
+            return Err(GraphQLRequestError::MethodNotAllowed);
+        }
+        self.check_rate_limit(operation.as_ref()).await?;
+
+        let operation_name = operation.as_ref().and_then(|op| op.name.clone());
+
+        // Serve catalog queries from the response cache when possible
+        let scope = match &self.api_key {
+            Some(api_key) => CacheScope::ApiKey(api_key.key_id),
+            None => CacheScope::for_user(self.user.as_ref().map(|u| u.id)),
+        };
+        let cache_key = operation
+            .filter(|op| self.response_cache.is_cacheable(op))
+            .map(|_| {
+                let variables = serde_json::to_value(&body.variables).unwrap_or(Value::Null);
+                ResponseCache::key(&query, body.operation_name.as_deref(), &variables, scope)
+            });
+        if let Some(key) = &cache_key {
+            let cached = self.response_cache.get(key);
+            metrics::observe_response_cache(cached.is_some());
+            if let Some(response) = cached {
+                return Ok(OperationResponse::Cached { response, scope });
+            }
+        }
+
+        // Create a new context with the user and request ID included
+        let ctx = self.context();
+
+        let data = GraphQLRequest::new(query, body.operation_name, body.variables);
+        let start_time = Instant::now();
+        let res = data.execute(&self.schema, &ctx).await;
+        metrics::observe_operation(operation_name.as_deref(), start_time.elapsed());
+
+        let body = serde_json::to_value(&res).unwrap_or(Value::Null);
+        metrics::record_graphql_errors(&body);
+
+        // Responses with errors are not cached so that failures are retried
+        match cache_key {
+            Some(key) if body.get("errors").is_none() => {
+                match self.response_cache.insert(key, body.clone()) {
+                    Some(response) => Ok(OperationResponse::Cached { response, scope }),
+                    None => Ok(OperationResponse::Uncached(body)),
+                }
+            }
+            _ => Ok(OperationResponse::Uncached(body)),
+        }
+    }
+}
+
+/// Handles GraphQL POST requests, executing a single operation or a batch of
+/// operations and returning the response (or array of responses) as JSON.
 pub async fn graphql_handler(
-    schema: web::Data<Arc<Schema>>,
     req: HttpRequest,
-    data: web::Json<GraphQLRequest>,
-    context_data: web::Data<Context>,
+    payload: web::Json<GraphQLPayload>,
 ) -> Result<HttpResponse, actix_web::Error> {
-    // Extract the authenticated user from the request extensions
-    let user = req.extensions().get::<User>().cloned();
+    let executor = Executor::from_request(&req)?;
 
-    // Create a new context with the user included
-    let ctx = Context::new(context_data.db.clone(), user);
+    match payload.into_inner() {
+        GraphQLPayload::Single(body) => match executor.execute(body, false).await {
+            Ok(response) => Ok(operation_response(&req, response)),
+            Err(e) => Ok(request_error_response(e)),
+        },
+        GraphQLPayload::Batch(bodies) => {
+            let max = executor.graphql_settings.max_batch_size;
+            let rejected = if max == 0 {
+                Some(GraphQLRequestError::BatchingDisabled)
+            } else if bodies.is_empty() {
+                Some(GraphQLRequestError::EmptyBatch)
+            } else if bodies.len() > max {
+                Some(GraphQLRequestError::BatchTooLarge {
+                    size: bodies.len(),
+                    max,
+                })
+            } else {
+                None
+            };
+            if let Some(e) = rejected {
+                warn!("Rejected batched GraphQL request: {}", e);
+                return Ok(request_error_response(e));
+            }
+
+            // Operations run one after another so each one's logs carry its own name
+            let mut responses = Vec::with_capacity(bodies.len());
+            for body in bodies {
+                match executor.execute(body, false).await {
+                    Ok(response) => responses.push(response.into_body()),
+                    Err(e) => responses.push(error_body(e)),
+                }
+            }
+            logger::set_operation_name(None);
+            Ok(HttpResponse::Ok()
+                .insert_header((header::CACHE_CONTROL, "no-store"))
+                .json(responses))
+        }
+    }
+}
+
+/// Handles GraphQL GET requests, which may only run query operations so that
+/// their responses can be cached by HTTP caches.
+pub async fn graphql_get_handler(
+    req: HttpRequest,
+    params: web::Query<GraphQLQueryParams>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let body = match params.into_inner().into_body() {
+        Ok(body) => body,
+        Err(e) => return Ok(request_error_response(e)),
+    };
 
-    let res = data.execute(&schema, &ctx).await;
+    let executor = Executor::from_request(&req)?;
+    match executor.execute(body, true).await {
+        Ok(response) => Ok(operation_response(&req, response)),
+        Err(e) => {
+            warn!("Rejected GraphQL GET request: {}", e);
+            Ok(request_error_response(e))
+        }
+    }
+}
+
+/// Builds the HTTP response for one operation, answering `If-None-Match`
+/// with `304 Not Modified` when the cached response is unchanged.
+fn operation_response(req: &HttpRequest, response: OperationResponse) -> HttpResponse {
+    let (response, scope) = match response {
+        OperationResponse::Uncached(body) => {
+            return HttpResponse::Ok()
+                .insert_header((header::CACHE_CONTROL, "no-store"))
+                .json(body)
+        }
+        OperationResponse::Cached { response, scope } => (response, scope),
+    };
+
+    let cache_control = format!(
+        "{}, max-age={}",
+        scope.cache_control(),
+        response.max_age().as_secs()
+    );
+    let not_modified = etag_matches(req, &response.etag);
+    let mut builder = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    builder
+        .insert_header((header::ETAG, response.etag.as_str()))
+        .insert_header((header::CACHE_CONTROL, cache_control))
+        .insert_header((header::VARY, "Authorization, Cookie"));
+
+    if not_modified {
+        builder.finish()
+    } else {
+        builder.json(&*response.body)
+    }
+}
+
+/// Returns `true` if the request's `If-None-Match` header lists `etag`.
+fn etag_matches(req: &HttpRequest, etag: &str) -> bool {
+    req.headers()
+        .get_all(header::IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .any(|candidate| {
+            candidate == "*" || candidate == etag || candidate.strip_prefix("W/") == Some(etag)
+        })
+}
+
+/// Returns shared application state registered with `App::app_data`.
+pub(crate) fn app_data<T: 'static>(req: &HttpRequest) -> Result<web::Data<T>, actix_web::Error> {
+    req.app_data::<web::Data<T>>().cloned().ok_or_else(|| {
+        error!(
+            "{} is not registered as app data",
+            std::any::type_name::<T>()
+        );
+        actix_web::error::ErrorInternalServerError("Server is misconfigured")
+    })
+}
+
+/// Decodes a JSON-encoded query string parameter.
+fn parse_json_param<T: serde::de::DeserializeOwned>(
+    name: &str,
+    value: Option<String>,
+) -> Result<Option<T>, GraphQLRequestError> {
+    match value.filter(|value| !value.is_empty()) {
+        Some(value) => serde_json::from_str(&value).map(Some).map_err(|_| {
+            GraphQLRequestError::InvalidParameter {
+                name: name.to_string(),
+            }
+        }),
+        None => Ok(None),
+    }
+}
+
+/// Serializes the response for an operation rejected before execution.
+pub(crate) fn error_body(error: impl IntoFieldError) -> Value {
+    let body = serde_json::to_value(GraphQLResponse::error(error.into_field_error()))
+        .unwrap_or(Value::Null);
+    metrics::record_graphql_errors(&body);
+    body
+}
 
-    Ok(HttpResponse::Ok().json(res))
+/// Builds the HTTP response for a request that cannot be executed as sent.
+fn request_error_response(error: GraphQLRequestError) -> HttpResponse {
+    let mut response = match error {
+        GraphQLRequestError::MethodNotAllowed => {
+            let mut response = HttpResponse::MethodNotAllowed();
+            response.insert_header((header::ALLOW, "POST"));
+            response
+        }
+        GraphQLRequestError::RateLimited {
+            limit,
+            reset,
+            retry_after,
+            ..
+        } => {
+            let decision = RateLimitDecision {
+                allowed: false,
+                limit,
+                remaining: 0,
+                reset,
+                retry_after: Some(retry_after),
+            };
+            let mut response = HttpResponse::TooManyRequests();
+            for header in decision.headers() {
+                response.insert_header(header);
+            }
+            response
+        }
+        _ => HttpResponse::BadRequest(),
+    };
+    response.json(error_body(error))
 }
@@ -0,0 +1,51 @@
+use crate::api_keys;
+use crate::graphql_schema::api_keys::types::{ApiKeyType, IssueApiKeyInput, IssuedApiKeyType};
+use crate::graphql_schema::context::Context;
+use crate::models::api_keys::ApiKeyScope;
+use crate::types::errors::ApiKeyError;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Issues an API key for the caller or another user; staff only.
+pub fn issue_api_key(context: &Context, input: IssueApiKeyInput) -> FieldResult<IssuedApiKeyType> {
+    let user = context.require_staff()?;
+    let scopes = input
+        .scopes
+        .into_iter()
+        .map(ApiKeyScope::from)
+        .collect::<Vec<_>>();
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ApiKeyError::from(e).into_field_error())?;
+    api_keys::issue(
+        conn,
+        user.id,
+        input.owner_id.unwrap_or(user.id),
+        &input.name,
+        &scopes,
+        input.expires_at,
+    )
+    .map(IssuedApiKeyType::from)
+    .map_err(ApiKeyError::into_field_error)
+}
+
+/// Replaces the secret of an API key; staff only.
+pub fn rotate_api_key(context: &Context, id: i32) -> FieldResult<IssuedApiKeyType> {
+    context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ApiKeyError::from(e).into_field_error())?;
+    api_keys::rotate(conn, id)
+        .map(IssuedApiKeyType::from)
+        .map_err(ApiKeyError::into_field_error)
+}
+
+/// Revokes an API key; staff only.
+pub fn revoke_api_key(context: &Context, id: i32) -> FieldResult<ApiKeyType> {
+    context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ApiKeyError::from(e).into_field_error())?;
+    api_keys::revoke(conn, id)
+        .map(ApiKeyType::from)
+        .map_err(ApiKeyError::into_field_error)
+}
@@ -0,0 +1,20 @@
+use crate::api_keys;
+use crate::graphql_schema::api_keys::types::ApiKeyType;
+use crate::graphql_schema::context::Context;
+use crate::types::errors::ApiKeyError;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Fetches API keys for staff, newest first, optionally of one owner.
+pub fn all_api_keys(
+    context: &Context,
+    owner_id: Option<i32>,
+    include_revoked: Option<bool>,
+) -> FieldResult<Vec<ApiKeyType>> {
+    context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ApiKeyError::from(e).into_field_error())?;
+    let keys = api_keys::list(conn, owner_id, include_revoked.unwrap_or(false))
+        .map_err(ApiKeyError::into_field_error)?;
+    Ok(keys.into_iter().map(ApiKeyType::from).collect())
+}
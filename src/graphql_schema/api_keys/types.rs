@@ -0,0 +1,112 @@
+use crate::models::api_keys::{ApiKey, ApiKeyScope};
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, GraphQLInputObject, GraphQLObject};
+
+#[derive(GraphQLEnum, Clone, Copy, PartialEq, Eq, Debug)]
+#[graphql(description = "What an API key may be used for")]
+pub enum ApiKeyScopeType {
+    /// Staff-only catalog queries, such as the audit log (`catalog:read`).
+    CatalogRead,
+    /// Discontinuing and restoring parts and reverting audited changes
+    /// (`catalog:write`).
+    CatalogWrite,
+    /// The owner's orders, or every order for staff (`orders:read`).
+    OrdersRead,
+}
+
+impl From<ApiKeyScope> for ApiKeyScopeType {
+    fn from(scope: ApiKeyScope) -> Self {
+        match scope {
+            ApiKeyScope::CatalogRead => ApiKeyScopeType::CatalogRead,
+            ApiKeyScope::CatalogWrite => ApiKeyScopeType::CatalogWrite,
+            ApiKeyScope::OrdersRead => ApiKeyScopeType::OrdersRead,
+        }
+    }
+}
+
+impl From<ApiKeyScopeType> for ApiKeyScope {
+    fn from(scope: ApiKeyScopeType) -> Self {
+        match scope {
+            ApiKeyScopeType::CatalogRead => ApiKeyScope::CatalogRead,
+            ApiKeyScopeType::CatalogWrite => ApiKeyScope::CatalogWrite,
+            ApiKeyScopeType::OrdersRead => ApiKeyScope::OrdersRead,
+        }
+    }
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(description = "The owner, name, scopes and expiry of a new API key")]
+pub struct IssueApiKeyInput {
+    /// Describes what the key is for, e.g. the integrator using it.
+    pub name: String,
+    /// User the key acts on behalf of; defaults to the caller.
+    #[graphql(name = "ownerId")]
+    pub owner_id: Option<i32>,
+    pub scopes: Vec<ApiKeyScopeType>,
+    /// When the key stops working; keys without an expiry work until revoked.
+    #[graphql(name = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "An API key; its secret is only shown when it is issued or rotated")]
+pub struct ApiKeyType {
+    pub id: i32,
+    #[graphql(name = "ownerId")]
+    pub owner_id: i32,
+    pub name: String,
+    /// Start of the secret, to tell keys apart.
+    pub prefix: String,
+    pub scopes: Vec<ApiKeyScopeType>,
+    #[graphql(name = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// When the key was last used, to within a few minutes.
+    #[graphql(name = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+    #[graphql(name = "revokedAt")]
+    pub revoked_at: Option<DateTime<Utc>>,
+    /// Staff member who issued the key.
+    #[graphql(name = "createdBy")]
+    pub created_by: Option<i32>,
+    #[graphql(name = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[graphql(name = "rotatedAt")]
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+impl From<ApiKey> for ApiKeyType {
+    fn from(key: ApiKey) -> Self {
+        ApiKeyType {
+            id: key.id,
+            owner_id: key.owner_id,
+            name: key.name,
+            prefix: key.prefix,
+            scopes: key.scopes.into_iter().map(Into::into).collect(),
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            revoked_at: key.revoked_at,
+            created_by: key.created_by,
+            created_at: key.created_at,
+            rotated_at: key.rotated_at,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A newly issued or rotated API key and its secret")]
+pub struct IssuedApiKeyType {
+    #[graphql(name = "apiKey")]
+    pub api_key: ApiKeyType,
+    /// Sent as `Authorization: ApiKey <secret>`. It is not stored and cannot
+    /// be retrieved again.
+    pub secret: String,
+}
+
+impl From<(ApiKey, String)> for IssuedApiKeyType {
+    fn from((key, secret): (ApiKey, String)) -> Self {
+        IssuedApiKeyType {
+            api_key: ApiKeyType::from(key),
+            secret,
+        }
+    }
+}
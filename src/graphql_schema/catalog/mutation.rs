@@ -0,0 +1,121 @@
+use crate::catalog;
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::parts::category_graphql::CategoryGraphQL;
+use crate::graphql_schema::parts::manufacturer_graphql::ManufacturerGraphQL;
+use crate::graphql_schema::parts::part_graphql::PartGraphQL;
+use crate::models::api_keys::ApiKeyScope;
+use crate::types::errors::CatalogError;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Discontinues a part; staff only.
+pub fn delete_part(context: &Context, part_id: i32) -> FieldResult<PartGraphQL> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let part =
+        catalog::delete_part(conn, user.id, part_id).map_err(CatalogError::into_field_error)?;
+    invalidate_responses(context);
+    Ok(PartGraphQL::from_part(part))
+}
+
+/// Brings a discontinued part back into listings; staff only.
+pub fn restore_part(context: &Context, part_id: i32) -> FieldResult<PartGraphQL> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let part =
+        catalog::restore_part(conn, user.id, part_id).map_err(CatalogError::into_field_error)?;
+    invalidate_responses(context);
+    Ok(PartGraphQL::from_part(part))
+}
+
+/// Discontinues a manufacturer; staff only.
+pub fn delete_manufacturer(
+    context: &Context,
+    manufacturer_id: i32,
+) -> FieldResult<ManufacturerGraphQL> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let manufacturer = catalog::delete_manufacturer(conn, user.id, manufacturer_id)
+        .map_err(CatalogError::into_field_error)?;
+    if let Some(cache) = &context.reference_cache {
+        cache.invalidate_manufacturers();
+    }
+    invalidate_responses(context);
+    Ok(ManufacturerGraphQL::from_manufacturer(manufacturer))
+}
+
+/// Brings a discontinued manufacturer back into listings; staff only.
+pub fn restore_manufacturer(
+    context: &Context,
+    manufacturer_id: i32,
+) -> FieldResult<ManufacturerGraphQL> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let manufacturer = catalog::restore_manufacturer(conn, user.id, manufacturer_id)
+        .map_err(CatalogError::into_field_error)?;
+    if let Some(cache) = &context.reference_cache {
+        cache.invalidate_manufacturers();
+    }
+    invalidate_responses(context);
+    Ok(ManufacturerGraphQL::from_manufacturer(manufacturer))
+}
+
+/// Discontinues a category; staff only.
+pub fn delete_category(context: &Context, category_id: i32) -> FieldResult<CategoryGraphQL> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let category = catalog::delete_category(conn, user.id, category_id)
+        .map_err(CatalogError::into_field_error)?;
+    if let Some(cache) = &context.reference_cache {
+        cache.invalidate_categories();
+    }
+    invalidate_responses(context);
+    Ok(CategoryGraphQL::from_category(category))
+}
+
+/// Brings a discontinued category back into listings; staff only.
+pub fn restore_category(context: &Context, category_id: i32) -> FieldResult<CategoryGraphQL> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let category = catalog::restore_category(conn, user.id, category_id)
+        .map_err(CatalogError::into_field_error)?;
+    if let Some(cache) = &context.reference_cache {
+        cache.invalidate_categories();
+    }
+    invalidate_responses(context);
+    Ok(CategoryGraphQL::from_category(category))
+}
+
+/// Undoes the change recorded by an audit log entry; staff only.
+pub fn revert_audit_entry(context: &Context, entry_id: i32) -> FieldResult<bool> {
+    let user = context.require_staff_scope(ApiKeyScope::CatalogWrite)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    catalog::revert(conn, user.id, entry_id).map_err(CatalogError::into_field_error)?;
+    // The entry may have changed a manufacturer or a category
+    if let Some(cache) = &context.reference_cache {
+        cache.invalidate();
+    }
+    invalidate_responses(context);
+    Ok(true)
+}
+
+/// Drops cached GraphQL responses so that a catalog change is served right
+/// away rather than after the next catalog revision check.
+fn invalidate_responses(context: &Context) {
+    if let Some(cache) = &context.response_cache {
+        cache.invalidate();
+    }
+}
@@ -0,0 +1,31 @@
+use crate::catalog;
+use crate::graphql_schema::catalog::types::AuditLogEntryType;
+use crate::graphql_schema::context::Context;
+use crate::models::api_keys::ApiKeyScope;
+use crate::types::errors::CatalogError;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Fetches catalog audit log entries for staff, newest first.
+pub fn audit_log(
+    context: &Context,
+    table_name: Option<String>,
+    row_key: Option<i32>,
+    actor_id: Option<i32>,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> FieldResult<Vec<AuditLogEntryType>> {
+    context.require_staff_scope(ApiKeyScope::CatalogRead)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| CatalogError::from(e).into_field_error())?;
+    let entries = catalog::audit_entries(
+        conn,
+        table_name.as_deref(),
+        row_key,
+        actor_id,
+        limit.map(i64::from),
+        offset.map(i64::from),
+    )
+    .map_err(CatalogError::into_field_error)?;
+    Ok(entries.into_iter().map(AuditLogEntryType::from).collect())
+}
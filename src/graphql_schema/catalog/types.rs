@@ -0,0 +1,40 @@
+use crate::models::audit_log::AuditLogEntry;
+use chrono::{DateTime, Utc};
+use juniper::GraphQLObject;
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A recorded write to a catalog table")]
+pub struct AuditLogEntryType {
+    pub id: i32,
+    /// User who made the change; null for changes outside a signed-in request.
+    #[graphql(name = "actorId")]
+    pub actor_id: Option<i32>,
+    #[graphql(name = "tableName")]
+    pub table_name: String,
+    /// Key of the changed row: the part ID for spec tables, otherwise the row ID.
+    #[graphql(name = "rowKey")]
+    pub row_key: i32,
+    /// `INSERT`, `UPDATE` or `DELETE`.
+    pub action: String,
+    /// The row before the change as JSON; null for inserts.
+    pub before: Option<String>,
+    /// The row after the change as JSON; null for deletes.
+    pub after: Option<String>,
+    #[graphql(name = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+}
+
+impl From<AuditLogEntry> for AuditLogEntryType {
+    fn from(entry: AuditLogEntry) -> Self {
+        AuditLogEntryType {
+            id: entry.id,
+            actor_id: entry.actor_id,
+            table_name: entry.table_name,
+            row_key: entry.row_key,
+            action: entry.action,
+            before: entry.before.map(|row| row.to_string()),
+            after: entry.after.map(|row| row.to_string()),
+            changed_at: entry.changed_at,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::configurations::{self, ConfigurationChanges};
+use crate::graphql_schema::configurations::types::{ConfigurationInput, ConfigurationType};
+use crate::graphql_schema::context::Context;
+use crate::types::errors::ConfigurationError;
+use juniper::{FieldResult, IntoFieldError, Nullable};
+
+/// Changes one of the caller's configurations, recording a revision.
+pub fn update_configuration(
+    context: &Context,
+    id: i32,
+    input: ConfigurationInput,
+) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let changes = ConfigurationChanges {
+        name: input.name,
+        description: match input.description {
+            Nullable::ImplicitNull => None,
+            Nullable::ExplicitNull => Some(None),
+            Nullable::Some(description) => {
+                let description = description.trim();
+                Some((!description.is_empty()).then(|| description.to_string()))
+            }
+        },
+        parts: input.parts.map(|parts| {
+            parts
+                .into_iter()
+                .map(|part| (part.part_id, part.quantity))
+                .collect()
+        }),
+    };
+
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::update(conn, user.id, id, changes)
+        .map(ConfigurationType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Sets one of the caller's configurations back to an earlier revision.
+pub fn restore_revision(
+    context: &Context,
+    configuration_id: i32,
+    revision: i32,
+) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::restore_revision(conn, user.id, configuration_id, revision)
+        .map(ConfigurationType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Creates a share link for one of the caller's configurations.
+pub fn share_configuration(context: &Context, id: i32) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::share(conn, user.id, id)
+        .map(ConfigurationType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Revokes the share link of one of the caller's configurations.
+pub fn revoke_configuration_share(context: &Context, id: i32) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::revoke_share(conn, user.id, id)
+        .map(ConfigurationType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Copies a shared configuration into the caller's account.
+pub fn fork_configuration(
+    context: &Context,
+    slug: String,
+    name: Option<String>,
+) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::fork(conn, user.id, &slug, name.as_deref())
+        .map(ConfigurationType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
@@ -0,0 +1,65 @@
+use crate::comparisons;
+use crate::configurations::{self, ConfigurationDetails};
+use crate::graphql_schema::configurations::types::{
+    ConfigurationComparisonType, ConfigurationType, SharedConfigurationType,
+};
+use crate::graphql_schema::context::Context;
+use crate::models::auth::User;
+use crate::types::errors::ConfigurationError;
+use diesel::PgConnection;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Fetches one of the caller's configurations.
+pub fn configuration_by_id(context: &Context, id: i32) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::find_for_user(conn, user.id, id)
+        .map(ConfigurationType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Fetches the configuration shared under `slug`. Works without signing in.
+pub fn shared_configuration(
+    context: &Context,
+    slug: &str,
+) -> FieldResult<Option<SharedConfigurationType>> {
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    configurations::find_shared(conn, slug)
+        .map(|shared| shared.map(SharedConfigurationType::from))
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Compares two configurations, each owned by the caller unless the caller is
+/// staff.
+pub fn compare_configurations(
+    context: &Context,
+    a: i32,
+    b: i32,
+) -> FieldResult<ConfigurationComparisonType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+    let a = visible_configuration(conn, user, a)?;
+    let b = visible_configuration(conn, user, b)?;
+    comparisons::compare(conn, a, b)
+        .map(ConfigurationComparisonType::from)
+        .map_err(ConfigurationError::into_field_error)
+}
+
+/// Fetches a configuration owned by `user`, or any configuration for staff.
+fn visible_configuration(
+    conn: &mut PgConnection,
+    user: &User,
+    id: i32,
+) -> FieldResult<ConfigurationDetails> {
+    let details = configurations::find(conn, id).map_err(ConfigurationError::into_field_error)?;
+    if details.configuration.user_id != Some(user.id) && !user.is_staff() {
+        return Err(ConfigurationError::NotFound(id).into_field_error());
+    }
+    Ok(details)
+}
@@ -0,0 +1,431 @@
+use crate::comparisons::{ChangeKind, Comparison, ComponentChange, SpecTotals};
+use crate::configurations::{
+    self, ConfigurationDetails, ConfiguredPart, QuantityChange, RevisionDetails, RevisionDiff,
+};
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::parts::part_graphql::PartGraphQL;
+use crate::graphql_schema::timed::Timed;
+use crate::types::errors::ConfigurationError;
+use chrono::{DateTime, Utc};
+use juniper::{
+    graphql_object, FieldResult, GraphQLEnum, GraphQLInputObject, GraphQLObject, IntoFieldError,
+    Nullable,
+};
+
+#[derive(GraphQLObject)]
+#[graphql(context = Context, description = "A part of a configuration")]
+pub struct ConfigurationPartType {
+    pub part: Timed<PartGraphQL>,
+    pub quantity: i32,
+}
+
+impl From<ConfiguredPart> for ConfigurationPartType {
+    fn from(configured: ConfiguredPart) -> Self {
+        ConfigurationPartType {
+            part: Timed(PartGraphQL::from_part(configured.part)),
+            quantity: configured.quantity,
+        }
+    }
+}
+
+/// A build of parts
+pub struct ConfigurationType {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub share_slug: Option<String>,
+    pub parts: Vec<ConfigurationPartType>,
+}
+
+impl From<ConfigurationDetails> for ConfigurationType {
+    fn from(details: ConfigurationDetails) -> Self {
+        let configuration = details.configuration;
+        ConfigurationType {
+            id: configuration.id,
+            user_id: configuration.user_id,
+            name: configuration.name,
+            description: configuration.description,
+            created_at: configuration.created_at.and_utc(),
+            updated_at: configuration.updated_at.and_utc(),
+            share_slug: configuration.share_slug,
+            parts: details
+                .parts
+                .into_iter()
+                .map(ConfigurationPartType::from)
+                .collect(),
+        }
+    }
+}
+
+#[graphql_object(context = Context, description = "A build of parts")]
+impl ConfigurationType {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(name = "userId")]
+    fn user_id(&self) -> Option<i32> {
+        self.user_id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    #[graphql(name = "createdAt")]
+    fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    #[graphql(name = "updatedAt")]
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.updated_at
+    }
+
+    /// Slug of the read-only share link, if the configuration is shared.
+    #[graphql(name = "shareSlug")]
+    fn share_slug(&self) -> Option<&str> {
+        self.share_slug.as_deref()
+    }
+
+    fn parts(&self) -> &[ConfigurationPartType] {
+        &self.parts
+    }
+
+    /// Snapshots taken after each change, newest first, keeping at most
+    /// `limit`. Only visible to the owner.
+    fn revisions(
+        &self,
+        context: &Context,
+        limit: Option<i32>,
+    ) -> FieldResult<Vec<ConfigurationRevisionType>> {
+        let user = context.require_user()?;
+        let conn = &mut context
+            .get_connection()
+            .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+        Ok(
+            configurations::revisions(conn, user.id, self.id, limit.map(i64::from))
+                .map_err(ConfigurationError::into_field_error)?
+                .into_iter()
+                .map(ConfigurationRevisionType::from)
+                .collect(),
+        )
+    }
+
+    /// What changed from revision `from` to revision `to`. Only visible to
+    /// the owner.
+    #[graphql(name = "revisionDiff")]
+    fn revision_diff(
+        &self,
+        context: &Context,
+        from: i32,
+        to: i32,
+    ) -> FieldResult<ConfigurationRevisionDiffType> {
+        let user = context.require_user()?;
+        let conn = &mut context
+            .get_connection()
+            .map_err(|e| ConfigurationError::from(e).into_field_error())?;
+        configurations::diff_revisions(conn, user.id, self.id, from, to)
+            .map(ConfigurationRevisionDiffType::from)
+            .map_err(ConfigurationError::into_field_error)
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(context = Context, description = "A snapshot of a configuration taken after a change")]
+pub struct ConfigurationRevisionType {
+    /// Numbered from 1 within the configuration.
+    pub revision: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub parts: Vec<ConfigurationPartType>,
+    /// The revision this one was restored from, if it was made by a restore.
+    #[graphql(name = "restoredFrom")]
+    pub restored_from: Option<i32>,
+    #[graphql(name = "createdAt")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<RevisionDetails> for ConfigurationRevisionType {
+    fn from(details: RevisionDetails) -> Self {
+        let revision = details.revision;
+        ConfigurationRevisionType {
+            revision: revision.revision,
+            name: revision.name,
+            description: revision.description,
+            parts: details
+                .parts
+                .into_iter()
+                .map(ConfigurationPartType::from)
+                .collect(),
+            restored_from: revision.restored_from,
+            created_at: revision.created_at.and_utc(),
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A text field's values in two revisions")]
+pub struct TextChangeType {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(context = Context, description = "A part whose quantity differs between two revisions")]
+pub struct QuantityChangeType {
+    pub part: Timed<PartGraphQL>,
+    pub before: i32,
+    pub after: i32,
+}
+
+impl From<QuantityChange> for QuantityChangeType {
+    fn from(change: QuantityChange) -> Self {
+        QuantityChangeType {
+            part: Timed(PartGraphQL::from_part(change.part)),
+            before: change.from,
+            after: change.to,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(
+    context = Context,
+    description = "What changed between two revisions of a configuration"
+)]
+pub struct ConfigurationRevisionDiffType {
+    #[graphql(name = "fromRevision")]
+    pub from_revision: i32,
+    #[graphql(name = "toRevision")]
+    pub to_revision: i32,
+    /// Null if the name is the same in both revisions.
+    pub name: Option<TextChangeType>,
+    /// Null if the description is the same in both revisions.
+    pub description: Option<TextChangeType>,
+    /// Parts only in the later revision.
+    pub added: Vec<ConfigurationPartType>,
+    /// Parts only in the earlier revision.
+    pub removed: Vec<ConfigurationPartType>,
+    /// Parts in both revisions with different quantities.
+    pub changed: Vec<QuantityChangeType>,
+}
+
+impl From<RevisionDiff> for ConfigurationRevisionDiffType {
+    fn from(diff: RevisionDiff) -> Self {
+        ConfigurationRevisionDiffType {
+            from_revision: diff.from,
+            to_revision: diff.to,
+            name: diff.name.map(|(before, after)| TextChangeType {
+                before: Some(before),
+                after: Some(after),
+            }),
+            description: diff
+                .description
+                .map(|(before, after)| TextChangeType { before, after }),
+            added: diff
+                .added
+                .into_iter()
+                .map(ConfigurationPartType::from)
+                .collect(),
+            removed: diff
+                .removed
+                .into_iter()
+                .map(ConfigurationPartType::from)
+                .collect(),
+            changed: diff
+                .changed
+                .into_iter()
+                .map(QuantityChangeType::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(
+    context = Context,
+    description = "A configuration opened through its share link, without its owner"
+)]
+pub struct SharedConfigurationType {
+    pub name: String,
+    pub description: Option<String>,
+    #[graphql(name = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[graphql(name = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    pub parts: Vec<ConfigurationPartType>,
+}
+
+impl From<ConfigurationDetails> for SharedConfigurationType {
+    fn from(details: ConfigurationDetails) -> Self {
+        let configuration = details.configuration;
+        SharedConfigurationType {
+            name: configuration.name,
+            description: configuration.description,
+            created_at: configuration.created_at.and_utc(),
+            updated_at: configuration.updated_at.and_utc(),
+            parts: details
+                .parts
+                .into_iter()
+                .map(ConfigurationPartType::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "Totals over a configuration's parts, counting quantities")]
+pub struct SpecTotalsType {
+    /// Sum of part prices; parts without a price count as zero.
+    pub price: String,
+    /// CPU cores.
+    pub cores: i32,
+    /// GPU memory, in GB.
+    pub vram: String,
+    /// Memory capacity, in GB.
+    #[graphql(name = "ramCapacity")]
+    pub ram_capacity: i32,
+    /// Storage capacity, in GB.
+    #[graphql(name = "storageCapacity")]
+    pub storage_capacity: i32,
+    /// CPU and GPU TDP, in watts.
+    #[graphql(name = "powerDraw")]
+    pub power_draw: i32,
+}
+
+impl From<SpecTotals> for SpecTotalsType {
+    fn from(totals: SpecTotals) -> Self {
+        SpecTotalsType {
+            price: totals.price.to_string(),
+            cores: totals.cores,
+            vram: totals.vram.to_string(),
+            ram_capacity: totals.ram_capacity,
+            storage_capacity: totals.storage_capacity,
+            power_draw: totals.power_draw,
+        }
+    }
+}
+
+#[derive(GraphQLEnum, Clone, Copy, PartialEq, Eq, Debug)]
+#[graphql(description = "How a component differs between two configurations")]
+pub enum ComponentChangeKindType {
+    /// Only in the second configuration.
+    Added,
+    /// Only in the first configuration.
+    Removed,
+    /// Swapped for another part of the same category, or used in a different
+    /// quantity.
+    Changed,
+}
+
+impl From<ChangeKind> for ComponentChangeKindType {
+    fn from(kind: ChangeKind) -> Self {
+        match kind {
+            ChangeKind::Added => ComponentChangeKindType::Added,
+            ChangeKind::Removed => ComponentChangeKindType::Removed,
+            ChangeKind::Changed => ComponentChangeKindType::Changed,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(
+    context = Context,
+    description = "A component that differs between two configurations"
+)]
+pub struct ComponentChangeType {
+    /// Null for parts without a category.
+    pub category: Option<String>,
+    pub change: ComponentChangeKindType,
+    /// Null if the component was added.
+    pub before: Option<ConfigurationPartType>,
+    /// Null if the component was removed.
+    pub after: Option<ConfigurationPartType>,
+    #[graphql(name = "priceDelta")]
+    pub price_delta: String,
+}
+
+impl From<ComponentChange> for ComponentChangeType {
+    fn from(change: ComponentChange) -> Self {
+        ComponentChangeType {
+            category: change.category,
+            change: change.kind.into(),
+            before: change.before.map(ConfigurationPartType::from),
+            after: change.after.map(ConfigurationPartType::from),
+            price_delta: change.price_delta.to_string(),
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "One side of a configuration comparison")]
+pub struct ComparedConfigurationType {
+    pub id: i32,
+    pub name: String,
+    pub totals: SpecTotalsType,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(
+    context = Context,
+    description = "What changes going from configuration a to configuration b"
+)]
+pub struct ConfigurationComparisonType {
+    pub a: ComparedConfigurationType,
+    pub b: ComparedConfigurationType,
+    /// Components that differ, grouped by category.
+    pub components: Vec<ComponentChangeType>,
+    /// Totals of b minus totals of a.
+    pub delta: SpecTotalsType,
+}
+
+impl From<Comparison> for ConfigurationComparisonType {
+    fn from(comparison: Comparison) -> Self {
+        let delta = comparison.b_totals.minus(&comparison.a_totals);
+        ConfigurationComparisonType {
+            a: ComparedConfigurationType {
+                id: comparison.a.id,
+                name: comparison.a.name,
+                totals: comparison.a_totals.into(),
+            },
+            b: ComparedConfigurationType {
+                id: comparison.b.id,
+                name: comparison.b.name,
+                totals: comparison.b_totals.into(),
+            },
+            components: comparison
+                .changes
+                .into_iter()
+                .map(ComponentChangeType::from)
+                .collect(),
+            delta: delta.into(),
+        }
+    }
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(description = "A part and how many of it a configuration uses")]
+pub struct ConfigurationPartInput {
+    #[graphql(name = "partId")]
+    pub part_id: i32,
+    pub quantity: i32,
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(description = "Changes to a configuration; omitted fields are left unchanged")]
+pub struct ConfigurationInput {
+    /// New name; must not be blank.
+    pub name: Option<String>,
+    /// New description. `null` or a blank string clears it.
+    pub description: Nullable<String>,
+    /// The complete new parts list, replacing the current one.
+    pub parts: Option<Vec<ConfigurationPartInput>>,
+}
@@ -3,35 +3,169 @@
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use diesel::PgConnection;
-use juniper::{FieldError, FieldResult};
+use juniper::{graphql_value, FieldError, FieldResult};
 use log::{debug, error, info};
+use std::sync::Arc;
+use std::time::Instant;
 
+use crate::api_keys::ApiKeyPrincipal;
+use crate::metrics;
+use crate::models::api_keys::ApiKeyScope;
 use crate::models::auth::User;
 use crate::models::parts::category::Category;
 use crate::models::parts::cpu_spec::CpuSpec;
 use crate::models::parts::gpu_spec::GpuSpec;
+use crate::models::parts::inventory::Inventory;
 use crate::models::parts::manufacturer::Manufacturer;
 use crate::models::parts::memory_spec::MemorySpec;
 use crate::models::parts::storage_spec::StorageSpec;
+use crate::notifications::NotificationHub;
+use crate::reference_cache::ReferenceCache;
+use crate::response_cache::ResponseCache;
+use crate::sessions::ClientInfo;
+use crate::verification::EmailLinks;
 
 /// Represents the context that holds the database connection pool.
 pub struct Context {
     pub db: Pool<ConnectionManager<PgConnection>>,
     pub user: Option<User>,
+    /// Session the user was authenticated with, if any.
+    pub session_id: Option<i32>,
+    /// API key the request was authenticated with, if any. `user` is then
+    /// the key's owner, and the request is limited to the key's scopes.
+    pub api_key: Option<ApiKeyPrincipal>,
+    /// User agent and address of the client that made the request.
+    pub client: ClientInfo,
+    /// Correlation ID of the request this context was created for.
+    pub request_id: Option<String>,
+    /// Shared cache of manufacturers and categories, when enabled.
+    pub reference_cache: Option<Arc<ReferenceCache>>,
+    /// Cache of GraphQL responses, for mutations to invalidate after catalog changes.
+    pub response_cache: Option<Arc<ResponseCache>>,
+    /// Source of database change events for subscriptions, when enabled.
+    pub notifications: Option<Arc<NotificationHub>>,
+    /// Sends email verification and sign-in links, when configured.
+    pub email_links: Option<Arc<EmailLinks>>,
 }
 
 impl Context {
     /// Creates a new context with the provided database connection pool.
     pub fn new(db: Pool<ConnectionManager<PgConnection>>, user: Option<User>) -> Self {
         if let Some(ref user) = user {
-            info!(
-                "Creating new Context with user: {}",
-                user.email.as_deref().unwrap_or("no email")
-            );
+            info!("Creating new Context with user ID: {}", user.id);
         } else {
             info!("Creating new Context without a user.");
         }
-        Context { db, user }
+        Context {
+            db,
+            user,
+            session_id: None,
+            api_key: None,
+            client: ClientInfo::default(),
+            request_id: None,
+            reference_cache: None,
+            response_cache: None,
+            notifications: None,
+            email_links: None,
+        }
+    }
+
+    /// Attaches the request's correlation ID to the context.
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Records the session the user was authenticated with.
+    pub fn with_session(mut self, session_id: Option<i32>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
+    /// Records the API key the request was authenticated with.
+    pub fn with_api_key(mut self, api_key: Option<ApiKeyPrincipal>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Records the client the request came from.
+    pub fn with_client(mut self, client: ClientInfo) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Resolves manufacturers and categories through the shared reference cache.
+    pub fn with_reference_cache(mut self, reference_cache: Option<Arc<ReferenceCache>>) -> Self {
+        self.reference_cache = reference_cache;
+        self
+    }
+
+    /// Lets mutations drop cached responses that their changes make stale.
+    pub fn with_response_cache(mut self, response_cache: Option<Arc<ResponseCache>>) -> Self {
+        self.response_cache = response_cache;
+        self
+    }
+
+    /// Lets subscriptions receive database change events from the hub.
+    pub fn with_notifications(mut self, notifications: Option<Arc<NotificationHub>>) -> Self {
+        self.notifications = notifications;
+        self
+    }
+
+    /// Lets mutations email verification and sign-in links.
+    pub fn with_email_links(mut self, email_links: Option<Arc<EmailLinks>>) -> Self {
+        self.email_links = email_links;
+        self
+    }
+
+    /// Returns the authenticated user, or an error for anonymous requests.
+    /// Requests made with an API key are refused, since no scope covers them.
+    pub fn require_user(&self) -> FieldResult<&User> {
+        self.authorize(None)
+    }
+
+    /// Returns the authenticated user if they are a staff member. Requests
+    /// made with an API key are refused, since no scope covers them.
+    pub fn require_staff(&self) -> FieldResult<&User> {
+        let user = self.require_user()?;
+        require_staff_role(user)?;
+        Ok(user)
+    }
+
+    /// Returns the authenticated user, accepting API keys that grant `scope`.
+    pub fn require_scope(&self, scope: ApiKeyScope) -> FieldResult<&User> {
+        self.authorize(Some(scope))
+    }
+
+    /// Returns the authenticated user if they are a staff member, accepting
+    /// API keys of staff members that grant `scope`.
+    pub fn require_staff_scope(&self, scope: ApiKeyScope) -> FieldResult<&User> {
+        let user = self.require_scope(scope)?;
+        require_staff_role(user)?;
+        Ok(user)
+    }
+
+    fn authorize(&self, scope: Option<ApiKeyScope>) -> FieldResult<&User> {
+        let user = self.user.as_ref().ok_or_else(|| {
+            FieldError::new(
+                "You must be logged in to access this resource.",
+                graphql_value!({ "code": "UNAUTHENTICATED" }),
+            )
+        })?;
+        if let Some(api_key) = &self.api_key {
+            let allowed = scope.is_some_and(|scope| api_key.has_scope(scope));
+            if !allowed {
+                let message = match scope {
+                    Some(scope) => format!("This API key does not have the {} scope.", scope),
+                    None => "This resource cannot be accessed with an API key.".to_string(),
+                };
+                return Err(FieldError::new(
+                    message,
+                    graphql_value!({ "code": "FORBIDDEN" }),
+                ));
+            }
+        }
+        Ok(user)
     }
 
     /// Retrieves a connection from the pool.
@@ -39,13 +173,25 @@ impl Context {
         &self,
     ) -> Result<PooledConnection<ConnectionManager<PgConnection>>, diesel::r2d2::PoolError> {
         debug!("Attempting to get a database connection from the pool.");
-        self.db.get()
+        let start_time = Instant::now();
+        let conn = self.db.get();
+        metrics::observe_pool_wait(start_time.elapsed());
+        conn
     }
 
-    /// Fetches a manufacturer by ID from the database.
+    /// Fetches a manufacturer by ID, from the reference cache when available.
     pub fn get_manufacturer_by_id(&self, manufacturer_id_val: i32) -> FieldResult<Manufacturer> {
         use crate::diesel_schema::parts::manufacturers::dsl::*;
 
+        // Rows missing from the cached snapshot may have been added since it was loaded
+        if let Some(cache) = &self.reference_cache {
+            match cache.manufacturer(manufacturer_id_val) {
+                Ok(Some(manufacturer)) => return Ok(manufacturer),
+                Ok(None) => debug!("Manufacturer {} not in cache.", manufacturer_id_val),
+                Err(e) => error!("Error reading manufacturer cache: {}", e),
+            }
+        }
+
         info!("Fetching manufacturer with ID: {}", manufacturer_id_val);
         let mut conn = self.get_connection().map_err(|e| {
             error!("Database connection error: {}", e);
@@ -70,10 +216,18 @@ impl Context {
         Ok(manufacturer)
     }
 
-    /// Fetches a category by ID from the database.
+    /// Fetches a category by ID, from the reference cache when available.
     pub fn get_category_by_id(&self, category_id_val: i32) -> FieldResult<Category> {
         use crate::diesel_schema::parts::categories::dsl::*;
 
+        if let Some(cache) = &self.reference_cache {
+            match cache.category(category_id_val) {
+                Ok(Some(category)) => return Ok(category),
+                Ok(None) => debug!("Category {} not in cache.", category_id_val),
+                Err(e) => error!("Error reading category cache: {}", e),
+            }
+        }
+
         info!("Fetching category with ID: {}", category_id_val);
         let mut conn = self.get_connection().map_err(|e| {
             error!("Database connection error: {}", e);
@@ -98,6 +252,33 @@ impl Context {
         Ok(category)
     }
 
+    /// Fetches the stock levels of a part from the database.
+    pub fn get_inventory_by_part_id(&self, part_id_val: i32) -> FieldResult<Option<Inventory>> {
+        use crate::diesel_schema::parts::inventory::dsl::*;
+
+        debug!("Fetching inventory for part ID: {}", part_id_val);
+        let mut conn = self.get_connection().map_err(|e| {
+            error!("Database connection error: {}", e);
+            FieldError::new(
+                "Database connection error",
+                juniper::Value::scalar(e.to_string()),
+            )
+        })?;
+
+        inventory
+            .filter(part_id.eq(part_id_val))
+            .select(Inventory::as_select())
+            .first::<Inventory>(&mut conn)
+            .optional()
+            .map_err(|e| {
+                error!("Error fetching inventory: {}", e);
+                FieldError::new(
+                    "Error fetching inventory",
+                    juniper::Value::scalar(e.to_string()),
+                )
+            })
+    }
+
     /// Fetches a GPU specification by part ID from the database.
     pub fn get_gpu_spec_by_part_id(&self, part_id_val: i32) -> FieldResult<Option<GpuSpec>> {
         use crate::diesel_schema::parts::gpu_specs::dsl::*;
@@ -234,5 +415,15 @@ impl Context {
     }
 }
 
+fn require_staff_role(user: &User) -> FieldResult<()> {
+    if !user.is_staff() {
+        return Err(FieldError::new(
+            "You do not have permission to access this resource.",
+            graphql_value!({ "code": "FORBIDDEN" }),
+        ));
+    }
+    Ok(())
+}
+
 /// Required to implement Juniper's `Context` trait for integration with GraphQL.
 impl juniper::Context for Context {}
@@ -1,9 +1,19 @@
+pub mod api_keys;
+pub mod catalog;
+pub mod configurations;
 pub mod context;
+pub mod orders;
 pub mod parts;
 pub mod prebuilt;
 pub mod queries;
+pub mod query_limits;
+pub mod quote_requests;
+pub mod root_mutation;
 pub mod root_query;
 pub mod schema;
 pub mod service;
 pub mod software;
+pub mod subscriptions;
+pub mod timed;
 pub mod users;
+pub mod wishlists;
@@ -0,0 +1,71 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::orders::query::single;
+use crate::graphql_schema::orders::types::{OrderStatusType, OrderType};
+use crate::models::orders::OrderStatus;
+use crate::orders;
+use crate::types::errors::OrderError;
+use juniper::{graphql_value, FieldError, FieldResult, IntoFieldError};
+
+/// Longest note a customer can attach to an order.
+const MAX_NOTES_LENGTH: usize = 2000;
+
+/// Converts a configuration into a draft order owned by the caller.
+pub fn create_order(
+    context: &Context,
+    configuration_id: i32,
+    notes: Option<String>,
+) -> FieldResult<OrderType> {
+    let user = context.require_user()?;
+    if notes
+        .as_ref()
+        .is_some_and(|notes| notes.chars().count() > MAX_NOTES_LENGTH)
+    {
+        return Err(FieldError::new(
+            format!("Notes must be at most {} characters", MAX_NOTES_LENGTH),
+            graphql_value!({ "code": "BAD_REQUEST" }),
+        ));
+    }
+
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| OrderError::from(e).into_field_error())?;
+    let order = orders::create_from_configuration(conn, user.id, configuration_id, notes)
+        .map_err(OrderError::into_field_error)?;
+    single(conn, order)
+}
+
+/// Submits one of the caller's draft orders, reserving its parts.
+pub fn submit_order(context: &Context, order_id: i32) -> FieldResult<OrderType> {
+    change_own_order(context, order_id, OrderStatus::Submitted)
+}
+
+/// Cancels one of the caller's orders that is not yet being built.
+pub fn cancel_order(context: &Context, order_id: i32) -> FieldResult<OrderType> {
+    change_own_order(context, order_id, OrderStatus::Cancelled)
+}
+
+/// Moves any order to a new status; staff only.
+pub fn update_order_status(
+    context: &Context,
+    order_id: i32,
+    status: OrderStatusType,
+) -> FieldResult<OrderType> {
+    let user = context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| OrderError::from(e).into_field_error())?;
+    let order = orders::change_status(conn, order_id, status.into(), user)
+        .map_err(OrderError::into_field_error)?;
+    single(conn, order)
+}
+
+/// Changes the status of one of the caller's own orders.
+fn change_own_order(context: &Context, order_id: i32, next: OrderStatus) -> FieldResult<OrderType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| OrderError::from(e).into_field_error())?;
+    let order =
+        orders::change_status(conn, order_id, next, user).map_err(OrderError::into_field_error)?;
+    single(conn, order)
+}
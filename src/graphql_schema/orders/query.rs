@@ -0,0 +1,70 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::orders::types::{OrderStatusType, OrderType};
+use crate::models::api_keys::ApiKeyScope;
+use crate::models::orders::Order;
+use crate::orders::{self, load_details};
+use crate::types::errors::OrderError;
+use diesel::PgConnection;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Fetches the caller's orders, newest first.
+pub fn my_orders(context: &Context) -> FieldResult<Vec<OrderType>> {
+    let user = context.require_scope(ApiKeyScope::OrdersRead)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| OrderError::from(e).into_field_error())?;
+    let orders = orders::for_user(conn, user.id).map_err(OrderError::into_field_error)?;
+    Ok(load_details(conn, orders)
+        .map_err(OrderError::into_field_error)?
+        .into_iter()
+        .map(OrderType::from)
+        .collect())
+}
+
+/// Fetches an order owned by the caller, or any order for staff.
+pub fn order_by_id(context: &Context, order_id: i32) -> FieldResult<OrderType> {
+    let user = context.require_scope(ApiKeyScope::OrdersRead)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| OrderError::from(e).into_field_error())?;
+    let order = orders::find(conn, order_id).map_err(OrderError::into_field_error)?;
+    if order.user_id != user.id && !user.is_staff() {
+        return Err(OrderError::NotFound.into_field_error());
+    }
+    single(conn, order)
+}
+
+/// Fetches orders of every customer for staff.
+pub fn all_orders(
+    context: &Context,
+    status: Option<OrderStatusType>,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> FieldResult<Vec<OrderType>> {
+    context.require_staff_scope(ApiKeyScope::OrdersRead)?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| OrderError::from(e).into_field_error())?;
+    let orders = orders::list(
+        conn,
+        status.map(Into::into),
+        limit.map(i64::from),
+        offset.map(i64::from),
+    )
+    .map_err(OrderError::into_field_error)?;
+    Ok(load_details(conn, orders)
+        .map_err(OrderError::into_field_error)?
+        .into_iter()
+        .map(OrderType::from)
+        .collect())
+}
+
+/// Loads the details of one order.
+pub(crate) fn single(conn: &mut PgConnection, order: Order) -> FieldResult<OrderType> {
+    let details = load_details(conn, vec![order]).map_err(OrderError::into_field_error)?;
+    Ok(details
+        .into_iter()
+        .next()
+        .map(OrderType::from)
+        .expect("details are loaded for every order"))
+}
@@ -0,0 +1,132 @@
+use crate::models::orders::{OrderItem, OrderStatus, OrderStatusChange};
+use crate::orders::OrderDetails;
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, GraphQLObject};
+
+#[derive(GraphQLEnum, Clone, Copy, PartialEq, Eq, Debug)]
+#[graphql(description = "Where an order is in its lifecycle")]
+pub enum OrderStatusType {
+    /// Created from a configuration but not yet submitted.
+    Draft,
+    /// Submitted by the customer; its parts are reserved.
+    Submitted,
+    /// Being assembled.
+    Building,
+    /// Shipped to the customer.
+    Shipped,
+    /// Cancelled before shipping.
+    Cancelled,
+}
+
+impl From<OrderStatus> for OrderStatusType {
+    fn from(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::Draft => OrderStatusType::Draft,
+            OrderStatus::Submitted => OrderStatusType::Submitted,
+            OrderStatus::Building => OrderStatusType::Building,
+            OrderStatus::Shipped => OrderStatusType::Shipped,
+            OrderStatus::Cancelled => OrderStatusType::Cancelled,
+        }
+    }
+}
+
+impl From<OrderStatusType> for OrderStatus {
+    fn from(status: OrderStatusType) -> Self {
+        match status {
+            OrderStatusType::Draft => OrderStatus::Draft,
+            OrderStatusType::Submitted => OrderStatus::Submitted,
+            OrderStatusType::Building => OrderStatus::Building,
+            OrderStatusType::Shipped => OrderStatus::Shipped,
+            OrderStatusType::Cancelled => OrderStatus::Cancelled,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A part on an order, as it was when the order was created")]
+pub struct OrderItemType {
+    #[graphql(name = "partId")]
+    pub part_id: i32,
+    pub name: String,
+    pub model: String,
+    pub quantity: i32,
+    #[graphql(name = "unitPrice")]
+    pub unit_price: String,
+    #[graphql(name = "lineTotal")]
+    pub line_total: String,
+}
+
+impl From<OrderItem> for OrderItemType {
+    fn from(item: OrderItem) -> Self {
+        OrderItemType {
+            line_total: item.line_total().to_string(),
+            part_id: item.part_id,
+            name: item.part_name,
+            model: item.part_model,
+            quantity: item.quantity,
+            unit_price: item.unit_price.to_string(),
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A change of an order's status")]
+pub struct OrderStatusChangeType {
+    pub status: OrderStatusType,
+    #[graphql(name = "changedBy")]
+    pub changed_by: Option<i32>,
+    #[graphql(name = "changedAt")]
+    pub changed_at: DateTime<Utc>,
+}
+
+impl From<OrderStatusChange> for OrderStatusChangeType {
+    fn from(change: OrderStatusChange) -> Self {
+        OrderStatusChangeType {
+            status: change.status.into(),
+            changed_by: change.changed_by,
+            changed_at: change.changed_at,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "An order for a configuration's parts")]
+pub struct OrderType {
+    pub id: i32,
+    #[graphql(name = "userId")]
+    pub user_id: i32,
+    #[graphql(name = "configurationId")]
+    pub configuration_id: Option<i32>,
+    pub status: OrderStatusType,
+    pub total: String,
+    pub notes: Option<String>,
+    pub items: Vec<OrderItemType>,
+    #[graphql(name = "statusHistory")]
+    pub status_history: Vec<OrderStatusChangeType>,
+    #[graphql(name = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[graphql(name = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<OrderDetails> for OrderType {
+    fn from(details: OrderDetails) -> Self {
+        let order = details.order;
+        OrderType {
+            id: order.id,
+            user_id: order.user_id,
+            configuration_id: order.configuration_id,
+            status: order.status.into(),
+            total: order.total.to_string(),
+            notes: order.notes,
+            items: details.items.into_iter().map(OrderItemType::from).collect(),
+            status_history: details
+                .history
+                .into_iter()
+                .map(OrderStatusChangeType::from)
+                .collect(),
+            created_at: order.created_at,
+            updated_at: order.updated_at,
+        }
+    }
+}
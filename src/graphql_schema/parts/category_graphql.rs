@@ -1,6 +1,7 @@
 // src/graphql_schema/parts/category_graphql.rs
 
 use crate::models::parts::category::Category;
+use chrono::{DateTime, Utc};
 use juniper::GraphQLObject;
 
 /// `CategoryGraphQL` struct representing Categories in the GraphQL schema.
@@ -10,6 +11,9 @@ pub struct CategoryGraphQL {
     pub id: i32,
     pub name: String,
     pub description: Option<String>,
+    /// Set once the category is discontinued.
+    #[graphql(name = "deletedAt")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl CategoryGraphQL {
@@ -19,6 +23,7 @@ impl CategoryGraphQL {
             id: category.id,
             name: category.name,
             description: category.description,
+            deleted_at: category.deleted_at,
         }
     }
 }
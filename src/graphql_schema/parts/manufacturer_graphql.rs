@@ -1,6 +1,7 @@
 // src/graphql_schema/parts/manufacturer_graphql.rs
 
 use crate::models::parts::manufacturer::Manufacturer;
+use chrono::{DateTime, Utc};
 use juniper::GraphQLObject;
 
 /// `ManufacturerGraphQL` struct representing Manufacturers in the GraphQL schema.
@@ -10,6 +11,9 @@ pub struct ManufacturerGraphQL {
     pub id: i32,
     pub name: String,
     pub website: Option<String>,
+    /// Set once the manufacturer is discontinued.
+    #[graphql(name = "deletedAt")]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl ManufacturerGraphQL {
@@ -19,6 +23,7 @@ impl ManufacturerGraphQL {
             id: manufacturer.id,
             name: manufacturer.name,
             website: manufacturer.website,
+            deleted_at: manufacturer.deleted_at,
         }
     }
 }
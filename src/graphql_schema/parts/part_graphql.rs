@@ -8,6 +8,7 @@ use crate::graphql_schema::parts::manufacturer_graphql::ManufacturerGraphQL;
 use crate::graphql_schema::parts::memory_spec_graphql::MemorySpecGraphQL;
 use crate::graphql_schema::parts::storage_spec_graphql::StorageSpecGraphQL;
 use crate::models::parts::part::Part;
+use chrono::{DateTime, Utc};
 use juniper::{graphql_object, FieldResult};
 use log::{error, info};
 
@@ -20,6 +21,7 @@ pub struct PartGraphQL {
     pub model: String,
     pub price: Option<String>,
     pub common_specifications: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 impl PartGraphQL {
@@ -33,8 +35,15 @@ impl PartGraphQL {
             model: part.model,
             price: part.price.map(|p| p.to_string()),
             common_specifications: part.common_specifications.map(|spec| spec.to_string()),
+            deleted_at: part.deleted_at,
         }
     }
+
+    /// Looks up the part's unreserved stock, treating untracked parts as having none.
+    fn available_quantity(&self, context: &Context) -> FieldResult<i32> {
+        let stock = context.get_inventory_by_part_id(self.id)?;
+        Ok(stock.map_or(0, |stock| stock.available_quantity().max(0)))
+    }
 }
 
 #[graphql_object(context = Context)]
@@ -59,6 +68,28 @@ impl PartGraphQL {
         self.common_specifications.as_deref()
     }
 
+    /// When the part was discontinued; discontinued parts are not listed or orderable.
+    fn deletedAt(&self) -> Option<DateTime<Utc>> {
+        self.deleted_at
+    }
+
+    /// Whether any units are available to order.
+    fn inStock(&self, context: &Context) -> FieldResult<bool> {
+        Ok(self.available_quantity(context)? > 0)
+    }
+
+    /// Units on hand that are not reserved for orders; 0 for untracked parts.
+    fn availableQuantity(&self, context: &Context) -> FieldResult<i32> {
+        self.available_quantity(context)
+    }
+
+    /// Whether available stock has fallen to the reorder threshold; false for
+    /// untracked parts.
+    fn needsReorder(&self, context: &Context) -> FieldResult<bool> {
+        let stock = context.get_inventory_by_part_id(self.id)?;
+        Ok(stock.is_some_and(|stock| stock.needs_reorder()))
+    }
+
     fn manufacturer(&self, context: &Context) -> FieldResult<Option<ManufacturerGraphQL>> {
         info!("Resolving manufacturer for part ID: {}", self.id);
         match self.manufacturer_id {
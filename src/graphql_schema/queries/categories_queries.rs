@@ -9,7 +9,7 @@ use log::error;
 pub struct CategoriesQueries;
 
 impl CategoriesQueries {
-    /// Fetches all categories from the database.
+    /// Fetches all categories that are not discontinued.
     pub fn get_all_categories(context: &Context) -> FieldResult<Vec<CategoryGraphQL>> {
         use crate::diesel_schema::parts::categories::dsl::*;
 
@@ -21,13 +21,16 @@ impl CategoriesQueries {
             )
         })?;
 
-        let categories_list = categories.load::<Category>(&mut conn).map_err(|e| {
-            error!("Error fetching categories: {}", e);
-            FieldError::new(
-                "Error fetching categories",
-                juniper::Value::scalar(e.to_string()),
-            )
-        })?;
+        let categories_list = categories
+            .filter(deleted_at.is_null())
+            .load::<Category>(&mut conn)
+            .map_err(|e| {
+                error!("Error fetching categories: {}", e);
+                FieldError::new(
+                    "Error fetching categories",
+                    juniper::Value::scalar(e.to_string()),
+                )
+            })?;
 
         let graphql_categories = categories_list
             .into_iter()
@@ -37,7 +40,7 @@ impl CategoriesQueries {
         Ok(graphql_categories)
     }
 
-    /// Fetches a specific category by ID.
+    /// Fetches a specific category by ID, unless it is discontinued.
     pub fn get_category_by_id(
         context: &Context,
         category_id_val: i32,
@@ -54,6 +57,7 @@ impl CategoriesQueries {
 
         let category_opt = categories
             .filter(id.eq(category_id_val))
+            .filter(deleted_at.is_null())
             .first::<Category>(&mut conn)
             .optional()
             .map_err(|e| {
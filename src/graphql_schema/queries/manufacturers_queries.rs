@@ -9,7 +9,7 @@ use log::error;
 pub struct ManufacturersQueries;
 
 impl ManufacturersQueries {
-    /// Fetches all manufacturers from the database.
+    /// Fetches all manufacturers that are not discontinued.
     pub fn get_all_manufacturers(context: &Context) -> FieldResult<Vec<ManufacturerGraphQL>> {
         use crate::diesel_schema::parts::manufacturers::dsl::*;
 
@@ -21,13 +21,16 @@ impl ManufacturersQueries {
             )
         })?;
 
-        let manufacturers_list = manufacturers.load::<Manufacturer>(&mut conn).map_err(|e| {
-            error!("Error fetching manufacturers: {}", e);
-            FieldError::new(
-                "Error fetching manufacturers",
-                juniper::Value::scalar(e.to_string()),
-            )
-        })?;
+        let manufacturers_list = manufacturers
+            .filter(deleted_at.is_null())
+            .load::<Manufacturer>(&mut conn)
+            .map_err(|e| {
+                error!("Error fetching manufacturers: {}", e);
+                FieldError::new(
+                    "Error fetching manufacturers",
+                    juniper::Value::scalar(e.to_string()),
+                )
+            })?;
 
         let graphql_manufacturers = manufacturers_list
             .into_iter()
@@ -37,7 +40,7 @@ impl ManufacturersQueries {
         Ok(graphql_manufacturers)
     }
 
-    /// Fetches a specific manufacturer by ID.
+    /// Fetches a specific manufacturer by ID, unless it is discontinued.
     pub fn get_manufacturer_by_id(
         context: &Context,
         manufacturer_id_val: i32,
@@ -54,6 +57,7 @@ impl ManufacturersQueries {
 
         let manufacturer_opt = manufacturers
             .filter(id.eq(manufacturer_id_val))
+            .filter(deleted_at.is_null())
             .first::<Manufacturer>(&mut conn)
             .optional()
             .map_err(|e| {
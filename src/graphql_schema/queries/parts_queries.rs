@@ -5,12 +5,15 @@ use diesel::prelude::*;
 use juniper::FieldResult;
 use log::error;
 
-/// Fetches all parts from the database with optional pagination.
+/// Fetches all parts that are not discontinued with optional pagination,
+/// optionally keeping only parts that are (or are not) in stock.
 pub fn get_all_parts(
     context: &Context,
     limit_val: Option<i32>,
     offset_val: Option<i32>,
+    in_stock: Option<bool>,
 ) -> FieldResult<Vec<PartGraphQL>> {
+    use crate::diesel_schema::parts::inventory;
     use crate::diesel_schema::parts::parts::dsl::*;
 
     let mut conn = context.get_connection().map_err(|e| {
@@ -21,7 +24,19 @@ pub fn get_all_parts(
         )
     })?;
 
-    let mut query = parts.into_boxed();
+    // Discontinued parts stay reachable by ID but are not listed
+    let mut query = parts.filter(deleted_at.is_null()).into_boxed();
+
+    if let Some(in_stock) = in_stock {
+        let stocked = inventory::table
+            .filter((inventory::quantity_on_hand - inventory::quantity_reserved).gt(0))
+            .select(inventory::part_id);
+        query = if in_stock {
+            query.filter(id.eq_any(stocked))
+        } else {
+            query.filter(diesel::dsl::not(id.eq_any(stocked)))
+        };
+    }
 
     if let Some(l) = limit_val {
         query = query.limit(l as i64); // Diesel expects i64 for limit and offset
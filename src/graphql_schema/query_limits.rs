@@ -0,0 +1,315 @@
+// src/graphql_schema/query_limits.rs
+
+use super::schema::Schema;
+use crate::settings::GraphQLSettings;
+use crate::types::errors::QueryLimitError;
+use juniper::meta::MetaType;
+use juniper::parser::parse_document_source;
+use juniper::{
+    DefaultScalarValue, Definition, InputValue, Operation, OperationType, Selection, Type,
+    Variables,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Arguments whose value bounds the number of items a list field returns.
+const LIST_SIZE_ARGUMENTS: [&str; 2] = ["limit", "first"];
+
+/// Depth and estimated cost of a GraphQL operation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryCost {
+    pub depth: usize,
+    pub complexity: usize,
+}
+
+/// Computes the depth and cost of the operation that would be executed for `query`.
+///
+/// Each field costs 1 unless overridden in `field_costs`, and the cost of a
+/// list field's selections is multiplied by its `limit`/`first` argument, or by
+/// `default_list_size` when neither is given. Introspection fields count toward
+/// depth but not cost. Returns `None` when the query does not parse or names no
+/// operation, leaving the error to be reported by execution.
+pub fn analyze_query(
+    schema: &Schema,
+    query: &str,
+    operation_name: Option<&str>,
+    variables: &Variables,
+    settings: &GraphQLSettings,
+) -> Option<QueryCost> {
+    let document = parse_document_source(query, &schema.schema).ok()?;
+
+    let mut operations = Vec::new();
+    let mut fragments = HashMap::new();
+    for definition in &document {
+        match definition {
+            Definition::Operation(operation) => operations.push(&operation.item),
+            Definition::Fragment(fragment) => {
+                fragments.insert(
+                    fragment.item.name.item,
+                    (
+                        fragment.item.type_condition.item,
+                        &fragment.item.selection_set,
+                    ),
+                );
+            }
+        }
+    }
+
+    let operation = select_operation(operations, operation_name)?;
+
+    let root_type = match operation.operation_type {
+        OperationType::Query => Some(schema.schema.concrete_query_type()),
+        OperationType::Mutation => schema.schema.concrete_mutation_type(),
+        OperationType::Subscription => schema.schema.concrete_subscription_type(),
+    };
+
+    let mut analyzer = Analyzer {
+        schema,
+        settings,
+        variables,
+        fragments,
+        visiting: Vec::new(),
+        measured: HashMap::new(),
+    };
+    Some(analyzer.measure(&operation.selection_set, root_type, false))
+}
+
+/// Name, type and root field names of the operation that would be executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperationSummary {
+    /// Name of the operation in the document, if it has one.
+    pub name: Option<String>,
+    pub operation_type: OperationType,
+    pub root_fields: Vec<String>,
+}
+
+/// Summarizes the operation that would be executed for `query`, or returns
+/// `None` when the query does not parse or names no operation.
+pub fn summarize_operation(
+    schema: &Schema,
+    query: &str,
+    operation_name: Option<&str>,
+) -> Option<OperationSummary> {
+    let document = parse_document_source(query, &schema.schema).ok()?;
+
+    let mut operations = Vec::new();
+    let mut fragments = HashMap::new();
+    for definition in &document {
+        match definition {
+            Definition::Operation(operation) => operations.push(&operation.item),
+            Definition::Fragment(fragment) => {
+                fragments.insert(fragment.item.name.item, &fragment.item.selection_set);
+            }
+        }
+    }
+    let operation = select_operation(operations, operation_name)?;
+
+    let mut root_fields = Vec::new();
+    collect_field_names(
+        &operation.selection_set,
+        &fragments,
+        &mut HashSet::new(),
+        &mut root_fields,
+    );
+    Some(OperationSummary {
+        name: operation.name.as_ref().map(|name| name.item.to_string()),
+        operation_type: operation.operation_type.clone(),
+        root_fields,
+    })
+}
+
+/// Collects the names of the fields in a selection set, expanding fragments.
+fn collect_field_names<'q>(
+    selections: &[Selection<'q, DefaultScalarValue>],
+    fragments: &HashMap<&str, &Vec<Selection<'q, DefaultScalarValue>>>,
+    expanded: &mut HashSet<&'q str>,
+    names: &mut Vec<String>,
+) {
+    for selection in selections {
+        match selection {
+            Selection::Field(field) => {
+                let name = field.item.name.item;
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+            Selection::FragmentSpread(spread) => {
+                // Each fragment is expanded once, which also stops fragment cycles
+                let name = spread.item.name.item;
+                if let Some(selection_set) = fragments.get(name) {
+                    if expanded.insert(name) {
+                        collect_field_names(selection_set, fragments, expanded, names);
+                    }
+                }
+            }
+            Selection::InlineFragment(fragment) => {
+                collect_field_names(&fragment.item.selection_set, fragments, expanded, names)
+            }
+        }
+    }
+}
+
+/// Picks the operation named in the request, or the only operation in the document.
+fn select_operation<'o, 'q>(
+    operations: Vec<&'o Operation<'q, DefaultScalarValue>>,
+    operation_name: Option<&str>,
+) -> Option<&'o Operation<'q, DefaultScalarValue>> {
+    match operation_name {
+        Some(name) => operations
+            .into_iter()
+            .find(|op| op.name.as_ref().map(|n| n.item) == Some(name)),
+        None if operations.len() == 1 => Some(operations[0]),
+        None => None,
+    }
+}
+
+/// Rejects queries that exceed the configured depth or complexity limits.
+pub fn check_query_limits(
+    cost: QueryCost,
+    settings: &GraphQLSettings,
+) -> Result<(), QueryLimitError> {
+    if settings.max_depth > 0 && cost.depth > settings.max_depth {
+        return Err(QueryLimitError::TooDeep {
+            depth: cost.depth,
+            max_depth: settings.max_depth,
+        });
+    }
+    if settings.max_complexity > 0 && cost.complexity > settings.max_complexity {
+        return Err(QueryLimitError::TooComplex {
+            complexity: cost.complexity,
+            max_complexity: settings.max_complexity,
+        });
+    }
+    Ok(())
+}
+
+type FragmentMap<'d, 'q> = HashMap<&'q str, (&'q str, &'d Vec<Selection<'q, DefaultScalarValue>>)>;
+
+struct Analyzer<'s, 'd, 'q> {
+    schema: &'s Schema,
+    settings: &'s GraphQLSettings,
+    variables: &'s Variables,
+    fragments: FragmentMap<'d, 'q>,
+    /// Fragments currently being expanded, guarding against fragment cycles.
+    visiting: Vec<&'q str>,
+    /// Costs of the fragments measured so far, keyed by name and whether they
+    /// were spread inside introspection, so repeated spreads are not walked again.
+    measured: HashMap<(&'q str, bool), QueryCost>,
+}
+
+impl<'s, 'd, 'q> Analyzer<'s, 'd, 'q> {
+    /// Measures a selection set whose fields belong to `parent`, if known.
+    fn measure(
+        &mut self,
+        selections: &'d [Selection<'q, DefaultScalarValue>],
+        parent: Option<&'s MetaType<'s>>,
+        introspection: bool,
+    ) -> QueryCost {
+        let mut total = QueryCost::default();
+
+        for selection in selections {
+            let cost = match selection {
+                Selection::Field(field) => {
+                    let field = &field.item;
+                    let name = field.name.item;
+                    let introspection = introspection || name.starts_with("__");
+                    let meta_field = parent.and_then(|p| p.field_by_name(name));
+
+                    let child_type = meta_field.and_then(|f| {
+                        self.schema
+                            .schema
+                            .concrete_type_by_name(f.field_type.innermost_name())
+                    });
+                    let children = match &field.selection_set {
+                        Some(selection_set) => {
+                            self.measure(selection_set, child_type, introspection)
+                        }
+                        None => QueryCost::default(),
+                    };
+
+                    let own_cost = if introspection {
+                        0
+                    } else {
+                        parent
+                            .and_then(|p| p.name())
+                            .and_then(|type_name| {
+                                self.settings
+                                    .field_costs
+                                    .get(&format!("{}.{}", type_name, name))
+                            })
+                            .copied()
+                            .unwrap_or(1)
+                    };
+
+                    let is_list = meta_field.is_some_and(|f| is_list_type(&f.field_type));
+                    let multiplier = if is_list {
+                        field
+                            .arguments
+                            .as_ref()
+                            .and_then(|args| {
+                                LIST_SIZE_ARGUMENTS
+                                    .iter()
+                                    .find_map(|arg| args.item.get(arg))
+                            })
+                            .and_then(|value| self.int_value(&value.item))
+                            .map(|size| size.max(0) as usize)
+                            .unwrap_or(self.settings.default_list_size)
+                    } else {
+                        1
+                    };
+
+                    QueryCost {
+                        depth: children.depth + 1,
+                        complexity: own_cost
+                            .saturating_add(multiplier.saturating_mul(children.complexity)),
+                    }
+                }
+                Selection::FragmentSpread(spread) => {
+                    let name = spread.item.name.item;
+                    if let Some(cost) = self.measured.get(&(name, introspection)) {
+                        *cost
+                    } else {
+                        match self.fragments.get(name).copied() {
+                            Some((type_condition, selection_set))
+                                if !self.visiting.contains(&name) =>
+                            {
+                                self.visiting.push(name);
+                                let fragment_type =
+                                    self.schema.schema.concrete_type_by_name(type_condition);
+                                let cost =
+                                    self.measure(selection_set, fragment_type, introspection);
+                                self.visiting.pop();
+                                self.measured.insert((name, introspection), cost);
+                                cost
+                            }
+                            _ => QueryCost::default(),
+                        }
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    let fragment_type = match &fragment.item.type_condition {
+                        Some(condition) => self.schema.schema.concrete_type_by_name(condition.item),
+                        None => parent,
+                    };
+                    self.measure(&fragment.item.selection_set, fragment_type, introspection)
+                }
+            };
+
+            total.depth = total.depth.max(cost.depth);
+            total.complexity = total.complexity.saturating_add(cost.complexity);
+        }
+
+        total
+    }
+
+    /// Resolves an integer argument, looking up variables in the request.
+    fn int_value(&self, value: &InputValue) -> Option<i32> {
+        match value {
+            InputValue::Variable(name) => self.variables.get(name)?.as_int_value(),
+            other => other.as_int_value(),
+        }
+    }
+}
+
+fn is_list_type(field_type: &Type) -> bool {
+    matches!(field_type, Type::List(_) | Type::NonNullList(_))
+}
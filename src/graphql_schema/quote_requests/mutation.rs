@@ -0,0 +1,154 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::quote_requests::types::{
+    QuoteRequestInput, QuoteRequestStatusType, QuoteRequestType,
+};
+use crate::graphql_schema::service::query::ServiceQuery;
+use crate::models::quote_requests::NewQuoteRequest;
+use crate::quote_requests;
+use crate::types::errors::QuoteRequestError;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Longest name or company name accepted on a quote request.
+const MAX_NAME_LENGTH: usize = 200;
+/// Longest email address accepted on a quote request.
+const MAX_EMAIL_LENGTH: usize = 320;
+/// Longest phone number accepted on a quote request.
+const MAX_PHONE_LENGTH: usize = 50;
+/// Longest requirements or staff notes accepted on a quote request.
+const MAX_TEXT_LENGTH: usize = 5000;
+
+/// Stores a quote request from a visitor or the signed-in user.
+pub fn submit_quote_request(
+    context: &Context,
+    input: QuoteRequestInput,
+) -> FieldResult<QuoteRequestType> {
+    let user_id = context.user.as_ref().map(|user| user.id);
+    let request = validate(input, user_id).map_err(QuoteRequestError::into_field_error)?;
+
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| QuoteRequestError::from(e).into_field_error())?;
+    quote_requests::submit(conn, request)
+        .map(QuoteRequestType::from)
+        .map_err(QuoteRequestError::into_field_error)
+}
+
+/// Assigns a quote request to a staff member, or unassigns it; staff only.
+pub fn assign_quote_request(
+    context: &Context,
+    id: i32,
+    assignee_id: Option<i32>,
+) -> FieldResult<QuoteRequestType> {
+    context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| QuoteRequestError::from(e).into_field_error())?;
+    quote_requests::assign(conn, id, assignee_id)
+        .map(QuoteRequestType::from)
+        .map_err(QuoteRequestError::into_field_error)
+}
+
+/// Updates the status and staff notes of a quote request; staff only.
+pub fn update_quote_request(
+    context: &Context,
+    id: i32,
+    status: Option<QuoteRequestStatusType>,
+    staff_notes: Option<String>,
+) -> FieldResult<QuoteRequestType> {
+    context.require_staff()?;
+    if let Some(notes) = &staff_notes {
+        check_length("Staff notes", notes, MAX_TEXT_LENGTH)
+            .map_err(QuoteRequestError::into_field_error)?;
+    }
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| QuoteRequestError::from(e).into_field_error())?;
+    quote_requests::update(conn, id, status.map(Into::into), staff_notes)
+        .map(QuoteRequestType::from)
+        .map_err(QuoteRequestError::into_field_error)
+}
+
+/// Checks the submitted fields and trims surrounding whitespace. Empty
+/// optional fields are stored as `NULL`.
+fn validate(
+    input: QuoteRequestInput,
+    user_id: Option<i32>,
+) -> Result<NewQuoteRequest, QuoteRequestError> {
+    if ServiceQuery::find_service(&input.service_slug).is_none() {
+        return Err(QuoteRequestError::UnknownService(input.service_slug));
+    }
+
+    let contact_name = required("Contact name", &input.contact_name, MAX_NAME_LENGTH)?;
+    let contact_email = required("Contact email", &input.contact_email, MAX_EMAIL_LENGTH)?;
+    if !is_plausible_email(&contact_email) {
+        return Err(QuoteRequestError::Validation(
+            "Contact email is not a valid email address".to_string(),
+        ));
+    }
+    let requirements = required("Requirements", &input.requirements, MAX_TEXT_LENGTH)?;
+    let contact_phone = optional("Contact phone", input.contact_phone, MAX_PHONE_LENGTH)?;
+    let company = optional("Company", input.company, MAX_NAME_LENGTH)?;
+
+    Ok(NewQuoteRequest {
+        user_id,
+        service_slug: input.service_slug,
+        configuration_id: input.configuration_id,
+        contact_name,
+        contact_email,
+        contact_phone,
+        company,
+        requirements,
+    })
+}
+
+fn required(field: &str, value: &str, max_length: usize) -> Result<String, QuoteRequestError> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(QuoteRequestError::Validation(format!(
+            "{} is required",
+            field
+        )));
+    }
+    check_length(field, value, max_length)?;
+    Ok(value.to_string())
+}
+
+fn optional(
+    field: &str,
+    value: Option<String>,
+    max_length: usize,
+) -> Result<Option<String>, QuoteRequestError> {
+    match value.as_deref().map(str::trim) {
+        None | Some("") => Ok(None),
+        Some(value) => {
+            check_length(field, value, max_length)?;
+            Ok(Some(value.to_string()))
+        }
+    }
+}
+
+fn check_length(field: &str, value: &str, max_length: usize) -> Result<(), QuoteRequestError> {
+    if value.chars().count() > max_length {
+        return Err(QuoteRequestError::Validation(format!(
+            "{} must be at most {} characters",
+            field, max_length
+        )));
+    }
+    Ok(())
+}
+
+/// Accepts addresses with a non-empty local part and a dotted domain; the
+/// address is only used to contact the requester, so this is deliberately loose.
+fn is_plausible_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !email.contains(char::is_whitespace)
+                && domain.contains('.')
+                && domain
+                    .split('.')
+                    .all(|label| !label.is_empty() && !label.contains('@'))
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,42 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::quote_requests::types::{QuoteRequestStatusType, QuoteRequestType};
+use crate::quote_requests;
+use crate::types::errors::QuoteRequestError;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Fetches quote requests for staff, optionally filtered by status or assignee.
+pub fn all_quote_requests(
+    context: &Context,
+    status: Option<QuoteRequestStatusType>,
+    assigned_to: Option<i32>,
+    limit: Option<i32>,
+    offset: Option<i32>,
+) -> FieldResult<Vec<QuoteRequestType>> {
+    context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| QuoteRequestError::from(e).into_field_error())?;
+    let quote_requests = quote_requests::list(
+        conn,
+        status.map(Into::into),
+        assigned_to,
+        limit.map(i64::from),
+        offset.map(i64::from),
+    )
+    .map_err(QuoteRequestError::into_field_error)?;
+    Ok(quote_requests
+        .into_iter()
+        .map(QuoteRequestType::from)
+        .collect())
+}
+
+/// Fetches a quote request by ID for staff.
+pub fn quote_request_by_id(context: &Context, id: i32) -> FieldResult<QuoteRequestType> {
+    context.require_staff()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| QuoteRequestError::from(e).into_field_error())?;
+    quote_requests::find(conn, id)
+        .map(QuoteRequestType::from)
+        .map_err(QuoteRequestError::into_field_error)
+}
@@ -0,0 +1,111 @@
+use crate::models::quote_requests::{QuoteRequest, QuoteRequestStatus};
+use chrono::{DateTime, Utc};
+use juniper::{GraphQLEnum, GraphQLInputObject, GraphQLObject};
+
+#[derive(GraphQLEnum, Clone, Copy, PartialEq, Eq, Debug)]
+#[graphql(description = "How far staff have followed up on a quote request")]
+pub enum QuoteRequestStatusType {
+    /// Received but not yet handled.
+    New,
+    /// Staff have reached out to the requester.
+    Contacted,
+    /// A quote has been sent.
+    Quoted,
+    /// The requester accepted the quote.
+    Won,
+    /// The requester declined or stopped responding.
+    Lost,
+}
+
+impl From<QuoteRequestStatus> for QuoteRequestStatusType {
+    fn from(status: QuoteRequestStatus) -> Self {
+        match status {
+            QuoteRequestStatus::New => QuoteRequestStatusType::New,
+            QuoteRequestStatus::Contacted => QuoteRequestStatusType::Contacted,
+            QuoteRequestStatus::Quoted => QuoteRequestStatusType::Quoted,
+            QuoteRequestStatus::Won => QuoteRequestStatusType::Won,
+            QuoteRequestStatus::Lost => QuoteRequestStatusType::Lost,
+        }
+    }
+}
+
+impl From<QuoteRequestStatusType> for QuoteRequestStatus {
+    fn from(status: QuoteRequestStatusType) -> Self {
+        match status {
+            QuoteRequestStatusType::New => QuoteRequestStatus::New,
+            QuoteRequestStatusType::Contacted => QuoteRequestStatus::Contacted,
+            QuoteRequestStatusType::Quoted => QuoteRequestStatus::Quoted,
+            QuoteRequestStatusType::Won => QuoteRequestStatus::Won,
+            QuoteRequestStatusType::Lost => QuoteRequestStatus::Lost,
+        }
+    }
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(description = "Contact details and requirements for a quote")]
+pub struct QuoteRequestInput {
+    /// Slug of the service the quote is for, as returned by `services`.
+    #[graphql(name = "serviceSlug")]
+    pub service_slug: String,
+    /// Optional configuration to base the quote on.
+    #[graphql(name = "configurationId")]
+    pub configuration_id: Option<i32>,
+    #[graphql(name = "contactName")]
+    pub contact_name: String,
+    #[graphql(name = "contactEmail")]
+    pub contact_email: String,
+    #[graphql(name = "contactPhone")]
+    pub contact_phone: Option<String>,
+    pub company: Option<String>,
+    pub requirements: String,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A request for a quote on one of the offered services")]
+pub struct QuoteRequestType {
+    pub id: i32,
+    #[graphql(name = "userId")]
+    pub user_id: Option<i32>,
+    #[graphql(name = "serviceSlug")]
+    pub service_slug: String,
+    #[graphql(name = "configurationId")]
+    pub configuration_id: Option<i32>,
+    #[graphql(name = "contactName")]
+    pub contact_name: String,
+    #[graphql(name = "contactEmail")]
+    pub contact_email: String,
+    #[graphql(name = "contactPhone")]
+    pub contact_phone: Option<String>,
+    pub company: Option<String>,
+    pub requirements: String,
+    pub status: QuoteRequestStatusType,
+    #[graphql(name = "assignedTo")]
+    pub assigned_to: Option<i32>,
+    #[graphql(name = "staffNotes")]
+    pub staff_notes: Option<String>,
+    #[graphql(name = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[graphql(name = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<QuoteRequest> for QuoteRequestType {
+    fn from(quote_request: QuoteRequest) -> Self {
+        QuoteRequestType {
+            id: quote_request.id,
+            user_id: quote_request.user_id,
+            service_slug: quote_request.service_slug,
+            configuration_id: quote_request.configuration_id,
+            contact_name: quote_request.contact_name,
+            contact_email: quote_request.contact_email,
+            contact_phone: quote_request.contact_phone,
+            company: quote_request.company,
+            requirements: quote_request.requirements,
+            status: quote_request.status.into(),
+            assigned_to: quote_request.assigned_to,
+            staff_notes: quote_request.staff_notes,
+            created_at: quote_request.created_at,
+            updated_at: quote_request.updated_at,
+        }
+    }
+}
@@ -0,0 +1,454 @@
+// src/graphql_schema/root_mutation.rs
+
+use crate::graphql_schema::api_keys::mutation as api_key_mutation;
+use crate::graphql_schema::api_keys::types::{ApiKeyType, IssueApiKeyInput, IssuedApiKeyType};
+use crate::graphql_schema::catalog::mutation as catalog_mutation;
+use crate::graphql_schema::configurations::mutation as configuration_mutation;
+use crate::graphql_schema::configurations::types::{ConfigurationInput, ConfigurationType};
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::orders::mutation;
+use crate::graphql_schema::orders::types::{OrderStatusType, OrderType};
+use crate::graphql_schema::parts::category_graphql::CategoryGraphQL;
+use crate::graphql_schema::parts::manufacturer_graphql::ManufacturerGraphQL;
+use crate::graphql_schema::parts::part_graphql::PartGraphQL;
+use crate::graphql_schema::quote_requests::mutation as quote_request_mutation;
+use crate::graphql_schema::quote_requests::types::{
+    QuoteRequestInput, QuoteRequestStatusType, QuoteRequestType,
+};
+use crate::graphql_schema::timed::Timed;
+use crate::graphql_schema::users::mutation as user_mutation;
+use crate::graphql_schema::users::types::{
+    LinkedAccountType, ProfileInput, SessionType, SignInPayload, UserType,
+};
+use crate::graphql_schema::wishlists::mutation as wishlist_mutation;
+use crate::graphql_schema::wishlists::types::WishlistType;
+use juniper::FieldResult;
+use log::{error, info};
+use std::future::Future;
+use std::time::Instant;
+
+/// RootMutation struct that defines the available GraphQL mutations in the API.
+pub struct RootMutation;
+
+#[juniper::graphql_object(context = Context)]
+impl RootMutation {
+    /// Converts a configuration into a draft order, freezing its parts and prices.
+    fn create_order(
+        context: &Context,
+        configuration_id: i32,
+        notes: Option<String>,
+    ) -> FieldResult<OrderType> {
+        info!(
+            "Executing 'createOrder' mutation with configuration_id: {}",
+            configuration_id
+        );
+        observe("createOrder", || {
+            mutation::create_order(context, configuration_id, notes)
+        })
+    }
+
+    /// Submits a draft order, reserving its parts.
+    fn submit_order(context: &Context, id: i32) -> FieldResult<OrderType> {
+        info!("Executing 'submitOrder' mutation with id: {}", id);
+        observe("submitOrder", || mutation::submit_order(context, id))
+    }
+
+    /// Cancels an order that is not yet being built.
+    fn cancel_order(context: &Context, id: i32) -> FieldResult<OrderType> {
+        info!("Executing 'cancelOrder' mutation with id: {}", id);
+        observe("cancelOrder", || mutation::cancel_order(context, id))
+    }
+
+    /// Moves an order to a new status. Staff only.
+    fn update_order_status(
+        context: &Context,
+        id: i32,
+        status: OrderStatusType,
+    ) -> FieldResult<OrderType> {
+        info!(
+            "Executing 'updateOrderStatus' mutation with id: {}, status: {:?}",
+            id, status
+        );
+        observe("updateOrderStatus", || {
+            mutation::update_order_status(context, id, status)
+        })
+    }
+
+    /// Requests a quote for one of the offered services. Visitors do not need
+    /// to sign in.
+    fn submit_quote_request(
+        context: &Context,
+        input: QuoteRequestInput,
+    ) -> FieldResult<QuoteRequestType> {
+        info!(
+            "Executing 'submitQuoteRequest' mutation for service: {}",
+            input.service_slug
+        );
+        observe("submitQuoteRequest", || {
+            quote_request_mutation::submit_quote_request(context, input)
+        })
+    }
+
+    /// Assigns a quote request to a staff member, or unassigns it when
+    /// `assigneeId` is null. Staff only.
+    fn assign_quote_request(
+        context: &Context,
+        id: i32,
+        assignee_id: Option<i32>,
+    ) -> FieldResult<QuoteRequestType> {
+        info!(
+            "Executing 'assignQuoteRequest' mutation with id: {}, assignee_id: {:?}",
+            id, assignee_id
+        );
+        observe("assignQuoteRequest", || {
+            quote_request_mutation::assign_quote_request(context, id, assignee_id)
+        })
+    }
+
+    /// Updates the status and staff notes of a quote request. Staff only.
+    fn update_quote_request(
+        context: &Context,
+        id: i32,
+        status: Option<QuoteRequestStatusType>,
+        staff_notes: Option<String>,
+    ) -> FieldResult<QuoteRequestType> {
+        info!(
+            "Executing 'updateQuoteRequest' mutation with id: {}, status: {:?}",
+            id, status
+        );
+        observe("updateQuoteRequest", || {
+            quote_request_mutation::update_quote_request(context, id, status, staff_notes)
+        })
+    }
+
+    /// Discontinues a part: it is no longer listed or orderable, but existing
+    /// configurations and orders keep referring to it. Staff only.
+    fn delete_part(context: &Context, id: i32) -> FieldResult<Timed<PartGraphQL>> {
+        info!("Executing 'deletePart' mutation with id: {}", id);
+        observe("deletePart", || catalog_mutation::delete_part(context, id)).map(Timed)
+    }
+
+    /// Brings a discontinued part back into listings. Staff only.
+    fn restore_part(context: &Context, id: i32) -> FieldResult<Timed<PartGraphQL>> {
+        info!("Executing 'restorePart' mutation with id: {}", id);
+        observe("restorePart", || {
+            catalog_mutation::restore_part(context, id)
+        })
+        .map(Timed)
+    }
+
+    /// Discontinues a manufacturer: it is no longer listed or fetchable by ID,
+    /// but parts keep referring to it. Staff only.
+    fn delete_manufacturer(context: &Context, id: i32) -> FieldResult<ManufacturerGraphQL> {
+        info!("Executing 'deleteManufacturer' mutation with id: {}", id);
+        observe("deleteManufacturer", || {
+            catalog_mutation::delete_manufacturer(context, id)
+        })
+    }
+
+    /// Brings a discontinued manufacturer back into listings. Staff only.
+    fn restore_manufacturer(context: &Context, id: i32) -> FieldResult<ManufacturerGraphQL> {
+        info!("Executing 'restoreManufacturer' mutation with id: {}", id);
+        observe("restoreManufacturer", || {
+            catalog_mutation::restore_manufacturer(context, id)
+        })
+    }
+
+    /// Discontinues a category: it is no longer listed or fetchable by ID, but
+    /// parts keep referring to it. Staff only.
+    fn delete_category(context: &Context, id: i32) -> FieldResult<CategoryGraphQL> {
+        info!("Executing 'deleteCategory' mutation with id: {}", id);
+        observe("deleteCategory", || {
+            catalog_mutation::delete_category(context, id)
+        })
+    }
+
+    /// Brings a discontinued category back into listings. Staff only.
+    fn restore_category(context: &Context, id: i32) -> FieldResult<CategoryGraphQL> {
+        info!("Executing 'restoreCategory' mutation with id: {}", id);
+        observe("restoreCategory", || {
+            catalog_mutation::restore_category(context, id)
+        })
+    }
+
+    /// Undoes the catalog change recorded by an audit log entry. Staff only.
+    fn revert_audit_entry(context: &Context, id: i32) -> FieldResult<bool> {
+        info!("Executing 'revertAuditEntry' mutation with id: {}", id);
+        observe("revertAuditEntry", || {
+            catalog_mutation::revert_audit_entry(context, id)
+        })
+    }
+
+    /// Updates the signed-in user's name, bio and image.
+    fn update_profile(context: &Context, input: ProfileInput) -> FieldResult<Timed<UserType>> {
+        info!("Executing 'updateProfile' mutation");
+        observe("updateProfile", || {
+            user_mutation::update_profile(context, input)
+        })
+        .map(Timed)
+    }
+
+    /// Unlinks the signed-in user's OAuth account for `provider`. Fails if it
+    /// is their last way to sign in.
+    fn unlink_account(context: &Context, provider: String) -> FieldResult<Vec<LinkedAccountType>> {
+        info!(
+            "Executing 'unlinkAccount' mutation with provider: {}",
+            provider
+        );
+        observe("unlinkAccount", || {
+            user_mutation::unlink_account(context, &provider)
+        })
+    }
+
+    /// Emails the signed-in user a link that verifies their email address.
+    async fn request_email_verification(context: &Context) -> FieldResult<bool> {
+        info!("Executing 'requestEmailVerification' mutation");
+        observe_async(
+            "requestEmailVerification",
+            user_mutation::request_email_verification(context),
+        )
+        .await
+    }
+
+    /// Redeems an email verification link, marking the address as verified.
+    fn verify_email(
+        context: &Context,
+        email: String,
+        token: String,
+    ) -> FieldResult<Timed<UserType>> {
+        info!("Executing 'verifyEmail' mutation");
+        observe("verifyEmail", || {
+            user_mutation::verify_email(context, &email, &token)
+        })
+        .map(Timed)
+    }
+
+    /// Emails a sign-in link to the given address. Always returns true, whether
+    /// or not a user has that address.
+    async fn request_magic_link(context: &Context, email: String) -> FieldResult<bool> {
+        info!("Executing 'requestMagicLink' mutation");
+        observe_async(
+            "requestMagicLink",
+            user_mutation::request_magic_link(context, &email),
+        )
+        .await
+    }
+
+    /// Redeems a sign-in link and starts a session.
+    fn sign_in_with_magic_link(
+        context: &Context,
+        email: String,
+        token: String,
+    ) -> FieldResult<SignInPayload> {
+        info!("Executing 'signInWithMagicLink' mutation");
+        observe("signInWithMagicLink", || {
+            user_mutation::sign_in_with_magic_link(context, &email, &token)
+        })
+    }
+
+    /// Signs one of the signed-in user's sessions out and returns the remaining ones.
+    fn revoke_session(context: &Context, id: i32) -> FieldResult<Vec<SessionType>> {
+        info!("Executing 'revokeSession' mutation with id: {}", id);
+        observe("revokeSession", || {
+            user_mutation::revoke_session(context, id)
+        })
+    }
+
+    /// Signs out every session of the signed-in user except the one the
+    /// request was made with.
+    fn revoke_all_other_sessions(context: &Context) -> FieldResult<Vec<SessionType>> {
+        info!("Executing 'revokeAllOtherSessions' mutation");
+        observe("revokeAllOtherSessions", || {
+            user_mutation::revoke_all_other_sessions(context)
+        })
+    }
+
+    /// Issues an API key acting on behalf of `ownerId` (the caller by
+    /// default) with the given scopes. The secret is only returned here.
+    /// Staff only.
+    fn issue_api_key(context: &Context, input: IssueApiKeyInput) -> FieldResult<IssuedApiKeyType> {
+        info!(
+            "Executing 'issueApiKey' mutation for owner: {:?}, scopes: {:?}",
+            input.owner_id, input.scopes
+        );
+        observe("issueApiKey", || {
+            api_key_mutation::issue_api_key(context, input)
+        })
+    }
+
+    /// Replaces the secret of an API key, returning the new one. The old
+    /// secret stops working immediately. Staff only.
+    fn rotate_api_key(context: &Context, id: i32) -> FieldResult<IssuedApiKeyType> {
+        info!("Executing 'rotateApiKey' mutation with id: {}", id);
+        observe("rotateApiKey", || {
+            api_key_mutation::rotate_api_key(context, id)
+        })
+    }
+
+    /// Revokes an API key. Staff only.
+    fn revoke_api_key(context: &Context, id: i32) -> FieldResult<ApiKeyType> {
+        info!("Executing 'revokeApiKey' mutation with id: {}", id);
+        observe("revokeApiKey", || {
+            api_key_mutation::revoke_api_key(context, id)
+        })
+    }
+
+    /// Saves a part to one of the signed-in user's wishlists, given by
+    /// `wishlistId` or by `name`. A named wishlist is created if needed, and
+    /// without either the part is saved to the default wishlist.
+    fn add_to_wishlist(
+        context: &Context,
+        part_id: i32,
+        wishlist_id: Option<i32>,
+        name: Option<String>,
+    ) -> FieldResult<WishlistType> {
+        info!(
+            "Executing 'addToWishlist' mutation with part_id: {}",
+            part_id
+        );
+        observe("addToWishlist", || {
+            wishlist_mutation::add_to_wishlist(context, part_id, wishlist_id, name)
+        })
+    }
+
+    /// Removes a part from one of the signed-in user's wishlists.
+    fn remove_from_wishlist(
+        context: &Context,
+        wishlist_id: i32,
+        part_id: i32,
+    ) -> FieldResult<WishlistType> {
+        info!(
+            "Executing 'removeFromWishlist' mutation with wishlist_id: {}, part_id: {}",
+            wishlist_id, part_id
+        );
+        observe("removeFromWishlist", || {
+            wishlist_mutation::remove_from_wishlist(context, wishlist_id, part_id)
+        })
+    }
+
+    /// Deletes one of the signed-in user's wishlists.
+    fn delete_wishlist(context: &Context, id: i32) -> FieldResult<bool> {
+        info!("Executing 'deleteWishlist' mutation with id: {}", id);
+        observe("deleteWishlist", || {
+            wishlist_mutation::delete_wishlist(context, id)
+        })
+    }
+
+    /// Creates a configuration with one of each available part on a
+    /// wishlist, named `name` or after the wishlist.
+    fn create_configuration_from_wishlist(
+        context: &Context,
+        wishlist_id: i32,
+        name: Option<String>,
+    ) -> FieldResult<Timed<ConfigurationType>> {
+        info!(
+            "Executing 'createConfigurationFromWishlist' mutation with wishlist_id: {}",
+            wishlist_id
+        );
+        observe("createConfigurationFromWishlist", || {
+            wishlist_mutation::create_configuration_from_wishlist(context, wishlist_id, name)
+        })
+        .map(Timed)
+    }
+
+    /// Changes the name, description or parts of one of the signed-in user's
+    /// configurations. Each change is recorded as a revision.
+    fn update_configuration(
+        context: &Context,
+        id: i32,
+        input: ConfigurationInput,
+    ) -> FieldResult<Timed<ConfigurationType>> {
+        info!("Executing 'updateConfiguration' mutation with id: {}", id);
+        observe("updateConfiguration", || {
+            configuration_mutation::update_configuration(context, id, input)
+        })
+        .map(Timed)
+    }
+
+    /// Sets one of the signed-in user's configurations back to an earlier
+    /// revision, recording the restore as a new revision.
+    fn restore_revision(
+        context: &Context,
+        configuration_id: i32,
+        revision: i32,
+    ) -> FieldResult<Timed<ConfigurationType>> {
+        info!(
+            "Executing 'restoreRevision' mutation with configuration_id: {}, revision: {}",
+            configuration_id, revision
+        );
+        observe("restoreRevision", || {
+            configuration_mutation::restore_revision(context, configuration_id, revision)
+        })
+        .map(Timed)
+    }
+
+    /// Creates a read-only share link for one of the signed-in user's
+    /// configurations, or keeps the one it has. The link is `shareSlug`.
+    fn share_configuration(context: &Context, id: i32) -> FieldResult<Timed<ConfigurationType>> {
+        info!("Executing 'shareConfiguration' mutation with id: {}", id);
+        observe("shareConfiguration", || {
+            configuration_mutation::share_configuration(context, id)
+        })
+        .map(Timed)
+    }
+
+    /// Revokes the share link of one of the signed-in user's configurations.
+    fn revoke_configuration_share(
+        context: &Context,
+        id: i32,
+    ) -> FieldResult<Timed<ConfigurationType>> {
+        info!(
+            "Executing 'revokeConfigurationShare' mutation with id: {}",
+            id
+        );
+        observe("revokeConfigurationShare", || {
+            configuration_mutation::revoke_configuration_share(context, id)
+        })
+        .map(Timed)
+    }
+
+    /// Copies the configuration behind a share link into the signed-in
+    /// user's account, named `name` or after the original.
+    fn fork_configuration(
+        context: &Context,
+        slug: String,
+        name: Option<String>,
+    ) -> FieldResult<Timed<ConfigurationType>> {
+        info!("Executing 'forkConfiguration' mutation");
+        observe("forkConfiguration", || {
+            configuration_mutation::fork_configuration(context, slug, name)
+        })
+        .map(Timed)
+    }
+}
+
+/// Runs a mutation resolver, logging failures with their duration.
+fn observe<T>(field: &str, resolve: impl FnOnce() -> FieldResult<T>) -> FieldResult<T> {
+    let start_time = Instant::now();
+    let result = resolve();
+    let duration = start_time.elapsed();
+    if let Err(e) = &result {
+        error!(
+            "Error executing '{}' mutation: {:?}. Duration: {:?}",
+            field, e, duration
+        );
+    }
+    result
+}
+
+/// Runs an asynchronous mutation resolver, logging failures with their duration.
+async fn observe_async<T>(
+    field: &str,
+    resolve: impl Future<Output = FieldResult<T>>,
+) -> FieldResult<T> {
+    let start_time = Instant::now();
+    let result = resolve.await;
+    let duration = start_time.elapsed();
+    if let Err(e) = &result {
+        error!(
+            "Error executing '{}' mutation: {:?}. Duration: {:?}",
+            field, e, duration
+        );
+    }
+    result
+}
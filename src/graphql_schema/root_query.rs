@@ -1,13 +1,32 @@
 // src/graphql_schema/root_query.rs
 
+use crate::graphql_schema::api_keys::query::all_api_keys;
+use crate::graphql_schema::api_keys::types::ApiKeyType;
+use crate::graphql_schema::catalog::query::audit_log;
+use crate::graphql_schema::catalog::types::AuditLogEntryType;
+use crate::graphql_schema::configurations::query::{
+    compare_configurations, configuration_by_id, shared_configuration,
+};
+use crate::graphql_schema::configurations::types::{
+    ConfigurationComparisonType, ConfigurationType, SharedConfigurationType,
+};
 use crate::graphql_schema::context::Context;
+use crate::graphql_schema::orders::query::{all_orders, my_orders, order_by_id};
+use crate::graphql_schema::orders::types::{OrderStatusType, OrderType};
 use crate::graphql_schema::parts::category_graphql::CategoryGraphQL;
 use crate::graphql_schema::parts::manufacturer_graphql::ManufacturerGraphQL;
 use crate::graphql_schema::parts::part_graphql::PartGraphQL;
 use crate::graphql_schema::queries::categories_queries::CategoriesQueries;
 use crate::graphql_schema::queries::manufacturers_queries::ManufacturersQueries;
 use crate::graphql_schema::queries::parts_queries::{get_all_parts, get_part_by_id};
-use crate::graphql_schema::users::query::UserQuery;
+use crate::graphql_schema::quote_requests::query::{all_quote_requests, quote_request_by_id};
+use crate::graphql_schema::quote_requests::types::{QuoteRequestStatusType, QuoteRequestType};
+use crate::graphql_schema::service::query::{Service, ServiceQuery};
+use crate::graphql_schema::timed::Timed;
+use crate::graphql_schema::users::query::{my_sessions, UserQuery};
+use crate::graphql_schema::users::types::{SessionType, UserType};
+use crate::graphql_schema::wishlists::query::my_wishlists;
+use crate::graphql_schema::wishlists::types::WishlistType;
 use juniper::{EmptyMutation, EmptySubscription, RootNode};
 use log::{error, info};
 use std::time::Instant;
@@ -23,12 +42,130 @@ impl RootQuery {
         "1.0"
     }
 
-    /// Fetches all parts from the database with optional pagination.
+    /// Returns the signed-in user, or null for anonymous requests.
+    fn me(context: &Context) -> Option<Timed<UserType>> {
+        info!("me query called");
+        context.user.clone().map(|user| Timed(UserType::from(user)))
+    }
+
+    /// Fetches the authenticated user's active sessions, most recently used first.
+    fn my_sessions(context: &Context) -> juniper::FieldResult<Vec<SessionType>> {
+        let start_time = Instant::now();
+        info!("Executing 'mySessions' query");
+        let result = my_sessions(context);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(sessions) => info!("Fetched {} sessions in {:?}", sessions.len(), duration),
+            Err(e) => error!(
+                "Error executing 'mySessions' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Fetches the signed-in user's wishlists with their saved parts.
+    fn wishlists(context: &Context) -> juniper::FieldResult<Vec<WishlistType>> {
+        let start_time = Instant::now();
+        info!("Executing 'wishlists' query");
+        let result = my_wishlists(context);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(wishlists) => info!("Fetched {} wishlists in {:?}", wishlists.len(), duration),
+            Err(e) => error!(
+                "Error executing 'wishlists' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Fetches one of the signed-in user's configurations.
+    fn configuration(context: &Context, id: i32) -> juniper::FieldResult<Timed<ConfigurationType>> {
+        let start_time = Instant::now();
+        info!("Executing 'configuration' query with id: {}", id);
+        let result = configuration_by_id(context, id);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(_) => info!("Fetched configuration {} in {:?}", id, duration),
+            Err(e) => error!(
+                "Error executing 'configuration' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result.map(Timed)
+    }
+
+    /// Fetches the configuration behind a share link, or null if the link is
+    /// unknown or was revoked. Works without signing in and leaves out the
+    /// owner.
+    fn shared_configuration(
+        context: &Context,
+        slug: String,
+    ) -> juniper::FieldResult<Option<SharedConfigurationType>> {
+        let start_time = Instant::now();
+        // The slug grants access to the configuration, so it is not logged
+        info!("Executing 'sharedConfiguration' query");
+        let result = shared_configuration(context, &slug);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(shared) => info!(
+                "Fetched shared configuration (found: {}) in {:?}",
+                shared.is_some(),
+                duration
+            ),
+            Err(e) => error!(
+                "Error executing 'sharedConfiguration' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Compares two configurations part by part and reports what changes
+    /// going from `a` to `b`. Each must belong to the caller unless the caller
+    /// is staff.
+    fn compare_configurations(
+        context: &Context,
+        a: i32,
+        b: i32,
+    ) -> juniper::FieldResult<ConfigurationComparisonType> {
+        let start_time = Instant::now();
+        info!(
+            "Executing 'compareConfigurations' query with a: {}, b: {}",
+            a, b
+        );
+        let result = compare_configurations(context, a, b);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(comparison) => info!(
+                "Compared configurations {} and {} ({} differing components) in {:?}",
+                a,
+                b,
+                comparison.components.len(),
+                duration
+            ),
+            Err(e) => error!(
+                "Error executing 'compareConfigurations' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Fetches all parts from the database with optional pagination. `inStock`
+    /// keeps only parts that have (or lack) unreserved stock.
     fn parts(
         context: &Context,
         limit: Option<i32>,
         offset: Option<i32>,
-    ) -> juniper::FieldResult<Vec<PartGraphQL>> {
+        in_stock: Option<bool>,
+    ) -> juniper::FieldResult<Vec<Timed<PartGraphQL>>> {
         // Authorization Check
         if let Some(user) = &context.user {
             if user.email.is_none() {
@@ -48,10 +185,10 @@ impl RootQuery {
 
         let start_time = Instant::now();
         info!(
-            "Executing 'parts' query with limit: {:?}, offset: {:?}",
-            limit, offset
+            "Executing 'parts' query with limit: {:?}, offset: {:?}, in_stock: {:?}",
+            limit, offset, in_stock
         );
-        let result = get_all_parts(context, limit, offset);
+        let result = get_all_parts(context, limit, offset, in_stock);
         let duration = start_time.elapsed();
 
         match &result {
@@ -61,11 +198,11 @@ impl RootQuery {
                 e, duration
             ),
         }
-        result
+        result.map(|parts| parts.into_iter().map(Timed).collect())
     }
 
     /// Fetches a specific part by ID.
-    fn part(context: &Context, part_id: i32) -> juniper::FieldResult<Option<PartGraphQL>> {
+    fn part(context: &Context, part_id: i32) -> juniper::FieldResult<Option<Timed<PartGraphQL>>> {
         let start_time = Instant::now();
         info!("Executing 'part' query with part_id: {}", part_id);
         let result = get_part_by_id(context, part_id);
@@ -82,7 +219,7 @@ impl RootQuery {
                 e, duration
             ),
         }
-        result
+        result.map(|part| part.map(Timed))
     }
 
     /// Fetches all manufacturers.
@@ -183,8 +320,176 @@ impl RootQuery {
         result
     }
 
-    fn user_query() -> UserQuery {
-        UserQuery
+    /// Fetches the authenticated user's orders, newest first.
+    fn my_orders(context: &Context) -> juniper::FieldResult<Vec<OrderType>> {
+        let start_time = Instant::now();
+        info!("Executing 'myOrders' query");
+        let result = my_orders(context);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(orders) => info!("Fetched {} orders in {:?}", orders.len(), duration),
+            Err(e) => error!(
+                "Error executing 'myOrders' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Fetches one of the authenticated user's orders; staff can fetch any order.
+    fn order(context: &Context, id: i32) -> juniper::FieldResult<OrderType> {
+        let start_time = Instant::now();
+        info!("Executing 'order' query with id: {}", id);
+        let result = order_by_id(context, id);
+        let duration = start_time.elapsed();
+
+        if let Err(e) = &result {
+            error!(
+                "Error executing 'order' query: {:?}. Duration: {:?}",
+                e, duration
+            );
+        }
+        result
+    }
+
+    /// Fetches orders of every customer, optionally filtered by status. Staff only.
+    fn orders(
+        context: &Context,
+        status: Option<OrderStatusType>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> juniper::FieldResult<Vec<OrderType>> {
+        let start_time = Instant::now();
+        info!(
+            "Executing 'orders' query with status: {:?}, limit: {:?}, offset: {:?}",
+            status, limit, offset
+        );
+        let result = all_orders(context, status, limit, offset);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(orders) => info!("Fetched {} orders in {:?}", orders.len(), duration),
+            Err(e) => error!(
+                "Error executing 'orders' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Lists the services that can be requested with `submitQuoteRequest`.
+    fn services() -> Vec<Service> {
+        info!("services query called");
+        ServiceQuery::get_services()
+    }
+
+    /// Fetches quote requests, newest first, optionally filtered by status or
+    /// assignee. Staff only.
+    fn quote_requests(
+        context: &Context,
+        status: Option<QuoteRequestStatusType>,
+        assigned_to: Option<i32>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> juniper::FieldResult<Vec<QuoteRequestType>> {
+        let start_time = Instant::now();
+        info!(
+            "Executing 'quoteRequests' query with status: {:?}, assigned_to: {:?}, limit: {:?}, offset: {:?}",
+            status, assigned_to, limit, offset
+        );
+        let result = all_quote_requests(context, status, assigned_to, limit, offset);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(quote_requests) => info!(
+                "Fetched {} quote requests in {:?}",
+                quote_requests.len(),
+                duration
+            ),
+            Err(e) => error!(
+                "Error executing 'quoteRequests' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Fetches a quote request by ID. Staff only.
+    fn quote_request(context: &Context, id: i32) -> juniper::FieldResult<QuoteRequestType> {
+        let start_time = Instant::now();
+        info!("Executing 'quoteRequest' query with id: {}", id);
+        let result = quote_request_by_id(context, id);
+        let duration = start_time.elapsed();
+
+        if let Err(e) = &result {
+            error!(
+                "Error executing 'quoteRequest' query: {:?}. Duration: {:?}",
+                e, duration
+            );
+        }
+        result
+    }
+
+    /// Fetches API keys, newest first, optionally of one owner. Revoked keys
+    /// are left out unless `includeRevoked` is set. Staff only.
+    fn api_keys(
+        context: &Context,
+        owner_id: Option<i32>,
+        include_revoked: Option<bool>,
+    ) -> juniper::FieldResult<Vec<ApiKeyType>> {
+        let start_time = Instant::now();
+        info!(
+            "Executing 'apiKeys' query with owner_id: {:?}, include_revoked: {:?}",
+            owner_id, include_revoked
+        );
+        let result = all_api_keys(context, owner_id, include_revoked);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(keys) => info!("Fetched {} API keys in {:?}", keys.len(), duration),
+            Err(e) => error!(
+                "Error executing 'apiKeys' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    /// Fetches catalog audit log entries, newest first, optionally for one
+    /// table, row or actor. Staff only.
+    fn audit_log(
+        context: &Context,
+        table_name: Option<String>,
+        row_key: Option<i32>,
+        actor_id: Option<i32>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> juniper::FieldResult<Vec<AuditLogEntryType>> {
+        let start_time = Instant::now();
+        info!(
+            "Executing 'auditLog' query with table_name: {:?}, row_key: {:?}, actor_id: {:?}",
+            table_name, row_key, actor_id
+        );
+        let result = audit_log(context, table_name, row_key, actor_id, limit, offset);
+        let duration = start_time.elapsed();
+
+        match &result {
+            Ok(entries) => info!(
+                "Fetched {} audit log entries in {:?}",
+                entries.len(),
+                duration
+            ),
+            Err(e) => error!(
+                "Error executing 'auditLog' query: {:?}. Duration: {:?}",
+                e, duration
+            ),
+        }
+        result
+    }
+
+    fn user_query() -> Timed<UserQuery> {
+        Timed(UserQuery)
     }
 }
 
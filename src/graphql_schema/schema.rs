@@ -1,11 +1,13 @@
-use super::context::Context;
+use super::root_mutation::RootMutation;
 use super::root_query::RootQuery;
-use juniper::{EmptyMutation, EmptySubscription, RootNode};
+use super::subscriptions::subscription::RootSubscription;
+use super::timed::Timed;
+use juniper::RootNode;
 
 /// The main GraphQL schema type for the application.
-pub type Schema = RootNode<'static, RootQuery, EmptyMutation<Context>, EmptySubscription<Context>>;
+pub type Schema = RootNode<'static, Timed<RootQuery>, Timed<RootMutation>, RootSubscription>;
 
 /// Creates and returns the main GraphQL schema.
 pub fn create_schema() -> Schema {
-    Schema::new(RootQuery, EmptyMutation::new(), EmptySubscription::new())
+    Schema::new(Timed(RootQuery), Timed(RootMutation), RootSubscription)
 }
@@ -0,0 +1,88 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::subscriptions::types::{
+    ConfigurationChangeType, PartPriceChangeType, PartStockChangeType,
+};
+use crate::notifications::NotificationHub;
+use diesel::prelude::*;
+use futures::Stream;
+use juniper::{graphql_subscription, graphql_value, FieldError, FieldResult};
+use log::error;
+use std::pin::Pin;
+use std::sync::Arc;
+
+type EventStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+/// Subscriptions fed by database change notifications.
+pub struct RootSubscription;
+
+#[graphql_subscription(context = Context)]
+impl RootSubscription {
+    /// Emits whenever the price of the part changes.
+    async fn part_price_changed(
+        context: &Context,
+        part_id: i32,
+    ) -> FieldResult<EventStream<PartPriceChangeType>> {
+        let hub = notification_hub(context)?;
+        let changes = hub.part_price_changes(part_id);
+        Ok(Box::pin(changes.map(PartPriceChangeType::from)) as EventStream<_>)
+    }
+
+    /// Emits whenever the available stock of the part changes.
+    async fn part_stock_changed(
+        context: &Context,
+        part_id: i32,
+    ) -> FieldResult<EventStream<PartStockChangeType>> {
+        let hub = notification_hub(context)?;
+        let changes = hub.part_stock_changes(part_id);
+        Ok(Box::pin(changes.map(PartStockChangeType::from)) as EventStream<_>)
+    }
+
+    /// Emits whenever the configuration or its list of parts changes.
+    async fn configuration_updated(
+        context: &Context,
+        id: i32,
+    ) -> FieldResult<EventStream<ConfigurationChangeType>> {
+        let hub = notification_hub(context)?;
+        check_configuration_access(context, id)?;
+        let changes = hub.configuration_updates(id);
+        Ok(Box::pin(changes.map(ConfigurationChangeType::from)) as EventStream<_>)
+    }
+}
+
+fn notification_hub(context: &Context) -> FieldResult<Arc<NotificationHub>> {
+    context.notifications.clone().ok_or_else(|| {
+        FieldError::new(
+            "Subscriptions are not available",
+            graphql_value!({ "code": "SUBSCRIPTIONS_DISABLED" }),
+        )
+    })
+}
+
+/// Allows subscribing to anonymous configurations and to the user's own.
+fn check_configuration_access(context: &Context, configuration_id: i32) -> FieldResult<()> {
+    use crate::diesel_schema::configurations::configurations::dsl::*;
+
+    let conn = &mut context.get_connection()?;
+    let owner = configurations
+        .find(configuration_id)
+        .select(user_id)
+        .first::<Option<i32>>(conn)
+        .optional()
+        .map_err(|e| {
+            error!("Error fetching configuration: {}", e);
+            FieldError::new(
+                "Error fetching configuration",
+                graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+            )
+        })?;
+
+    match owner {
+        Some(None) => Ok(()),
+        Some(Some(owner_id)) if context.user.as_ref().map(|u| u.id) == Some(owner_id) => Ok(()),
+        // Configurations of other users are reported as missing so their IDs are not revealed
+        _ => Err(FieldError::new(
+            "Configuration not found",
+            graphql_value!({ "code": "NOT_FOUND" }),
+        )),
+    }
+}
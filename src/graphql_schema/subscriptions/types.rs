@@ -0,0 +1,81 @@
+use crate::notifications::{
+    ConfigurationAction, ConfigurationUpdated, PartPriceChanged, PartStockChanged,
+};
+use juniper::{GraphQLEnum, GraphQLObject};
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A change to a part's price")]
+pub struct PartPriceChangeType {
+    #[graphql(name = "partId")]
+    pub part_id: i32,
+    #[graphql(name = "oldPrice")]
+    pub old_price: Option<String>,
+    pub price: Option<String>,
+}
+
+impl From<PartPriceChanged> for PartPriceChangeType {
+    fn from(change: PartPriceChanged) -> Self {
+        PartPriceChangeType {
+            part_id: change.part_id,
+            old_price: change.old_price,
+            price: change.price,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A change to a part's available stock")]
+pub struct PartStockChangeType {
+    #[graphql(name = "partId")]
+    pub part_id: i32,
+    #[graphql(name = "availableQuantity")]
+    pub available_quantity: i32,
+    #[graphql(name = "inStock")]
+    pub in_stock: bool,
+}
+
+impl From<PartStockChanged> for PartStockChangeType {
+    fn from(change: PartStockChanged) -> Self {
+        let available_quantity = change.available_quantity.max(0);
+        PartStockChangeType {
+            part_id: change.part_id,
+            available_quantity,
+            in_stock: available_quantity > 0,
+        }
+    }
+}
+
+#[derive(GraphQLEnum, Clone, Copy, PartialEq, Eq, Debug)]
+#[graphql(description = "What happened to a configuration")]
+pub enum ConfigurationChangeAction {
+    /// The configuration or its parts were changed.
+    Updated,
+    /// The configuration was deleted.
+    Deleted,
+}
+
+impl From<ConfigurationAction> for ConfigurationChangeAction {
+    fn from(action: ConfigurationAction) -> Self {
+        match action {
+            ConfigurationAction::Updated => ConfigurationChangeAction::Updated,
+            ConfigurationAction::Deleted => ConfigurationChangeAction::Deleted,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A change to a configuration or its parts")]
+pub struct ConfigurationChangeType {
+    #[graphql(name = "configurationId")]
+    pub configuration_id: i32,
+    pub action: ConfigurationChangeAction,
+}
+
+impl From<ConfigurationUpdated> for ConfigurationChangeType {
+    fn from(change: ConfigurationUpdated) -> Self {
+        ConfigurationChangeType {
+            configuration_id: change.configuration_id,
+            action: change.action.into(),
+        }
+    }
+}
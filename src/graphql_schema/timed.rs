@@ -0,0 +1,123 @@
+// src/graphql_schema/timed.rs
+
+use crate::metrics;
+use juniper::marker::IsOutputType;
+use juniper::meta::MetaType;
+use juniper::{
+    Arguments, BoxFuture, ExecutionResult, Executor, GraphQLType, GraphQLValue, GraphQLValueAsync,
+    Registry, ScalarValue,
+};
+use std::ops::Deref;
+use std::time::Instant;
+
+/// Wraps a GraphQL object so that every field resolved on it is recorded in
+/// [`metrics::GRAPHQL_RESOLVER_DURATION_SECONDS`], labeled with the object's
+/// schema type name and the field name.
+///
+/// juniper offers no hook around field resolution, so objects are timed where
+/// they enter the schema: the root types, and the fields that return objects
+/// with resolvers of their own. The wrapper is invisible in the schema, and
+/// the time of a field includes the time spent resolving its selection set.
+pub struct Timed<T>(pub T);
+
+impl<T> Deref for Timed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<S, T> GraphQLType<S> for Timed<T>
+where
+    T: GraphQLType<S>,
+    S: ScalarValue,
+{
+    fn name(info: &Self::TypeInfo) -> Option<&str> {
+        T::name(info)
+    }
+
+    fn meta<'r>(info: &Self::TypeInfo, registry: &mut Registry<'r, S>) -> MetaType<'r, S>
+    where
+        S: 'r,
+    {
+        T::meta(info, registry)
+    }
+}
+
+// `resolve` keeps its default implementation, which resolves the selection
+// set through `resolve_field` on the wrapper rather than on the inner value.
+impl<S, T> GraphQLValue<S> for Timed<T>
+where
+    T: GraphQLValue<S>,
+    S: ScalarValue,
+{
+    type Context = T::Context;
+    type TypeInfo = T::TypeInfo;
+
+    fn type_name<'i>(&self, info: &'i Self::TypeInfo) -> Option<&'i str> {
+        self.0.type_name(info)
+    }
+
+    fn concrete_type_name(&self, context: &Self::Context, info: &Self::TypeInfo) -> String {
+        self.0.concrete_type_name(context, info)
+    }
+
+    fn resolve_field(
+        &self,
+        info: &Self::TypeInfo,
+        field_name: &str,
+        arguments: &Arguments<S>,
+        executor: &Executor<Self::Context, S>,
+    ) -> ExecutionResult<S> {
+        let start_time = Instant::now();
+        let result = self.0.resolve_field(info, field_name, arguments, executor);
+        observe(self.type_name(info), field_name, start_time);
+        result
+    }
+}
+
+impl<S, T> GraphQLValueAsync<S> for Timed<T>
+where
+    T: GraphQLValueAsync<S>,
+    T::TypeInfo: Sync,
+    T::Context: Sync,
+    S: ScalarValue + Send + Sync,
+{
+    fn resolve_field_async<'a>(
+        &'a self,
+        info: &'a Self::TypeInfo,
+        field_name: &'a str,
+        arguments: &'a Arguments<S>,
+        executor: &'a Executor<Self::Context, S>,
+    ) -> BoxFuture<'a, ExecutionResult<S>> {
+        Box::pin(async move {
+            let start_time = Instant::now();
+            let result = self
+                .0
+                .resolve_field_async(info, field_name, arguments, executor)
+                .await;
+            observe(self.type_name(info), field_name, start_time);
+            result
+        })
+    }
+}
+
+impl<S, T> IsOutputType<S> for Timed<T>
+where
+    T: IsOutputType<S>,
+    S: ScalarValue,
+{
+    fn mark() {
+        T::mark()
+    }
+}
+
+/// Records the time since `start_time` against the field of the named type.
+fn observe(type_name: Option<&str>, field_name: &str, start_time: Instant) {
+    metrics::observe_resolver(
+        type_name.unwrap_or("Unknown"),
+        field_name,
+        start_time.elapsed(),
+    );
+}
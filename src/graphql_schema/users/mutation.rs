@@ -0,0 +1,213 @@
+use crate::accounts;
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::timed::Timed;
+use crate::graphql_schema::users::types::{
+    LinkedAccountType, ProfileInput, SessionType, SignInPayload, UserType,
+};
+use crate::mailer::parse_mailbox;
+use crate::models::auth::{ProfileChanges, Session};
+use crate::profiles;
+use crate::sessions;
+use crate::types::errors::{
+    AccountError, MailError, ProfileError, SessionError, VerificationError,
+};
+use crate::verification::{self, EmailLinks};
+use juniper::{FieldResult, IntoFieldError, Nullable};
+use url::Url;
+
+/// Longest display name a user can set.
+const MAX_NAME_LENGTH: usize = 100;
+/// Longest bio a user can set.
+const MAX_BIO_LENGTH: usize = 1000;
+/// Longest profile image URL a user can set.
+const MAX_IMAGE_URL_LENGTH: usize = 2048;
+
+/// Updates the caller's own name, bio and image.
+pub fn update_profile(context: &Context, input: ProfileInput) -> FieldResult<UserType> {
+    let user = context.require_user()?;
+    let changes = validate(input).map_err(ProfileError::into_field_error)?;
+
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| ProfileError::from(e).into_field_error())?;
+    profiles::update_profile(conn, user.id, changes)
+        .map(UserType::from)
+        .map_err(ProfileError::into_field_error)
+}
+
+/// Turns the input into profile changes. Omitted fields are left unchanged,
+/// while `null` and blank strings clear a field.
+fn validate(input: ProfileInput) -> Result<ProfileChanges, ProfileError> {
+    let name = field(input.name, "Name", MAX_NAME_LENGTH)?;
+    let bio = field(input.bio, "Bio", MAX_BIO_LENGTH)?;
+    let image = field(input.image, "Image", MAX_IMAGE_URL_LENGTH)?;
+    if let Some(Some(image)) = &image {
+        check_image_url(image)?;
+    }
+    Ok(ProfileChanges { name, bio, image })
+}
+
+fn field(
+    value: Nullable<String>,
+    field: &str,
+    max_length: usize,
+) -> Result<Option<Option<String>>, ProfileError> {
+    match value {
+        Nullable::ImplicitNull => Ok(None),
+        Nullable::ExplicitNull => Ok(Some(None)),
+        Nullable::Some(value) => {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(Some(None));
+            }
+            if value.chars().count() > max_length {
+                return Err(ProfileError::Validation(format!(
+                    "{} must be at most {} characters",
+                    field, max_length
+                )));
+            }
+            Ok(Some(Some(value.to_string())))
+        }
+    }
+}
+
+/// Accepts only absolute `http` and `https` URLs, so profile images cannot
+/// point at `javascript:` or `data:` URLs.
+fn check_image_url(image: &str) -> Result<(), ProfileError> {
+    let valid = Url::parse(image)
+        .map(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some())
+        .unwrap_or(false);
+    if !valid {
+        return Err(ProfileError::Validation(
+            "Image must be an http or https URL".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Unlinks the caller's OAuth accounts for `provider`, returning the accounts
+/// that remain.
+pub fn unlink_account(context: &Context, provider: &str) -> FieldResult<Vec<LinkedAccountType>> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| AccountError::from(e).into_field_error())?;
+    Ok(accounts::unlink(conn, user.id, provider)
+        .map_err(AccountError::into_field_error)?
+        .into_iter()
+        .map(LinkedAccountType::from)
+        .collect())
+}
+
+/// Emails the caller a link that verifies their email address.
+pub async fn request_email_verification(context: &Context) -> FieldResult<bool> {
+    let user = context.require_user()?;
+    let email_links = email_links(context).map_err(VerificationError::into_field_error)?;
+    let (email, token) = {
+        let conn = &mut context
+            .get_connection()
+            .map_err(|e| VerificationError::from(e).into_field_error())?;
+        verification::request_verification(conn, user.id, email_links.verification_ttl())
+            .map_err(VerificationError::into_field_error)?
+    };
+    email_links
+        .send_verification(&email, &token)
+        .await
+        .map_err(|e| VerificationError::from(e).into_field_error())?;
+    Ok(true)
+}
+
+/// Emails a sign-in link to `email` if it belongs to a user. The result is the
+/// same either way, so the mutation does not reveal which addresses exist.
+pub async fn request_magic_link(context: &Context, email: &str) -> FieldResult<bool> {
+    let email = email.trim();
+    if parse_mailbox(email).is_err() {
+        return Err(VerificationError::Validation(
+            "Email must be a valid email address".to_string(),
+        )
+        .into_field_error());
+    }
+    let email_links = email_links(context).map_err(VerificationError::into_field_error)?;
+    let issued = {
+        let conn = &mut context
+            .get_connection()
+            .map_err(|e| VerificationError::from(e).into_field_error())?;
+        verification::request_sign_in(conn, email, email_links.magic_link_ttl())
+            .map_err(VerificationError::into_field_error)?
+    };
+    if let Some((email, token)) = issued {
+        email_links
+            .send_magic_link(&email, &token)
+            .await
+            .map_err(|e| VerificationError::from(e).into_field_error())?;
+    }
+    Ok(true)
+}
+
+/// Redeems an email verification link.
+pub fn verify_email(context: &Context, email: &str, token: &str) -> FieldResult<UserType> {
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| VerificationError::from(e).into_field_error())?;
+    verification::verify_email(conn, email, token)
+        .map(UserType::from)
+        .map_err(VerificationError::into_field_error)
+}
+
+/// Redeems a sign-in link and starts a session.
+pub fn sign_in_with_magic_link(
+    context: &Context,
+    email: &str,
+    token: &str,
+) -> FieldResult<SignInPayload> {
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| VerificationError::from(e).into_field_error())?;
+    let (user, session) = verification::sign_in(conn, email, token, &context.client)
+        .map_err(VerificationError::into_field_error)?;
+    Ok(SignInPayload {
+        user: Timed(UserType::from(user)),
+        session_token: session.session_token,
+        expires: session.expires,
+    })
+}
+
+/// Signs one of the caller's sessions out, returning the remaining ones.
+pub fn revoke_session(context: &Context, session_id: i32) -> FieldResult<Vec<SessionType>> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| SessionError::from(e).into_field_error())?;
+    let remaining =
+        sessions::revoke(conn, user.id, session_id).map_err(SessionError::into_field_error)?;
+    Ok(to_session_types(context, remaining))
+}
+
+/// Signs out every session of the caller except the one the request was made
+/// with, returning the remaining ones.
+pub fn revoke_all_other_sessions(context: &Context) -> FieldResult<Vec<SessionType>> {
+    let user = context.require_user()?;
+    let current_session_id = context
+        .session_id
+        .ok_or_else(|| SessionError::NoCurrentSession.into_field_error())?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| SessionError::from(e).into_field_error())?;
+    let remaining = sessions::revoke_others(conn, user.id, current_session_id)
+        .map_err(SessionError::into_field_error)?;
+    Ok(to_session_types(context, remaining))
+}
+
+fn to_session_types(context: &Context, sessions: Vec<Session>) -> Vec<SessionType> {
+    sessions
+        .into_iter()
+        .map(|session| SessionType::new(session, context.session_id))
+        .collect()
+}
+
+fn email_links(context: &Context) -> Result<&EmailLinks, VerificationError> {
+    context
+        .email_links
+        .as_deref()
+        .ok_or(VerificationError::DeliveryError(MailError::NotConfigured))
+}
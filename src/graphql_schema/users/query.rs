@@ -1,31 +1,50 @@
 use crate::graphql_schema::context::Context;
-use crate::graphql_schema::users::types::UserType;
+use crate::graphql_schema::timed::Timed;
+use crate::graphql_schema::users::types::{SessionType, UserType};
 use crate::models::auth::User;
+use crate::sessions;
+use crate::types::errors::SessionError;
 use diesel::prelude::*;
-use juniper::{FieldError, FieldResult};
+use juniper::{FieldError, FieldResult, IntoFieldError};
 
 pub struct UserQuery;
 
 #[juniper::graphql_object(Context = Context)]
 impl UserQuery {
-    fn user(context: &Context, id: i32) -> FieldResult<UserType> {
-        use crate::diesel_schema::users::users::dsl::*;
+    fn user(context: &Context, id: i32) -> FieldResult<Timed<UserType>> {
+        use crate::diesel_schema::users::users::dsl::users;
 
         let conn = &mut context.get_connection()?;
         let user = users
             .find(id)
             .first::<User>(conn)
-            .map_err(|e| FieldError::from(e))?;
+            .map_err(FieldError::from)?;
 
-        Ok(UserType::from(user))
+        Ok(Timed(UserType::from(user)))
     }
 
-    fn users(context: &Context) -> FieldResult<Vec<UserType>> {
+    fn users(context: &Context) -> FieldResult<Vec<Timed<UserType>>> {
         use crate::diesel_schema::users::users::dsl::*;
 
         let conn = &mut context.get_connection()?;
-        let user_list = users.load::<User>(conn).map_err(|e| FieldError::from(e))?;
+        let user_list = users.load::<User>(conn).map_err(FieldError::from)?;
 
-        Ok(user_list.into_iter().map(UserType::from).collect())
+        Ok(user_list
+            .into_iter()
+            .map(|user| Timed(UserType::from(user)))
+            .collect())
     }
 }
+
+/// Fetches the authenticated user's unexpired sessions, most recently used first.
+pub fn my_sessions(context: &Context) -> FieldResult<Vec<SessionType>> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| SessionError::from(e).into_field_error())?;
+    Ok(sessions::list(conn, user.id)
+        .map_err(SessionError::into_field_error)?
+        .into_iter()
+        .map(|session| SessionType::new(session, context.session_id))
+        .collect())
+}
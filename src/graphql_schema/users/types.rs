@@ -1,13 +1,19 @@
+use crate::accounts;
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::timed::Timed;
+use crate::models::auth::{LinkedAccount, Session};
+use crate::types::errors::AccountError;
 use chrono::{DateTime, Utc};
-use juniper::GraphQLObject;
+use juniper::{
+    graphql_object, graphql_value, FieldError, FieldResult, GraphQLInputObject, GraphQLObject,
+    IntoFieldError, Nullable,
+};
 
-#[derive(GraphQLObject)]
-#[graphql(description = "A user in the system")]
+/// A user in the system
 pub struct UserType {
     pub id: i32,
     pub name: Option<String>,
     pub email: Option<String>,
-    #[graphql(name = "emailVerified")]
     pub email_verified: Option<DateTime<Utc>>,
     pub image: Option<String>,
     pub bio: Option<String>,
@@ -25,3 +31,124 @@ impl From<crate::models::auth::User> for UserType {
         }
     }
 }
+
+#[graphql_object(context = Context, description = "A user in the system")]
+impl UserType {
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email.as_deref()
+    }
+
+    #[graphql(name = "emailVerified")]
+    fn email_verified(&self) -> Option<DateTime<Utc>> {
+        self.email_verified
+    }
+
+    fn image(&self) -> Option<&str> {
+        self.image.as_deref()
+    }
+
+    fn bio(&self) -> Option<&str> {
+        self.bio.as_deref()
+    }
+
+    /// OAuth accounts the user signs in with. Only visible to the user themselves.
+    #[graphql(name = "linkedAccounts")]
+    fn linked_accounts(&self, context: &Context) -> FieldResult<Vec<LinkedAccountType>> {
+        let user = context.require_user()?;
+        if user.id != self.id {
+            return Err(FieldError::new(
+                "You can only see your own linked accounts.",
+                graphql_value!({ "code": "FORBIDDEN" }),
+            ));
+        }
+        let conn = &mut context
+            .get_connection()
+            .map_err(|e| AccountError::from(e).into_field_error())?;
+        Ok(accounts::linked_accounts(conn, self.id)
+            .map_err(AccountError::into_field_error)?
+            .into_iter()
+            .map(LinkedAccountType::from)
+            .collect())
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "An OAuth account linked to a user; its tokens are never exposed")]
+pub struct LinkedAccountType {
+    pub provider: String,
+    #[graphql(name = "type")]
+    pub type_: String,
+    #[graphql(name = "providerAccountId")]
+    pub provider_account_id: String,
+}
+
+impl From<LinkedAccount> for LinkedAccountType {
+    fn from(account: LinkedAccount) -> Self {
+        LinkedAccountType {
+            provider: account.provider,
+            type_: account.type_,
+            provider_account_id: account.provider_account_id,
+        }
+    }
+}
+
+#[derive(GraphQLInputObject, Debug)]
+#[graphql(description = "Changes to the caller's profile; omitted fields are left unchanged")]
+pub struct ProfileInput {
+    /// Display name, at most 100 characters. `null` or a blank string clears it.
+    pub name: Nullable<String>,
+    /// Short biography, at most 1000 characters. `null` or a blank string clears it.
+    pub bio: Nullable<String>,
+    /// `http` or `https` URL of the profile image. `null` or a blank string clears it.
+    pub image: Nullable<String>,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(context = Context, description = "A session started by following a sign-in link")]
+pub struct SignInPayload {
+    pub user: Timed<UserType>,
+    /// Secret identifying the session; shown only once.
+    pub session_token: String,
+    pub expires: DateTime<Utc>,
+}
+
+#[derive(GraphQLObject)]
+#[graphql(description = "A signed-in session of the current user; its token is never exposed")]
+pub struct SessionType {
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires: DateTime<Utc>,
+    /// When the session was last used, to within a few minutes.
+    pub last_seen_at: Option<DateTime<Utc>>,
+    /// User agent of the client that signed in, or for sessions started
+    /// through NextAuth, of the first client that used the session.
+    pub user_agent: Option<String>,
+    /// Address of the client that signed in, or for sessions started through
+    /// NextAuth, of the first client that used the session.
+    pub ip_address: Option<String>,
+    /// Whether this is the session the request was made with.
+    pub current: bool,
+}
+
+impl SessionType {
+    /// Converts a session, flagging it if it is `current_session_id`.
+    pub fn new(session: Session, current_session_id: Option<i32>) -> Self {
+        SessionType {
+            current: current_session_id == Some(session.id),
+            id: session.id,
+            created_at: session.created_at,
+            expires: session.expires,
+            last_seen_at: session.last_seen_at,
+            user_agent: session.user_agent,
+            ip_address: session.ip_address,
+        }
+    }
+}
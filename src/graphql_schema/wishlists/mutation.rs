@@ -0,0 +1,77 @@
+use crate::graphql_schema::configurations::types::ConfigurationType;
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::wishlists::types::WishlistType;
+use crate::types::errors::WishlistError;
+use crate::wishlists::{self, WishlistTarget};
+use juniper::{FieldResult, IntoFieldError};
+
+/// Saves a part to the wishlist `wishlist_id`, or to the wishlist called
+/// `name`, which is created if the caller has none by that name. Without
+/// either the part goes to the default wishlist.
+pub fn add_to_wishlist(
+    context: &Context,
+    part_id: i32,
+    wishlist_id: Option<i32>,
+    name: Option<String>,
+) -> FieldResult<WishlistType> {
+    let user = context.require_user()?;
+    let target = match (wishlist_id, name.as_deref()) {
+        (Some(_), Some(_)) => {
+            return Err(WishlistError::Validation(
+                "Give either a wishlist ID or a name, not both".to_string(),
+            )
+            .into_field_error())
+        }
+        (Some(id), None) => WishlistTarget::Id(id),
+        (None, Some(name)) => WishlistTarget::Name(name),
+        (None, None) => WishlistTarget::Name(wishlists::DEFAULT_NAME),
+    };
+
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| WishlistError::from(e).into_field_error())?;
+    wishlists::add_part(conn, user.id, target, part_id)
+        .map(WishlistType::from)
+        .map_err(WishlistError::into_field_error)
+}
+
+/// Removes a part from one of the caller's wishlists.
+pub fn remove_from_wishlist(
+    context: &Context,
+    wishlist_id: i32,
+    part_id: i32,
+) -> FieldResult<WishlistType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| WishlistError::from(e).into_field_error())?;
+    wishlists::remove_part(conn, user.id, wishlist_id, part_id)
+        .map(WishlistType::from)
+        .map_err(WishlistError::into_field_error)
+}
+
+/// Deletes one of the caller's wishlists.
+pub fn delete_wishlist(context: &Context, id: i32) -> FieldResult<bool> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| WishlistError::from(e).into_field_error())?;
+    wishlists::delete(conn, user.id, id)
+        .map(|_| true)
+        .map_err(WishlistError::into_field_error)
+}
+
+/// Creates a configuration owned by the caller from one of their wishlists.
+pub fn create_configuration_from_wishlist(
+    context: &Context,
+    wishlist_id: i32,
+    name: Option<String>,
+) -> FieldResult<ConfigurationType> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| WishlistError::from(e).into_field_error())?;
+    wishlists::create_configuration(conn, user.id, wishlist_id, name.as_deref())
+        .map(ConfigurationType::from)
+        .map_err(WishlistError::into_field_error)
+}
@@ -0,0 +1,18 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::wishlists::types::WishlistType;
+use crate::types::errors::WishlistError;
+use crate::wishlists;
+use juniper::{FieldResult, IntoFieldError};
+
+/// Fetches the caller's wishlists, oldest first.
+pub fn my_wishlists(context: &Context) -> FieldResult<Vec<WishlistType>> {
+    let user = context.require_user()?;
+    let conn = &mut context
+        .get_connection()
+        .map_err(|e| WishlistError::from(e).into_field_error())?;
+    Ok(wishlists::list(conn, user.id)
+        .map_err(WishlistError::into_field_error)?
+        .into_iter()
+        .map(WishlistType::from)
+        .collect())
+}
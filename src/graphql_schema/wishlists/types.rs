@@ -0,0 +1,52 @@
+use crate::graphql_schema::context::Context;
+use crate::graphql_schema::parts::part_graphql::PartGraphQL;
+use crate::graphql_schema::timed::Timed;
+use crate::wishlists::{SavedPart, WishlistDetails};
+use chrono::{DateTime, Utc};
+use juniper::GraphQLObject;
+
+#[derive(GraphQLObject)]
+#[graphql(context = Context, description = "A part saved to a wishlist")]
+pub struct WishlistItemType {
+    pub part: Timed<PartGraphQL>,
+    #[graphql(name = "addedAt")]
+    pub added_at: DateTime<Utc>,
+}
+
+impl From<SavedPart> for WishlistItemType {
+    fn from(saved: SavedPart) -> Self {
+        WishlistItemType {
+            part: Timed(PartGraphQL::from_part(saved.part)),
+            added_at: saved.added_at,
+        }
+    }
+}
+
+#[derive(GraphQLObject)]
+#[graphql(context = Context, description = "A named list of parts saved by the signed-in user")]
+pub struct WishlistType {
+    pub id: i32,
+    pub name: String,
+    #[graphql(name = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[graphql(name = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+    /// Saved parts, most recently added first.
+    pub items: Vec<WishlistItemType>,
+}
+
+impl From<WishlistDetails> for WishlistType {
+    fn from(details: WishlistDetails) -> Self {
+        WishlistType {
+            id: details.wishlist.id,
+            name: details.wishlist.name,
+            created_at: details.wishlist.created_at,
+            updated_at: details.wishlist.updated_at,
+            items: details
+                .items
+                .into_iter()
+                .map(WishlistItemType::from)
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,480 @@
+// src/graphql_ws.rs
+
+use crate::graphql_handler::{app_data, error_body, Executor, GraphQLBody, PreparedOperation};
+use crate::metrics;
+use crate::settings::SubscriptionSettings;
+use actix_codec::{Decoder, Encoder};
+use actix_http::ws::{self, CloseCode, CloseReason, Codec, Frame, Message};
+use actix_web::guard::GuardContext;
+use actix_web::http::header;
+use actix_web::rt::task::JoinHandle;
+use actix_web::rt::time::{interval_at, sleep, Instant as RtInstant};
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures::channel::mpsc::{channel, Sender};
+use futures::{SinkExt, StreamExt};
+use juniper::http::{GraphQLRequest, GraphQLResponse};
+use juniper::{DefaultScalarValue, OperationType};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Subprotocol of the `graphql-ws` library.
+pub const GRAPHQL_TRANSPORT_WS_PROTOCOL: &str = "graphql-transport-ws";
+
+/// Legacy subprotocol of `subscriptions-transport-ws`, still used by many clients.
+pub const GRAPHQL_WS_PROTOCOL: &str = "graphql-ws";
+
+/// How long a client has to send `connection_init` after connecting.
+const CONNECTION_INIT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Messages queued for a client before operations wait for it to read them.
+const OUTGOING_BUFFER: usize = 64;
+
+/// The GraphQL over WebSocket protocol spoken on a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Protocol {
+    /// `graphql-transport-ws`
+    TransportWs,
+    /// `graphql-ws` (legacy)
+    Legacy,
+}
+
+impl Protocol {
+    /// Picks the protocol from the client's `Sec-WebSocket-Protocol` header,
+    /// defaulting to `graphql-transport-ws` when the header is absent.
+    fn negotiate(req: &HttpRequest) -> Option<Self> {
+        let offered: Vec<&str> = req
+            .headers()
+            .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+
+        if offered.is_empty() || offered.contains(&GRAPHQL_TRANSPORT_WS_PROTOCOL) {
+            Some(Protocol::TransportWs)
+        } else if offered.contains(&GRAPHQL_WS_PROTOCOL) {
+            Some(Protocol::Legacy)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Protocol::TransportWs => GRAPHQL_TRANSPORT_WS_PROTOCOL,
+            Protocol::Legacy => GRAPHQL_WS_PROTOCOL,
+        }
+    }
+}
+
+/// A message received from the client.
+#[derive(Debug, Deserialize)]
+struct ClientMessage {
+    #[serde(rename = "type")]
+    kind: String,
+    id: Option<String>,
+    payload: Option<Value>,
+}
+
+/// Returns `true` for requests asking to upgrade the connection to WebSocket.
+pub fn is_websocket_upgrade(ctx: &GuardContext) -> bool {
+    ctx.head().upgrade()
+}
+
+/// Handles WebSocket connections to `/graphql`, running queries, mutations and
+/// subscriptions over the `graphql-transport-ws` or legacy `graphql-ws` protocol.
+pub async fn graphql_ws_handler(
+    req: HttpRequest,
+    payload: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    let protocol = match Protocol::negotiate(&req) {
+        Some(protocol) => protocol,
+        None => {
+            return Ok(HttpResponse::BadRequest().body(format!(
+                "Unsupported WebSocket subprotocol; use {} or {}",
+                GRAPHQL_TRANSPORT_WS_PROTOCOL, GRAPHQL_WS_PROTOCOL
+            )))
+        }
+    };
+
+    ws::verify_handshake(req.head())?;
+    // Mirrors `ws::handshake_response`, which builds an actix-http response
+    let accept = match req.headers().get(header::SEC_WEBSOCKET_KEY) {
+        Some(key) => ws::hash_key(key.as_bytes()),
+        None => return Err(ws::HandshakeError::BadWebsocketKey.into()),
+    };
+    let executor = Executor::from_request(&req)?;
+    let settings = app_data::<SubscriptionSettings>(&req)?;
+
+    let (sender, receiver) = channel(OUTGOING_BUFFER);
+    let connection = Connection {
+        protocol,
+        executor,
+        sender,
+        operations: HashMap::new(),
+        max_operations: settings.max_operations,
+        initialized: false,
+    };
+    actix_web::rt::spawn(connection.run(payload, settings.keep_alive()));
+
+    let mut response = HttpResponse::SwitchingProtocols();
+    response
+        .upgrade("websocket")
+        .insert_header((header::SEC_WEBSOCKET_ACCEPT, &accept[..]));
+    if req.headers().contains_key(header::SEC_WEBSOCKET_PROTOCOL) {
+        response.insert_header((header::SEC_WEBSOCKET_PROTOCOL, protocol.name()));
+    }
+    let mut codec = Codec::new();
+    let frames = receiver.map(move |message| {
+        let mut buf = BytesMut::new();
+        codec.encode(message, &mut buf).map(|()| buf.freeze())
+    });
+    Ok(response.streaming(frames))
+}
+
+/// State of one WebSocket connection.
+struct Connection {
+    protocol: Protocol,
+    executor: Executor,
+    sender: Sender<Message>,
+    /// Running operations by client-chosen ID.
+    operations: HashMap<String, JoinHandle<()>>,
+    max_operations: usize,
+    initialized: bool,
+}
+
+impl Connection {
+    /// Reads frames until the client disconnects or the connection is closed.
+    async fn run(mut self, mut payload: web::Payload, keep_alive: Duration) {
+        let mut codec = Codec::new();
+        let mut buf = BytesMut::new();
+        let mut keep_alive = interval_at(RtInstant::now() + keep_alive, keep_alive);
+        let init_deadline = sleep(CONNECTION_INIT_TIMEOUT);
+        tokio::pin!(init_deadline);
+
+        'connection: loop {
+            loop {
+                match codec.decode(&mut buf) {
+                    Ok(Some(frame)) => {
+                        if !self.handle_frame(frame).await {
+                            break 'connection;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        debug!("Invalid WebSocket frame: {}", e);
+                        self.close(CloseCode::Protocol, "Invalid frame").await;
+                        break 'connection;
+                    }
+                }
+            }
+
+            tokio::select! {
+                chunk = payload.next() => match chunk {
+                    Some(Ok(bytes)) => buf.extend_from_slice(&bytes),
+                    _ => break,
+                },
+                _ = keep_alive.tick(), if self.initialized => self.send_keep_alive().await,
+                _ = &mut init_deadline, if !self.initialized => {
+                    self.close(CloseCode::Other(4408), "Connection initialisation timeout")
+                        .await;
+                    break;
+                }
+            }
+        }
+
+        for (_, operation) in self.operations.drain() {
+            operation.abort();
+        }
+    }
+
+    /// Handles one frame, returning `false` once the connection should close.
+    async fn handle_frame(&mut self, frame: Frame) -> bool {
+        match frame {
+            Frame::Text(bytes) => self.handle_text(&bytes).await,
+            Frame::Ping(bytes) => {
+                self.send(Message::Pong(bytes)).await;
+                true
+            }
+            Frame::Pong(_) => true,
+            Frame::Close(reason) => {
+                self.send(Message::Close(reason)).await;
+                false
+            }
+            Frame::Binary(_) | Frame::Continuation(_) => {
+                self.close(CloseCode::Unsupported, "Only text messages are supported")
+                    .await;
+                false
+            }
+        }
+    }
+
+    /// Handles a protocol message, returning `false` once the connection should close.
+    async fn handle_text(&mut self, bytes: &Bytes) -> bool {
+        let message: ClientMessage = match serde_json::from_slice(bytes) {
+            Ok(message) => message,
+            Err(_) => {
+                self.close(CloseCode::Other(4400), "Invalid message received")
+                    .await;
+                return false;
+            }
+        };
+
+        match (self.protocol, message.kind.as_str()) {
+            (_, "connection_init") => {
+                if self.initialized {
+                    if self.protocol == Protocol::TransportWs {
+                        self.close(CloseCode::Other(4429), "Too many initialisation requests")
+                            .await;
+                        return false;
+                    }
+                    return true;
+                }
+                self.initialized = true;
+                self.send_json(json!({ "type": "connection_ack" })).await;
+                if self.protocol == Protocol::Legacy {
+                    self.send_keep_alive().await;
+                }
+                true
+            }
+            (Protocol::TransportWs, "ping") => {
+                let mut pong = json!({ "type": "pong" });
+                if let Some(payload) = message.payload {
+                    pong["payload"] = payload;
+                }
+                self.send_json(pong).await;
+                true
+            }
+            (Protocol::TransportWs, "pong") => true,
+            (Protocol::TransportWs, "subscribe") | (Protocol::Legacy, "start") => {
+                self.start(message.id, message.payload).await
+            }
+            (Protocol::TransportWs, "complete") | (Protocol::Legacy, "stop") => {
+                if let Some(id) = message.id {
+                    self.stop(&id).await;
+                }
+                true
+            }
+            (Protocol::Legacy, "connection_terminate") => false,
+            (_, kind) => {
+                debug!("Unexpected WebSocket message type: {}", kind);
+                self.close(CloseCode::Other(4400), "Unknown message type")
+                    .await;
+                false
+            }
+        }
+    }
+
+    /// Starts an operation, returning `false` if the connection was closed instead.
+    async fn start(&mut self, id: Option<String>, payload: Option<Value>) -> bool {
+        if !self.initialized {
+            self.close(CloseCode::Other(4401), "Unauthorized").await;
+            return false;
+        }
+        let id = match id {
+            Some(id) => id,
+            None => {
+                self.close(CloseCode::Other(4400), "Operation ID is required")
+                    .await;
+                return false;
+            }
+        };
+
+        self.operations
+            .retain(|_, operation| !operation.is_finished());
+        if self.operations.contains_key(&id) {
+            self.close(
+                CloseCode::Other(4409),
+                &format!("Subscriber for {} already exists", id),
+            )
+            .await;
+            return false;
+        }
+        if self.operations.len() >= self.max_operations {
+            let errors = json!([{ "message": "Too many operations on this connection" }]);
+            send_error(&mut self.sender, self.protocol, &id, errors).await;
+            return true;
+        }
+
+        let body = match payload.map(serde_json::from_value::<GraphQLBody>) {
+            Some(Ok(body)) => body,
+            _ => {
+                let errors = json!([{ "message": "Invalid operation payload" }]);
+                send_error(&mut self.sender, self.protocol, &id, errors).await;
+                return true;
+            }
+        };
+
+        let operation = run_operation(
+            self.executor.clone(),
+            self.protocol,
+            id.clone(),
+            body,
+            self.sender.clone(),
+        );
+        self.operations.insert(id, actix_web::rt::spawn(operation));
+        true
+    }
+
+    /// Cancels a running operation.
+    async fn stop(&mut self, id: &str) {
+        if let Some(operation) = self.operations.remove(id) {
+            operation.abort();
+        }
+        // The legacy protocol acknowledges `stop` with `complete`
+        if self.protocol == Protocol::Legacy {
+            self.send_json(json!({ "type": "complete", "id": id }))
+                .await;
+        }
+    }
+
+    async fn send_keep_alive(&mut self) {
+        match self.protocol {
+            Protocol::TransportWs => self.send_json(json!({ "type": "ping" })).await,
+            Protocol::Legacy => self.send_json(json!({ "type": "ka" })).await,
+        }
+    }
+
+    async fn close(&mut self, code: CloseCode, description: &str) {
+        info!("Closing GraphQL WebSocket connection: {}", description);
+        self.send(Message::Close(Some(CloseReason {
+            code,
+            description: Some(description.to_string()),
+        })))
+        .await;
+    }
+
+    async fn send_json(&mut self, message: Value) {
+        send_json(&mut self.sender, message).await;
+    }
+
+    async fn send(&mut self, message: Message) {
+        // Sending fails only after the client has disconnected
+        let _ = self.sender.send(message).await;
+    }
+}
+
+/// Runs one operation, streaming its results to the client.
+async fn run_operation(
+    executor: Executor,
+    protocol: Protocol,
+    id: String,
+    body: GraphQLBody,
+    mut sender: Sender<Message>,
+) {
+    let PreparedOperation { query, operation } = match executor.prepare(&body).await {
+        Ok(prepared) => prepared,
+        Err(error_body) => {
+            send_error(&mut sender, protocol, &id, error_body["errors"].clone()).await;
+            return;
+        }
+    };
+    if let Err(e) = executor.check_rate_limit(operation.as_ref()).await {
+        send_error(&mut sender, protocol, &id, error_body(e)["errors"].clone()).await;
+        return;
+    }
+    let context = executor.context();
+    let next_type = match protocol {
+        Protocol::TransportWs => "next",
+        Protocol::Legacy => "data",
+    };
+
+    let is_subscription = matches!(
+        operation,
+        Some(ref op) if op.operation_type == OperationType::Subscription
+    );
+    if !is_subscription {
+        let start_time = Instant::now();
+        let request = GraphQLRequest::new(query, body.operation_name, body.variables);
+        let response = request.execute(&executor.schema, &context).await;
+        metrics::observe_operation(
+            operation.as_ref().and_then(|op| op.name.as_deref()),
+            start_time.elapsed(),
+        );
+
+        let response = serde_json::to_value(&response).unwrap_or(Value::Null);
+        metrics::record_graphql_errors(&response);
+        send_json(
+            &mut sender,
+            json!({ "type": next_type, "id": id, "payload": response }),
+        )
+        .await;
+        send_json(&mut sender, json!({ "type": "complete", "id": id })).await;
+        return;
+    }
+
+    let _active = metrics::track_subscription();
+    let variables = body.variables();
+    let resolved = juniper::resolve_into_stream(
+        &query,
+        body.operation_name.as_deref(),
+        &executor.schema,
+        &variables,
+        &context,
+    )
+    .await;
+    let fields = match resolved {
+        Ok((juniper::Value::Object(fields), errors)) if errors.is_empty() => fields,
+        Ok((_, errors)) => {
+            let errors = serde_json::to_value(&errors).unwrap_or(Value::Null);
+            send_error(&mut sender, protocol, &id, errors).await;
+            return;
+        }
+        Err(e) => {
+            let response =
+                serde_json::to_value(GraphQLResponse::<DefaultScalarValue>::from_result(Err(e)))
+                    .unwrap_or(Value::Null);
+            send_error(&mut sender, protocol, &id, response["errors"].clone()).await;
+            return;
+        }
+    };
+
+    // Subscriptions select exactly one root field, which validation enforces
+    for (field, value) in fields {
+        let mut events = match value {
+            juniper::Value::Scalar(events) => events,
+            _ => continue,
+        };
+        while let Some(event) = events.next().await {
+            let payload = match event {
+                Ok(value) => json!({ "data": { field.as_str(): value } }),
+                Err(e) => json!({ "data": null, "errors": [e] }),
+            };
+            if sender
+                .send(Message::Text(
+                    json!({ "type": next_type, "id": id, "payload": payload })
+                        .to_string()
+                        .into(),
+                ))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+    send_json(&mut sender, json!({ "type": "complete", "id": id })).await;
+}
+
+/// Reports errors that ended an operation before it produced results.
+async fn send_error(sender: &mut Sender<Message>, protocol: Protocol, id: &str, errors: Value) {
+    warn!("GraphQL WebSocket operation {} failed: {}", id, errors);
+    let payload = match protocol {
+        Protocol::TransportWs => errors,
+        // The legacy protocol sends a single error object
+        Protocol::Legacy => errors.get(0).cloned().unwrap_or(errors),
+    };
+    send_json(
+        sender,
+        json!({ "type": "error", "id": id, "payload": payload }),
+    )
+    .await;
+}
+
+async fn send_json(sender: &mut Sender<Message>, message: Value) {
+    let _ = sender.send(Message::Text(message.to_string().into())).await;
+}
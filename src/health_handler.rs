@@ -0,0 +1,144 @@
+// src/health_handler.rs
+
+use crate::graphql_schema::context::Context;
+use crate::migration::MIGRATIONS;
+use actix_web::{web, HttpResponse};
+use diesel::PgConnection;
+use diesel_migrations::MigrationHarness;
+use log::error;
+use serde::Serialize;
+use std::time::Duration;
+
+/// Crate version embedded at compile time.
+pub const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Git commit embedded at compile time by `build.rs`.
+pub const GIT_COMMIT: &str = env!("GIT_COMMIT");
+
+/// Maximum time the readiness probe waits for a pooled connection.
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Response body for `/healthz`.
+#[derive(Serialize)]
+struct HealthStatus {
+    status: &'static str,
+}
+
+/// Response body for `/readyz`.
+#[derive(Serialize)]
+struct ReadinessStatus {
+    status: &'static str,
+    checks: ReadinessChecks,
+}
+
+/// Outcome of each readiness check.
+#[derive(Serialize)]
+struct ReadinessChecks {
+    database: String,
+    migrations: String,
+}
+
+/// Response body for `/version`.
+#[derive(Serialize)]
+struct BuildInfo {
+    version: &'static str,
+    git_commit: &'static str,
+    migration_version: Option<String>,
+}
+
+/// Liveness probe: reports that the process is up without touching the database.
+pub async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(HealthStatus { status: "ok" })
+}
+
+/// Readiness probe: checks that a connection can be checked out of the pool and
+/// that no embedded migrations are pending.
+///
+/// Responds with `200 OK` when ready and `503 Service Unavailable` otherwise.
+pub async fn readyz(context: web::Data<Context>) -> HttpResponse {
+    let pool = context.db.clone();
+
+    let checks = web::block(move || {
+        let mut conn = match pool.get_timeout(READINESS_TIMEOUT) {
+            Ok(conn) => conn,
+            Err(e) => {
+                return ReadinessChecks {
+                    database: format!("error: {}", e),
+                    migrations: "unknown".to_string(),
+                }
+            }
+        };
+
+        let migrations = match conn.has_pending_migration(MIGRATIONS) {
+            Ok(false) => "ok".to_string(),
+            Ok(true) => "pending".to_string(),
+            Err(e) => format!("error: {}", e),
+        };
+
+        ReadinessChecks {
+            database: "ok".to_string(),
+            migrations,
+        }
+    })
+    .await;
+
+    match checks {
+        Ok(checks) if checks.database == "ok" && checks.migrations == "ok" => HttpResponse::Ok()
+            .json(ReadinessStatus {
+                status: "ready",
+                checks,
+            }),
+        Ok(checks) => {
+            error!(
+                "Readiness check failed: database={}, migrations={}",
+                checks.database, checks.migrations
+            );
+            HttpResponse::ServiceUnavailable().json(ReadinessStatus {
+                status: "not_ready",
+                checks,
+            })
+        }
+        Err(e) => {
+            error!("Readiness check could not run: {}", e);
+            HttpResponse::ServiceUnavailable().json(ReadinessStatus {
+                status: "not_ready",
+                checks: ReadinessChecks {
+                    database: "unknown".to_string(),
+                    migrations: "unknown".to_string(),
+                },
+            })
+        }
+    }
+}
+
+/// Build information: crate version, git commit and the latest applied migration.
+///
+/// The migration version is `null` when the database cannot be reached.
+pub async fn version(context: web::Data<Context>) -> HttpResponse {
+    let pool = context.db.clone();
+
+    let migration_version = web::block(move || {
+        let mut conn = pool.get_timeout(READINESS_TIMEOUT).ok()?;
+        latest_applied_migration(&mut conn)
+    })
+    .await
+    .ok()
+    .flatten();
+
+    HttpResponse::Ok().json(BuildInfo {
+        version: CRATE_VERSION,
+        git_commit: GIT_COMMIT,
+        migration_version,
+    })
+}
+
+/// Returns the most recent applied migration version, if any.
+fn latest_applied_migration(conn: &mut PgConnection) -> Option<String> {
+    match conn.applied_migrations() {
+        Ok(versions) => versions.iter().map(|v| v.to_string()).max(),
+        Err(e) => {
+            error!("Failed to read applied migrations: {}", e);
+            None
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// src/inventory.rs
+
+use crate::diesel_schema::parts::inventory;
+use crate::models::parts::inventory::Inventory;
+use crate::types::errors::InventoryError;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::PgConnection;
+
+/// A quantity of one part held back for an order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StockReservation {
+    pub part_id: i32,
+    pub quantity: i32,
+}
+
+/// Reserves stock for each line, failing without changes if any part lacks
+/// available stock.
+pub fn reserve(
+    conn: &mut PgConnection,
+    reservations: &[StockReservation],
+) -> Result<(), InventoryError> {
+    conn.transaction(|conn| {
+        for reservation in locking_order(reservations) {
+            let stock = inventory::table
+                .find(reservation.part_id)
+                .select(Inventory::as_select())
+                .for_update()
+                .first::<Inventory>(conn)
+                .optional()?;
+            let available = stock.as_ref().map_or(0, Inventory::available_quantity);
+            if reservation.quantity > available {
+                return Err(InventoryError::InsufficientStock {
+                    part_id: reservation.part_id,
+                    requested: reservation.quantity,
+                    available,
+                });
+            }
+
+            diesel::update(inventory::table.find(reservation.part_id))
+                .set((
+                    inventory::quantity_reserved
+                        .eq(inventory::quantity_reserved + reservation.quantity),
+                    inventory::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// Returns reserved stock to the available pool, e.g. when an order is cancelled.
+pub fn release(
+    conn: &mut PgConnection,
+    reservations: &[StockReservation],
+) -> Result<(), InventoryError> {
+    conn.transaction(|conn| {
+        for reservation in locking_order(reservations) {
+            diesel::update(inventory::table.find(reservation.part_id))
+                .set((
+                    inventory::quantity_reserved
+                        .eq(inventory::quantity_reserved - reservation.quantity),
+                    inventory::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// Removes reserved stock from the shelf, e.g. when an order ships.
+pub fn fulfil(
+    conn: &mut PgConnection,
+    reservations: &[StockReservation],
+) -> Result<(), InventoryError> {
+    conn.transaction(|conn| {
+        for reservation in locking_order(reservations) {
+            diesel::update(inventory::table.find(reservation.part_id))
+                .set((
+                    inventory::quantity_on_hand
+                        .eq(inventory::quantity_on_hand - reservation.quantity),
+                    inventory::quantity_reserved
+                        .eq(inventory::quantity_reserved - reservation.quantity),
+                    inventory::updated_at.eq(now),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
+/// Sorts reservations by part so concurrent transactions lock rows in the same
+/// order and cannot deadlock.
+fn locking_order(reservations: &[StockReservation]) -> Vec<StockReservation> {
+    let mut sorted = reservations.to_vec();
+    sorted.sort_by_key(|reservation| reservation.part_id);
+    sorted
+}
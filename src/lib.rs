@@ -1,8 +1,32 @@
+pub mod accounts;
+pub mod api_keys;
+pub mod catalog;
+pub mod comparisons;
+pub mod configurations;
 pub mod data_import;
 pub mod diesel_schema;
 pub mod graphql_handler;
 pub mod graphql_schema;
+pub mod graphql_ws;
+pub mod health_handler;
+pub mod inventory;
+pub mod logger;
+pub mod mailer;
+pub mod metrics;
 pub mod middleware;
 pub mod migration;
 pub mod models;
+pub mod notifications;
+pub mod orders;
+pub mod persisted_queries;
+pub mod profiles;
+pub mod quote_requests;
+pub mod rate_limit;
+pub mod redaction;
+pub mod reference_cache;
+pub mod response_cache;
+pub mod sessions;
+pub mod settings;
 pub mod types;
+pub mod verification;
+pub mod wishlists;
@@ -0,0 +1,120 @@
+// src/logger.rs
+
+use crate::settings::{LogFormat, LoggingSettings};
+use chrono::{SecondsFormat, Utc};
+use env_logger::Env;
+use log::kv::{self, Key, Value as KvValue, VisitSource};
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::future::Future;
+use std::io::Write;
+
+/// Per-request fields attached to every log record emitted while the request is handled.
+#[derive(Debug, Default)]
+pub struct RequestLogContext {
+    pub request_id: String,
+    operation_name: RefCell<Option<String>>,
+    user_id: RefCell<Option<i32>>,
+}
+
+tokio::task_local! {
+    static REQUEST_LOG_CONTEXT: RequestLogContext;
+}
+
+/// Runs `future` with `request_id` attached to every log record it emits.
+pub async fn with_request_id<F: Future>(request_id: String, future: F) -> F::Output {
+    let context = RequestLogContext {
+        request_id,
+        ..RequestLogContext::default()
+    };
+    REQUEST_LOG_CONTEXT.scope(context, future).await
+}
+
+/// Returns the ID of the request currently being handled, if any.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_LOG_CONTEXT
+        .try_with(|ctx| ctx.request_id.clone())
+        .ok()
+}
+
+/// Attaches the GraphQL operation name to the current request's log records.
+pub fn set_operation_name(operation_name: Option<&str>) {
+    let _ = REQUEST_LOG_CONTEXT.try_with(|ctx| {
+        *ctx.operation_name.borrow_mut() = operation_name.map(str::to_string);
+    });
+}
+
+/// Attaches the authenticated user's ID to the current request's log records.
+pub fn set_user_id(user_id: Option<i32>) {
+    let _ = REQUEST_LOG_CONTEXT.try_with(|ctx| {
+        *ctx.user_id.borrow_mut() = user_id;
+    });
+}
+
+/// Initializes the global logger with the configured level and format.
+pub fn init(settings: &LoggingSettings) {
+    let mut builder =
+        env_logger::Builder::from_env(Env::default().default_filter_or(&settings.level));
+
+    if settings.format == LogFormat::Json {
+        builder.format(|buf, record| {
+            let line = json_record(record);
+            writeln!(buf, "{}", line)
+        });
+    }
+
+    builder.init();
+}
+
+/// Serializes a log record, its key-values and the request fields as one JSON object.
+fn json_record(record: &log::Record) -> Value {
+    let mut fields = Map::new();
+    fields.insert(
+        "timestamp".to_string(),
+        Value::from(Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)),
+    );
+    fields.insert("level".to_string(), Value::from(record.level().as_str()));
+    fields.insert("target".to_string(), Value::from(record.target()));
+    fields.insert(
+        "message".to_string(),
+        Value::from(record.args().to_string()),
+    );
+
+    let _ = REQUEST_LOG_CONTEXT.try_with(|ctx| {
+        fields.insert(
+            "request_id".to_string(),
+            Value::from(ctx.request_id.as_str()),
+        );
+        if let Some(operation_name) = ctx.operation_name.borrow().as_deref() {
+            fields.insert("operation_name".to_string(), Value::from(operation_name));
+        }
+        if let Some(user_id) = *ctx.user_id.borrow() {
+            fields.insert("user_id".to_string(), Value::from(user_id));
+        }
+    });
+
+    let _ = record.key_values().visit(&mut JsonFields(&mut fields));
+
+    Value::Object(fields)
+}
+
+/// Collects structured key-values (e.g. `info!(duration_ms = 3; "...")`) into a JSON map.
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for JsonFields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: KvValue<'kvs>) -> Result<(), kv::Error> {
+        let json = if let Some(v) = value.to_i64() {
+            Value::from(v)
+        } else if let Some(v) = value.to_u64() {
+            Value::from(v)
+        } else if let Some(v) = value.to_f64() {
+            Value::from(v)
+        } else if let Some(v) = value.to_bool() {
+            Value::from(v)
+        } else {
+            Value::from(value.to_string())
+        };
+        self.0.insert(key.as_str().to_string(), json);
+        Ok(())
+    }
+}
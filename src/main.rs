@@ -5,47 +5,65 @@ use actix_web::{guard, web, App, HttpServer, Responder};
 use diesel::r2d2::{ConnectionManager, Pool};
 use diesel::PgConnection;
 use dotenv::dotenv;
-use env_logger::Env;
-use log::{error, info};
-use rust_backend::diesel_schema::users;
-use rust_backend::graphql_handler::graphql_handler;
+use log::{error, info, warn};
+use rust_backend::graphql_handler::{graphql_get_handler, graphql_handler};
 use rust_backend::graphql_schema::context::Context;
 use rust_backend::graphql_schema::schema::create_schema;
+use rust_backend::graphql_ws::{graphql_ws_handler, is_websocket_upgrade};
+use rust_backend::health_handler::{healthz, readyz, version};
+use rust_backend::logger;
+use rust_backend::mailer;
+use rust_backend::metrics::{self, metrics_handler};
+use rust_backend::middleware::auth::Authentication;
 use rust_backend::middleware::logging::GraphQLLogging;
+use rust_backend::middleware::rate_limit::RateLimiting;
+use rust_backend::middleware::request_id::{RequestId, REQUEST_ID_HEADER};
 use rust_backend::middleware::timing::Timing;
-use rust_backend::models::auth::User;
-use std::env;
+use rust_backend::notifications::{spawn_listener, NotificationHub};
+use rust_backend::persisted_queries::PersistedQueryStore;
+use rust_backend::rate_limit::{RateLimiter, RATE_LIMIT_HEADERS};
+use rust_backend::redaction::mask_connection_string;
+use rust_backend::reference_cache::ReferenceCache;
+use rust_backend::response_cache::{spawn_revision_poller, ResponseCache};
+use rust_backend::sessions::spawn_cleanup;
+use rust_backend::settings::{CorsSettings, MailTransport, Settings};
+use rust_backend::verification::EmailLinks;
 use std::sync::Arc;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     println!("Starting the Rust backend server...");
 
-    // Initialize the logger with environment variables or default to "info" level
-    env_logger::Builder::from_env(Env::default().default_filter_or("info")).init();
-    info!("Logger initialized");
-
     // Load environment variables from .env file
     dotenv().ok();
-    info!("Loaded environment variables from .env");
-
-    // Retrieve DATABASE_URL from environment variables
-    let database_url = match env::var("DATABASE_URL") {
-        Ok(url) => {
-            println!("DATABASE_URL found: {}", url);
-            info!("Database URL: {}", url);
-            url
-        }
-        Err(_) => {
-            error!("DATABASE_URL must be set in .env");
-            println!("Error: DATABASE_URL must be set in .env");
+
+    // Load and validate settings from the environment and optional config file
+    let settings = match Settings::load() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: invalid configuration: {}", e);
             std::process::exit(1);
         }
     };
 
+    // Initialize the logger with the configured level and format
+    logger::init(&settings.logging);
+    info!("Logger initialized");
+    info!("Loaded settings: {:?}", settings.server);
+    info!(
+        "Database URL: {}",
+        mask_connection_string(&settings.database.url)
+    );
+
     // Set up Diesel connection pool
-    let manager = ConnectionManager::<PgConnection>::new(&database_url);
-    let pool = match Pool::builder().build(manager) {
+    let manager = ConnectionManager::<PgConnection>::new(&settings.database.url);
+    let pool = match Pool::builder()
+        .max_size(settings.database.pool_max_size)
+        .min_idle(settings.database.pool_min_idle)
+        .connection_timeout(settings.database.connection_timeout())
+        .idle_timeout(settings.database.idle_timeout())
+        .build(manager)
+    {
         Ok(pool) => {
             info!("Database connection pool created successfully.");
             pool
@@ -56,61 +74,218 @@ async fn main() -> std::io::Result<()> {
         }
     };
 
+    // Register Prometheus metrics so they are exported before first use
+    metrics::init();
+
     // Create Juniper GraphQL schema using the new modular RootQuery
     let schema = create_schema();
     let schema = Arc::new(schema);
     info!("GraphQL schema created.");
 
+    // Register persisted queries from the manifest, if one is configured
+    let persisted_query_store = web::Data::new(PersistedQueryStore::new(
+        pool.clone(),
+        settings.persisted_queries.cache_size,
+    ));
+    if let Some(manifest) = &settings.persisted_queries.manifest {
+        if let Err(e) = persisted_query_store.load_manifest(manifest) {
+            error!("Failed to load persisted queries: {}", e);
+            std::process::exit(1);
+        }
+    }
+    if settings.persisted_queries.allowlist_only {
+        info!("Only persisted queries are allowed to run.");
+    }
+    let persisted_query_settings = web::Data::new(settings.persisted_queries.clone());
+
+    // Cache catalog query responses until the catalog revision changes
+    let response_cache = web::Data::new(ResponseCache::new(
+        pool.clone(),
+        settings.response_cache.clone(),
+    ));
+    if settings.response_cache.enabled {
+        match response_cache.refresh_revision() {
+            Ok(revision) => info!("Response cache enabled at catalog revision {}.", revision),
+            Err(e) => warn!(
+                "Response cache disabled until the catalog revision can be read: {}",
+                e
+            ),
+        }
+        spawn_revision_poller(response_cache.clone());
+    }
+
+    // Resolve part manufacturers and categories from memory
+    let reference_cache = if settings.reference_cache.enabled {
+        let reference_cache = Arc::new(ReferenceCache::new(
+            pool.clone(),
+            settings.reference_cache.ttl(),
+        ));
+        let hook_cache = reference_cache.clone();
+        response_cache.on_revision_change(move || hook_cache.invalidate());
+        Some(reference_cache)
+    } else {
+        None
+    };
+
+    // Feed subscriptions with change notifications from the database
+    let notifications = if settings.subscriptions.enabled {
+        let hub = Arc::new(NotificationHub::new(settings.subscriptions.buffer_size));
+        if let Err(e) = spawn_listener(settings.database.url.clone(), hub.clone()) {
+            error!("Failed to start the database notification listener: {}", e);
+            std::process::exit(1);
+        }
+        Some(hub)
+    } else {
+        info!("GraphQL subscriptions are disabled.");
+        None
+    };
+    let subscription_settings = web::Data::new(settings.subscriptions.clone());
+
+    // Deliver verification and sign-in links
+    let email_links = match mailer::from_settings(&settings.mail) {
+        Ok(mailer) => Arc::new(EmailLinks::new(mailer, settings.mail.clone())),
+        Err(e) => {
+            error!("Failed to set up the mailer: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if settings.mail.transport == MailTransport::Outbox {
+        warn!(
+            "Emails are written to {} instead of being sent.",
+            settings.mail.outbox_dir
+        );
+    }
+
+    // Delete expired sessions and verification tokens
+    match settings.sessions.cleanup_interval() {
+        Some(interval) => spawn_cleanup(pool.clone(), interval),
+        None => info!("Expired session cleanup is disabled."),
+    }
+    let auth_pool = pool.clone();
+
+    // Limit requests per client, sharing buckets between instances with the Postgres store
+    let rate_limiter = web::Data::new(RateLimiter::from_settings(
+        &settings.rate_limit,
+        pool.clone(),
+    ));
+    if !settings.rate_limit.enabled {
+        info!("Rate limiting is disabled.");
+    }
+
     // Initialize GraphQL context with the database pool
-    let context = web::Data::new(Context::new(pool, None));
+    let context = web::Data::new(
+        Context::new(pool, None)
+            .with_reference_cache(reference_cache)
+            .with_notifications(notifications)
+            .with_email_links(Some(email_links)),
+    );
 
     // Clone schema for use in server closure
     let schema_clone = schema.clone();
+    let cors_settings = settings.cors.clone();
+    let logging_settings = settings.logging.clone();
+    let graphql_settings = web::Data::new(settings.graphql.clone());
+    let playground_enabled = settings.graphql.playground;
+    if !playground_enabled {
+        info!("GraphQL Playground is disabled.");
+    }
 
     // Start the Actix-web server
-    let server = HttpServer::new(move || {
-        // Configure CORS to allow frontend access (adjust origins as needed)
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .supports_credentials();
-
-        App::new()
+    let mut server = HttpServer::new(move || {
+        let mut app = App::new()
+            .wrap(RateLimiting::new(rate_limiter.clone().into_inner())) // Runs after authentication to key buckets by user or API key
+            .wrap(Authentication::new(auth_pool.clone())) // Runs with the request ID set
             .wrap(Timing)
-            .wrap(GraphQLLogging) // Logs GraphQL query and variables
-            .wrap(cors)
+            .wrap(GraphQLLogging::new(logging_settings.clone())) // Logs query and redacted variables
+            .wrap(build_cors(&cors_settings))
+            .wrap(RequestId) // Outermost, so every log line carries the request ID
             // Share the GraphQL schema with handlers
             .app_data(web::Data::new(schema_clone.clone()))
             // Share the GraphQL context with handlers
             .app_data(context.clone())
+            // Share the query depth and complexity limits with the GraphQL handler
+            .app_data(graphql_settings.clone())
+            // Share the persisted query store with the GraphQL handler
+            .app_data(persisted_query_store.clone())
+            .app_data(persisted_query_settings.clone())
+            // Share the response cache with the GraphQL handler
+            .app_data(response_cache.clone())
+            // Share the rate limiter with the GraphQL handler for per-operation limits
+            .app_data(rate_limiter.clone())
+            // Share the keep-alive interval with the WebSocket handler
+            .app_data(subscription_settings.clone())
             // GraphQL endpoint
             .service(
                 web::resource("/graphql")
-                    .guard(guard::Post())
-                    .to(graphql_handler),
+                    .route(web::post().to(graphql_handler))
+                    .route(
+                        web::get()
+                            .guard(guard::fn_guard(is_websocket_upgrade))
+                            .to(graphql_ws_handler),
+                    )
+                    .route(web::get().to(graphql_get_handler)),
             )
-            // GraphQL Playground endpoint
+            // Orchestrator probes and build information
+            .service(web::resource("/healthz").guard(guard::Get()).to(healthz))
+            .service(web::resource("/readyz").guard(guard::Get()).to(readyz))
+            .service(web::resource("/version").guard(guard::Get()).to(version))
+            // Prometheus metrics
             .service(
+                web::resource("/metrics")
+                    .guard(guard::Get())
+                    .to(metrics_handler),
+            );
+
+        // GraphQL Playground endpoint
+        if playground_enabled {
+            app = app.service(
                 web::resource("/")
                     .guard(guard::Get())
                     .to(playground_handler),
-            )
-    })
-    .bind("0.0.0.0:8080");
+            );
+        }
+
+        app
+    });
+
+    if let Some(workers) = settings.server.workers {
+        server = server.workers(workers);
+    }
 
-    match server {
+    let bind_address = settings.bind_address();
+    match server.bind(&bind_address) {
         Ok(server) => {
-            info!("Server started successfully at http://0.0.0.0:8080");
+            info!("Server started successfully at http://{}", bind_address);
             server.run().await
         }
         Err(e) => {
-            error!("Failed to start server: {}", e);
+            error!("Failed to bind server to {}: {}", bind_address, e);
             std::process::exit(1);
         }
     }
 }
 
+/// Builds the CORS policy from the configured allowed origins.
+fn build_cors(settings: &CorsSettings) -> Cors {
+    let cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        .expose_headers([REQUEST_ID_HEADER])
+        .expose_headers(RATE_LIMIT_HEADERS);
+
+    if settings.allows_any_origin() {
+        // Credentials cannot be combined with a wildcard origin
+        return cors.allow_any_origin();
+    }
+
+    settings
+        .allowed_origins
+        .iter()
+        .fold(cors.supports_credentials(), |cors, origin| {
+            cors.allowed_origin(origin)
+        })
+}
+
 /// Handler to serve the GraphQL Playground UI
 async fn playground_handler() -> impl Responder {
     // Import Juniper's playground source
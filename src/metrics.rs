@@ -0,0 +1,275 @@
+// src/metrics.rs
+
+use crate::graphql_schema::context::Context;
+use actix_web::{web, HttpResponse};
+use log::error;
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Encoder, Histogram, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec,
+    TextEncoder,
+};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Total HTTP requests by method, matched route and status code.
+pub static HTTP_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "http_requests_total",
+        "Total HTTP requests by method, route and status",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register http_requests_total")
+});
+
+/// HTTP request latency by method, matched route and status code.
+pub static HTTP_REQUEST_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "http_request_duration_seconds",
+        "HTTP request latency in seconds by method, route and status",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register http_request_duration_seconds")
+});
+
+/// GraphQL operation execution time by operation name, as labeled by
+/// [`operation_label`].
+pub static GRAPHQL_OPERATION_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "graphql_operation_duration_seconds",
+        "GraphQL operation execution time in seconds by operation name",
+        &["operation"]
+    )
+    .expect("Failed to register graphql_operation_duration_seconds")
+});
+
+/// GraphQL resolver execution time by parent type and field, recorded for
+/// the fields of objects wrapped in [`Timed`](crate::graphql_schema::timed::Timed).
+pub static GRAPHQL_RESOLVER_DURATION_SECONDS: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "graphql_resolver_duration_seconds",
+        "Execution time in seconds of GraphQL resolvers by parent type and field",
+        &["type", "field"],
+        vec![0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0]
+    )
+    .expect("Failed to register graphql_resolver_duration_seconds")
+});
+
+/// GraphQL errors returned to clients by error message.
+pub static GRAPHQL_ERRORS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "graphql_errors_total",
+        "GraphQL errors returned to clients by error message",
+        &["message"]
+    )
+    .expect("Failed to register graphql_errors_total")
+});
+
+/// GraphQL response cache lookups by result (`hit` or `miss`).
+pub static RESPONSE_CACHE_LOOKUPS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "graphql_response_cache_lookups_total",
+        "GraphQL response cache lookups by result",
+        &["result"]
+    )
+    .expect("Failed to register graphql_response_cache_lookups_total")
+});
+
+/// Requests refused by the rate limiter, by the limit that refused them
+/// (`route` or `operation`).
+pub static RATE_LIMITED_REQUESTS_TOTAL: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "rate_limited_requests_total",
+        "Requests refused by the rate limiter",
+        &["scope"]
+    )
+    .expect("Failed to register rate_limited_requests_total")
+});
+
+/// GraphQL subscriptions currently streaming to WebSocket clients.
+pub static GRAPHQL_ACTIVE_SUBSCRIPTIONS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "graphql_active_subscriptions",
+        "GraphQL subscriptions currently streaming to clients"
+    )
+    .expect("Failed to register graphql_active_subscriptions")
+});
+
+/// Database pool connections by state (`in_use` or `idle`).
+pub static DB_POOL_CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "db_pool_connections",
+        "Database pool connections by state",
+        &["state"]
+    )
+    .expect("Failed to register db_pool_connections")
+});
+
+/// Time spent waiting to check a connection out of the database pool.
+pub static DB_POOL_WAIT_SECONDS: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "db_pool_wait_seconds",
+        "Time spent waiting for a database pool connection in seconds",
+        vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0]
+    )
+    .expect("Failed to register db_pool_wait_seconds")
+});
+
+/// Label used for errors that are not raised by a resolver (parse and validation errors).
+const REQUEST_ERROR_LABEL: &str = "Invalid GraphQL request";
+
+/// Label of operations without a name.
+const ANONYMOUS_OPERATION_LABEL: &str = "anonymous";
+
+/// Label of named operations once the label set is full.
+const OTHER_OPERATION_LABEL: &str = "other";
+
+/// Most operation names seen in requests that get a label of their own, on
+/// top of the names from the persisted query manifest.
+const MAX_OPERATION_LABELS: usize = 100;
+
+/// Operation names used as labels, and how many of them came from requests.
+static OPERATION_LABELS: Lazy<Mutex<(HashSet<String>, usize)>> =
+    Lazy::new(|| Mutex::new((HashSet::new(), 0)));
+
+/// Registers every metric so they are exported before their first observation.
+pub fn init() {
+    Lazy::force(&HTTP_REQUESTS_TOTAL);
+    Lazy::force(&HTTP_REQUEST_DURATION_SECONDS);
+    Lazy::force(&GRAPHQL_OPERATION_DURATION_SECONDS);
+    Lazy::force(&GRAPHQL_RESOLVER_DURATION_SECONDS);
+    Lazy::force(&GRAPHQL_ERRORS_TOTAL);
+    Lazy::force(&RESPONSE_CACHE_LOOKUPS_TOTAL);
+    Lazy::force(&RATE_LIMITED_REQUESTS_TOTAL);
+    Lazy::force(&GRAPHQL_ACTIVE_SUBSCRIPTIONS);
+    Lazy::force(&DB_POOL_CONNECTIONS);
+    Lazy::force(&DB_POOL_WAIT_SECONDS);
+}
+
+/// Records a completed HTTP request.
+pub fn observe_http_request(method: &str, route: &str, status: u16, duration: Duration) {
+    let status = status.to_string();
+    let labels = [method, route, status.as_str()];
+    HTTP_REQUESTS_TOTAL.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION_SECONDS
+        .with_label_values(&labels)
+        .observe(duration.as_secs_f64());
+}
+
+/// Records the execution time of a GraphQL operation, given the name of the
+/// operation as parsed from its document.
+pub fn observe_operation(operation: Option<&str>, duration: Duration) {
+    GRAPHQL_OPERATION_DURATION_SECONDS
+        .with_label_values(&[&operation_label(operation)])
+        .observe(duration.as_secs_f64());
+}
+
+/// Gives an operation name from the persisted query manifest a label of its own.
+pub fn register_operation_name(name: &str) {
+    OPERATION_LABELS.lock().unwrap().0.insert(name.to_string());
+}
+
+/// Returns the label of an operation. Names are client-controlled, so only
+/// manifest names and the first [`MAX_OPERATION_LABELS`] other names become
+/// labels, keeping the number of series bounded.
+fn operation_label(operation: Option<&str>) -> String {
+    let Some(name) = operation else {
+        return ANONYMOUS_OPERATION_LABEL.to_string();
+    };
+    let mut labels = OPERATION_LABELS.lock().unwrap();
+    let (names, from_requests) = &mut *labels;
+    if names.contains(name) {
+        return name.to_string();
+    }
+    if *from_requests < MAX_OPERATION_LABELS {
+        *from_requests += 1;
+        names.insert(name.to_string());
+        return name.to_string();
+    }
+    OTHER_OPERATION_LABEL.to_string()
+}
+
+/// Records the execution time of a resolver.
+pub fn observe_resolver(type_name: &str, field: &str, duration: Duration) {
+    GRAPHQL_RESOLVER_DURATION_SECONDS
+        .with_label_values(&[type_name, field])
+        .observe(duration.as_secs_f64());
+}
+
+/// Records a response cache lookup.
+pub fn observe_response_cache(hit: bool) {
+    RESPONSE_CACHE_LOOKUPS_TOTAL
+        .with_label_values(&[if hit { "hit" } else { "miss" }])
+        .inc();
+}
+
+/// Records a request refused by the rate limiter.
+pub fn observe_rate_limited(scope: &str) {
+    RATE_LIMITED_REQUESTS_TOTAL
+        .with_label_values(&[scope])
+        .inc();
+}
+
+/// Counts a subscription as active until the returned guard is dropped.
+pub fn track_subscription() -> SubscriptionGuard {
+    GRAPHQL_ACTIVE_SUBSCRIPTIONS.inc();
+    SubscriptionGuard(())
+}
+
+/// Decrements the active subscription count when dropped.
+pub struct SubscriptionGuard(());
+
+impl Drop for SubscriptionGuard {
+    fn drop(&mut self) {
+        GRAPHQL_ACTIVE_SUBSCRIPTIONS.dec();
+    }
+}
+
+/// Records the time spent waiting for a database pool connection.
+pub fn observe_pool_wait(duration: Duration) {
+    DB_POOL_WAIT_SECONDS.observe(duration.as_secs_f64());
+}
+
+/// Counts the errors in a serialized GraphQL response.
+///
+/// Errors raised by resolvers carry a `path` and are labeled with their
+/// `FieldError` message; parse and validation errors are grouped under a
+/// single label to keep client-controlled text out of the label set.
+pub fn record_graphql_errors(response: &Value) {
+    let errors = match response.get("errors").and_then(Value::as_array) {
+        Some(errors) => errors,
+        None => return,
+    };
+
+    for err in errors {
+        let message = match (err.get("path"), err.get("message").and_then(Value::as_str)) {
+            (Some(_), Some(message)) => message,
+            _ => REQUEST_ERROR_LABEL,
+        };
+        GRAPHQL_ERRORS_TOTAL.with_label_values(&[message]).inc();
+    }
+}
+
+/// Handler for `/metrics`, exposing all metrics in the Prometheus text format.
+pub async fn metrics_handler(context: web::Data<Context>) -> HttpResponse {
+    let state = context.db.state();
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(state.idle_connections as i64);
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["in_use"])
+        .set((state.connections - state.idle_connections) as i64);
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(e) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        error!("Failed to encode metrics: {}", e);
+        return HttpResponse::InternalServerError().finish();
+    }
+
+    HttpResponse::Ok()
+        .content_type(encoder.format_type())
+        .body(buffer)
+}
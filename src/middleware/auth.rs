@@ -1 +1,170 @@
 // src/middleware/auth.rs
+
+use crate::api_keys::{self, ApiKeyPrincipal};
+use crate::rate_limit::RateLimiter;
+use crate::sessions::{self, ClientInfo, CurrentSession};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::{web, Error, HttpMessage};
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use futures::future::{ok, LocalBoxFuture, Ready};
+use log::{debug, error};
+use std::rc::Rc;
+use std::task::{Context as TaskContext, Poll};
+
+/// Cookies a NextAuth frontend keeps the session token in, checked in order.
+pub const SESSION_COOKIES: [&str; 2] = [
+    "__Secure-next-auth.session-token",
+    "next-auth.session-token",
+];
+
+/// Middleware that authenticates requests carrying a session token or an
+/// API key.
+///
+/// API keys are read from an `Authorization: ApiKey` header. Session tokens
+/// are read from an `Authorization: Bearer` header or, failing that, from the
+/// NextAuth session cookie. The user is stored in the request extensions
+/// together with the session, or with the key's [`ApiKeyPrincipal`]; requests
+/// without valid credentials continue anonymously.
+pub struct Authentication {
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl Authentication {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        Authentication { pool }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Authentication
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuthenticationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AuthenticationMiddleware {
+            service: Rc::new(service),
+            pool: self.pool.clone(),
+        })
+    }
+}
+
+pub struct AuthenticationMiddleware<S> {
+    service: Rc<S>,
+    pool: Pool<ConnectionManager<PgConnection>>,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthenticationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, ctx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let credential = credential(&req);
+        let srv = Rc::clone(&self.service);
+        let pool = self.pool.clone();
+
+        Box::pin(async move {
+            match credential {
+                Some(Credential::Session(token)) => authenticate_session(&req, pool, token).await,
+                Some(Credential::ApiKey(secret)) => authenticate_api_key(&req, pool, secret).await,
+                None => {}
+            }
+            srv.call(req).await
+        })
+    }
+}
+
+/// Credentials presented by a request.
+enum Credential {
+    Session(String),
+    ApiKey(String),
+}
+
+async fn authenticate_session(
+    req: &ServiceRequest,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    token: String,
+) {
+    let trust_proxy_headers = req
+        .app_data::<web::Data<RateLimiter>>()
+        .is_some_and(|limiter| limiter.trust_proxy_headers());
+    let client = ClientInfo::from_request(req.request(), trust_proxy_headers);
+    let result = web::block(move || {
+        let conn = &mut pool.get()?;
+        sessions::authenticate(conn, &token, &client)
+    })
+    .await;
+    match result {
+        Ok(Ok(Some((user, session)))) => {
+            debug!("Authenticated user {} with session {}", user.id, session.id);
+            req.extensions_mut().insert(user);
+            req.extensions_mut().insert(CurrentSession(session.id));
+        }
+        Ok(Ok(None)) => debug!("Ignoring an unknown or expired session token"),
+        Ok(Err(e)) => error!("Failed to authenticate session: {}", e),
+        Err(e) => error!("Failed to authenticate session: {}", e),
+    }
+}
+
+async fn authenticate_api_key(
+    req: &ServiceRequest,
+    pool: Pool<ConnectionManager<PgConnection>>,
+    secret: String,
+) {
+    let result = web::block(move || {
+        let conn = &mut pool.get()?;
+        api_keys::authenticate(conn, &secret)
+    })
+    .await;
+    match result {
+        Ok(Ok(Some((user, key)))) => {
+            debug!("Authenticated user {} with API key {}", user.id, key.id);
+            req.extensions_mut().insert(user);
+            req.extensions_mut().insert(ApiKeyPrincipal::from(&key));
+        }
+        Ok(Ok(None)) => debug!("Ignoring an unknown, expired or revoked API key"),
+        Ok(Err(e)) => error!("Failed to authenticate API key: {}", e),
+        Err(e) => error!("Failed to authenticate API key: {}", e),
+    }
+}
+
+/// Returns the credentials of a request, preferring the `Authorization` header.
+fn credential(req: &ServiceRequest) -> Option<Credential> {
+    let header = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split_once(' '))
+        .map(|(scheme, value)| (scheme, value.trim().to_string()))
+        .filter(|(_, value)| !value.is_empty());
+    match header {
+        Some((scheme, token)) if scheme.eq_ignore_ascii_case("bearer") => {
+            Some(Credential::Session(token))
+        }
+        Some((scheme, secret)) if scheme.eq_ignore_ascii_case("apikey") => {
+            Some(Credential::ApiKey(secret))
+        }
+        _ => SESSION_COOKIES
+            .iter()
+            .find_map(|name| req.cookie(name))
+            .map(|cookie| cookie.value().to_string())
+            .filter(|token| !token.is_empty())
+            .map(Credential::Session),
+    }
+}
@@ -1,17 +1,36 @@
 // src/middleware/logging.rs
 
+use crate::redaction;
+use crate::settings::{LoggingSettings, QueryLogMode};
 use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform};
 use actix_web::web::Bytes;
-use actix_web::{Error, HttpMessage};
+use actix_web::{web, Error, HttpMessage};
 use futures::future::{ok, LocalBoxFuture, Ready};
 use futures::stream::{self, StreamExt};
 use log::info;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::rc::Rc;
 use std::task::{Context as TaskContext, Poll};
 
-/// Middleware to log incoming GraphQL requests and their variables
-pub struct GraphQLLogging;
+/// Middleware to log incoming GraphQL requests and their variables.
+///
+/// Variables and inline arguments matching the configured redaction patterns
+/// are masked, logged text is truncated to the configured size, and in `hash` mode only the
+/// operation name and a hash of the query are logged.
+#[derive(Default)]
+pub struct GraphQLLogging {
+    settings: Rc<LoggingSettings>,
+}
+
+impl GraphQLLogging {
+    /// Creates the middleware with the given redaction and truncation settings.
+    pub fn new(settings: LoggingSettings) -> Self {
+        GraphQLLogging {
+            settings: Rc::new(settings),
+        }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for GraphQLLogging
 where
@@ -27,12 +46,14 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ok(GraphQLLoggingMiddleware {
             service: Rc::new(service),
+            settings: Rc::clone(&self.settings),
         })
     }
 }
 
 pub struct GraphQLLoggingMiddleware<S> {
     service: Rc<S>,
+    settings: Rc<LoggingSettings>,
 }
 
 impl<S, B> Service<ServiceRequest> for GraphQLLoggingMiddleware<S>
@@ -55,6 +76,7 @@ where
         let path = req.path().to_string();
 
         let srv = Rc::clone(&self.service);
+        let settings = Rc::clone(&self.settings);
 
         Box::pin(async move {
             if method == "POST" && path == "/graphql" {
@@ -71,12 +93,17 @@ where
                 // Convert the collected bytes into `Bytes`
                 let body = Bytes::from(body_bytes.clone());
 
-                // Attempt to parse the JSON body
+                // Attempt to parse the JSON body, which may hold a batch of operations
                 if let Ok(json) = serde_json::from_slice::<Value>(&body_bytes) {
-                    let query = json.get("query").and_then(|q| q.as_str()).unwrap_or("");
-                    let variables = json.get("variables").unwrap_or(&Value::Null);
-                    info!("GraphQL Query: {}", query);
-                    info!("GraphQL Variables: {}", variables);
+                    match json.as_array() {
+                        Some(batch) => {
+                            info!("GraphQL Batch: {} operations", batch.len());
+                            batch
+                                .iter()
+                                .for_each(|operation| log_graphql_request(operation, &settings));
+                        }
+                        None => log_graphql_request(&json, &settings),
+                    }
                 } else {
                     info!("GraphQL Request could not be parsed as JSON.");
                 }
@@ -86,6 +113,8 @@ where
                     stream::once(async move { Ok(body) }).boxed_local(), // Boxes the stream to fit the expected trait with a 'static lifetime
                 );
                 req.set_payload(new_payload);
+            } else if method == "GET" && path == "/graphql" {
+                log_graphql_request(&query_string_to_json(req.query_string()), &settings);
             }
 
             // Proceed with the request
@@ -94,3 +123,71 @@ where
         })
     }
 }
+
+/// Converts the query string of a GET request into the shape of a POST body,
+/// decoding the JSON-encoded `variables` and `extensions` parameters.
+fn query_string_to_json(query_string: &str) -> Value {
+    let params = web::Query::<HashMap<String, String>>::from_query(query_string)
+        .map(|params| params.into_inner())
+        .unwrap_or_default();
+
+    let fields = params
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match key.as_str() {
+                "variables" | "extensions" => {
+                    serde_json::from_str(&value).unwrap_or(Value::String(value))
+                }
+                _ => Value::String(value),
+            };
+            (key, value)
+        })
+        .collect();
+    Value::Object(fields)
+}
+
+/// Logs a parsed GraphQL request body according to the logging settings.
+fn log_graphql_request(json: &Value, settings: &LoggingSettings) {
+    let operation_name = json
+        .get("operationName")
+        .and_then(|o| o.as_str())
+        .unwrap_or("anonymous");
+    let query = match json.get("query").and_then(|q| q.as_str()) {
+        Some(query) => query,
+        None => {
+            // Persisted queries may be sent by hash alone
+            let hash = json
+                .pointer("/extensions/persistedQuery/sha256Hash")
+                .and_then(|h| h.as_str())
+                .unwrap_or("none");
+            info!(
+                "GraphQL Operation: {} (persisted query sha256: {})",
+                operation_name, hash
+            );
+            return;
+        }
+    };
+
+    match settings.query_mode {
+        QueryLogMode::Hash => {
+            info!(
+                "GraphQL Operation: {} (query sha256: {})",
+                operation_name,
+                redaction::query_hash(query)
+            );
+        }
+        QueryLogMode::Full => {
+            let variables = json.get("variables").unwrap_or(&Value::Null);
+            let variables = redaction::redact_json(variables, &settings.redact_variables);
+            let query = redaction::redact_query(query, &settings.redact_variables);
+            info!(
+                "GraphQL Query: {}",
+                redaction::truncate(&query, settings.max_body_bytes)
+            );
+            info!(
+                "GraphQL Variables: {}",
+                redaction::truncate(&variables.to_string(), settings.max_body_bytes)
+            );
+        }
+    }
+}
@@ -1,3 +1,5 @@
+pub mod auth;
 pub mod logging;
+pub mod rate_limit;
+pub mod request_id;
 pub mod timing;
-// pub mod auth; // ❌ Removed
@@ -0,0 +1,102 @@
+// src/middleware/rate_limit.rs
+
+use crate::rate_limit::RateLimiter;
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::{Error, HttpResponse};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use serde_json::json;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context as TaskContext, Poll};
+
+/// Middleware that takes a token from the client's bucket for every request
+/// and answers `429 Too Many Requests` once the bucket is empty.
+///
+/// Responses carry `RateLimit-Limit`, `RateLimit-Remaining` and
+/// `RateLimit-Reset` headers, and refused ones `Retry-After` too. It must run
+/// after the authentication middleware, so that signed-in clients and API
+/// keys get buckets of their own.
+pub struct RateLimiting {
+    limiter: Arc<RateLimiter>,
+}
+
+impl RateLimiting {
+    pub fn new(limiter: Arc<RateLimiter>) -> Self {
+        RateLimiting { limiter }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimiting
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitingMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitingMiddleware {
+            service: Rc::new(service),
+            limiter: self.limiter.clone(),
+        })
+    }
+}
+
+pub struct RateLimitingMiddleware<S> {
+    service: Rc<S>,
+    limiter: Arc<RateLimiter>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitingMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, ctx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let srv = Rc::clone(&self.service);
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            let client = limiter.client(req.request());
+            let decision = limiter.check_route(req.path(), &client).await;
+
+            if let Some(decision) = decision.filter(|decision| !decision.allowed) {
+                let mut response = HttpResponse::TooManyRequests();
+                for header in decision.headers() {
+                    response.insert_header(header);
+                }
+                // Shaped like a GraphQL error so that clients handle it like any other
+                let message = format!(
+                    "Too many requests; retry in {} seconds",
+                    decision.retry_after.unwrap_or_default()
+                );
+                let body = json!({
+                    "errors": [{ "message": message, "extensions": { "code": "RATE_LIMITED" } }]
+                });
+                return Ok(req.into_response(response.json(body)).map_into_right_body());
+            }
+
+            let mut res = srv.call(req).await?;
+            // Operation limits refused by the handler set their own headers
+            let headers = res.headers_mut();
+            if let Some(decision) = decision.filter(|_| !headers.contains_key("ratelimit-limit")) {
+                for (name, value) in decision.headers() {
+                    headers.insert(name, value);
+                }
+            }
+            Ok(res.map_into_left_body())
+        })
+    }
+}
@@ -0,0 +1,96 @@
+// src/middleware/request_id.rs
+
+use crate::logger;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::task::{Context as TaskContext, Poll};
+use uuid::Uuid;
+
+/// Header used to read and return the request correlation ID.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest incoming request ID that is honored; longer values are replaced.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Correlation ID of the current request, stored in the request extensions.
+#[derive(Debug, Clone)]
+pub struct CorrelationId(pub String);
+
+/// Middleware that assigns each request a correlation ID.
+///
+/// An incoming `X-Request-Id` header is honored when it is a short, printable
+/// ASCII value; otherwise a UUID is generated. The ID is stored in the request
+/// extensions, attached to every log record emitted while handling the request
+/// and echoed back in the `X-Request-Id` response header.
+pub struct RequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestIdMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, ctx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| is_valid_request_id(value))
+            .map(str::to_string)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        req.extensions_mut()
+            .insert(CorrelationId(request_id.clone()));
+        let srv = Rc::clone(&self.service);
+
+        Box::pin(logger::with_request_id(request_id.clone(), async move {
+            let mut res = srv.call(req).await?;
+            if let Ok(value) = HeaderValue::from_str(&request_id) {
+                res.headers_mut()
+                    .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            Ok(res)
+        }))
+    }
+}
+
+/// Returns `true` if an incoming request ID is safe to propagate into logs and headers.
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
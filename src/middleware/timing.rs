@@ -1,5 +1,6 @@
 // src/middleware/timing.rs
 
+use crate::metrics;
 use actix_web::{
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
     Error,
@@ -10,7 +11,8 @@ use std::rc::Rc;
 use std::task::{Context as TaskContext, Poll};
 use std::time::Instant;
 
-/// Middleware to measure and log the duration of each request.
+/// Middleware to measure and log the duration of each request and record it in
+/// the HTTP request metrics.
 pub struct Timing;
 
 impl<S, B> Transform<S, ServiceRequest> for Timing
@@ -50,12 +52,25 @@ where
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         let start = Instant::now();
+        let method = req.method().to_string();
         let srv = Rc::clone(&self.service);
 
         Box::pin(async move {
             let res = srv.call(req).await?;
             let duration = start.elapsed();
-            info!("Request processed in {:?}", duration);
+            info!(
+                duration_ms = duration.as_secs_f64() * 1000.0;
+                "Request processed in {:?}",
+                duration
+            );
+
+            // Label by route pattern rather than raw path to keep cardinality bounded
+            let route = res
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| "unmatched".to_string());
+            metrics::observe_http_request(&method, &route, res.status().as_u16(), duration);
+
             Ok(res)
         })
     }
@@ -0,0 +1,92 @@
+// src/models/api_keys.rs
+
+use crate::diesel_schema::api_keys::api_keys;
+use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// What an API key may be used for.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, AsExpression, FromSqlRow,
+)]
+#[diesel(sql_type = Text)]
+pub enum ApiKeyScope {
+    /// Staff-only catalog queries, such as the audit log.
+    #[serde(rename = "catalog:read")]
+    CatalogRead,
+    /// Discontinuing and restoring parts and reverting audited changes.
+    #[serde(rename = "catalog:write")]
+    CatalogWrite,
+    /// The owner's orders, or every order for staff.
+    #[serde(rename = "orders:read")]
+    OrdersRead,
+}
+
+impl ApiKeyScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiKeyScope::CatalogRead => "catalog:read",
+            ApiKeyScope::CatalogWrite => "catalog:write",
+            ApiKeyScope::OrdersRead => "orders:read",
+        }
+    }
+}
+
+impl fmt::Display for ApiKeyScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql<Text, Pg> for ApiKeyScope {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Text, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Pg> for ApiKeyScope {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Pg>>::from_sql(bytes)?.as_str() {
+            "catalog:read" => Ok(ApiKeyScope::CatalogRead),
+            "catalog:write" => Ok(ApiKeyScope::CatalogWrite),
+            "orders:read" => Ok(ApiKeyScope::OrdersRead),
+            other => Err(format!("Unknown API key scope: {}", other).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = api_keys)]
+pub struct ApiKey {
+    pub id: i32,
+    pub owner_id: i32,
+    pub name: String,
+    pub prefix: String,
+    #[serde(skip_serializing)]
+    pub secret_hash: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = api_keys)]
+pub struct NewApiKey {
+    pub owner_id: i32,
+    pub name: String,
+    pub prefix: String,
+    pub secret_hash: String,
+    pub scopes: Vec<ApiKeyScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i32>,
+}
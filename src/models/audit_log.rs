@@ -0,0 +1,22 @@
+// src/models/audit_log.rs
+
+use crate::diesel_schema::audit_log::audit_log;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// One write to a catalog table, recorded by the `record_audit_log` trigger.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = audit_log)]
+pub struct AuditLogEntry {
+    pub id: i32,
+    pub actor_id: Option<i32>,
+    pub table_name: String,
+    pub row_key: i32,
+    /// `INSERT`, `UPDATE` or `DELETE`.
+    pub action: String,
+    pub before: Option<JsonValue>,
+    pub after: Option<JsonValue>,
+    pub changed_at: DateTime<Utc>,
+}
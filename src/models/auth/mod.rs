@@ -13,6 +13,34 @@ pub struct User {
     pub email_verified: Option<DateTime<Utc>>,
     pub image: Option<String>,
     pub bio: Option<String>,
+    pub role: String,
+}
+
+impl User {
+    /// Role of users allowed to manage every customer's orders.
+    pub const STAFF_ROLE: &'static str = "staff";
+
+    /// Returns `true` for staff members.
+    pub fn is_staff(&self) -> bool {
+        self.role == Self::STAFF_ROLE
+    }
+}
+
+/// Profile fields a user changes about themselves. `None` leaves a field as
+/// it is and `Some(None)` clears it.
+#[derive(Debug, Default, AsChangeset)]
+#[diesel(table_name = users)]
+pub struct ProfileChanges {
+    pub name: Option<Option<String>>,
+    pub bio: Option<Option<String>>,
+    pub image: Option<Option<String>>,
+}
+
+impl ProfileChanges {
+    /// Returns `true` when no field would change.
+    pub fn is_empty(&self) -> bool {
+        self.name.is_none() && self.bio.is_none() && self.image.is_none()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable)]
@@ -23,7 +51,12 @@ pub struct Session {
     pub user_id: i32,
     pub expires: DateTime<Utc>,
     #[diesel(column_name = sessionToken)]
+    #[serde(skip_serializing)]
     pub session_token: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable)]
@@ -37,15 +70,30 @@ pub struct Account {
     pub provider: String,
     #[diesel(column_name = providerAccountId)]
     pub provider_account_id: String,
+    #[serde(skip_serializing)]
     pub refresh_token: Option<String>,
+    #[serde(skip_serializing)]
     pub access_token: Option<String>,
     pub expires_at: Option<i64>,
+    #[serde(skip_serializing)]
     pub id_token: Option<String>,
     pub scope: Option<String>,
     pub session_state: Option<String>,
     pub token_type: Option<String>,
 }
 
+/// The parts of an `Account` that are safe to show to its owner; the OAuth
+/// tokens are never loaded.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable)]
+#[diesel(table_name = accounts)]
+pub struct LinkedAccount {
+    pub provider: String,
+    #[diesel(column_name = type_)]
+    pub type_: String,
+    #[diesel(column_name = providerAccountId)]
+    pub provider_account_id: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Queryable, Selectable)]
 #[diesel(table_name = verification_tokens)]
 #[diesel(primary_key(identifier, token))]
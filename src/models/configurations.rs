@@ -0,0 +1,60 @@
+// src/models/configurations.rs
+
+use crate::diesel_schema::configurations::{
+    configuration_revision_parts, configuration_revisions, configurations,
+};
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A build of parts, owned by a user or anonymous.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = configurations)]
+pub struct Configuration {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub description: Option<String>,
+    /// Stored without a time zone, in UTC.
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// Slug of the read-only share link, if the configuration is shared.
+    pub share_slug: Option<String>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = configurations)]
+pub struct NewConfiguration {
+    pub user_id: Option<i32>,
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// A snapshot of a configuration taken after a change. Revisions are never
+/// changed once written.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = configuration_revisions)]
+pub struct ConfigurationRevision {
+    pub id: i32,
+    pub configuration_id: i32,
+    /// Numbered from 1 within the configuration.
+    pub revision: i32,
+    pub name: String,
+    pub description: Option<String>,
+    /// The revision this one was restored from, if any.
+    pub restored_from: Option<i32>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A part of a configuration revision.
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable, Associations,
+)]
+#[diesel(table_name = configuration_revision_parts)]
+#[diesel(primary_key(revision_id, part_id))]
+#[diesel(belongs_to(ConfigurationRevision, foreign_key = revision_id))]
+pub struct RevisionPart {
+    pub revision_id: i32,
+    pub part_id: i32,
+    pub quantity: i32,
+}
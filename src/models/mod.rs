@@ -1,2 +1,9 @@
+pub mod api_keys;
+pub mod audit_log;
 pub mod auth;
+pub mod configurations;
+pub mod orders;
 pub mod parts;
+pub mod persisted_query;
+pub mod quote_requests;
+pub mod wishlists;
@@ -0,0 +1,158 @@
+// src/models/orders.rs
+
+use crate::diesel_schema::orders::{order_items, order_status_changes, orders};
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where an order is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum OrderStatus {
+    Draft,
+    Submitted,
+    Building,
+    Shipped,
+    Cancelled,
+}
+
+impl OrderStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderStatus::Draft => "draft",
+            OrderStatus::Submitted => "submitted",
+            OrderStatus::Building => "building",
+            OrderStatus::Shipped => "shipped",
+            OrderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    /// Returns `true` if an order may move from this status to `next`.
+    ///
+    /// Orders advance from draft to submitted, building and shipped, and can
+    /// be cancelled until they ship.
+    pub fn can_transition_to(&self, next: OrderStatus) -> bool {
+        matches!(
+            (self, next),
+            (OrderStatus::Draft, OrderStatus::Submitted)
+                | (OrderStatus::Submitted, OrderStatus::Building)
+                | (OrderStatus::Building, OrderStatus::Shipped)
+                | (
+                    OrderStatus::Draft | OrderStatus::Submitted | OrderStatus::Building,
+                    OrderStatus::Cancelled
+                )
+        )
+    }
+
+    /// Returns `true` while the order's parts are reserved in the inventory.
+    pub fn holds_stock(&self) -> bool {
+        matches!(self, OrderStatus::Submitted | OrderStatus::Building)
+    }
+}
+
+impl fmt::Display for OrderStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql<Text, Pg> for OrderStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Text, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Pg> for OrderStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Pg>>::from_sql(bytes)?.as_str() {
+            "draft" => Ok(OrderStatus::Draft),
+            "submitted" => Ok(OrderStatus::Submitted),
+            "building" => Ok(OrderStatus::Building),
+            "shipped" => Ok(OrderStatus::Shipped),
+            "cancelled" => Ok(OrderStatus::Cancelled),
+            other => Err(format!("Unknown order status: {}", other).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = orders)]
+pub struct Order {
+    pub id: i32,
+    pub user_id: i32,
+    pub configuration_id: Option<i32>,
+    pub status: OrderStatus,
+    pub total: BigDecimal,
+    pub notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = orders)]
+pub struct NewOrder {
+    pub user_id: i32,
+    pub configuration_id: Option<i32>,
+    pub status: OrderStatus,
+    pub total: BigDecimal,
+    pub notes: Option<String>,
+}
+
+/// A part on an order, with the name and price it had when the order was created.
+#[derive(
+    Debug,
+    Clone,
+    Serialize,
+    Deserialize,
+    Queryable,
+    Selectable,
+    Insertable,
+    Identifiable,
+    Associations,
+)]
+#[diesel(table_name = order_items)]
+#[diesel(primary_key(order_id, part_id))]
+#[diesel(belongs_to(Order))]
+pub struct OrderItem {
+    pub order_id: i32,
+    pub part_id: i32,
+    pub part_name: String,
+    pub part_model: String,
+    pub quantity: i32,
+    pub unit_price: BigDecimal,
+}
+
+impl OrderItem {
+    pub fn line_total(&self) -> BigDecimal {
+        &self.unit_price * BigDecimal::from(self.quantity)
+    }
+}
+
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable, Associations,
+)]
+#[diesel(table_name = order_status_changes)]
+#[diesel(belongs_to(Order))]
+pub struct OrderStatusChange {
+    pub id: i32,
+    pub order_id: i32,
+    pub status: OrderStatus,
+    pub changed_by: Option<i32>,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = order_status_changes)]
+pub struct NewOrderStatusChange {
+    pub order_id: i32,
+    pub status: OrderStatus,
+    pub changed_by: Option<i32>,
+}
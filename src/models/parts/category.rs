@@ -1,13 +1,17 @@
 // src/models/parts/category.rs
 
 use crate::diesel_schema::parts::categories;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Queryable, Insertable, Serialize, Deserialize, Debug)]
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone)]
 #[diesel(table_name = categories)]
 pub struct Category {
     pub id: i32,
     pub name: String,
     pub description: Option<String>,
+    /// Set once the row is discontinued; it then disappears from listings.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
@@ -0,0 +1,29 @@
+// src/models/parts/inventory.rs
+
+use crate::diesel_schema::parts::inventory;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = inventory)]
+pub struct Inventory {
+    pub part_id: i32,
+    pub quantity_on_hand: i32,
+    pub quantity_reserved: i32,
+    pub reorder_threshold: i32,
+    pub supplier_sku: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Inventory {
+    /// Units on hand that are not reserved for an order.
+    pub fn available_quantity(&self) -> i32 {
+        self.quantity_on_hand - self.quantity_reserved
+    }
+
+    /// Returns `true` when available stock has fallen to the reorder threshold.
+    pub fn needs_reorder(&self) -> bool {
+        self.available_quantity() <= self.reorder_threshold
+    }
+}
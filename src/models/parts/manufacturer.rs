@@ -1,13 +1,17 @@
 // src/models/parts/manufacturer.rs
 
 use crate::diesel_schema::parts::manufacturers;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
-#[derive(Queryable, Insertable, Serialize, Deserialize, Debug)]
+#[derive(Queryable, Insertable, Serialize, Deserialize, Debug, Clone)]
 #[diesel(table_name = manufacturers)]
 pub struct Manufacturer {
     pub id: i32,
     pub name: String,
     pub website: Option<String>,
+    /// Set once the row is discontinued; it then disappears from listings.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
@@ -1,6 +1,7 @@
 pub mod category;
 pub mod cpu_spec;
 pub mod gpu_spec;
+pub mod inventory;
 pub mod manufacturer;
 pub mod memory_spec;
 pub mod part;
@@ -2,6 +2,7 @@
 
 use crate::diesel_schema::parts::parts;
 use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -17,6 +18,9 @@ pub struct Part {
     pub price: Option<BigDecimal>,
     #[serde(deserialize_with = "deserialize_optional_json")]
     pub common_specifications: Option<JsonValue>,
+    /// Set once the row is discontinued; it then disappears from listings.
+    #[serde(default)]
+    pub deleted_at: Option<DateTime<Utc>>,
 }
 
 // Deserialize function for common_specifications
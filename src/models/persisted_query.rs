@@ -0,0 +1,23 @@
+// src/models/persisted_query.rs
+
+use crate::diesel_schema::persisted_queries::persisted_queries;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Queryable, Selectable, Serialize, Deserialize, Debug, Clone)]
+#[diesel(table_name = persisted_queries)]
+pub struct PersistedQuery {
+    pub hash: String,
+    pub query: String,
+    pub operation_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Debug)]
+#[diesel(table_name = persisted_queries)]
+pub struct NewPersistedQuery<'a> {
+    pub hash: &'a str,
+    pub query: &'a str,
+    pub operation_name: Option<&'a str>,
+}
@@ -0,0 +1,93 @@
+// src/models/quote_requests.rs
+
+use crate::diesel_schema::quote_requests::quote_requests;
+use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::prelude::*;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How far staff have followed up on a quote request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteRequestStatus {
+    New,
+    Contacted,
+    Quoted,
+    Won,
+    Lost,
+}
+
+impl QuoteRequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuoteRequestStatus::New => "new",
+            QuoteRequestStatus::Contacted => "contacted",
+            QuoteRequestStatus::Quoted => "quoted",
+            QuoteRequestStatus::Won => "won",
+            QuoteRequestStatus::Lost => "lost",
+        }
+    }
+}
+
+impl fmt::Display for QuoteRequestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl ToSql<Text, Pg> for QuoteRequestStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <str as ToSql<Text, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Pg> for QuoteRequestStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match <String as FromSql<Text, Pg>>::from_sql(bytes)?.as_str() {
+            "new" => Ok(QuoteRequestStatus::New),
+            "contacted" => Ok(QuoteRequestStatus::Contacted),
+            "quoted" => Ok(QuoteRequestStatus::Quoted),
+            "won" => Ok(QuoteRequestStatus::Won),
+            "lost" => Ok(QuoteRequestStatus::Lost),
+            other => Err(format!("Unknown quote request status: {}", other).into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = quote_requests)]
+pub struct QuoteRequest {
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub service_slug: String,
+    pub configuration_id: Option<i32>,
+    pub contact_name: String,
+    pub contact_email: String,
+    pub contact_phone: Option<String>,
+    pub company: Option<String>,
+    pub requirements: String,
+    pub status: QuoteRequestStatus,
+    pub assigned_to: Option<i32>,
+    pub staff_notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = quote_requests)]
+pub struct NewQuoteRequest {
+    pub user_id: Option<i32>,
+    pub service_slug: String,
+    pub configuration_id: Option<i32>,
+    pub contact_name: String,
+    pub contact_email: String,
+    pub contact_phone: Option<String>,
+    pub company: Option<String>,
+    pub requirements: String,
+}
@@ -0,0 +1,30 @@
+// src/models/wishlists.rs
+
+use crate::diesel_schema::wishlists::{wishlist_items, wishlists};
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A named list of parts bookmarked by a user.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = wishlists)]
+pub struct Wishlist {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A part on a wishlist.
+#[derive(
+    Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable, Associations,
+)]
+#[diesel(table_name = wishlist_items)]
+#[diesel(primary_key(wishlist_id, part_id))]
+#[diesel(belongs_to(Wishlist))]
+pub struct WishlistItem {
+    pub wishlist_id: i32,
+    pub part_id: i32,
+    pub added_at: DateTime<Utc>,
+}
@@ -0,0 +1,268 @@
+// src/notifications.rs
+
+use crate::types::errors::NotificationError;
+use diesel::connection::SimpleConnection;
+use diesel::{Connection, PgConnection};
+use futures::stream::{self, Stream};
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// Channel notified by the `parts` trigger when a part's price changes.
+pub const PART_PRICE_CHANGED_CHANNEL: &str = "part_price_changed";
+
+/// Channel notified by the `inventory` trigger when a part's available stock changes.
+pub const PART_STOCK_CHANGED_CHANNEL: &str = "part_stock_changed";
+
+/// Channel notified by the `configurations` and `configuration_parts` triggers.
+pub const CONFIGURATION_UPDATED_CHANNEL: &str = "configuration_updated";
+
+const CHANNELS: [&str; 3] = [
+    PART_PRICE_CHANGED_CHANNEL,
+    PART_STOCK_CHANGED_CHANNEL,
+    CONFIGURATION_UPDATED_CHANNEL,
+];
+
+/// How often the listener checks the connection for new notifications.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long the listener waits for notifications before probing the connection.
+const PROBE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Longest delay between reconnection attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Payload of a `part_price_changed` notification.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartPriceChanged {
+    pub part_id: i32,
+    pub old_price: Option<String>,
+    pub price: Option<String>,
+}
+
+/// Payload of a `part_stock_changed` notification.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartStockChanged {
+    pub part_id: i32,
+    pub available_quantity: i32,
+}
+
+/// What happened to a configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ConfigurationAction {
+    Updated,
+    Deleted,
+}
+
+/// Payload of a `configuration_updated` notification.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationUpdated {
+    pub configuration_id: i32,
+    pub action: ConfigurationAction,
+}
+
+/// A change published by a database trigger.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeEvent {
+    PartPriceChanged(PartPriceChanged),
+    PartStockChanged(PartStockChanged),
+    ConfigurationUpdated(ConfigurationUpdated),
+}
+
+impl ChangeEvent {
+    /// Decodes the JSON payload of a notification received on `channel`.
+    pub fn parse(channel: &str, payload: &str) -> Result<Self, NotificationError> {
+        let invalid = |reason: String| NotificationError::InvalidPayload {
+            channel: channel.to_string(),
+            reason,
+        };
+        match channel {
+            PART_PRICE_CHANGED_CHANNEL => serde_json::from_str(payload)
+                .map(ChangeEvent::PartPriceChanged)
+                .map_err(|e| invalid(e.to_string())),
+            PART_STOCK_CHANGED_CHANNEL => serde_json::from_str(payload)
+                .map(ChangeEvent::PartStockChanged)
+                .map_err(|e| invalid(e.to_string())),
+            CONFIGURATION_UPDATED_CHANNEL => serde_json::from_str(payload)
+                .map(ChangeEvent::ConfigurationUpdated)
+                .map_err(|e| invalid(e.to_string())),
+            _ => Err(invalid("unknown channel".to_string())),
+        }
+    }
+}
+
+/// Fans out change events from the database listener to GraphQL subscriptions.
+pub struct NotificationHub {
+    sender: broadcast::Sender<ChangeEvent>,
+    listening: AtomicBool,
+}
+
+impl NotificationHub {
+    /// Creates a hub buffering up to `capacity` events for slow subscribers.
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        NotificationHub {
+            sender,
+            listening: AtomicBool::new(false),
+        }
+    }
+
+    /// Sends an event to every current subscriber.
+    pub fn publish(&self, event: ChangeEvent) {
+        // Sending only fails when nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// Returns `true` while the database listener is connected.
+    pub fn is_listening(&self) -> bool {
+        self.listening.load(Ordering::SeqCst)
+    }
+
+    /// Streams price changes of the given part.
+    pub fn part_price_changes(
+        &self,
+        part_id: i32,
+    ) -> impl Stream<Item = PartPriceChanged> + Send + 'static {
+        self.events(move |event| match event {
+            ChangeEvent::PartPriceChanged(change) if change.part_id == part_id => Some(change),
+            _ => None,
+        })
+    }
+
+    /// Streams changes to the available stock of the given part.
+    pub fn part_stock_changes(
+        &self,
+        part_id: i32,
+    ) -> impl Stream<Item = PartStockChanged> + Send + 'static {
+        self.events(move |event| match event {
+            ChangeEvent::PartStockChanged(change) if change.part_id == part_id => Some(change),
+            _ => None,
+        })
+    }
+
+    /// Streams changes to the given configuration.
+    pub fn configuration_updates(
+        &self,
+        configuration_id: i32,
+    ) -> impl Stream<Item = ConfigurationUpdated> + Send + 'static {
+        self.events(move |event| match event {
+            ChangeEvent::ConfigurationUpdated(change)
+                if change.configuration_id == configuration_id =>
+            {
+                Some(change)
+            }
+            _ => None,
+        })
+    }
+
+    /// Streams the events selected by `filter`.
+    fn events<T, F>(&self, filter: F) -> impl Stream<Item = T> + Send + 'static
+    where
+        T: Send + 'static,
+        F: Fn(ChangeEvent) -> Option<T> + Send + Sync + 'static,
+    {
+        let receiver = self.sender.subscribe();
+        stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if let Some(item) = filter(event) {
+                            return Some((item, (receiver, filter)));
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!(
+                            "Subscriber fell behind and missed {} change events",
+                            skipped
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+/// Starts a background thread that listens for change notifications and
+/// publishes them to `hub`, reconnecting with backoff when the connection drops.
+///
+/// Notifications sent while the listener is reconnecting are lost.
+pub fn spawn_listener(database_url: String, hub: Arc<NotificationHub>) -> io::Result<()> {
+    thread::Builder::new()
+        .name("pg-listener".to_string())
+        .spawn(move || {
+            let mut delay = Duration::from_secs(1);
+            loop {
+                match PgListener::connect(&database_url, &CHANNELS) {
+                    Ok(mut listener) => {
+                        info!("Listening for database change notifications.");
+                        hub.listening.store(true, Ordering::SeqCst);
+                        delay = Duration::from_secs(1);
+                        if let Err(e) = listener.run(&hub) {
+                            error!("Stopped listening for database changes: {}", e);
+                        }
+                        hub.listening.store(false, Ordering::SeqCst);
+                    }
+                    Err(e) => error!("Failed to listen for database changes: {}", e),
+                }
+                thread::sleep(delay);
+                delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+            }
+        })
+        .map(|_| ())
+}
+
+/// A dedicated connection subscribed to notification channels.
+struct PgListener {
+    conn: PgConnection,
+}
+
+impl PgListener {
+    /// Connects to the database and runs `LISTEN` for each channel.
+    fn connect(database_url: &str, channels: &[&str]) -> Result<Self, NotificationError> {
+        let mut conn = PgConnection::establish(database_url)
+            .map_err(|e| NotificationError::ConnectionFailed(e.to_string()))?;
+        for channel in channels {
+            conn.batch_execute(&format!("LISTEN {}", channel))
+                .map_err(|e| NotificationError::ListenFailed {
+                    channel: channel.to_string(),
+                    reason: e.to_string(),
+                })?;
+        }
+        Ok(PgListener { conn })
+    }
+
+    /// Publishes notifications until the connection fails.
+    fn run(&mut self, hub: &NotificationHub) -> Result<(), NotificationError> {
+        let mut last_probe = Instant::now();
+        loop {
+            for notification in self.conn.notifications_iter() {
+                let notification =
+                    notification.map_err(|e| NotificationError::ConnectionLost(e.to_string()))?;
+                match ChangeEvent::parse(&notification.channel, &notification.payload) {
+                    Ok(event) => hub.publish(event),
+                    Err(e) => warn!("Ignoring notification: {}", e),
+                }
+                last_probe = Instant::now();
+            }
+
+            if last_probe.elapsed() >= PROBE_INTERVAL {
+                // Nothing arrived for a while; probe the connection so a dropped one is noticed
+                self.conn
+                    .batch_execute("SELECT 1")
+                    .map_err(|e| NotificationError::ConnectionLost(e.to_string()))?;
+                last_probe = Instant::now();
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+// src/orders.rs
+
+use crate::diesel_schema::configurations::configurations;
+use crate::diesel_schema::orders::{order_items, order_status_changes, orders};
+use crate::diesel_schema::parts::{configuration_parts, parts};
+use crate::inventory::{self, StockReservation};
+use crate::models::auth::User;
+use crate::models::orders::{
+    NewOrder, NewOrderStatusChange, Order, OrderItem, OrderStatus, OrderStatusChange,
+};
+use crate::types::errors::OrderError;
+use bigdecimal::BigDecimal;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+
+/// An order together with its items and status history.
+pub struct OrderDetails {
+    pub order: Order,
+    pub items: Vec<OrderItem>,
+    pub history: Vec<OrderStatusChange>,
+}
+
+/// Creates a draft order from a configuration, freezing its parts, quantities
+/// and current prices.
+///
+/// Anonymous configurations and the user's own configurations can be ordered.
+pub fn create_from_configuration(
+    conn: &mut PgConnection,
+    user_id: i32,
+    configuration_id: i32,
+    notes: Option<String>,
+) -> Result<Order, OrderError> {
+    conn.transaction(|conn| {
+        let owner = configurations::table
+            .find(configuration_id)
+            .select(configurations::user_id)
+            .first::<Option<i32>>(conn)
+            .optional()?;
+        match owner {
+            Some(None) => {}
+            Some(Some(owner_id)) if owner_id == user_id => {}
+            _ => return Err(OrderError::ConfigurationNotFound),
+        }
+
+        let lines = configuration_parts::table
+            .inner_join(parts::table)
+            .filter(configuration_parts::configuration_id.eq(configuration_id))
+            .select((
+                parts::id,
+                parts::name,
+                parts::model,
+                parts::price,
+                parts::deleted_at.is_not_null(),
+                configuration_parts::quantity,
+            ))
+            .order(parts::id)
+            .load::<(i32, String, String, Option<BigDecimal>, bool, i32)>(conn)?;
+        if lines.is_empty() {
+            return Err(OrderError::EmptyConfiguration(configuration_id));
+        }
+
+        let mut items = Vec::with_capacity(lines.len());
+        for (part_id, part_name, part_model, price, discontinued, quantity) in lines {
+            if discontinued {
+                return Err(OrderError::DiscontinuedPart(part_id));
+            }
+            let unit_price = price.ok_or(OrderError::UnpricedPart(part_id))?;
+            items.push(OrderItem {
+                order_id: 0,
+                part_id,
+                part_name,
+                part_model,
+                quantity,
+                unit_price,
+            });
+        }
+        let total = items
+            .iter()
+            .map(OrderItem::line_total)
+            .fold(BigDecimal::from(0), |sum, line| sum + line);
+
+        let order = diesel::insert_into(orders::table)
+            .values(NewOrder {
+                user_id,
+                configuration_id: Some(configuration_id),
+                status: OrderStatus::Draft,
+                total,
+                notes,
+            })
+            .returning(Order::as_returning())
+            .get_result(conn)?;
+        for item in &mut items {
+            item.order_id = order.id;
+        }
+        diesel::insert_into(order_items::table)
+            .values(&items)
+            .execute(conn)?;
+        record_status(conn, order.id, OrderStatus::Draft, Some(user_id))?;
+
+        info!(
+            "Created order {} from configuration {} for user {}",
+            order.id, configuration_id, user_id
+        );
+        Ok(order)
+    })
+}
+
+/// Moves an order to `next`, reserving stock when it is submitted, releasing
+/// it when it is cancelled and removing it from the shelf when it ships.
+///
+/// Staff can change any order. Other users can only change their own, and
+/// not once it is being built; this is checked on the locked row, so a
+/// concurrent move to `Building` cannot slip in between.
+pub fn change_status(
+    conn: &mut PgConnection,
+    order_id: i32,
+    next: OrderStatus,
+    changed_by: &User,
+) -> Result<Order, OrderError> {
+    conn.transaction(|conn| {
+        let order = orders::table
+            .find(order_id)
+            .select(Order::as_select())
+            .for_update()
+            .first::<Order>(conn)
+            .optional()?
+            .ok_or(OrderError::NotFound)?;
+        if !changed_by.is_staff() && order.user_id != changed_by.id {
+            return Err(OrderError::NotFound);
+        }
+        let building = !changed_by.is_staff() && order.status == OrderStatus::Building;
+        if building || !order.status.can_transition_to(next) {
+            return Err(OrderError::InvalidTransition {
+                from: order.status.to_string(),
+                to: next.to_string(),
+            });
+        }
+
+        let reservations = OrderItem::belonging_to(&order)
+            .select(OrderItem::as_select())
+            .load::<OrderItem>(conn)?
+            .into_iter()
+            .map(|item| StockReservation {
+                part_id: item.part_id,
+                quantity: item.quantity,
+            })
+            .collect::<Vec<_>>();
+        match next {
+            OrderStatus::Submitted => inventory::reserve(conn, &reservations)?,
+            OrderStatus::Cancelled if order.status.holds_stock() => {
+                inventory::release(conn, &reservations)?
+            }
+            OrderStatus::Shipped => inventory::fulfil(conn, &reservations)?,
+            _ => {}
+        }
+
+        let order = diesel::update(orders::table.find(order_id))
+            .set((orders::status.eq(next), orders::updated_at.eq(now)))
+            .returning(Order::as_returning())
+            .get_result(conn)?;
+        record_status(conn, order_id, next, Some(changed_by.id))?;
+
+        info!(
+            "Order {} changed to {} by user {}",
+            order_id, next, changed_by.id
+        );
+        Ok(order)
+    })
+}
+
+/// Fetches an order by ID.
+pub fn find(conn: &mut PgConnection, order_id: i32) -> Result<Order, OrderError> {
+    orders::table
+        .find(order_id)
+        .select(Order::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(OrderError::NotFound)
+}
+
+/// Fetches a user's orders, newest first.
+pub fn for_user(conn: &mut PgConnection, user_id: i32) -> Result<Vec<Order>, OrderError> {
+    Ok(orders::table
+        .filter(orders::user_id.eq(user_id))
+        .order((orders::created_at.desc(), orders::id.desc()))
+        .select(Order::as_select())
+        .load(conn)?)
+}
+
+/// Fetches orders of every user, newest first, optionally only those in `status`.
+pub fn list(
+    conn: &mut PgConnection,
+    status: Option<OrderStatus>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<Order>, OrderError> {
+    let mut query = orders::table
+        .order((orders::created_at.desc(), orders::id.desc()))
+        .select(Order::as_select())
+        .into_boxed();
+    if let Some(status) = status {
+        query = query.filter(orders::status.eq(status));
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = offset {
+        query = query.offset(offset);
+    }
+    Ok(query.load(conn)?)
+}
+
+/// Loads the items and status history of `orders` with one query each.
+pub fn load_details(
+    conn: &mut PgConnection,
+    orders: Vec<Order>,
+) -> Result<Vec<OrderDetails>, OrderError> {
+    let items = OrderItem::belonging_to(&orders)
+        .select(OrderItem::as_select())
+        .order(order_items::part_id)
+        .load::<OrderItem>(conn)?
+        .grouped_by(&orders);
+    let history = OrderStatusChange::belonging_to(&orders)
+        .select(OrderStatusChange::as_select())
+        .order(order_status_changes::id)
+        .load::<OrderStatusChange>(conn)?
+        .grouped_by(&orders);
+
+    Ok(orders
+        .into_iter()
+        .zip(items)
+        .zip(history)
+        .map(|((order, items), history)| OrderDetails {
+            order,
+            items,
+            history,
+        })
+        .collect())
+}
+
+fn record_status(
+    conn: &mut PgConnection,
+    order_id: i32,
+    status: OrderStatus,
+    changed_by: Option<i32>,
+) -> Result<(), OrderError> {
+    diesel::insert_into(order_status_changes::table)
+        .values(NewOrderStatusChange {
+            order_id,
+            status,
+            changed_by,
+        })
+        .execute(conn)?;
+    Ok(())
+}
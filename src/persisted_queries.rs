@@ -0,0 +1,312 @@
+// src/persisted_queries.rs
+
+use crate::diesel_schema::persisted_queries::persisted_queries;
+use crate::metrics;
+use crate::models::persisted_query::NewPersistedQuery;
+use crate::redaction::query_hash;
+use crate::settings::PersistedQuerySettings;
+use crate::types::errors::PersistedQueryError;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use log::{debug, info};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+/// The only version of the persisted query protocol.
+const PROTOCOL_VERSION: i32 = 1;
+
+/// The `extensions.persistedQuery` object of an Apollo APQ request.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PersistedQueryExtension {
+    pub version: i32,
+    pub sha256_hash: String,
+}
+
+/// An Apollo persisted query manifest, as written by `generate-persisted-query-manifest`.
+#[derive(Debug, Deserialize)]
+struct Manifest {
+    operations: Vec<ManifestOperation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestOperation {
+    id: String,
+    name: Option<String>,
+    body: String,
+}
+
+/// A bounded map of query texts by hash that evicts the least recently used
+/// entry when full.
+struct LruCache {
+    entries: HashMap<String, (Arc<str>, u64)>,
+    capacity: usize,
+    clock: u64,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        LruCache {
+            entries: HashMap::new(),
+            capacity,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, hash: &str) -> Option<Arc<str>> {
+        self.clock += 1;
+        let clock = self.clock;
+        self.entries.get_mut(hash).map(|(query, used)| {
+            *used = clock;
+            Arc::clone(query)
+        })
+    }
+
+    fn insert(&mut self, hash: &str, query: Arc<str>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(hash) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(hash, _)| hash.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(hash.to_string(), (query, self.clock));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// Persisted queries keyed by their SHA-256 hash.
+///
+/// Allowlisted queries, such as those from the manifest, are stored in the
+/// `persisted_queries` table with a bounded in-memory cache in front. Once a
+/// manifest is loaded, it replaces the table contents and is kept in memory
+/// whole, so lookups are answered without the database. Queries registered by
+/// clients through automatic persisted queries are only kept in a separate
+/// bounded in-memory cache, so they never become allowlisted and cannot grow
+/// the table.
+pub struct PersistedQueryStore {
+    db: Pool<ConnectionManager<PgConnection>>,
+    allowed: Mutex<LruCache>,
+    manifest: Mutex<Option<HashMap<String, Arc<str>>>>,
+    registered: Mutex<LruCache>,
+}
+
+impl PersistedQueryStore {
+    /// Creates a store keeping up to `cache_size` allowlisted and up to
+    /// `cache_size` client-registered queries in memory.
+    pub fn new(db: Pool<ConnectionManager<PgConnection>>, cache_size: usize) -> Self {
+        PersistedQueryStore {
+            db,
+            allowed: Mutex::new(LruCache::new(cache_size)),
+            manifest: Mutex::new(None),
+            registered: Mutex::new(LruCache::new(cache_size)),
+        }
+    }
+
+    /// Looks up an allowlisted or client-registered query by hash.
+    pub fn get(&self, hash: &str) -> Result<Option<Arc<str>>, PersistedQueryError> {
+        if let Some(query) = self.registered.lock().unwrap().get(hash) {
+            return Ok(Some(query));
+        }
+        self.get_allowed(hash)
+    }
+
+    /// Looks up an allowlisted query by hash.
+    pub fn get_allowed(&self, hash: &str) -> Result<Option<Arc<str>>, PersistedQueryError> {
+        if let Some(manifest) = &*self.manifest.lock().unwrap() {
+            return Ok(manifest.get(hash).cloned());
+        }
+        if let Some(query) = self.allowed.lock().unwrap().get(hash) {
+            return Ok(Some(query));
+        }
+
+        let mut conn = self.db.get()?;
+        let query = persisted_queries::table
+            .find(hash)
+            .select(persisted_queries::query)
+            .first::<String>(&mut conn)
+            .optional()?;
+
+        Ok(query.map(|query| {
+            let query: Arc<str> = Arc::from(query);
+            self.allowed
+                .lock()
+                .unwrap()
+                .insert(hash, Arc::clone(&query));
+            query
+        }))
+    }
+
+    /// Registers a query sent by a client under its hash, in memory only.
+    pub fn register(&self, hash: &str, query: &str) {
+        debug!("Registered persisted query {}", hash);
+        self.registered
+            .lock()
+            .unwrap()
+            .insert(hash, Arc::from(query));
+    }
+
+    /// Allowlists a query under its hash; allowlisting a known hash is a no-op.
+    pub fn allow(
+        &self,
+        hash: &str,
+        query: &str,
+        operation_name: Option<&str>,
+    ) -> Result<(), PersistedQueryError> {
+        let mut conn = self.db.get()?;
+        diesel::insert_into(persisted_queries::table)
+            .values(&NewPersistedQuery {
+                hash,
+                query,
+                operation_name,
+            })
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+
+        debug!("Allowlisted persisted query {}", hash);
+        if let Some(manifest) = &mut *self.manifest.lock().unwrap() {
+            manifest.insert(hash.to_string(), Arc::from(query));
+        }
+        self.allowed.lock().unwrap().insert(hash, Arc::from(query));
+        Ok(())
+    }
+
+    /// Makes the operations in an Apollo persisted query manifest the
+    /// allowlist, replacing the table contents so that operations removed from
+    /// the manifest stop being allowed. Returns the number of operations read.
+    pub fn load_manifest(&self, path: &str) -> Result<usize, PersistedQueryError> {
+        let manifest_error = |reason: String| PersistedQueryError::ManifestError {
+            path: path.to_string(),
+            reason,
+        };
+
+        let contents = fs::read_to_string(path).map_err(|e| manifest_error(e.to_string()))?;
+        let manifest: Manifest =
+            serde_json::from_str(&contents).map_err(|e| manifest_error(e.to_string()))?;
+
+        let mut hashes = Vec::with_capacity(manifest.operations.len());
+        for operation in &manifest.operations {
+            let hash = operation.id.to_ascii_lowercase();
+            if query_hash(&operation.body) != hash {
+                return Err(manifest_error(format!(
+                    "id of operation {} is not the SHA-256 hash of its body",
+                    operation.name.as_deref().unwrap_or(&operation.id)
+                )));
+            }
+            hashes.push(hash);
+        }
+
+        let rows = manifest
+            .operations
+            .iter()
+            .zip(&hashes)
+            .map(|(operation, hash)| NewPersistedQuery {
+                hash,
+                query: &operation.body,
+                operation_name: operation.name.as_deref(),
+            })
+            .collect::<Vec<_>>();
+        let mut conn = self.db.get()?;
+        conn.transaction(|conn| {
+            diesel::delete(persisted_queries::table).execute(conn)?;
+            diesel::insert_into(persisted_queries::table)
+                .values(&rows)
+                .on_conflict_do_nothing()
+                .execute(conn)?;
+            Ok::<_, PersistedQueryError>(())
+        })?;
+
+        let queries = manifest
+            .operations
+            .iter()
+            .zip(hashes)
+            .map(|(operation, hash)| (hash, Arc::from(operation.body.as_str())))
+            .collect();
+        *self.manifest.lock().unwrap() = Some(queries);
+        self.allowed.lock().unwrap().clear();
+        for name in manifest
+            .operations
+            .iter()
+            .filter_map(|operation| operation.name.as_deref())
+        {
+            metrics::register_operation_name(name);
+        }
+
+        info!(
+            "Registered {} persisted queries from {}",
+            manifest.operations.len(),
+            path
+        );
+        Ok(manifest.operations.len())
+    }
+}
+
+/// Resolves the query text to execute for a request.
+///
+/// Follows the Apollo APQ protocol: a hash alone runs the registered query or
+/// fails with `PersistedQueryNotFound`, and a hash sent with its query registers
+/// it in memory. In allowlist-only mode nothing is registered from requests
+/// and only allowlisted queries can run, whether sent by hash or in full.
+pub fn resolve_query(
+    store: &PersistedQueryStore,
+    settings: &PersistedQuerySettings,
+    query: Option<String>,
+    persisted_query: Option<&PersistedQueryExtension>,
+) -> Result<String, PersistedQueryError> {
+    let hash = match persisted_query {
+        Some(_) if !settings.enabled => return Err(PersistedQueryError::NotSupported),
+        Some(ext) if ext.version != PROTOCOL_VERSION => {
+            return Err(PersistedQueryError::UnsupportedVersion)
+        }
+        Some(ext) => Some(ext.sha256_hash.to_ascii_lowercase()),
+        None => None,
+    };
+
+    match (query, hash) {
+        (None, None) => Err(PersistedQueryError::MissingQuery),
+        (None, Some(hash)) => {
+            let query = if settings.allowlist_only {
+                store.get_allowed(&hash)?
+            } else {
+                store.get(&hash)?
+            };
+            query
+                .map(|query| query.to_string())
+                .ok_or(PersistedQueryError::NotFound)
+        }
+        (Some(query), Some(hash)) => {
+            if query_hash(&query) != hash {
+                return Err(PersistedQueryError::HashMismatch);
+            }
+            if settings.allowlist_only {
+                store
+                    .get_allowed(&hash)?
+                    .ok_or(PersistedQueryError::NotAllowed)?;
+            } else {
+                store.register(&hash, &query);
+            }
+            Ok(query)
+        }
+        (Some(query), None) => {
+            if settings.allowlist_only {
+                store
+                    .get_allowed(&query_hash(&query))?
+                    .ok_or(PersistedQueryError::NotAllowed)?;
+            }
+            Ok(query)
+        }
+    }
+}
@@ -0,0 +1,34 @@
+// src/profiles.rs
+
+use crate::diesel_schema::users::users;
+use crate::models::auth::{ProfileChanges, User};
+use crate::types::errors::ProfileError;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+
+/// Applies `changes` to a user's own profile and returns the updated user.
+pub fn update_profile(
+    conn: &mut PgConnection,
+    user_id: i32,
+    changes: ProfileChanges,
+) -> Result<User, ProfileError> {
+    // Diesel rejects empty changesets, so there is nothing to write
+    let user = if changes.is_empty() {
+        users::table
+            .find(user_id)
+            .select(User::as_select())
+            .first(conn)
+            .optional()?
+    } else {
+        diesel::update(users::table.find(user_id))
+            .set(&changes)
+            .returning(User::as_returning())
+            .get_result(conn)
+            .optional()?
+    };
+
+    let user = user.ok_or(ProfileError::NotFound)?;
+    info!("User {} updated their profile", user_id);
+    Ok(user)
+}
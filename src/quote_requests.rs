@@ -0,0 +1,146 @@
+// src/quote_requests.rs
+
+use crate::diesel_schema::configurations::configurations;
+use crate::diesel_schema::quote_requests::quote_requests;
+use crate::diesel_schema::users::users;
+use crate::models::auth::User;
+use crate::models::quote_requests::{NewQuoteRequest, QuoteRequest, QuoteRequestStatus};
+use crate::types::errors::QuoteRequestError;
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+
+/// Stores a quote request from a visitor or, when `request.user_id` is set, a
+/// signed-in user.
+///
+/// An attached configuration must be anonymous or belong to the requester.
+pub fn submit(
+    conn: &mut PgConnection,
+    request: NewQuoteRequest,
+) -> Result<QuoteRequest, QuoteRequestError> {
+    if let Some(configuration_id) = request.configuration_id {
+        let owner = configurations::table
+            .find(configuration_id)
+            .select(configurations::user_id)
+            .first::<Option<i32>>(conn)
+            .optional()?;
+        match owner {
+            Some(None) => {}
+            Some(owner_id) if owner_id == request.user_id => {}
+            _ => return Err(QuoteRequestError::ConfigurationNotFound),
+        }
+    }
+
+    let quote_request = diesel::insert_into(quote_requests::table)
+        .values(&request)
+        .returning(QuoteRequest::as_returning())
+        .get_result(conn)?;
+    info!(
+        "Received quote request {} for service '{}'",
+        quote_request.id, quote_request.service_slug
+    );
+    Ok(quote_request)
+}
+
+/// Assigns a quote request to a staff member, or unassigns it when
+/// `assignee` is `None`.
+pub fn assign(
+    conn: &mut PgConnection,
+    quote_request_id: i32,
+    assignee: Option<i32>,
+) -> Result<QuoteRequest, QuoteRequestError> {
+    if let Some(assignee) = assignee {
+        let is_staff = users::table
+            .find(assignee)
+            .select(users::role)
+            .first::<String>(conn)
+            .optional()?
+            .is_some_and(|role| role == User::STAFF_ROLE);
+        if !is_staff {
+            return Err(QuoteRequestError::InvalidAssignee(assignee));
+        }
+    }
+
+    let quote_request = diesel::update(quote_requests::table.find(quote_request_id))
+        .set((
+            quote_requests::assigned_to.eq(assignee),
+            quote_requests::updated_at.eq(now),
+        ))
+        .returning(QuoteRequest::as_returning())
+        .get_result(conn)
+        .optional()?
+        .ok_or(QuoteRequestError::NotFound)?;
+    info!(
+        "Quote request {} assigned to {:?}",
+        quote_request_id, assignee
+    );
+    Ok(quote_request)
+}
+
+/// Updates the status and staff notes of a quote request. Fields that are
+/// `None` are left unchanged.
+pub fn update(
+    conn: &mut PgConnection,
+    quote_request_id: i32,
+    status: Option<QuoteRequestStatus>,
+    staff_notes: Option<String>,
+) -> Result<QuoteRequest, QuoteRequestError> {
+    conn.transaction(|conn| {
+        let current = find(conn, quote_request_id)?;
+        let quote_request = diesel::update(quote_requests::table.find(quote_request_id))
+            .set((
+                quote_requests::status.eq(status.unwrap_or(current.status)),
+                quote_requests::staff_notes.eq(staff_notes.or(current.staff_notes)),
+                quote_requests::updated_at.eq(now),
+            ))
+            .returning(QuoteRequest::as_returning())
+            .get_result(conn)?;
+        info!(
+            "Quote request {} updated to status {}",
+            quote_request_id, quote_request.status
+        );
+        Ok(quote_request)
+    })
+}
+
+/// Fetches a quote request by ID.
+pub fn find(
+    conn: &mut PgConnection,
+    quote_request_id: i32,
+) -> Result<QuoteRequest, QuoteRequestError> {
+    quote_requests::table
+        .find(quote_request_id)
+        .select(QuoteRequest::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(QuoteRequestError::NotFound)
+}
+
+/// Fetches quote requests, newest first, optionally only those in `status`
+/// or assigned to `assigned_to`.
+pub fn list(
+    conn: &mut PgConnection,
+    status: Option<QuoteRequestStatus>,
+    assigned_to: Option<i32>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<QuoteRequest>, QuoteRequestError> {
+    let mut query = quote_requests::table
+        .order((quote_requests::created_at.desc(), quote_requests::id.desc()))
+        .select(QuoteRequest::as_select())
+        .into_boxed();
+    if let Some(status) = status {
+        query = query.filter(quote_requests::status.eq(status));
+    }
+    if let Some(assigned_to) = assigned_to {
+        query = query.filter(quote_requests::assigned_to.eq(assigned_to));
+    }
+    if let Some(limit) = limit {
+        query = query.limit(limit);
+    }
+    if let Some(offset) = offset {
+        query = query.offset(offset);
+    }
+    Ok(query.load(conn)?)
+}
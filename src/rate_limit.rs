@@ -0,0 +1,413 @@
+// src/rate_limit.rs
+
+use crate::api_keys::ApiKeyPrincipal;
+use crate::diesel_schema::rate_limits::rate_limit_buckets;
+use crate::metrics;
+use crate::models::auth::User;
+use crate::settings::{RateLimit, RateLimitSettings, RateLimitStoreKind};
+use crate::types::errors::RateLimitError;
+use actix_web::http::header::{HeaderName, HeaderValue, RETRY_AFTER};
+use actix_web::{web, HttpMessage, HttpRequest};
+use chrono::Utc;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::sql_types::{Bool, Double, Text};
+use diesel::PgConnection;
+use futures::future::BoxFuture;
+use log::{error, info, warn};
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Headers describing the client's limit, exposed to browser clients.
+pub const RATE_LIMIT_HEADERS: [&str; 4] = [
+    "RateLimit-Limit",
+    "RateLimit-Remaining",
+    "RateLimit-Reset",
+    "Retry-After",
+];
+
+/// Buckets the memory store keeps before it drops the ones that have refilled.
+const MEMORY_STORE_PRUNE_THRESHOLD: usize = 10_000;
+
+/// Acquisitions between deletions of refilled buckets by the Postgres store.
+const POSTGRES_STORE_PRUNE_INTERVAL: u64 = 1_000;
+
+/// Who a request is counted against: the API key it was made with, else its
+/// user, else its client address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RateLimitClient {
+    ApiKey(i32),
+    User(i32),
+    Address(String),
+}
+
+impl RateLimitClient {
+    /// Identifies the client of a request that passed the authentication
+    /// middleware. Anonymous clients are identified by the peer address, or by
+    /// the address in the proxy headers if `trust_proxy_headers` is set.
+    pub fn from_request(req: &HttpRequest, trust_proxy_headers: bool) -> Self {
+        if let Some(api_key) = req.extensions().get::<ApiKeyPrincipal>() {
+            return RateLimitClient::ApiKey(api_key.key_id);
+        }
+        if let Some(user) = req.extensions().get::<User>() {
+            return RateLimitClient::User(user.id);
+        }
+        let address = client_address(req, trust_proxy_headers);
+        // Clients without a known address share one bucket
+        RateLimitClient::Address(address.map_or_else(|| "unknown".to_string(), |a| a.to_string()))
+    }
+}
+
+/// Returns the address of the client that sent a request: the peer address,
+/// or the address in the proxy headers if `trust_proxy_headers` is set.
+pub fn client_address(req: &HttpRequest, trust_proxy_headers: bool) -> Option<IpAddr> {
+    if trust_proxy_headers {
+        // Forwarded headers are client-controlled, so keep only valid addresses
+        req.connection_info()
+            .realip_remote_addr()
+            .and_then(|addr| addr.parse::<IpAddr>().ok())
+    } else {
+        req.peer_addr().map(|addr| addr.ip())
+    }
+}
+
+impl fmt::Display for RateLimitClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateLimitClient::ApiKey(id) => write!(f, "api-key:{}", id),
+            RateLimitClient::User(id) => write!(f, "user:{}", id),
+            RateLimitClient::Address(address) => write!(f, "ip:{}", address),
+        }
+    }
+}
+
+/// Outcome of taking a token from a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// Size of the bucket.
+    pub limit: u32,
+    /// Whole tokens left in the bucket.
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset: u64,
+    /// Seconds until the next token, for denied requests.
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimitDecision {
+    /// Describes a bucket of `limit` left with `tokens` after a request.
+    fn new(limit: RateLimit, tokens: f64, allowed: bool) -> Self {
+        let per_second = per_second(limit);
+        let seconds = |missing: f64| (missing.max(0.0) / per_second).ceil() as u64;
+        RateLimitDecision {
+            allowed,
+            limit: limit.burst,
+            remaining: tokens.max(0.0).floor() as u32,
+            reset: seconds(f64::from(limit.burst) - tokens),
+            retry_after: (!allowed).then(|| seconds(1.0 - tokens).max(1)),
+        }
+    }
+
+    /// Returns the `RateLimit-Limit`, `RateLimit-Remaining` and
+    /// `RateLimit-Reset` headers, and `Retry-After` for denied requests.
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = vec![
+            (
+                HeaderName::from_static("ratelimit-limit"),
+                HeaderValue::from(self.limit),
+            ),
+            (
+                HeaderName::from_static("ratelimit-remaining"),
+                HeaderValue::from(self.remaining),
+            ),
+            (
+                HeaderName::from_static("ratelimit-reset"),
+                HeaderValue::from(self.reset),
+            ),
+        ];
+        if let Some(retry_after) = self.retry_after {
+            headers.push((RETRY_AFTER, HeaderValue::from(retry_after)));
+        }
+        headers
+    }
+}
+
+/// Keeps token buckets. Buckets that do not exist yet are full.
+pub trait RateLimitStore: Send + Sync {
+    /// Refills the bucket `key` for the time since it was last used and takes
+    /// a token from it if one is left.
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limit: RateLimit,
+    ) -> BoxFuture<'a, Result<RateLimitDecision, RateLimitError>>;
+}
+
+/// Builds the store selected by the settings.
+pub fn store_from_settings(
+    settings: &RateLimitSettings,
+    pool: Pool<ConnectionManager<PgConnection>>,
+) -> Arc<dyn RateLimitStore> {
+    match settings.store {
+        RateLimitStoreKind::Memory => Arc::new(MemoryStore::new()),
+        RateLimitStoreKind::Postgres => Arc::new(PostgresStore::new(pool)),
+    }
+}
+
+/// Keeps buckets in memory, so every instance limits clients on its own.
+#[derive(Default)]
+pub struct MemoryStore {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    full_at: Instant,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+}
+
+impl RateLimitStore for MemoryStore {
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limit: RateLimit,
+    ) -> BoxFuture<'a, Result<RateLimitDecision, RateLimitError>> {
+        Box::pin(async move {
+            let now = Instant::now();
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let (tokens, allowed) = match buckets.get(key) {
+                Some(bucket) => take(limit, bucket.tokens, now - bucket.updated),
+                None => take(limit, f64::from(limit.burst), Duration::ZERO),
+            };
+            let missing = f64::from(limit.burst) - tokens;
+            let full_at = now + Duration::from_secs_f64(missing / per_second(limit));
+            buckets.insert(
+                key.to_string(),
+                Bucket {
+                    tokens,
+                    updated: now,
+                    full_at,
+                },
+            );
+            if buckets.len() > MEMORY_STORE_PRUNE_THRESHOLD {
+                buckets.retain(|_, bucket| bucket.full_at > now);
+            }
+            Ok(RateLimitDecision::new(limit, tokens, allowed))
+        })
+    }
+}
+
+/// Keeps buckets in the `rate_limit_buckets` table, so that every instance
+/// shares them.
+pub struct PostgresStore {
+    pool: Pool<ConnectionManager<PgConnection>>,
+    acquisitions: AtomicU64,
+}
+
+#[derive(QueryableByName)]
+struct AcquiredToken {
+    #[diesel(sql_type = Double)]
+    tokens: f64,
+    #[diesel(sql_type = Bool)]
+    allowed: bool,
+}
+
+/// Refills and takes from a bucket in one statement; the row lock keeps
+/// concurrent requests for the same bucket from taking the same token.
+const ACQUIRE_SQL: &str = "
+    WITH previous AS (
+        SELECT tokens, updated_at FROM rate_limit_buckets WHERE key = $1 FOR UPDATE
+    ), refilled AS (
+        SELECT COALESCE(
+            (SELECT LEAST($2, tokens + EXTRACT(EPOCH FROM now() - updated_at)::float8 * $3)
+             FROM previous),
+            $2
+        ) AS tokens
+    ), taken AS (
+        SELECT tokens >= 1 AS allowed,
+               CASE WHEN tokens >= 1 THEN tokens - 1 ELSE tokens END AS tokens
+        FROM refilled
+    ), saved AS (
+        INSERT INTO rate_limit_buckets (key, tokens, updated_at, full_at)
+        SELECT $1, tokens, now(), now() + make_interval(secs => ($2 - tokens) / $3)
+        FROM taken
+        ON CONFLICT (key) DO UPDATE
+        SET tokens = EXCLUDED.tokens,
+            updated_at = EXCLUDED.updated_at,
+            full_at = EXCLUDED.full_at
+    )
+    SELECT tokens, allowed FROM taken";
+
+impl PostgresStore {
+    pub fn new(pool: Pool<ConnectionManager<PgConnection>>) -> Self {
+        PostgresStore {
+            pool,
+            acquisitions: AtomicU64::new(0),
+        }
+    }
+}
+
+impl RateLimitStore for PostgresStore {
+    fn acquire<'a>(
+        &'a self,
+        key: &'a str,
+        limit: RateLimit,
+    ) -> BoxFuture<'a, Result<RateLimitDecision, RateLimitError>> {
+        Box::pin(async move {
+            let pool = self.pool.clone();
+            let key = key.to_string();
+            let prune = self
+                .acquisitions
+                .fetch_add(1, Ordering::Relaxed)
+                .is_multiple_of(POSTGRES_STORE_PRUNE_INTERVAL);
+            let acquired = web::block(move || {
+                let conn = &mut pool.get()?;
+                if prune {
+                    delete_full_buckets(conn)?;
+                }
+                diesel::sql_query(ACQUIRE_SQL)
+                    .bind::<Text, _>(key)
+                    .bind::<Double, _>(f64::from(limit.burst))
+                    .bind::<Double, _>(per_second(limit))
+                    .get_result::<AcquiredToken>(conn)
+                    .map_err(RateLimitError::from)
+            })
+            .await??;
+            Ok(RateLimitDecision::new(
+                limit,
+                acquired.tokens,
+                acquired.allowed,
+            ))
+        })
+    }
+}
+
+/// Deletes buckets that have refilled, which behave like missing ones.
+pub fn delete_full_buckets(conn: &mut PgConnection) -> Result<usize, RateLimitError> {
+    let deleted = diesel::delete(
+        rate_limit_buckets::table.filter(rate_limit_buckets::full_at.le(Utc::now())),
+    )
+    .execute(conn)?;
+    if deleted > 0 {
+        info!("Deleted {} refilled rate limit buckets", deleted);
+    }
+    Ok(deleted)
+}
+
+/// Applies the configured limits to clients.
+pub struct RateLimiter {
+    settings: RateLimitSettings,
+    store: Arc<dyn RateLimitStore>,
+}
+
+impl RateLimiter {
+    pub fn new(settings: RateLimitSettings, store: Arc<dyn RateLimitStore>) -> Self {
+        RateLimiter { settings, store }
+    }
+
+    /// Creates a limiter using the store selected by the settings.
+    pub fn from_settings(
+        settings: &RateLimitSettings,
+        pool: Pool<ConnectionManager<PgConnection>>,
+    ) -> Self {
+        RateLimiter::new(settings.clone(), store_from_settings(settings, pool))
+    }
+
+    /// Identifies the client a request is counted against.
+    pub fn client(&self, req: &HttpRequest) -> RateLimitClient {
+        RateLimitClient::from_request(req, self.settings.trust_proxy_headers)
+    }
+
+    /// Returns `true` if client addresses are read from proxy headers.
+    pub fn trust_proxy_headers(&self) -> bool {
+        self.settings.trust_proxy_headers
+    }
+
+    /// Takes a token for a request to `path` from the route's own bucket, or
+    /// from the default bucket shared by routes without a limit. Returns
+    /// `None` when rate limiting is disabled or the path is exempt.
+    pub async fn check_route(
+        &self,
+        path: &str,
+        client: &RateLimitClient,
+    ) -> Option<RateLimitDecision> {
+        if !self.settings.enabled || self.settings.exempt_paths.iter().any(|p| p == path) {
+            return None;
+        }
+        let (key, limit) = match self.settings.routes.get(path) {
+            Some(limit) => (format!("route:{}:{}", path, client), *limit),
+            None => (format!("default:{}", client), self.settings.default),
+        };
+        let decision = self.acquire(&key, limit).await?;
+        if !decision.allowed {
+            warn!("Rate limited {} on {}", client, path);
+            metrics::observe_rate_limited("route");
+        }
+        Some(decision)
+    }
+
+    /// Takes a token for each root field of an operation that has a limit of
+    /// its own, and returns the first field that is over its limit.
+    pub async fn check_operation(
+        &self,
+        root_fields: &[String],
+        client: &RateLimitClient,
+    ) -> Option<(String, RateLimitDecision)> {
+        if !self.settings.enabled {
+            return None;
+        }
+        for field in root_fields {
+            let Some(limit) = self.settings.operations.get(field) else {
+                continue;
+            };
+            let key = format!("op:{}:{}", field, client);
+            match self.acquire(&key, *limit).await {
+                Some(decision) if !decision.allowed => {
+                    warn!("Rate limited {} on {}", client, field);
+                    metrics::observe_rate_limited("operation");
+                    return Some((field.clone(), decision));
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Requests are let through when the store fails, so that an outage of
+    /// the store does not take the API down with it.
+    async fn acquire(&self, key: &str, limit: RateLimit) -> Option<RateLimitDecision> {
+        match self.store.acquire(key, limit).await {
+            Ok(decision) => Some(decision),
+            Err(e) => {
+                error!("Failed to check rate limit: {}", e);
+                None
+            }
+        }
+    }
+}
+
+fn per_second(limit: RateLimit) -> f64 {
+    f64::from(limit.per_minute) / 60.0
+}
+
+/// Refills a bucket holding `tokens` for `elapsed` and takes a token if one is
+/// left. Returns the tokens left and whether one was taken.
+fn take(limit: RateLimit, tokens: f64, elapsed: Duration) -> (f64, bool) {
+    let refilled = f64::from(limit.burst).min(tokens + elapsed.as_secs_f64() * per_second(limit));
+    if refilled >= 1.0 {
+        (refilled - 1.0, true)
+    } else {
+        (refilled, false)
+    }
+}
@@ -0,0 +1,118 @@
+// src/reference_cache.rs
+
+use crate::diesel_schema::parts::{categories, manufacturers};
+use crate::models::parts::category::Category;
+use crate::models::parts::manufacturer::Manufacturer;
+use crate::types::errors::CacheError;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Rows of one table keyed by ID, as loaded at `loaded_at`.
+struct Snapshot<T> {
+    rows: Arc<HashMap<i32, T>>,
+    loaded_at: Instant,
+}
+
+/// A whole-table cache that is reloaded once its snapshot is older than the TTL.
+struct TableCache<T> {
+    snapshot: RwLock<Option<Snapshot<T>>>,
+}
+
+impl<T> TableCache<T> {
+    fn new() -> Self {
+        TableCache {
+            snapshot: RwLock::new(None),
+        }
+    }
+
+    /// Returns the cached rows, calling `load` when they are missing or stale.
+    fn rows(
+        &self,
+        ttl: Duration,
+        load: impl FnOnce() -> Result<HashMap<i32, T>, CacheError>,
+    ) -> Result<Arc<HashMap<i32, T>>, CacheError> {
+        if let Some(snapshot) = self.snapshot.read().unwrap().as_ref() {
+            if snapshot.loaded_at.elapsed() < ttl {
+                return Ok(snapshot.rows.clone());
+            }
+        }
+
+        let rows = Arc::new(load()?);
+        *self.snapshot.write().unwrap() = Some(Snapshot {
+            rows: rows.clone(),
+            loaded_at: Instant::now(),
+        });
+        Ok(rows)
+    }
+
+    fn invalidate(&self) {
+        *self.snapshot.write().unwrap() = None;
+    }
+}
+
+/// Shared in-memory cache of the manufacturers and categories reference tables.
+///
+/// Each table is loaded in full on first use and reloaded after the TTL, so
+/// resolving a part's manufacturer or category needs no database round-trip.
+/// Call the `invalidate_*` methods after changing these tables in-process.
+pub struct ReferenceCache {
+    db: Pool<ConnectionManager<PgConnection>>,
+    ttl: Duration,
+    manufacturers: TableCache<Manufacturer>,
+    categories: TableCache<Category>,
+}
+
+impl ReferenceCache {
+    /// Creates an empty cache whose snapshots are reused for `ttl`.
+    pub fn new(db: Pool<ConnectionManager<PgConnection>>, ttl: Duration) -> Self {
+        ReferenceCache {
+            db,
+            ttl,
+            manufacturers: TableCache::new(),
+            categories: TableCache::new(),
+        }
+    }
+
+    /// Returns the manufacturer with the given ID, if it exists in the snapshot.
+    pub fn manufacturer(&self, manufacturer_id: i32) -> Result<Option<Manufacturer>, CacheError> {
+        let rows = self.manufacturers.rows(self.ttl, || {
+            debug!("Loading manufacturers into the reference cache.");
+            let mut conn = self.db.get()?;
+            let rows = manufacturers::table.load::<Manufacturer>(&mut conn)?;
+            Ok(rows.into_iter().map(|m| (m.id, m)).collect())
+        })?;
+        Ok(rows.get(&manufacturer_id).cloned())
+    }
+
+    /// Returns the category with the given ID, if it exists in the snapshot.
+    pub fn category(&self, category_id: i32) -> Result<Option<Category>, CacheError> {
+        let rows = self.categories.rows(self.ttl, || {
+            debug!("Loading categories into the reference cache.");
+            let mut conn = self.db.get()?;
+            let rows = categories::table.load::<Category>(&mut conn)?;
+            Ok(rows.into_iter().map(|c| (c.id, c)).collect())
+        })?;
+        Ok(rows.get(&category_id).cloned())
+    }
+
+    /// Drops the cached manufacturers so they are reloaded on next use.
+    pub fn invalidate_manufacturers(&self) {
+        self.manufacturers.invalidate();
+    }
+
+    /// Drops the cached categories so they are reloaded on next use.
+    pub fn invalidate_categories(&self) {
+        self.categories.invalidate();
+    }
+
+    /// Drops every cached table.
+    pub fn invalidate(&self) {
+        self.invalidate_manufacturers();
+        self.invalidate_categories();
+    }
+}
@@ -0,0 +1,305 @@
+// src/response_cache.rs
+
+use crate::diesel_schema::parts::catalog_revision;
+use crate::graphql_schema::query_limits::OperationSummary;
+use crate::settings::ResponseCacheSettings;
+use crate::types::errors::CacheError;
+use actix_web::web;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use juniper::OperationType;
+use log::{error, info};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Who a cached response may be shared with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheScope {
+    /// Responses to anonymous requests, which shared HTTP caches may store.
+    Public,
+    /// Responses to an authenticated user, cached per user.
+    User(i32),
+    /// Responses to requests made with an API key, cached per key since keys
+    /// may be limited to fewer fields than their owner.
+    ApiKey(i32),
+}
+
+impl CacheScope {
+    /// Returns the scope for a request made by `user_id`, if authenticated.
+    pub fn for_user(user_id: Option<i32>) -> Self {
+        user_id.map_or(CacheScope::Public, CacheScope::User)
+    }
+
+    /// Returns the `Cache-Control` directive matching the scope.
+    pub fn cache_control(&self) -> &'static str {
+        match self {
+            CacheScope::Public => "public",
+            CacheScope::User(_) | CacheScope::ApiKey(_) => "private",
+        }
+    }
+
+    fn key_part(&self) -> String {
+        match self {
+            CacheScope::Public => "public".to_string(),
+            CacheScope::User(id) => format!("user:{}", id),
+            CacheScope::ApiKey(id) => format!("api-key:{}", id),
+        }
+    }
+}
+
+/// A cached GraphQL response.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub body: Arc<Value>,
+    /// Strong validator derived from the response body.
+    pub etag: String,
+    revision: i64,
+    expires_at: Instant,
+}
+
+impl CachedResponse {
+    /// Returns how much longer the response may be served.
+    pub fn max_age(&self) -> Duration {
+        self.expires_at.saturating_duration_since(Instant::now())
+    }
+}
+
+/// In-memory cache of GraphQL query responses for catalog data.
+///
+/// Entries are tied to the catalog revision, a counter bumped by database
+/// triggers whenever catalog tables change. The revision is polled by
+/// [`spawn_revision_poller`], so changes made by other processes (such as the
+/// data import) invalidate cached responses within one polling interval.
+/// Caching is disabled until the revision has been read once.
+pub struct ResponseCache {
+    db: Pool<ConnectionManager<PgConnection>>,
+    settings: ResponseCacheSettings,
+    entries: RwLock<HashMap<String, CachedResponse>>,
+    revision: RwLock<Option<i64>>,
+    revision_hooks: RwLock<Vec<Box<dyn Fn() + Send + Sync>>>,
+}
+
+impl ResponseCache {
+    /// Creates an empty cache.
+    pub fn new(db: Pool<ConnectionManager<PgConnection>>, settings: ResponseCacheSettings) -> Self {
+        ResponseCache {
+            db,
+            settings,
+            entries: RwLock::new(HashMap::new()),
+            revision: RwLock::new(None),
+            revision_hooks: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Registers a hook called whenever the catalog revision changes, so other
+    /// caches of catalog data can be dropped along with cached responses.
+    pub fn on_revision_change(&self, hook: impl Fn() + Send + Sync + 'static) {
+        self.revision_hooks.write().unwrap().push(Box::new(hook));
+    }
+
+    /// Returns `true` if responses to the operation may be cached: a query
+    /// whose root fields are all configured as cacheable.
+    pub fn is_cacheable(&self, operation: &OperationSummary) -> bool {
+        self.settings.enabled
+            && operation.operation_type == OperationType::Query
+            && !operation.root_fields.is_empty()
+            && operation
+                .root_fields
+                .iter()
+                .all(|field| self.settings.cacheable_fields.contains(field))
+    }
+
+    /// Builds the cache key for a request from its normalized query, operation
+    /// name, variables and scope.
+    pub fn key(
+        query: &str,
+        operation_name: Option<&str>,
+        variables: &Value,
+        scope: CacheScope,
+    ) -> String {
+        // An absent variables object is equivalent to an empty one
+        let variables = match variables {
+            Value::Null => "{}".to_string(),
+            other => other.to_string(),
+        };
+
+        let mut hasher = Sha256::new();
+        for part in [
+            normalize_query(query).as_str(),
+            operation_name.unwrap_or(""),
+            variables.as_str(),
+            scope.key_part().as_str(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Returns the cached response for `key` if it is still fresh.
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        let revision = (*self.revision.read().unwrap())?;
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(key)
+            .filter(|entry| entry.revision == revision && entry.max_age() > Duration::ZERO)
+            .cloned()
+    }
+
+    /// Caches a response, returning the stored entry, or `None` while the
+    /// catalog revision is unknown.
+    pub fn insert(&self, key: String, body: Value) -> Option<CachedResponse> {
+        let revision = (*self.revision.read().unwrap())?;
+        let etag = format!("\"{:.32x}\"", Sha256::digest(body.to_string().as_bytes()));
+        let response = CachedResponse {
+            body: Arc::new(body),
+            etag,
+            revision,
+            expires_at: Instant::now() + self.settings.ttl(),
+        };
+
+        let mut entries = self.entries.write().unwrap();
+        if entries.len() >= self.settings.max_entries && !entries.contains_key(&key) {
+            // Drop expired entries first, then an arbitrary one if still full
+            entries.retain(|_, entry| entry.max_age() > Duration::ZERO);
+            if entries.len() >= self.settings.max_entries {
+                if let Some(evicted) = entries.keys().next().cloned() {
+                    entries.remove(&evicted);
+                }
+            }
+        }
+        entries.insert(key, response.clone());
+        Some(response)
+    }
+
+    /// Drops every cached response. Call after changing catalog data in-process
+    /// to avoid serving stale responses until the next revision poll.
+    pub fn invalidate(&self) {
+        let mut entries = self.entries.write().unwrap();
+        if !entries.is_empty() {
+            info!("Invalidated {} cached GraphQL responses", entries.len());
+        }
+        entries.clear();
+    }
+
+    /// Reads the catalog revision from the database, invalidating cached
+    /// responses when it has changed.
+    pub fn refresh_revision(&self) -> Result<i64, CacheError> {
+        let mut conn = self.db.get()?;
+        let latest = catalog_revision::table
+            .select(catalog_revision::revision)
+            .first::<i64>(&mut conn)?;
+
+        let mut revision = self.revision.write().unwrap();
+        if *revision != Some(latest) {
+            if revision.is_some() {
+                info!("Catalog revision changed to {}", latest);
+                self.invalidate();
+                for hook in self.revision_hooks.read().unwrap().iter() {
+                    hook();
+                }
+            }
+            *revision = Some(latest);
+        }
+        Ok(latest)
+    }
+
+    /// Returns the configured cache settings.
+    pub fn settings(&self) -> &ResponseCacheSettings {
+        &self.settings
+    }
+}
+
+/// Polls the catalog revision in the background so that cached responses are
+/// dropped after catalog changes.
+pub fn spawn_revision_poller(cache: web::Data<ResponseCache>) {
+    if !cache.settings().enabled {
+        return;
+    }
+
+    let interval = cache.settings().revision_check_interval();
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let cache = cache.clone();
+            match web::block(move || cache.refresh_revision()).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => error!("Failed to read catalog revision: {}", e),
+                Err(e) => error!("Failed to read catalog revision: {}", e),
+            }
+        }
+    });
+}
+
+/// Normalizes a query for use in cache keys by dropping comments and
+/// insignificant whitespace and commas, leaving string literals untouched.
+pub fn normalize_query(query: &str) -> String {
+    let mut normalized = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    let mut pending_space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                for c in chars.by_ref() {
+                    if c == '\n' || c == '\r' {
+                        break;
+                    }
+                }
+                pending_space = true;
+            }
+            c if c.is_whitespace() || c == ',' || c == '\u{feff}' => pending_space = true,
+            c => {
+                if pending_space && !normalized.is_empty() {
+                    normalized.push(' ');
+                }
+                pending_space = false;
+                normalized.push(c);
+
+                if c == '"' {
+                    copy_string(&mut chars, &mut normalized);
+                }
+            }
+        }
+    }
+
+    normalized
+}
+
+/// Copies the rest of a string literal whose opening quote was already copied.
+fn copy_string(chars: &mut std::iter::Peekable<std::str::Chars>, out: &mut String) {
+    // Block strings are opened with three quotes
+    if chars.peek() == Some(&'"') {
+        out.push(chars.next().unwrap());
+        if chars.peek() != Some(&'"') {
+            // An empty string: ""
+            return;
+        }
+        out.push(chars.next().unwrap());
+
+        let body_start = out.len();
+        for c in chars.by_ref() {
+            out.push(c);
+            let body = &out[body_start..];
+            if body.ends_with("\"\"\"") && !body.ends_with("\\\"\"\"") {
+                return;
+            }
+        }
+        return;
+    }
+
+    let mut escaped = false;
+    for c in chars.by_ref() {
+        out.push(c);
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return,
+            _ => escaped = false,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+// src/sessions.rs
+
+use crate::diesel_schema::users::{sessions, users, verification_tokens};
+use crate::models::auth::{Session, User};
+use crate::rate_limit;
+use crate::types::errors::SessionError;
+use actix_web::{web, HttpRequest};
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::PgConnection;
+use log::{error, info};
+use std::time::Duration;
+
+/// How stale `last_seen_at` may get before a request updates it, so that
+/// busy sessions do not cause a write on every request.
+const LAST_SEEN_RESOLUTION_MINUTES: i64 = 5;
+
+/// Longest user agent stored with a session.
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// The client a request came from, recorded when a session starts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+}
+
+impl ClientInfo {
+    /// Reads the user agent and client address of a request. The address is
+    /// the peer address, or the one in the `Forwarded` or `X-Forwarded-For`
+    /// header if `trust_proxy_headers` is set.
+    pub fn from_request(req: &HttpRequest, trust_proxy_headers: bool) -> Self {
+        let user_agent = req
+            .headers()
+            .get(actix_web::http::header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LENGTH).collect());
+        let ip_address =
+            rate_limit::client_address(req, trust_proxy_headers).map(|addr| addr.to_string());
+        ClientInfo {
+            user_agent,
+            ip_address,
+        }
+    }
+}
+
+/// Session the current request was authenticated with, stored in the request
+/// extensions by the authentication middleware.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrentSession(pub i32);
+
+/// Looks up the unexpired session with `token` and its user, and records
+/// that the session was used. Sessions created by NextAuth are stored without
+/// a client, so `client` is recorded for them on first use.
+pub fn authenticate(
+    conn: &mut PgConnection,
+    token: &str,
+    client: &ClientInfo,
+) -> Result<Option<(User, Session)>, SessionError> {
+    let mut found = sessions::table
+        .inner_join(users::table)
+        .filter(sessions::sessionToken.eq(token))
+        .filter(sessions::expires.gt(Utc::now()))
+        .select((User::as_select(), Session::as_select()))
+        .first::<(User, Session)>(conn)
+        .optional()?;
+    if let Some((_, session)) = &mut found {
+        let stale = Utc::now() - ChronoDuration::minutes(LAST_SEEN_RESOLUTION_MINUTES);
+        diesel::update(sessions::table.find(session.id))
+            .filter(
+                sessions::last_seen_at
+                    .is_null()
+                    .or(sessions::last_seen_at.lt(stale)),
+            )
+            .set(sessions::last_seen_at.eq(Utc::now()))
+            .execute(conn)?;
+
+        let unrecorded = session.user_agent.is_none() && session.ip_address.is_none();
+        if unrecorded && *client != ClientInfo::default() {
+            let recorded = diesel::update(sessions::table.find(session.id))
+                .filter(sessions::user_agent.is_null())
+                .filter(sessions::ip_address.is_null())
+                .set((
+                    sessions::user_agent.eq(&client.user_agent),
+                    sessions::ip_address.eq(&client.ip_address),
+                ))
+                .returning(Session::as_returning())
+                .get_result(conn)
+                .optional()?;
+            if let Some(recorded) = recorded {
+                *session = recorded;
+            }
+        }
+    }
+    Ok(found)
+}
+
+/// Fetches the user's unexpired sessions, most recently used first.
+pub fn list(conn: &mut PgConnection, user_id: i32) -> Result<Vec<Session>, SessionError> {
+    Ok(sessions::table
+        .filter(sessions::userId.eq(user_id))
+        .filter(sessions::expires.gt(Utc::now()))
+        .order((
+            sessions::last_seen_at.desc().nulls_last(),
+            sessions::created_at.desc(),
+            sessions::id.desc(),
+        ))
+        .select(Session::as_select())
+        .load(conn)?)
+}
+
+/// Signs one of the user's sessions out and returns the remaining ones.
+pub fn revoke(
+    conn: &mut PgConnection,
+    user_id: i32,
+    session_id: i32,
+) -> Result<Vec<Session>, SessionError> {
+    let deleted = diesel::delete(
+        sessions::table
+            .find(session_id)
+            .filter(sessions::userId.eq(user_id)),
+    )
+    .execute(conn)?;
+    if deleted == 0 {
+        return Err(SessionError::NotFound(session_id));
+    }
+    info!("Session {} revoked by user {}", session_id, user_id);
+    list(conn, user_id)
+}
+
+/// Signs every session of the user except `current_session_id` out and
+/// returns the remaining ones.
+pub fn revoke_others(
+    conn: &mut PgConnection,
+    user_id: i32,
+    current_session_id: i32,
+) -> Result<Vec<Session>, SessionError> {
+    let deleted = diesel::delete(
+        sessions::table
+            .filter(sessions::userId.eq(user_id))
+            .filter(sessions::id.ne(current_session_id)),
+    )
+    .execute(conn)?;
+    info!("{} other sessions revoked by user {}", deleted, user_id);
+    list(conn, user_id)
+}
+
+/// Deletes expired sessions and verification tokens, returning how many of
+/// each were removed.
+pub fn delete_expired(conn: &mut PgConnection) -> Result<(usize, usize), SessionError> {
+    let now = Utc::now();
+    let sessions =
+        diesel::delete(sessions::table.filter(sessions::expires.le(now))).execute(conn)?;
+    let tokens =
+        diesel::delete(verification_tokens::table.filter(verification_tokens::expires.le(now)))
+            .execute(conn)?;
+    Ok((sessions, tokens))
+}
+
+/// Spawns a background task that deletes expired sessions and verification
+/// tokens every `interval`.
+pub fn spawn_cleanup(pool: Pool<ConnectionManager<PgConnection>>, interval: Duration) {
+    actix_web::rt::spawn(async move {
+        let mut ticker = actix_web::rt::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let pool = pool.clone();
+            let result = web::block(move || {
+                let conn = &mut pool.get()?;
+                delete_expired(conn)
+            })
+            .await;
+            match result {
+                Ok(Ok((0, 0))) => {}
+                Ok(Ok((sessions, tokens))) => info!(
+                    "Deleted {} expired sessions and {} expired verification tokens",
+                    sessions, tokens
+                ),
+                Ok(Err(e)) => error!("Failed to delete expired sessions: {}", e),
+                Err(e) => error!("Failed to delete expired sessions: {}", e),
+            }
+        }
+    });
+}
@@ -1,5 +1,6 @@
 use diesel::result::Error as DieselError;
 use diesel_migrations::MigrationError as DieselMigrationError;
+use juniper::{graphql_value, FieldError, IntoFieldError};
 use std::env::VarError;
 use std::error::Error;
 use thiserror::Error;
@@ -55,3 +56,756 @@ pub enum DataImportError {
     #[error("Custom error: {0}")]
     CustomError(String),
 }
+
+/// Custom error enum for handling server configuration errors.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// Represents a failure to read the configuration file.
+    #[error("Failed to read config file '{path}': {source}")]
+    FileError {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// Represents a TOML parsing error in the configuration file.
+    #[error("Failed to parse config file: {0}")]
+    ParseError(#[from] toml::de::Error),
+
+    /// Represents an environment variable that could not be parsed.
+    #[error("Invalid value '{value}' for environment variable {key}: {reason}")]
+    InvalidEnvVar {
+        key: String,
+        value: String,
+        reason: String,
+    },
+
+    /// Represents a setting that failed validation.
+    #[error("Invalid setting '{key}': {reason}")]
+    ValidationError { key: String, reason: String },
+}
+
+/// Custom error enum for GraphQL queries rejected before execution.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum QueryLimitError {
+    /// Represents a query nested deeper than the configured maximum.
+    #[error("Query depth exceeds the maximum allowed depth of {max_depth}")]
+    TooDeep { depth: usize, max_depth: usize },
+
+    /// Represents a query whose estimated cost exceeds the configured maximum.
+    #[error("Query complexity exceeds the maximum allowed complexity of {max_complexity}")]
+    TooComplex {
+        complexity: usize,
+        max_complexity: usize,
+    },
+}
+
+impl IntoFieldError for QueryLimitError {
+    fn into_field_error(self) -> FieldError {
+        let message = self.to_string();
+        let extensions = match self {
+            QueryLimitError::TooDeep { depth, max_depth } => graphql_value!({
+                "code": "QUERY_TOO_DEEP",
+                "depth": (depth as i32),
+                "maxDepth": (max_depth as i32),
+            }),
+            QueryLimitError::TooComplex {
+                complexity,
+                max_complexity,
+            } => graphql_value!({
+                "code": "QUERY_TOO_COMPLEX",
+                "complexity": (complexity.min(i32::MAX as usize) as i32),
+                "maxComplexity": (max_complexity.min(i32::MAX as usize) as i32),
+            }),
+        };
+        FieldError::new(message, extensions)
+    }
+}
+
+/// Custom error enum for resolving persisted GraphQL queries.
+#[derive(Debug, Error)]
+pub enum PersistedQueryError {
+    /// Represents a persisted query hash sent while persisted queries are disabled.
+    #[error("PersistedQueryNotSupported")]
+    NotSupported,
+
+    /// Represents a persisted query extension with an unknown protocol version.
+    #[error("Unsupported persisted query version")]
+    UnsupportedVersion,
+
+    /// Represents a hash that has not been registered; clients retry with the full query.
+    #[error("PersistedQueryNotFound")]
+    NotFound,
+
+    /// Represents a query whose SHA-256 hash differs from the one sent with it.
+    #[error("Provided sha256Hash does not match query")]
+    HashMismatch,
+
+    /// Represents a query rejected because it is not on the allowlist.
+    #[error("Query is not in the persisted query allowlist")]
+    NotAllowed,
+
+    /// Represents a request carrying neither a query nor a persisted query hash.
+    #[error("Must provide a query string or a persisted query hash")]
+    MissingQuery,
+
+    /// Represents an invalid persisted query manifest.
+    #[error("Invalid persisted query manifest '{path}': {reason}")]
+    ManifestError { path: String, reason: String },
+
+    /// Represents a Diesel ORM error while reading or storing queries.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for PersistedQueryError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            PersistedQueryError::NotSupported => "PERSISTED_QUERY_NOT_SUPPORTED",
+            PersistedQueryError::NotFound => "PERSISTED_QUERY_NOT_FOUND",
+            PersistedQueryError::NotAllowed => "PERSISTED_QUERY_NOT_ALLOWED",
+            PersistedQueryError::UnsupportedVersion
+            | PersistedQueryError::HashMismatch
+            | PersistedQueryError::MissingQuery => "BAD_REQUEST",
+            PersistedQueryError::ManifestError { .. }
+            | PersistedQueryError::DatabaseError(_)
+            | PersistedQueryError::PoolError(_) => {
+                // Internal details stay in the server logs
+                return FieldError::new(
+                    "Failed to load persisted query",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Custom error enum for GraphQL HTTP requests that cannot be executed as sent.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum GraphQLRequestError {
+    /// Represents a batched request while batching is disabled.
+    #[error("Batched requests are not supported")]
+    BatchingDisabled,
+
+    /// Represents a batch with no operations.
+    #[error("Batched request must contain at least one operation")]
+    EmptyBatch,
+
+    /// Represents a batch with more operations than allowed.
+    #[error("Batched request exceeds the maximum of {max} operations")]
+    BatchTooLarge { size: usize, max: usize },
+
+    /// Represents a mutation or subscription sent with GET.
+    #[error("Only query operations can be sent with GET")]
+    MethodNotAllowed,
+
+    /// Represents a query string parameter that is not valid JSON.
+    #[error("Query parameter '{name}' must be a JSON object")]
+    InvalidParameter { name: String },
+
+    /// Represents an operation whose root field is over its rate limit.
+    #[error("Too many requests for {field}; retry in {retry_after} seconds")]
+    RateLimited {
+        field: String,
+        limit: u32,
+        reset: u64,
+        retry_after: u64,
+    },
+}
+
+impl IntoFieldError for GraphQLRequestError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            GraphQLRequestError::MethodNotAllowed => "METHOD_NOT_ALLOWED",
+            GraphQLRequestError::RateLimited { .. } => "RATE_LIMITED",
+            _ => "BAD_REQUEST",
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Custom error enum for refreshing in-memory caches from the database.
+#[derive(Debug, Error)]
+pub enum CacheError {
+    /// Represents a Diesel ORM error while reading cached data.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+/// Errors raised while listening for database change notifications.
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    /// Represents a failure to open the listening connection.
+    #[error("Failed to connect to the database: {0}")]
+    ConnectionFailed(String),
+
+    /// Represents a failure to subscribe to a notification channel.
+    #[error("Failed to listen on channel '{channel}': {reason}")]
+    ListenFailed { channel: String, reason: String },
+
+    /// Represents a broken listening connection.
+    #[error("Lost the database connection: {0}")]
+    ConnectionLost(String),
+
+    /// Represents a notification whose payload could not be decoded.
+    #[error("Invalid payload on channel '{channel}': {reason}")]
+    InvalidPayload { channel: String, reason: String },
+}
+
+/// Errors raised while reserving or releasing stock.
+#[derive(Debug, Error)]
+pub enum InventoryError {
+    /// Represents a part without enough unreserved stock.
+    #[error("Insufficient stock for part {part_id}: requested {requested}, available {available}")]
+    InsufficientStock {
+        part_id: i32,
+        requested: i32,
+        available: i32,
+    },
+
+    /// Represents a Diesel ORM error while updating stock.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+}
+
+impl IntoFieldError for InventoryError {
+    fn into_field_error(self) -> FieldError {
+        let extensions = match self {
+            InventoryError::InsufficientStock {
+                part_id,
+                requested,
+                available,
+            } => graphql_value!({
+                "code": "INSUFFICIENT_STOCK",
+                "partId": part_id,
+                "requested": requested,
+                "available": available,
+            }),
+            InventoryError::DatabaseError(_) => {
+                return FieldError::new(
+                    "Failed to update inventory",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), extensions)
+    }
+}
+
+/// Errors raised while creating orders or changing their status.
+#[derive(Debug, Error)]
+pub enum OrderError {
+    /// Represents a missing order, or one the caller may not see.
+    #[error("Order not found")]
+    NotFound,
+
+    /// Represents a missing configuration, or one the caller may not order.
+    #[error("Configuration not found")]
+    ConfigurationNotFound,
+
+    /// Represents a configuration without parts.
+    #[error("Configuration {0} has no parts")]
+    EmptyConfiguration(i32),
+
+    /// Represents a part that cannot be ordered because it has no price.
+    #[error("Part {0} has no price")]
+    UnpricedPart(i32),
+
+    /// Represents a part that cannot be ordered because it is discontinued.
+    #[error("Part {0} is discontinued")]
+    DiscontinuedPart(i32),
+
+    /// Represents a status change the lifecycle does not allow.
+    #[error("Cannot change order status from {from} to {to}")]
+    InvalidTransition { from: String, to: String },
+
+    /// Represents a failure to reserve or release stock.
+    #[error(transparent)]
+    InventoryError(#[from] InventoryError),
+
+    /// Represents a Diesel ORM error while reading or writing orders.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for OrderError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            OrderError::NotFound | OrderError::ConfigurationNotFound => "NOT_FOUND",
+            OrderError::EmptyConfiguration(_)
+            | OrderError::UnpricedPart(_)
+            | OrderError::DiscontinuedPart(_) => "BAD_REQUEST",
+            OrderError::InvalidTransition { .. } => "INVALID_STATUS_TRANSITION",
+            OrderError::InventoryError(e) => return e.into_field_error(),
+            OrderError::DatabaseError(_) | OrderError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process order",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while submitting or managing quote requests.
+#[derive(Debug, Error)]
+pub enum QuoteRequestError {
+    /// Represents a missing quote request.
+    #[error("Quote request not found")]
+    NotFound,
+
+    /// Represents a service slug that no advertised service has.
+    #[error("Unknown service: {0}")]
+    UnknownService(String),
+
+    /// Represents a missing configuration, or one the caller may not share.
+    #[error("Configuration not found")]
+    ConfigurationNotFound,
+
+    /// Represents an assignee who is not a staff member.
+    #[error("User {0} is not a staff member")]
+    InvalidAssignee(i32),
+
+    /// Represents contact details or requirements that fail validation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// Represents a Diesel ORM error while reading or writing quote requests.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for QuoteRequestError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            QuoteRequestError::NotFound | QuoteRequestError::ConfigurationNotFound => "NOT_FOUND",
+            QuoteRequestError::UnknownService(_)
+            | QuoteRequestError::InvalidAssignee(_)
+            | QuoteRequestError::Validation(_) => "BAD_REQUEST",
+            QuoteRequestError::DatabaseError(_) | QuoteRequestError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process quote request",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while discontinuing catalog rows or reverting audited changes.
+#[derive(Debug, Error)]
+pub enum CatalogError {
+    /// Represents a missing part.
+    #[error("Part {0} not found")]
+    PartNotFound(i32),
+
+    /// Represents a missing manufacturer.
+    #[error("Manufacturer {0} not found")]
+    ManufacturerNotFound(i32),
+
+    /// Represents a missing category.
+    #[error("Category {0} not found")]
+    CategoryNotFound(i32),
+
+    /// Represents a missing audit log entry.
+    #[error("Audit log entry {0} not found")]
+    AuditEntryNotFound(i32),
+
+    /// Represents an audit log entry recording an insert, which cannot be undone
+    /// without breaking references to the row.
+    #[error("Audit log entry {0} records an insert and cannot be reverted")]
+    IrreversibleAuditEntry(i32),
+
+    /// Represents a Diesel ORM error while changing the catalog.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for CatalogError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            CatalogError::PartNotFound(_)
+            | CatalogError::ManufacturerNotFound(_)
+            | CatalogError::CategoryNotFound(_)
+            | CatalogError::AuditEntryNotFound(_) => "NOT_FOUND",
+            CatalogError::IrreversibleAuditEntry(_) => "BAD_REQUEST",
+            CatalogError::DatabaseError(_) | CatalogError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to update catalog",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while users update their own profile.
+#[derive(Debug, Error)]
+pub enum ProfileError {
+    /// Represents a user record that no longer exists.
+    #[error("User not found")]
+    NotFound,
+
+    /// Represents a profile field that fails validation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// Represents a Diesel ORM error while reading or writing the profile.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for ProfileError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            ProfileError::NotFound => "NOT_FOUND",
+            ProfileError::Validation(_) => "BAD_REQUEST",
+            ProfileError::DatabaseError(_) | ProfileError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to update profile",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while listing or unlinking a user's OAuth accounts.
+#[derive(Debug, Error)]
+pub enum AccountError {
+    /// Represents a provider the user has no linked account for.
+    #[error("No {0} account is linked")]
+    NotLinked(String),
+
+    /// Represents an unlink that would leave the user unable to sign in.
+    #[error("Cannot unlink the last way to sign in")]
+    LastLoginMethod,
+
+    /// Represents a Diesel ORM error while reading or removing accounts.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for AccountError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            AccountError::NotLinked(_) => "NOT_FOUND",
+            AccountError::LastLoginMethod => "LAST_LOGIN_METHOD",
+            AccountError::DatabaseError(_) | AccountError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process linked accounts",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while composing or delivering an email.
+#[derive(Debug, Error)]
+pub enum MailError {
+    /// Represents a server started without a way to send email.
+    #[error("Email delivery is not configured")]
+    NotConfigured,
+
+    /// Represents a sender or recipient that is not a valid mailbox.
+    #[error("Invalid email address: {0}")]
+    InvalidAddress(String),
+
+    /// Represents a message that could not be assembled.
+    #[error("Failed to build message: {0}")]
+    MessageError(#[from] lettre::error::Error),
+
+    /// Represents a failure to deliver a message through the SMTP server.
+    #[error("SMTP error: {0}")]
+    SmtpError(#[from] lettre::transport::smtp::Error),
+
+    /// Represents a failure to write a message to the outbox directory.
+    #[error("Outbox error: {0}")]
+    OutboxError(#[from] std::io::Error),
+}
+
+/// Errors raised while issuing or redeeming email verification and sign-in links.
+#[derive(Debug, Error)]
+pub enum VerificationError {
+    /// Represents a token that is unknown, already used or expired.
+    #[error("The link is invalid or has expired")]
+    InvalidToken,
+
+    /// Represents a verification request for an address that is already verified.
+    #[error("Email address is already verified")]
+    AlreadyVerified,
+
+    /// Represents a user without an email address to verify.
+    #[error("No email address is set")]
+    MissingEmail,
+
+    /// Represents input that is not a plausible email address.
+    #[error("{0}")]
+    Validation(String),
+
+    /// Represents a failure to send the link.
+    #[error("Failed to send email: {0}")]
+    DeliveryError(#[from] MailError),
+
+    /// Represents a Diesel ORM error while reading or writing tokens.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for VerificationError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            VerificationError::InvalidToken => "INVALID_TOKEN",
+            VerificationError::AlreadyVerified => "ALREADY_VERIFIED",
+            VerificationError::MissingEmail | VerificationError::Validation(_) => "BAD_REQUEST",
+            VerificationError::DeliveryError(_) => {
+                return FieldError::new(
+                    "Failed to send email",
+                    graphql_value!({ "code": "MAIL_DELIVERY_FAILED" }),
+                );
+            }
+            VerificationError::DatabaseError(_) | VerificationError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process email verification",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while authenticating, listing or revoking sessions.
+#[derive(Debug, Error)]
+pub enum SessionError {
+    /// Represents a session that does not exist or belongs to another user.
+    #[error("Session {0} not found")]
+    NotFound(i32),
+
+    /// Represents a request that was not authenticated with a session token.
+    #[error("This request was not made with a session")]
+    NoCurrentSession,
+
+    /// Represents a Diesel ORM error while reading or writing sessions.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for SessionError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            SessionError::NotFound(_) => "NOT_FOUND",
+            SessionError::NoCurrentSession => "BAD_REQUEST",
+            SessionError::DatabaseError(_) | SessionError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process sessions",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while issuing, rotating, revoking or authenticating API keys.
+#[derive(Debug, Error)]
+pub enum ApiKeyError {
+    /// Represents a missing API key.
+    #[error("API key {0} not found")]
+    NotFound(i32),
+
+    /// Represents a missing owner for a new key.
+    #[error("User {0} not found")]
+    OwnerNotFound(i32),
+
+    /// Represents a key that was revoked and can no longer be rotated.
+    #[error("API key {0} has been revoked")]
+    Revoked(i32),
+
+    /// Represents a name, scope list or expiry that fails validation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// Represents a Diesel ORM error while reading or writing API keys.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for ApiKeyError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            ApiKeyError::NotFound(_) | ApiKeyError::OwnerNotFound(_) => "NOT_FOUND",
+            ApiKeyError::Revoked(_) | ApiKeyError::Validation(_) => "BAD_REQUEST",
+            ApiKeyError::DatabaseError(_) | ApiKeyError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process API keys",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised by a rate limit store.
+#[derive(Debug, Error)]
+pub enum RateLimitError {
+    /// Represents a Diesel ORM error while reading or writing buckets.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+
+    /// Represents a blocking database task that could not run to completion.
+    #[error("Blocking task failed: {0}")]
+    TaskFailed(#[from] actix_web::error::BlockingError),
+}
+
+/// Errors raised while creating, changing, reading or sharing configurations.
+#[derive(Debug, Error)]
+pub enum ConfigurationError {
+    /// Represents a missing configuration, or one the caller may not see.
+    #[error("Configuration {0} not found")]
+    NotFound(i32),
+
+    /// Represents a share slug that is unknown or was revoked.
+    #[error("Shared configuration not found")]
+    ShareNotFound,
+
+    /// Represents a revision number the configuration does not have.
+    #[error("Revision {1} of configuration {0} not found")]
+    RevisionNotFound(i32, i32),
+
+    /// Represents a missing or discontinued part.
+    #[error("Part {0} not found")]
+    PartNotFound(i32),
+
+    /// Represents input that fails validation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// Represents a Diesel ORM error while reading or writing configurations.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for ConfigurationError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            ConfigurationError::NotFound(_)
+            | ConfigurationError::ShareNotFound
+            | ConfigurationError::RevisionNotFound(..)
+            | ConfigurationError::PartNotFound(_) => "NOT_FOUND",
+            ConfigurationError::Validation(_) => "BAD_REQUEST",
+            ConfigurationError::DatabaseError(_) | ConfigurationError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process configuration",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
+
+/// Errors raised while managing wishlists.
+#[derive(Debug, Error)]
+pub enum WishlistError {
+    /// Represents a missing wishlist, or one of another user.
+    #[error("Wishlist {0} not found")]
+    NotFound(i32),
+
+    /// Represents a missing or discontinued part.
+    #[error("Part {0} not found")]
+    PartNotFound(i32),
+
+    /// Represents a wishlist without parts that can be put in a configuration.
+    #[error("Wishlist {0} has no available parts")]
+    EmptyWishlist(i32),
+
+    /// Represents a name that fails validation.
+    #[error("{0}")]
+    Validation(String),
+
+    /// Represents a failure to create the configuration for a wishlist.
+    #[error(transparent)]
+    ConfigurationError(#[from] ConfigurationError),
+
+    /// Represents a Diesel ORM error while reading or writing wishlists.
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] DieselError),
+
+    /// Represents a failure to check out a database connection.
+    #[error("Connection pool error: {0}")]
+    PoolError(#[from] diesel::r2d2::PoolError),
+}
+
+impl IntoFieldError for WishlistError {
+    fn into_field_error(self) -> FieldError {
+        let code = match self {
+            WishlistError::NotFound(_) | WishlistError::PartNotFound(_) => "NOT_FOUND",
+            WishlistError::EmptyWishlist(_) | WishlistError::Validation(_) => "BAD_REQUEST",
+            WishlistError::ConfigurationError(e) => return e.into_field_error(),
+            WishlistError::DatabaseError(_) | WishlistError::PoolError(_) => {
+                return FieldError::new(
+                    "Failed to process wishlists",
+                    graphql_value!({ "code": "INTERNAL_SERVER_ERROR" }),
+                );
+            }
+        };
+        FieldError::new(self.to_string(), graphql_value!({ "code": code }))
+    }
+}
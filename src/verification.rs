@@ -0,0 +1,292 @@
+// src/verification.rs
+
+use crate::diesel_schema::users::{sessions, users, verification_tokens};
+use crate::mailer::{Email, Mailer};
+use crate::models::auth::{Session, User, VerificationToken};
+use crate::sessions::ClientInfo;
+use crate::settings::MailSettings;
+use crate::types::errors::{MailError, VerificationError};
+use chrono::{Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text};
+use diesel::PgConnection;
+use log::info;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+use uuid::Uuid;
+
+/// How long a session created by a sign-in link lasts.
+pub const SESSION_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+diesel::define_sql_function!(fn lower(value: Nullable<Text>) -> Nullable<Text>);
+
+/// What a token was issued for. A token can only be redeemed for the purpose
+/// it was issued for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenPurpose {
+    /// Confirms that a signed-in user controls their email address.
+    VerifyEmail,
+    /// Signs a user in without a password or OAuth provider.
+    SignIn,
+}
+
+impl TokenPurpose {
+    /// Value of the `type` query parameter in links for this purpose.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TokenPurpose::VerifyEmail => "verify-email",
+            TokenPurpose::SignIn => "sign-in",
+        }
+    }
+
+    /// Tokens are stored per purpose and address, so requesting one kind of
+    /// link leaves the other valid.
+    fn identifier(self, email: &str) -> String {
+        format!("{}:{}", self.as_str(), email)
+    }
+}
+
+/// Issues a verification token for the user's own email address.
+pub fn request_verification(
+    conn: &mut PgConnection,
+    user_id: i32,
+    ttl: Duration,
+) -> Result<(String, String), VerificationError> {
+    let user = users::table
+        .find(user_id)
+        .select(User::as_select())
+        .first(conn)?;
+    let email = user.email.ok_or(VerificationError::MissingEmail)?;
+    if user.email_verified.is_some() {
+        return Err(VerificationError::AlreadyVerified);
+    }
+    let token = issue_token(conn, TokenPurpose::VerifyEmail, &email, ttl)?;
+    info!("Issued email verification token for user {}", user_id);
+    Ok((email, token))
+}
+
+/// Issues a sign-in token for the user with `email`, if there is one.
+///
+/// Returns `None` for unknown addresses; callers should not reveal the
+/// difference, so the endpoint cannot be used to discover accounts.
+pub fn request_sign_in(
+    conn: &mut PgConnection,
+    email: &str,
+    ttl: Duration,
+) -> Result<Option<(String, String)>, VerificationError> {
+    let Some(user) = find_by_email(conn, email)? else {
+        info!("Sign-in link requested for an unknown address");
+        return Ok(None);
+    };
+    // Only users with an address can be found by one
+    let email = user.email.unwrap_or_default();
+    let token = issue_token(conn, TokenPurpose::SignIn, &email, ttl)?;
+    info!("Issued sign-in token for user {}", user.id);
+    Ok(Some((email, token)))
+}
+
+/// Redeems a verification token and marks the address as verified.
+pub fn verify_email(
+    conn: &mut PgConnection,
+    email: &str,
+    token: &str,
+) -> Result<User, VerificationError> {
+    let user = conn.transaction(|conn| redeem(conn, TokenPurpose::VerifyEmail, email, token))?;
+    info!("Verified the email address of user {}", user.id);
+    Ok(user)
+}
+
+/// Redeems a sign-in token and starts a session for `client`. Following the
+/// link proves control of the address, so it is marked as verified too.
+pub fn sign_in(
+    conn: &mut PgConnection,
+    email: &str,
+    token: &str,
+    client: &ClientInfo,
+) -> Result<(User, Session), VerificationError> {
+    let (user, session) = conn.transaction(|conn| {
+        let user = redeem(conn, TokenPurpose::SignIn, email, token)?;
+        let session = diesel::insert_into(sessions::table)
+            .values((
+                sessions::userId.eq(user.id),
+                sessions::expires.eq(Utc::now() + chrono_duration(SESSION_TTL)),
+                sessions::sessionToken.eq(new_token()),
+                sessions::user_agent.eq(&client.user_agent),
+                sessions::ip_address.eq(&client.ip_address),
+            ))
+            .returning(Session::as_returning())
+            .get_result(conn)?;
+        Ok::<_, VerificationError>((user, session))
+    })?;
+    info!("User {} signed in with an email link", user.id);
+    Ok((user, session))
+}
+
+/// Consumes the token and sets `emailVerified` if it was not set yet.
+fn redeem(
+    conn: &mut PgConnection,
+    purpose: TokenPurpose,
+    email: &str,
+    token: &str,
+) -> Result<User, VerificationError> {
+    let user = find_by_email(conn, email)?.ok_or(VerificationError::InvalidToken)?;
+    let stored_email = user.email.as_deref().unwrap_or_default();
+
+    // Deleting the row makes every token single-use, even under concurrent requests
+    let stored = diesel::delete(
+        verification_tokens::table
+            .filter(verification_tokens::identifier.eq(purpose.identifier(stored_email)))
+            .filter(verification_tokens::token.eq(hash_token(token))),
+    )
+    .returning(VerificationToken::as_returning())
+    .get_result(conn)
+    .optional()?;
+    if stored.is_none_or(|stored| stored.expires <= Utc::now()) {
+        return Err(VerificationError::InvalidToken);
+    }
+
+    diesel::update(users::table.find(user.id))
+        .filter(users::emailVerified.is_null())
+        .set(users::emailVerified.eq(Utc::now()))
+        .execute(conn)?;
+    Ok(users::table
+        .find(user.id)
+        .select(User::as_select())
+        .first(conn)?)
+}
+
+/// Stores the hash of a new token for `email`, replacing any earlier token
+/// for the same purpose, and returns the token itself.
+fn issue_token(
+    conn: &mut PgConnection,
+    purpose: TokenPurpose,
+    email: &str,
+    ttl: Duration,
+) -> Result<String, VerificationError> {
+    let token = new_token();
+    let identifier = purpose.identifier(email);
+    conn.transaction(|conn| {
+        diesel::delete(
+            verification_tokens::table.filter(verification_tokens::identifier.eq(&identifier)),
+        )
+        .execute(conn)?;
+        diesel::insert_into(verification_tokens::table)
+            .values((
+                verification_tokens::identifier.eq(&identifier),
+                verification_tokens::token.eq(hash_token(&token)),
+                verification_tokens::expires.eq(Utc::now() + chrono_duration(ttl)),
+            ))
+            .execute(conn)
+    })?;
+    Ok(token)
+}
+
+/// Finds a user by email address, ignoring case.
+fn find_by_email(conn: &mut PgConnection, email: &str) -> Result<Option<User>, VerificationError> {
+    Ok(users::table
+        .filter(lower(users::email).eq(email.trim().to_lowercase()))
+        .order(users::id)
+        .select(User::as_select())
+        .first(conn)
+        .optional()?)
+}
+
+/// Generates a random token from two v4 UUIDs, which come from the operating
+/// system's secure random number generator.
+pub(crate) fn new_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Tokens are stored hashed, so a leaked table cannot be used to sign in.
+pub(crate) fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+// Lifetimes are bounded by the settings validation, so the cast cannot wrap
+fn chrono_duration(duration: Duration) -> ChronoDuration {
+    ChronoDuration::seconds(duration.as_secs() as i64)
+}
+
+/// Composes verification and sign-in emails and hands them to the mailer.
+pub struct EmailLinks {
+    mailer: Arc<dyn Mailer>,
+    settings: MailSettings,
+}
+
+impl EmailLinks {
+    pub fn new(mailer: Arc<dyn Mailer>, settings: MailSettings) -> Self {
+        EmailLinks { mailer, settings }
+    }
+
+    /// How long a verification link stays valid.
+    pub fn verification_ttl(&self) -> Duration {
+        self.settings.verification_ttl()
+    }
+
+    /// How long a sign-in link stays valid.
+    pub fn magic_link_ttl(&self) -> Duration {
+        self.settings.magic_link_ttl()
+    }
+
+    /// Builds the frontend link that redeems `token`.
+    pub fn link(&self, purpose: TokenPurpose, email: &str, token: &str) -> String {
+        match Url::parse(&self.settings.link_base_url) {
+            Ok(mut url) => {
+                url.query_pairs_mut()
+                    .append_pair("type", purpose.as_str())
+                    .append_pair("email", email)
+                    .append_pair("token", token);
+                url.to_string()
+            }
+            // Validated at startup
+            Err(_) => self.settings.link_base_url.clone(),
+        }
+    }
+
+    /// Emails a verification link to `email`.
+    pub async fn send_verification(&self, email: &str, token: &str) -> Result<(), MailError> {
+        let link = self.link(TokenPurpose::VerifyEmail, email, token);
+        let body = format!(
+            "Confirm your email address by opening this link:\n\n{}\n\n\
+             The link expires in {}. If you did not ask for it, you can ignore this email.\n",
+            link,
+            describe(self.verification_ttl())
+        );
+        self.send(email, "Confirm your email address", body).await
+    }
+
+    /// Emails a sign-in link to `email`.
+    pub async fn send_magic_link(&self, email: &str, token: &str) -> Result<(), MailError> {
+        let link = self.link(TokenPurpose::SignIn, email, token);
+        let body = format!(
+            "Sign in by opening this link:\n\n{}\n\n\
+             The link expires in {} and can be used once. If you did not ask for it, \
+             you can ignore this email.\n",
+            link,
+            describe(self.magic_link_ttl())
+        );
+        self.send(email, "Your sign-in link", body).await
+    }
+
+    async fn send(&self, to: &str, subject: &str, body: String) -> Result<(), MailError> {
+        let email = Email {
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body,
+        };
+        self.mailer.send(&email).await
+    }
+}
+
+/// Describes a link lifetime in words, e.g. "24 hours" or "15 minutes".
+fn describe(duration: Duration) -> String {
+    let minutes = duration.as_secs().div_ceil(60);
+    match minutes {
+        1 => "1 minute".to_string(),
+        minutes if minutes % 60 != 0 => format!("{} minutes", minutes),
+        60 => "1 hour".to_string(),
+        minutes => format!("{} hours", minutes / 60),
+    }
+}
@@ -0,0 +1,254 @@
+// src/wishlists.rs
+
+use crate::configurations::{self, ConfigurationDetails};
+use crate::diesel_schema::parts::parts;
+use crate::diesel_schema::wishlists::{wishlist_items, wishlists};
+use crate::models::configurations::NewConfiguration;
+use crate::models::parts::part::Part;
+use crate::models::wishlists::{Wishlist, WishlistItem};
+use crate::types::errors::WishlistError;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::PgConnection;
+use log::info;
+
+/// Name of the wishlist parts are saved to when no list is given.
+pub const DEFAULT_NAME: &str = "Wishlist";
+
+/// Longest wishlist name accepted.
+const MAX_NAME_LENGTH: usize = 100;
+
+/// A wishlist together with its parts, most recently added first.
+pub struct WishlistDetails {
+    pub wishlist: Wishlist,
+    pub items: Vec<SavedPart>,
+}
+
+/// A part on a wishlist and when it was added.
+pub struct SavedPart {
+    pub part: Part,
+    pub added_at: DateTime<Utc>,
+}
+
+/// The wishlist a part is saved to.
+pub enum WishlistTarget<'a> {
+    /// An existing wishlist of the user.
+    Id(i32),
+    /// The user's wishlist with this name, which is created if needed.
+    Name(&'a str),
+}
+
+/// Fetches the user's wishlists, oldest first.
+pub fn list(conn: &mut PgConnection, user_id: i32) -> Result<Vec<WishlistDetails>, WishlistError> {
+    let lists = wishlists::table
+        .filter(wishlists::user_id.eq(user_id))
+        .order((wishlists::created_at, wishlists::id))
+        .select(Wishlist::as_select())
+        .load(conn)?;
+    with_items(conn, lists)
+}
+
+/// Fetches one of the user's wishlists.
+pub fn find(
+    conn: &mut PgConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<WishlistDetails, WishlistError> {
+    let wishlist = find_owned(conn, user_id, wishlist_id)?;
+    let mut details = with_items(conn, vec![wishlist])?;
+    Ok(details.remove(0))
+}
+
+/// Saves a part to a wishlist. Saving a part that is already on the list
+/// leaves it unchanged.
+pub fn add_part(
+    conn: &mut PgConnection,
+    user_id: i32,
+    target: WishlistTarget,
+    part_id: i32,
+) -> Result<WishlistDetails, WishlistError> {
+    let wishlist_id = conn.transaction(|conn| {
+        // Discontinued parts cannot be saved, like they cannot be ordered
+        let available = diesel::select(diesel::dsl::exists(
+            parts::table
+                .find(part_id)
+                .filter(parts::deleted_at.is_null()),
+        ))
+        .get_result::<bool>(conn)?;
+        if !available {
+            return Err(WishlistError::PartNotFound(part_id));
+        }
+
+        let wishlist_id = match target {
+            WishlistTarget::Id(id) => find_owned(conn, user_id, id)?.id,
+            WishlistTarget::Name(name) => find_or_create(conn, user_id, &validate_name(name)?)?,
+        };
+        let added = diesel::insert_into(wishlist_items::table)
+            .values((
+                wishlist_items::wishlist_id.eq(wishlist_id),
+                wishlist_items::part_id.eq(part_id),
+            ))
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        if added > 0 {
+            touch(conn, wishlist_id)?;
+            info!("Added part {} to wishlist {}", part_id, wishlist_id);
+        }
+        Ok(wishlist_id)
+    })?;
+    find(conn, user_id, wishlist_id)
+}
+
+/// Removes a part from one of the user's wishlists. Removing a part that is
+/// not on the list leaves it unchanged.
+pub fn remove_part(
+    conn: &mut PgConnection,
+    user_id: i32,
+    wishlist_id: i32,
+    part_id: i32,
+) -> Result<WishlistDetails, WishlistError> {
+    find_owned(conn, user_id, wishlist_id)?;
+    let removed =
+        diesel::delete(wishlist_items::table.find((wishlist_id, part_id))).execute(conn)?;
+    if removed > 0 {
+        touch(conn, wishlist_id)?;
+        info!("Removed part {} from wishlist {}", part_id, wishlist_id);
+    }
+    find(conn, user_id, wishlist_id)
+}
+
+/// Deletes one of the user's wishlists and its items.
+pub fn delete(
+    conn: &mut PgConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<(), WishlistError> {
+    let deleted = diesel::delete(
+        wishlists::table
+            .find(wishlist_id)
+            .filter(wishlists::user_id.eq(user_id)),
+    )
+    .execute(conn)?;
+    if deleted == 0 {
+        return Err(WishlistError::NotFound(wishlist_id));
+    }
+    info!("Deleted wishlist {} of user {}", wishlist_id, user_id);
+    Ok(())
+}
+
+/// Creates a configuration owned by the user with one of each part on the
+/// wishlist, named `name` or else after the wishlist. Discontinued parts are
+/// left out; the wishlist itself is kept.
+pub fn create_configuration(
+    conn: &mut PgConnection,
+    user_id: i32,
+    wishlist_id: i32,
+    name: Option<&str>,
+) -> Result<ConfigurationDetails, WishlistError> {
+    let details = find(conn, user_id, wishlist_id)?;
+    let name = match name {
+        Some(name) => validate_name(name)?,
+        None => details.wishlist.name.clone(),
+    };
+    let parts = details
+        .items
+        .iter()
+        .filter(|item| item.part.deleted_at.is_none())
+        .map(|item| (item.part.id, 1))
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return Err(WishlistError::EmptyWishlist(wishlist_id));
+    }
+
+    let configuration = configurations::create(
+        conn,
+        NewConfiguration {
+            user_id: Some(user_id),
+            name,
+            description: None,
+        },
+        &parts,
+    )?;
+    info!(
+        "Created configuration {} from wishlist {}",
+        configuration.configuration.id, wishlist_id
+    );
+    Ok(configuration)
+}
+
+fn find_owned(
+    conn: &mut PgConnection,
+    user_id: i32,
+    wishlist_id: i32,
+) -> Result<Wishlist, WishlistError> {
+    wishlists::table
+        .find(wishlist_id)
+        .filter(wishlists::user_id.eq(user_id))
+        .select(Wishlist::as_select())
+        .first(conn)
+        .optional()?
+        .ok_or(WishlistError::NotFound(wishlist_id))
+}
+
+/// Returns the ID of the user's wishlist named `name`, creating it if needed.
+fn find_or_create(conn: &mut PgConnection, user_id: i32, name: &str) -> Result<i32, WishlistError> {
+    // Concurrent requests may create the same list, so insert first and then read
+    diesel::insert_into(wishlists::table)
+        .values((wishlists::user_id.eq(user_id), wishlists::name.eq(name)))
+        .on_conflict((wishlists::user_id, wishlists::name))
+        .do_nothing()
+        .execute(conn)?;
+    Ok(wishlists::table
+        .filter(wishlists::user_id.eq(user_id))
+        .filter(wishlists::name.eq(name))
+        .select(wishlists::id)
+        .first(conn)?)
+}
+
+fn touch(conn: &mut PgConnection, wishlist_id: i32) -> Result<(), WishlistError> {
+    diesel::update(wishlists::table.find(wishlist_id))
+        .set(wishlists::updated_at.eq(Utc::now()))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Loads the parts of each wishlist, keeping the order of `lists`.
+fn with_items(
+    conn: &mut PgConnection,
+    lists: Vec<Wishlist>,
+) -> Result<Vec<WishlistDetails>, WishlistError> {
+    let items = WishlistItem::belonging_to(&lists)
+        .inner_join(parts::table)
+        .order((wishlist_items::added_at.desc(), parts::id))
+        .select((WishlistItem::as_select(), Part::as_select()))
+        .load::<(WishlistItem, Part)>(conn)?;
+    Ok(items
+        .grouped_by(&lists)
+        .into_iter()
+        .zip(lists)
+        .map(|(items, wishlist)| WishlistDetails {
+            wishlist,
+            items: items
+                .into_iter()
+                .map(|(item, part)| SavedPart {
+                    part,
+                    added_at: item.added_at,
+                })
+                .collect(),
+        })
+        .collect())
+}
+
+fn validate_name(name: &str) -> Result<String, WishlistError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(WishlistError::Validation("Name is required".to_string()));
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err(WishlistError::Validation(format!(
+            "Name must be at most {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    Ok(name.to_string())
+}
@@ -0,0 +1,186 @@
+// tests/api_keys_tests.rs
+
+mod utils;
+
+use actix_web::http::header;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use rust_backend::api_keys::{self, SECRET_PREFIX};
+use rust_backend::diesel_schema::api_keys::api_keys as api_keys_table;
+use rust_backend::models::api_keys::ApiKeyScope;
+use serde_json::{json, Value};
+use utils::{cleanup, error_code, execute, insert_user, test_pool};
+
+const ISSUE: &str = "mutation ($input: IssueApiKeyInput!) {
+    issueApiKey(input: $input) { secret apiKey { id ownerId prefix scopes createdBy } }
+}";
+
+#[actix_rt::test]
+async fn test_issue_rotate_and_revoke_api_keys() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    let partner = insert_user(conn, "customer");
+    let input = json!({
+        "input": {
+            "name": "  Reseller feed  ",
+            "ownerId": partner.id,
+            "scopes": ["ORDERS_READ", "CATALOG_READ", "ORDERS_READ"],
+        }
+    });
+
+    let response = execute(&pool, Some(&partner), ISSUE, input.clone()).await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    let response = execute(&pool, Some(&staff), ISSUE, input).await;
+    let issued = &response["data"]["issueApiKey"];
+    let secret = issued["secret"].as_str().unwrap().to_string();
+    let id = issued["apiKey"]["id"].as_i64().unwrap() as i32;
+    assert!(secret.starts_with(SECRET_PREFIX));
+    assert!(secret.starts_with(issued["apiKey"]["prefix"].as_str().unwrap()));
+    assert_eq!(issued["apiKey"]["ownerId"], partner.id);
+    assert_eq!(issued["apiKey"]["createdBy"], staff.id);
+    assert_eq!(
+        issued["apiKey"]["scopes"],
+        json!(["ORDERS_READ", "CATALOG_READ"])
+    );
+    // Only a hash of the secret is stored
+    let stored = api_keys_table::table
+        .find(id)
+        .select(api_keys_table::secret_hash)
+        .first::<String>(conn)
+        .unwrap();
+    assert_ne!(stored, secret);
+    let (owner, _) = api_keys::authenticate(conn, &secret).unwrap().unwrap();
+    assert_eq!(owner.id, partner.id);
+
+    for (input, code) in [
+        (
+            json!({ "name": " ", "scopes": ["ORDERS_READ"] }),
+            "BAD_REQUEST",
+        ),
+        (json!({ "name": "Feed", "scopes": [] }), "BAD_REQUEST"),
+        (
+            json!({
+                "name": "Feed",
+                "scopes": ["ORDERS_READ"],
+                "expiresAt": (Utc::now() - Duration::days(1)).to_rfc3339(),
+            }),
+            "BAD_REQUEST",
+        ),
+        (
+            json!({ "name": "Feed", "ownerId": -1, "scopes": ["ORDERS_READ"] }),
+            "NOT_FOUND",
+        ),
+    ] {
+        let response = execute(&pool, Some(&staff), ISSUE, json!({ "input": input })).await;
+        assert_eq!(error_code(&response), code);
+    }
+
+    let rotate = "mutation ($id: Int!) { rotateApiKey(id: $id) { secret apiKey { rotatedAt } } }";
+    let response = execute(&pool, Some(&staff), rotate, json!({ "id": id })).await;
+    let rotated = response["data"]["rotateApiKey"]["secret"].as_str().unwrap();
+    assert_ne!(rotated, secret);
+    assert!(response["data"]["rotateApiKey"]["apiKey"]["rotatedAt"].is_string());
+    assert!(api_keys::authenticate(conn, &secret).unwrap().is_none());
+    assert!(api_keys::authenticate(conn, rotated).unwrap().is_some());
+
+    let revoke = "mutation ($id: Int!) { revokeApiKey(id: $id) { revokedAt } }";
+    let response = execute(&pool, Some(&staff), revoke, json!({ "id": id })).await;
+    assert!(response["data"]["revokeApiKey"]["revokedAt"].is_string());
+    assert!(api_keys::authenticate(conn, rotated).unwrap().is_none());
+    let response = execute(&pool, Some(&staff), rotate, json!({ "id": id })).await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    let list = "query ($owner: Int, $all: Boolean) {
+        apiKeys(ownerId: $owner, includeRevoked: $all) { id }
+    }";
+    let variables = json!({ "owner": partner.id, "all": false });
+    let response = execute(&pool, Some(&staff), list, variables).await;
+    assert_eq!(response["data"]["apiKeys"], json!([]));
+    let variables = json!({ "owner": partner.id, "all": true });
+    let response = execute(&pool, Some(&staff), list, variables).await;
+    assert_eq!(response["data"]["apiKeys"], json!([{ "id": id }]));
+
+    cleanup(conn, &[staff.id, partner.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_api_key_requests_are_limited_to_scopes() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    let partner = insert_user(conn, "customer");
+    let (_, orders_key) = api_keys::issue(
+        conn,
+        staff.id,
+        partner.id,
+        "Orders",
+        &[ApiKeyScope::OrdersRead],
+        None,
+    )
+    .unwrap();
+    let (_, partner_catalog_key) = api_keys::issue(
+        conn,
+        staff.id,
+        partner.id,
+        "Catalog",
+        &[ApiKeyScope::CatalogRead],
+        None,
+    )
+    .unwrap();
+    let (staff_key, staff_catalog_key) = api_keys::issue(
+        conn,
+        staff.id,
+        staff.id,
+        "Audit",
+        &[ApiKeyScope::CatalogRead],
+        None,
+    )
+    .unwrap();
+    let (expired, expired_key) = api_keys::issue(
+        conn,
+        staff.id,
+        partner.id,
+        "Expired",
+        &[ApiKeyScope::OrdersRead],
+        Some(Utc::now() + Duration::minutes(1)),
+    )
+    .unwrap();
+    diesel::update(api_keys_table::table.find(expired.id))
+        .set(api_keys_table::expires_at.eq(Utc::now() - Duration::minutes(1)))
+        .execute(conn)
+        .unwrap();
+    let app = setup_test_app!();
+
+    let call = async |secret: &str, query: &str| -> Value {
+        let req = test::TestRequest::post()
+            .uri("/graphql")
+            .insert_header((header::AUTHORIZATION, format!("ApiKey {}", secret)))
+            .set_json(json!({ "query": query }))
+            .to_request();
+        test::call_and_read_body_json(&app, req).await
+    };
+
+    let response = call(&orders_key, "{ myOrders { id } }").await;
+    assert_eq!(response["data"]["myOrders"], json!([]));
+    let response = call(&orders_key, "{ auditLog { id } }").await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+    // Fields without a scope cannot be used with a key
+    let response = call(&orders_key, "mutation { revokeAllOtherSessions { id } }").await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    // Keys cannot exceed their owner's role
+    let response = call(&partner_catalog_key, "{ auditLog { id } }").await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+    let response = call(&staff_catalog_key, "{ auditLog { id } }").await;
+    assert!(response["data"]["auditLog"].is_array());
+    let last_used = api_keys::find(conn, staff_key.id).unwrap().last_used_at;
+    assert!(last_used.is_some());
+
+    let response = call(&expired_key, "{ myOrders { id } }").await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    cleanup(conn, &[staff.id, partner.id], &[], &[]);
+}
@@ -0,0 +1,475 @@
+// tests/catalog_audit_tests.rs
+
+mod utils;
+
+use bigdecimal::BigDecimal;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::catalog::{audit_entries, revert, with_actor};
+use rust_backend::diesel_schema::audit_log::audit_log;
+use rust_backend::diesel_schema::parts::{categories, cpu_specs, manufacturers, parts};
+use rust_backend::graphql_schema::context::Context;
+use rust_backend::models::auth::User;
+use rust_backend::reference_cache::ReferenceCache;
+use rust_backend::response_cache::{CacheScope, ResponseCache};
+use rust_backend::settings::ResponseCacheSettings;
+use rust_backend::types::errors::CatalogError;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use utils::{
+    cleanup, error_code, execute, execute_in, insert_configuration, insert_user, sync_ids,
+    test_pool,
+};
+use uuid::Uuid;
+
+/// Inserts a priced part with a CPU spec of `cores` cores as `actor` and returns its ID.
+fn insert_cpu(conn: &mut PgConnection, actor: &User, cores: i32) -> i32 {
+    sync_ids(conn, "parts");
+    with_actor(conn, actor.id, |conn| {
+        let part_id = diesel::insert_into(parts::table)
+            .values((
+                parts::name.eq(format!("Audit Test {}", Uuid::new_v4())),
+                parts::model.eq("AT-1"),
+                parts::price.eq(Some(BigDecimal::from(250))),
+            ))
+            .returning(parts::id)
+            .get_result(conn)?;
+        diesel::insert_into(cpu_specs::table)
+            .values((cpu_specs::part_id.eq(part_id), cpu_specs::cores.eq(cores)))
+            .execute(conn)?;
+        Ok::<_, diesel::result::Error>(part_id)
+    })
+    .expect("Failed to insert part")
+}
+
+fn cores(conn: &mut PgConnection, part_id: i32) -> Option<i32> {
+    cpu_specs::table
+        .find(part_id)
+        .select(cpu_specs::cores)
+        .first(conn)
+        .expect("Failed to read CPU spec")
+}
+
+#[test]
+fn test_spec_changes_are_audited_and_reverted() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    let part_id = insert_cpu(conn, &staff, 8);
+
+    with_actor(conn, staff.id, |conn| {
+        diesel::update(cpu_specs::table.find(part_id))
+            .set(cpu_specs::cores.eq(16))
+            .execute(conn)
+    })
+    .unwrap();
+    // Writes that change nothing are not recorded
+    diesel::update(cpu_specs::table.find(part_id))
+        .set(cpu_specs::cores.eq(16))
+        .execute(conn)
+        .unwrap();
+
+    // Part IDs can be reused by other tests, so only look at this actor's entries
+    let entries = audit_entries(
+        conn,
+        Some("cpu_specs"),
+        Some(part_id),
+        Some(staff.id),
+        None,
+        None,
+    )
+    .unwrap();
+    let actions = entries
+        .iter()
+        .map(|entry| entry.action.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(actions, ["UPDATE", "INSERT"]);
+    let update = &entries[0];
+    assert_eq!(update.actor_id, Some(staff.id));
+    assert_eq!(update.before.as_ref().unwrap()["cores"], 8);
+    assert_eq!(update.after.as_ref().unwrap()["cores"], 16);
+
+    revert(conn, staff.id, update.id).unwrap();
+    assert_eq!(cores(conn, part_id), Some(8));
+    let latest = &audit_entries(
+        conn,
+        Some("cpu_specs"),
+        Some(part_id),
+        Some(staff.id),
+        Some(1),
+        None,
+    )
+    .unwrap()[0];
+    assert_eq!(latest.action, "UPDATE");
+    assert_eq!(latest.after.as_ref().unwrap()["cores"], 8);
+
+    let insert = &entries[1];
+    assert!(matches!(
+        revert(conn, staff.id, insert.id),
+        Err(CatalogError::IrreversibleAuditEntry(id)) if id == insert.id
+    ));
+    assert!(matches!(
+        revert(conn, staff.id, -1),
+        Err(CatalogError::AuditEntryNotFound(-1))
+    ));
+
+    cleanup(conn, &[staff.id], &[], &[part_id]);
+}
+
+#[test]
+fn test_deleted_rows_are_reinserted_on_revert() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    sync_ids(conn, "manufacturers");
+    let manufacturer_id = diesel::insert_into(manufacturers::table)
+        .values((
+            manufacturers::name.eq(format!("Audit Test {}", Uuid::new_v4())),
+            manufacturers::website.eq(Some("https://example.com")),
+        ))
+        .returning(manufacturers::id)
+        .get_result::<i32>(conn)
+        .unwrap();
+
+    with_actor(conn, staff.id, |conn| {
+        diesel::delete(manufacturers::table.find(manufacturer_id)).execute(conn)
+    })
+    .unwrap();
+    let deletion = &audit_entries(
+        conn,
+        Some("manufacturers"),
+        Some(manufacturer_id),
+        Some(staff.id),
+        None,
+        None,
+    )
+    .unwrap()[0];
+    assert_eq!(deletion.action, "DELETE");
+    assert!(deletion.after.is_none());
+
+    revert(conn, staff.id, deletion.id).unwrap();
+    let website = manufacturers::table
+        .find(manufacturer_id)
+        .select(manufacturers::website)
+        .first::<Option<String>>(conn)
+        .unwrap();
+    assert_eq!(website.as_deref(), Some("https://example.com"));
+
+    diesel::delete(manufacturers::table.find(manufacturer_id))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(
+        audit_log::table
+            .filter(audit_log::table_name.eq("manufacturers"))
+            .filter(audit_log::row_key.eq(manufacturer_id)),
+    )
+    .execute(conn)
+    .unwrap();
+    cleanup(conn, &[staff.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_reverts_invalidate_caches() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    sync_ids(conn, "manufacturers");
+    let manufacturer_id = diesel::insert_into(manufacturers::table)
+        .values(manufacturers::name.eq(format!("Audit Test {}", Uuid::new_v4())))
+        .returning(manufacturers::id)
+        .get_result::<i32>(conn)
+        .unwrap();
+    let reference_cache = Arc::new(ReferenceCache::new(pool.clone(), Duration::from_secs(300)));
+    let response_cache = Arc::new(ResponseCache::new(
+        pool.clone(),
+        ResponseCacheSettings::default(),
+    ));
+    let context = Context::new(pool.clone(), Some(staff.clone()))
+        .with_reference_cache(Some(reference_cache.clone()))
+        .with_response_cache(Some(response_cache.clone()));
+
+    let response = execute_in(
+        &context,
+        "mutation ($id: Int!) { deleteManufacturer(id: $id) { deletedAt } }",
+        json!({ "id": manufacturer_id }),
+    )
+    .await;
+    assert!(response["data"]["deleteManufacturer"]["deletedAt"].is_string());
+    let deletion = audit_entries(
+        conn,
+        Some("manufacturers"),
+        Some(manufacturer_id),
+        Some(staff.id),
+        None,
+        None,
+    )
+    .unwrap()[0]
+        .id;
+
+    // Warm both caches with the deleted manufacturer
+    let cached = reference_cache.manufacturer(manufacturer_id).unwrap();
+    assert!(cached.unwrap().deleted_at.is_some());
+    response_cache.refresh_revision().unwrap();
+    let key = ResponseCache::key(
+        "{ manufacturers { id } }",
+        None,
+        &Value::Null,
+        CacheScope::Public,
+    );
+    response_cache.insert(key.clone(), json!({ "data": { "manufacturers": [] } }));
+    assert!(response_cache.get(&key).is_some());
+
+    let response = execute_in(
+        &context,
+        "mutation ($id: Int!) { revertAuditEntry(id: $id) }",
+        json!({ "id": deletion }),
+    )
+    .await;
+    assert_eq!(response["data"]["revertAuditEntry"], true);
+    let cached = reference_cache.manufacturer(manufacturer_id).unwrap();
+    assert!(cached.unwrap().deleted_at.is_none());
+    assert!(response_cache.get(&key).is_none());
+
+    diesel::delete(manufacturers::table.find(manufacturer_id))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(
+        audit_log::table
+            .filter(audit_log::table_name.eq("manufacturers"))
+            .filter(audit_log::row_key.eq(manufacturer_id)),
+    )
+    .execute(conn)
+    .unwrap();
+    cleanup(conn, &[staff.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_deleted_parts_leave_listings_but_stay_referenced() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    let customer = insert_user(conn, "customer");
+    let part_id = insert_cpu(conn, &staff, 8);
+    let configuration_id = insert_configuration(conn, None, &[(part_id, 1)]);
+
+    let delete = "mutation ($id: Int!) { deletePart(id: $id) { id deletedAt } }";
+    let response = execute(&pool, Some(&customer), delete, json!({ "id": part_id })).await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    let response = execute(&pool, Some(&staff), delete, json!({ "id": part_id })).await;
+    assert!(response["data"]["deletePart"]["deletedAt"].is_string());
+    // Deleting again keeps the original timestamp
+    let again = execute(&pool, Some(&staff), delete, json!({ "id": part_id })).await;
+    assert_eq!(
+        again["data"]["deletePart"]["deletedAt"],
+        response["data"]["deletePart"]["deletedAt"]
+    );
+
+    let listed = |response: &Value| {
+        response["data"]["parts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|part| part["id"] == part_id)
+    };
+    let response = execute(&pool, Some(&staff), "{ parts { id } }", json!({})).await;
+    assert!(!listed(&response));
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "query ($id: Int!) { part(partId: $id) { id deletedAt } }",
+        json!({ "id": part_id }),
+    )
+    .await;
+    assert!(response["data"]["part"]["deletedAt"].is_string());
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        "mutation ($id: Int!) { createOrder(configurationId: $id) { id } }",
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "mutation ($id: Int!) { restorePart(id: $id) { deletedAt } }",
+        json!({ "id": part_id }),
+    )
+    .await;
+    assert_eq!(response["data"]["restorePart"]["deletedAt"], Value::Null);
+    let response = execute(&pool, Some(&staff), "{ parts { id } }", json!({})).await;
+    assert!(listed(&response));
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "query ($id: Int!, $actor: Int!) {
+            auditLog(tableName: \"parts\", rowKey: $id, actorId: $actor) { actorId action }
+        }",
+        json!({ "id": part_id, "actor": staff.id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["auditLog"],
+        json!([
+            { "actorId": staff.id, "action": "UPDATE" },
+            { "actorId": staff.id, "action": "UPDATE" },
+            { "actorId": staff.id, "action": "INSERT" },
+        ])
+    );
+
+    let response = execute(&pool, Some(&customer), "{ auditLog { id } }", json!({})).await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    cleanup(
+        conn,
+        &[staff.id, customer.id],
+        &[configuration_id],
+        &[part_id],
+    );
+}
+
+#[actix_rt::test]
+async fn test_deleted_manufacturers_and_categories_stay_referenced() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let staff = insert_user(conn, "staff");
+    let customer = insert_user(conn, "customer");
+    sync_ids(conn, "manufacturers");
+    sync_ids(conn, "categories");
+    let manufacturer_id = diesel::insert_into(manufacturers::table)
+        .values(manufacturers::name.eq(format!("Audit Test {}", Uuid::new_v4())))
+        .returning(manufacturers::id)
+        .get_result::<i32>(conn)
+        .unwrap();
+    let category_id = diesel::insert_into(categories::table)
+        .values(categories::name.eq(format!("Audit Test {}", Uuid::new_v4())))
+        .returning(categories::id)
+        .get_result::<i32>(conn)
+        .unwrap();
+    let part_id = insert_cpu(conn, &staff, 8);
+    diesel::update(parts::table.find(part_id))
+        .set((
+            parts::manufacturer_id.eq(manufacturer_id),
+            parts::category_id.eq(category_id),
+        ))
+        .execute(conn)
+        .unwrap();
+
+    let delete = "mutation ($id: Int!) { deleteManufacturer(id: $id) { deletedAt } }";
+    let response = execute(
+        &pool,
+        Some(&customer),
+        delete,
+        json!({ "id": manufacturer_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+    let response = execute(
+        &pool,
+        Some(&staff),
+        delete,
+        json!({ "id": manufacturer_id }),
+    )
+    .await;
+    assert!(response["data"]["deleteManufacturer"]["deletedAt"].is_string());
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "mutation ($id: Int!) { deleteCategory(id: $id) { deletedAt } }",
+        json!({ "id": category_id }),
+    )
+    .await;
+    assert!(response["data"]["deleteCategory"]["deletedAt"].is_string());
+
+    let lookups = "query ($m: Int!, $c: Int!, $p: Int!) {
+        manufacturers { id }
+        categories { id }
+        manufacturer(manufacturerId: $m) { id }
+        category(categoryId: $c) { id }
+        part(partId: $p) { manufacturer { id deletedAt } category { id deletedAt } }
+    }";
+    let ids = json!({ "m": manufacturer_id, "c": category_id, "p": part_id });
+    let listed = |response: &Value, field: &str, id: i32| {
+        response["data"][field]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|row| row["id"] == id)
+    };
+    let response = execute(&pool, Some(&customer), lookups, ids.clone()).await;
+    assert!(!listed(&response, "manufacturers", manufacturer_id));
+    assert!(!listed(&response, "categories", category_id));
+    assert_eq!(response["data"]["manufacturer"], Value::Null);
+    assert_eq!(response["data"]["category"], Value::Null);
+    let part = &response["data"]["part"];
+    assert_eq!(part["manufacturer"]["id"], manufacturer_id);
+    assert!(part["manufacturer"]["deletedAt"].is_string());
+    assert_eq!(part["category"]["id"], category_id);
+    assert!(part["category"]["deletedAt"].is_string());
+
+    for mutation in ["restoreManufacturer", "restoreCategory"] {
+        let id = if mutation == "restoreManufacturer" {
+            manufacturer_id
+        } else {
+            category_id
+        };
+        let response = execute(
+            &pool,
+            Some(&staff),
+            &format!(
+                "mutation ($id: Int!) {{ {}(id: $id) {{ deletedAt }} }}",
+                mutation
+            ),
+            json!({ "id": id }),
+        )
+        .await;
+        assert_eq!(response["data"][mutation]["deletedAt"], Value::Null);
+    }
+    let response = execute(&pool, Some(&customer), lookups, ids).await;
+    assert!(listed(&response, "manufacturers", manufacturer_id));
+    assert!(listed(&response, "categories", category_id));
+    assert_eq!(response["data"]["manufacturer"]["id"], manufacturer_id);
+    assert_eq!(response["data"]["category"]["id"], category_id);
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "mutation { deleteCategory(id: -1) { id } }",
+        json!({}),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    for (table, id) in [
+        ("manufacturers", manufacturer_id),
+        ("categories", category_id),
+    ] {
+        let actions = audit_entries(conn, Some(table), Some(id), Some(staff.id), None, None)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.action)
+            .collect::<Vec<_>>();
+        assert_eq!(actions, ["UPDATE", "UPDATE"]);
+    }
+
+    cleanup(conn, &[staff.id, customer.id], &[], &[part_id]);
+    diesel::delete(manufacturers::table.find(manufacturer_id))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(categories::table.find(category_id))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(
+        audit_log::table
+            .filter(audit_log::table_name.eq_any(["manufacturers", "categories"]))
+            .filter(audit_log::row_key.eq_any([manufacturer_id, category_id])),
+    )
+    .execute(conn)
+    .unwrap();
+}
@@ -0,0 +1,305 @@
+// tests/configuration_comparison_tests.rs
+
+mod utils;
+
+use bigdecimal::BigDecimal;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::parts::{categories, cpu_specs, gpu_specs, memory_specs, parts};
+use rust_backend::models::auth::User;
+use serde_json::{json, Value};
+use std::str::FromStr;
+use utils::{
+    error_code, execute, insert_configuration, insert_part, insert_user, sync_ids, test_pool,
+};
+use uuid::Uuid;
+
+fn insert_category(conn: &mut PgConnection, name: &str) -> i32 {
+    sync_ids(conn, "categories");
+    diesel::insert_into(categories::table)
+        .values(categories::name.eq(format!("{} {}", name, Uuid::new_v4())))
+        .returning(categories::id)
+        .get_result(conn)
+        .expect("Failed to insert category")
+}
+
+/// Inserts a part in `category_id` priced at `price`.
+fn insert_categorized_part(conn: &mut PgConnection, category_id: Option<i32>, price: &str) -> i32 {
+    let part_id = insert_part(conn, price);
+    diesel::update(parts::table.find(part_id))
+        .set(parts::category_id.eq(category_id))
+        .execute(conn)
+        .expect("Failed to categorize part");
+    part_id
+}
+
+/// A CPU and a GPU category, CPUs with 8 cores at 65 W and 16 cores at 105 W,
+/// a GPU with 12 GB at 200 W and an uncategorized 32 GB memory kit.
+struct Catalog {
+    category_ids: Vec<i32>,
+    cpu: i32,
+    faster_cpu: i32,
+    gpu: i32,
+    ram: i32,
+}
+
+fn insert_catalog(conn: &mut PgConnection) -> Catalog {
+    let cpus = insert_category(conn, "Comparison CPU");
+    let gpus = insert_category(conn, "Comparison GPU");
+    let cpu = insert_categorized_part(conn, Some(cpus), "200.00");
+    let faster_cpu = insert_categorized_part(conn, Some(cpus), "350.00");
+    let gpu = insert_categorized_part(conn, Some(gpus), "500.00");
+    let ram = insert_categorized_part(conn, None, "100.00");
+    for (part_id, cores, tdp) in [(cpu, 8, 65), (faster_cpu, 16, 105)] {
+        diesel::insert_into(cpu_specs::table)
+            .values((
+                cpu_specs::part_id.eq(part_id),
+                cpu_specs::cores.eq(Some(cores)),
+                cpu_specs::tdp.eq(Some(tdp)),
+            ))
+            .execute(conn)
+            .expect("Failed to insert CPU specs");
+    }
+    diesel::insert_into(gpu_specs::table)
+        .values((
+            gpu_specs::part_id.eq(gpu),
+            gpu_specs::vram_size.eq(Some(BigDecimal::from(12))),
+            gpu_specs::tdp.eq(Some(200)),
+        ))
+        .execute(conn)
+        .expect("Failed to insert GPU specs");
+    diesel::insert_into(memory_specs::table)
+        .values((
+            memory_specs::part_id.eq(ram),
+            memory_specs::capacity.eq(Some(32)),
+        ))
+        .execute(conn)
+        .expect("Failed to insert memory specs");
+    Catalog {
+        category_ids: vec![cpus, gpus],
+        cpu,
+        faster_cpu,
+        gpu,
+        ram,
+    }
+}
+
+/// Deletes the users with their configurations, then the catalog.
+fn cleanup(conn: &mut PgConnection, user_ids: &[i32], catalog: &Catalog) {
+    let part_ids = [catalog.cpu, catalog.faster_cpu, catalog.gpu, catalog.ram];
+    utils::cleanup(conn, user_ids, &[], &part_ids);
+    diesel::delete(categories::table.filter(categories::id.eq_any(&catalog.category_ids)))
+        .execute(conn)
+        .unwrap();
+}
+
+/// Parses a decimal returned as a string, so scales don't matter.
+fn decimal(value: &Value) -> BigDecimal {
+    BigDecimal::from_str(
+        value
+            .as_str()
+            .unwrap_or_else(|| panic!("Expected a decimal, got {}", value)),
+    )
+    .unwrap()
+}
+
+const TOTALS: &str = "price cores vram ramCapacity storageCapacity powerDraw";
+
+fn compare_query() -> String {
+    format!(
+        "query ($a: Int!, $b: Int!) {{
+            compareConfigurations(a: $a, b: $b) {{
+                a {{ id totals {{ {totals} }} }}
+                b {{ id totals {{ {totals} }} }}
+                components {{
+                    category change priceDelta
+                    before {{ part {{ id }} quantity }}
+                    after {{ part {{ id }} quantity }}
+                }}
+                delta {{ {totals} }}
+            }}
+        }}",
+        totals = TOTALS
+    )
+}
+
+/// Checks `totals` against price, cores, VRAM, RAM, storage and power draw.
+fn assert_totals(totals: &Value, expected: (&str, i64, i64, i64, i64, i64)) {
+    let (price, cores, vram, ram, storage, power) = expected;
+    assert_eq!(
+        decimal(&totals["price"]),
+        BigDecimal::from_str(price).unwrap()
+    );
+    assert_eq!(totals["cores"], cores);
+    assert_eq!(decimal(&totals["vram"]), BigDecimal::from(vram));
+    assert_eq!(totals["ramCapacity"], ram);
+    assert_eq!(totals["storageCapacity"], storage);
+    assert_eq!(totals["powerDraw"], power);
+}
+
+#[actix_rt::test]
+async fn test_compare_configurations() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let owner = insert_user(conn, "customer");
+    let catalog = insert_catalog(conn);
+    let a = insert_configuration(conn, Some(owner.id), &[(catalog.cpu, 1), (catalog.ram, 1)]);
+    let b = insert_configuration(
+        conn,
+        Some(owner.id),
+        &[(catalog.faster_cpu, 1), (catalog.gpu, 1), (catalog.ram, 2)],
+    );
+
+    let response = execute(
+        &pool,
+        Some(&owner),
+        &compare_query(),
+        json!({ "a": a, "b": b }),
+    )
+    .await;
+    let comparison = &response["data"]["compareConfigurations"];
+    assert_eq!(comparison["a"]["id"], a, "Unexpected {}", response);
+    assert_eq!(comparison["b"]["id"], b);
+    assert_totals(&comparison["a"]["totals"], ("300", 8, 0, 32, 0, 65));
+    assert_totals(&comparison["b"]["totals"], ("1050", 16, 12, 64, 0, 305));
+    assert_totals(&comparison["delta"], ("750", 8, 12, 32, 0, 240));
+
+    // The CPU is swapped, the GPU added and the memory doubled, in category
+    // order with uncategorized parts last
+    let components = comparison["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3, "Unexpected {}", response);
+    assert!(components[0]["category"]
+        .as_str()
+        .unwrap()
+        .starts_with("Comparison CPU"));
+    assert_eq!(components[0]["change"], "CHANGED");
+    assert_eq!(
+        components[0]["before"],
+        json!({ "part": { "id": catalog.cpu }, "quantity": 1 })
+    );
+    assert_eq!(
+        components[0]["after"],
+        json!({ "part": { "id": catalog.faster_cpu }, "quantity": 1 })
+    );
+    assert_eq!(decimal(&components[0]["priceDelta"]), BigDecimal::from(150));
+
+    assert!(components[1]["category"]
+        .as_str()
+        .unwrap()
+        .starts_with("Comparison GPU"));
+    assert_eq!(components[1]["change"], "ADDED");
+    assert_eq!(components[1]["before"], Value::Null);
+    assert_eq!(
+        components[1]["after"],
+        json!({ "part": { "id": catalog.gpu }, "quantity": 1 })
+    );
+    assert_eq!(decimal(&components[1]["priceDelta"]), BigDecimal::from(500));
+
+    assert_eq!(components[2]["category"], Value::Null);
+    assert_eq!(components[2]["change"], "CHANGED");
+    assert_eq!(components[2]["before"]["quantity"], 1);
+    assert_eq!(components[2]["after"]["quantity"], 2);
+    assert_eq!(decimal(&components[2]["priceDelta"]), BigDecimal::from(100));
+
+    // The other way round, the GPU is removed and the deltas flip
+    let response = execute(
+        &pool,
+        Some(&owner),
+        &compare_query(),
+        json!({ "a": b, "b": a }),
+    )
+    .await;
+    let comparison = &response["data"]["compareConfigurations"];
+    assert_eq!(comparison["components"][1]["change"], "REMOVED");
+    assert_eq!(comparison["components"][1]["after"], Value::Null);
+    assert_totals(&comparison["delta"], ("-750", -8, -12, -32, 0, -240));
+
+    cleanup(conn, &[owner.id], &catalog);
+}
+
+#[actix_rt::test]
+async fn test_compare_configurations_saturates_large_quantities() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let owner = insert_user(conn, "customer");
+    let catalog = insert_catalog(conn);
+    let a = insert_configuration(conn, Some(owner.id), &[(catalog.cpu, 1)]);
+    let b = insert_configuration(
+        conn,
+        Some(owner.id),
+        &[(catalog.cpu, i32::MAX), (catalog.faster_cpu, i32::MAX)],
+    );
+
+    let response = execute(
+        &pool,
+        Some(&owner),
+        &compare_query(),
+        json!({ "a": a, "b": b }),
+    )
+    .await;
+    let comparison = &response["data"]["compareConfigurations"];
+    let max = i32::MAX as i64;
+    assert_eq!(
+        comparison["b"]["totals"]["cores"], max,
+        "Unexpected {}",
+        response
+    );
+    assert_eq!(comparison["b"]["totals"]["powerDraw"], max);
+    assert_eq!(comparison["delta"]["cores"], max - 8);
+    assert_eq!(
+        decimal(&comparison["b"]["totals"]["price"]),
+        BigDecimal::from(550) * BigDecimal::from(i32::MAX)
+    );
+
+    cleanup(conn, &[owner.id], &catalog);
+}
+
+#[actix_rt::test]
+async fn test_compare_configurations_requires_owner_or_staff() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let owner = insert_user(conn, "customer");
+    let other = insert_user(conn, "customer");
+    let staff = insert_user(conn, User::STAFF_ROLE);
+    let catalog = insert_catalog(conn);
+    let a = insert_configuration(conn, Some(owner.id), &[(catalog.cpu, 1)]);
+    let b = insert_configuration(conn, Some(owner.id), &[(catalog.faster_cpu, 1)]);
+    let others = insert_configuration(conn, Some(other.id), &[(catalog.gpu, 1)]);
+    let variables = json!({ "a": a, "b": b });
+
+    let response = execute(&pool, None, &compare_query(), variables.clone()).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+    let response = execute(&pool, Some(&other), &compare_query(), variables.clone()).await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    // Owning one of the two is not enough
+    let response = execute(
+        &pool,
+        Some(&owner),
+        &compare_query(),
+        json!({ "a": a, "b": others }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    let response = execute(
+        &pool,
+        Some(&owner),
+        &compare_query(),
+        json!({ "a": a, "b": -1 }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    // Staff can compare any customer's builds
+    let response = execute(
+        &pool,
+        Some(&staff),
+        &compare_query(),
+        json!({ "a": a, "b": others }),
+    )
+    .await;
+    let comparison = &response["data"]["compareConfigurations"];
+    assert_eq!(comparison["b"]["id"], others, "Unexpected {}", response);
+    assert_totals(&comparison["delta"], ("300", -8, 12, 0, 0, 135));
+
+    cleanup(conn, &[owner.id, other.id, staff.id], &catalog);
+}
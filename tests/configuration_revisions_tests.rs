@@ -0,0 +1,312 @@
+// tests/configuration_revisions_tests.rs
+
+mod utils;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::configurations::{configuration_revisions, configurations};
+use rust_backend::diesel_schema::parts::parts;
+use rust_backend::models::auth::User;
+use serde_json::{json, Value};
+use utils::{cleanup, error_code, execute, insert_part, insert_user, test_pool, DbPool};
+
+const UPDATE: &str = "mutation ($id: Int!, $input: ConfigurationInput!) {
+    updateConfiguration(id: $id, input: $input) { id name }
+}";
+
+const REVISIONS: &str = "query ($id: Int!) {
+    configuration(id: $id) {
+        revisions { revision name description restoredFrom parts { part { id } quantity } }
+    }
+}";
+
+const DIFF: &str = "query ($id: Int!, $from: Int!, $to: Int!) {
+    configuration(id: $id) {
+        revisionDiff(from: $from, to: $to) {
+            fromRevision toRevision
+            name { before after }
+            description { before after }
+            added { part { id } quantity }
+            removed { part { id } quantity }
+            changed { part { id } before after }
+        }
+    }
+}";
+
+const RESTORE: &str = "mutation ($configurationId: Int!, $revision: Int!) {
+    restoreRevision(configurationId: $configurationId, revision: $revision) {
+        name description parts { part { id } quantity }
+    }
+}";
+
+async fn revisions(pool: &DbPool, user: &User, configuration_id: i64) -> Vec<Value> {
+    let response = execute(
+        pool,
+        Some(user),
+        REVISIONS,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    response["data"]["configuration"]["revisions"]
+        .as_array()
+        .unwrap_or_else(|| panic!("Unexpected {}", response))
+        .clone()
+}
+
+#[actix_rt::test]
+async fn test_changes_are_recorded_as_revisions() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let (cpu, gpu) = (insert_part(conn, "79.99"), insert_part(conn, "79.99"));
+
+    // Creating a configuration records its first revision
+    execute(
+        &pool,
+        Some(&user),
+        "mutation ($partId: Int!) { addToWishlist(partId: $partId) { id } }",
+        json!({ "partId": cpu }),
+    )
+    .await;
+    let response = execute(&pool, Some(&user), "{ wishlists { id } }", json!({})).await;
+    let wishlist_id = response["data"]["wishlists"][0]["id"].clone();
+    let response = execute(
+        &pool,
+        Some(&user),
+        "mutation ($wishlistId: Int!) {
+            createConfigurationFromWishlist(wishlistId: $wishlistId) { id }
+        }",
+        json!({ "wishlistId": wishlist_id }),
+    )
+    .await;
+    let id = response["data"]["createConfigurationFromWishlist"]["id"]
+        .as_i64()
+        .unwrap_or_else(|| panic!("Unexpected {}", response));
+    let history = revisions(&pool, &user, id).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0]["revision"], 1);
+    assert_eq!(history[0]["name"], "Wishlist");
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "id": id, "input": {
+            "name": "Streaming build",
+            "parts": [{ "partId": gpu, "quantity": 2 }, { "partId": cpu, "quantity": 1 }],
+        } }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["updateConfiguration"]["name"], "Streaming build",
+        "Unexpected {}",
+        response
+    );
+    // Saving the same state again records nothing
+    execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "id": id, "input": { "name": "Streaming build" } }),
+    )
+    .await;
+    execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "id": id, "input": { "description": "Quiet" } }),
+    )
+    .await;
+    let history = revisions(&pool, &user, id).await;
+    let numbers = history
+        .iter()
+        .map(|revision| revision["revision"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(numbers, vec![3, 2, 1]);
+    assert_eq!(
+        history[1]["parts"],
+        json!([
+            { "part": { "id": cpu }, "quantity": 1 },
+            { "part": { "id": gpu }, "quantity": 2 },
+        ])
+    );
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        DIFF,
+        json!({ "id": id, "from": 1, "to": 3 }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["configuration"]["revisionDiff"],
+        json!({
+            "fromRevision": 1,
+            "toRevision": 3,
+            "name": { "before": "Wishlist", "after": "Streaming build" },
+            "description": { "before": null, "after": "Quiet" },
+            "added": [{ "part": { "id": gpu }, "quantity": 2 }],
+            "removed": [],
+            "changed": [],
+        })
+    );
+
+    execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "id": id, "input": { "parts": [{ "partId": gpu, "quantity": 1 }] } }),
+    )
+    .await;
+    let response = execute(
+        &pool,
+        Some(&user),
+        DIFF,
+        json!({ "id": id, "from": 3, "to": 4 }),
+    )
+    .await;
+    let diff = &response["data"]["configuration"]["revisionDiff"];
+    assert_eq!(diff["name"], Value::Null, "Unexpected {}", response);
+    assert_eq!(diff["added"], json!([]));
+    assert_eq!(
+        diff["removed"],
+        json!([{ "part": { "id": cpu }, "quantity": 1 }])
+    );
+    assert_eq!(
+        diff["changed"],
+        json!([{ "part": { "id": gpu }, "before": 2, "after": 1 }])
+    );
+
+    // Restoring is recorded as a new revision and keeps the later ones
+    let response = execute(
+        &pool,
+        Some(&user),
+        RESTORE,
+        json!({ "configurationId": id, "revision": 2 }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["restoreRevision"],
+        json!({
+            "name": "Streaming build",
+            "description": null,
+            "parts": [
+                { "part": { "id": cpu }, "quantity": 1 },
+                { "part": { "id": gpu }, "quantity": 2 },
+            ],
+        })
+    );
+    let history = revisions(&pool, &user, id).await;
+    assert_eq!(history.len(), 5);
+    assert_eq!(history[0]["revision"], 5);
+    assert_eq!(history[0]["restoredFrom"], 2);
+    let response = execute(
+        &pool,
+        Some(&user),
+        RESTORE,
+        json!({ "configurationId": id, "revision": 99 }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    // Invalid changes are refused without a revision
+    for input in [
+        json!({ "name": " " }),
+        json!({ "parts": [{ "partId": cpu, "quantity": 0 }] }),
+        json!({ "parts": [{ "partId": cpu, "quantity": 1 }, { "partId": cpu, "quantity": 2 }] }),
+    ] {
+        let response = execute(
+            &pool,
+            Some(&user),
+            UPDATE,
+            json!({ "id": id, "input": input }),
+        )
+        .await;
+        assert_eq!(error_code(&response), "BAD_REQUEST");
+    }
+    diesel::update(parts::table.find(cpu))
+        .set(parts::deleted_at.eq(Some(Utc::now())))
+        .execute(conn)
+        .unwrap();
+    let response = execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "id": id, "input": { "parts": [{ "partId": cpu, "quantity": 3 }] } }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    assert_eq!(revisions(&pool, &user, id).await.len(), 5);
+    // Nor can a revision that uses it be restored
+    let response = execute(
+        &pool,
+        Some(&user),
+        RESTORE,
+        json!({ "configurationId": id, "revision": 1 }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    assert_eq!(revisions(&pool, &user, id).await.len(), 5);
+
+    cleanup(conn, &[user.id], &[], &[cpu, gpu]);
+}
+
+#[actix_rt::test]
+async fn test_revisions_are_private_and_immutable() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let (owner, other) = (insert_user(conn, "customer"), insert_user(conn, "customer"));
+    let cpu = insert_part(conn, "79.99");
+    // Inserted directly, so the configuration has no history yet
+    let id = diesel::insert_into(configurations::table)
+        .values((
+            configurations::name.eq("Imported build"),
+            configurations::user_id.eq(Some(owner.id)),
+        ))
+        .returning(configurations::id)
+        .get_result::<i32>(conn)
+        .unwrap();
+
+    let response = execute(&pool, None, REVISIONS, json!({ "id": id })).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+    let response = execute(&pool, Some(&other), REVISIONS, json!({ "id": id })).await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    let response = execute(
+        &pool,
+        Some(&other),
+        UPDATE,
+        json!({ "id": id, "input": { "name": "Mine now" } }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    // The first change also records the state before it
+    execute(
+        &pool,
+        Some(&owner),
+        UPDATE,
+        json!({ "id": id, "input": { "parts": [{ "partId": cpu, "quantity": 1 }] } }),
+    )
+    .await;
+    let history = revisions(&pool, &owner, id as i64).await;
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[1]["name"], "Imported build");
+    assert_eq!(history[1]["parts"], json!([]));
+    let response = execute(
+        &pool,
+        Some(&other),
+        RESTORE,
+        json!({ "configurationId": id, "revision": 1 }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let changed = diesel::update(
+        configuration_revisions::table.filter(configuration_revisions::configuration_id.eq(id)),
+    )
+    .set(configuration_revisions::name.eq("Rewritten"))
+    .execute(conn);
+    assert!(changed.is_err(), "Revisions should not be changeable");
+
+    cleanup(conn, &[owner.id, other.id], &[], &[cpu]);
+}
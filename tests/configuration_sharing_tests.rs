@@ -0,0 +1,215 @@
+// tests/configuration_sharing_tests.rs
+
+mod utils;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::configurations::configurations;
+use serde_json::{json, Value};
+use utils::{
+    cleanup, error_code, execute, insert_configuration, insert_part, insert_user, test_pool,
+};
+
+/// Inserts a described build owned by `owner`.
+fn insert_build(conn: &mut PgConnection, owner: i32, lines: &[(i32, i32)]) -> i32 {
+    let configuration_id = insert_configuration(conn, Some(owner), lines);
+    diesel::update(configurations::table.find(configuration_id))
+        .set((
+            configurations::name.eq("Gaming build"),
+            configurations::description.eq(Some("Quiet and fast")),
+        ))
+        .execute(conn)
+        .expect("Failed to describe configuration");
+    configuration_id
+}
+
+const SHARE: &str = "mutation ($id: Int!) { shareConfiguration(id: $id) { id shareSlug } }";
+
+const REVOKE: &str = "mutation ($id: Int!) { revokeConfigurationShare(id: $id) { id shareSlug } }";
+
+const SHARED: &str = "query ($slug: String!) {
+    sharedConfiguration(slug: $slug) { name description parts { part { id } quantity } }
+}";
+
+const FORK: &str = "mutation ($slug: String!, $name: String) {
+    forkConfiguration(slug: $slug, name: $name) {
+        id userId name description shareSlug parts { part { id } quantity }
+    }
+}";
+
+#[actix_rt::test]
+async fn test_share_and_revoke_configuration_links() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let (owner, other) = (insert_user(conn, "customer"), insert_user(conn, "customer"));
+    let (cpu, ram) = (insert_part(conn, "149.99"), insert_part(conn, "149.99"));
+    let configuration_id = insert_build(conn, owner.id, &[(cpu, 1), (ram, 2)]);
+
+    // Only the owner can share a configuration
+    let response = execute(&pool, None, SHARE, json!({ "id": configuration_id })).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+    let response = execute(
+        &pool,
+        Some(&other),
+        SHARE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let response = execute(
+        &pool,
+        Some(&owner),
+        SHARE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let slug = response["data"]["shareConfiguration"]["shareSlug"]
+        .as_str()
+        .unwrap_or_else(|| panic!("Unexpected {}", response))
+        .to_string();
+    // Sharing again keeps the link handed out earlier
+    let response = execute(
+        &pool,
+        Some(&owner),
+        SHARE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    assert_eq!(response["data"]["shareConfiguration"]["shareSlug"], slug);
+
+    // Anyone with the slug can open the build without signing in
+    let response = execute(&pool, None, SHARED, json!({ "slug": slug })).await;
+    assert_eq!(
+        response["data"]["sharedConfiguration"],
+        json!({
+            "name": "Gaming build",
+            "description": "Quiet and fast",
+            "parts": [
+                { "part": { "id": cpu }, "quantity": 1 },
+                { "part": { "id": ram }, "quantity": 2 },
+            ],
+        })
+    );
+    // The owner is not exposed
+    let response = execute(
+        &pool,
+        None,
+        "query ($slug: String!) { sharedConfiguration(slug: $slug) { userId } }",
+        json!({ "slug": slug }),
+    )
+    .await;
+    assert!(response["errors"].is_array(), "Unexpected {}", response);
+
+    let response = execute(
+        &pool,
+        Some(&other),
+        REVOKE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    let response = execute(
+        &pool,
+        Some(&owner),
+        REVOKE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["revokeConfigurationShare"]["shareSlug"],
+        Value::Null
+    );
+    let response = execute(&pool, None, SHARED, json!({ "slug": slug })).await;
+    assert_eq!(response["data"]["sharedConfiguration"], Value::Null);
+
+    // Sharing after a revocation gives a new link
+    let response = execute(
+        &pool,
+        Some(&owner),
+        SHARE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let new_slug = response["data"]["shareConfiguration"]["shareSlug"]
+        .as_str()
+        .unwrap();
+    assert_ne!(new_slug, slug);
+
+    cleanup(conn, &[owner.id, other.id], &[], &[cpu, ram]);
+}
+
+#[actix_rt::test]
+async fn test_fork_shared_configuration() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let (owner, friend) = (insert_user(conn, "customer"), insert_user(conn, "customer"));
+    let (cpu, ram) = (insert_part(conn, "149.99"), insert_part(conn, "149.99"));
+    let configuration_id = insert_build(conn, owner.id, &[(cpu, 1), (ram, 2)]);
+    let response = execute(
+        &pool,
+        Some(&owner),
+        SHARE,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let slug = response["data"]["shareConfiguration"]["shareSlug"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = execute(&pool, None, FORK, json!({ "slug": slug })).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+    let response = execute(
+        &pool,
+        Some(&friend),
+        FORK,
+        json!({ "slug": "not-a-shared-configuration" }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    let response = execute(
+        &pool,
+        Some(&friend),
+        FORK,
+        json!({ "slug": slug, "name": "  " }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    // The copy belongs to the caller, keeps the parts and is not shared
+    let response = execute(&pool, Some(&friend), FORK, json!({ "slug": slug })).await;
+    let fork = &response["data"]["forkConfiguration"];
+    assert_ne!(fork["id"], configuration_id, "Unexpected {}", response);
+    assert_eq!(fork["userId"], friend.id);
+    assert_eq!(fork["name"], "Gaming build");
+    assert_eq!(fork["description"], "Quiet and fast");
+    assert_eq!(fork["shareSlug"], Value::Null);
+    assert_eq!(
+        fork["parts"],
+        json!([
+            { "part": { "id": cpu }, "quantity": 1 },
+            { "part": { "id": ram }, "quantity": 2 },
+        ])
+    );
+
+    let response = execute(
+        &pool,
+        Some(&friend),
+        FORK,
+        json!({ "slug": slug, "name": "My version" }),
+    )
+    .await;
+    assert_eq!(response["data"]["forkConfiguration"]["name"], "My version");
+
+    // The original stays with its owner and stays shared
+    let (owner_id, share_slug) = configurations::table
+        .find(configuration_id)
+        .select((configurations::user_id, configurations::share_slug))
+        .first::<(Option<i32>, Option<String>)>(conn)
+        .unwrap();
+    assert_eq!(owner_id, Some(owner.id));
+    assert_eq!(share_slug, Some(slug));
+
+    cleanup(conn, &[owner.id, friend.id], &[], &[cpu, ram]);
+}
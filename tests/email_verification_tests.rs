@@ -0,0 +1,270 @@
+// tests/email_verification_tests.rs
+
+mod utils;
+
+use chrono::{Duration, Utc};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use futures::future::{self, BoxFuture};
+use rust_backend::diesel_schema::users::{sessions, users, verification_tokens};
+use rust_backend::graphql_schema::context::Context;
+use rust_backend::mailer::{parse_mailbox, Email, Mailer, OutboxMailer};
+use rust_backend::models::auth::User;
+use rust_backend::settings::MailSettings;
+use rust_backend::types::errors::MailError;
+use rust_backend::verification::EmailLinks;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use url::Url;
+use utils::{error_code, execute_in, insert_user, test_pool, DbPool};
+use uuid::Uuid;
+
+/// Keeps sent emails in memory so tests can follow their links.
+#[derive(Default)]
+struct RecordingMailer {
+    sent: Mutex<Vec<Email>>,
+}
+
+impl RecordingMailer {
+    fn sent(&self) -> Vec<Email> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+impl Mailer for RecordingMailer {
+    fn send<'a>(&'a self, email: &'a Email) -> BoxFuture<'a, Result<(), MailError>> {
+        self.sent.lock().unwrap().push(email.clone());
+        Box::pin(future::ready(Ok(())))
+    }
+}
+
+/// Deletes the user with their verification tokens.
+fn cleanup(conn: &mut PgConnection, user: &User) {
+    let email = user.email.clone().unwrap();
+    diesel::delete(
+        verification_tokens::table
+            .filter(verification_tokens::identifier.like(format!("%:{}", email))),
+    )
+    .execute(conn)
+    .unwrap();
+    utils::cleanup(conn, &[user.id], &[], &[]);
+}
+
+/// Executes a GraphQL operation as `user`, sending email through `mailer`.
+async fn execute(
+    pool: &DbPool,
+    mailer: &Arc<RecordingMailer>,
+    user: Option<&User>,
+    query: &str,
+    variables: Value,
+) -> Value {
+    let email_links = EmailLinks::new(mailer.clone(), MailSettings::default());
+    let context =
+        Context::new(pool.clone(), user.cloned()).with_email_links(Some(Arc::new(email_links)));
+    execute_in(&context, query, variables).await
+}
+
+/// Returns the `type`, `email` and `token` parameters of the link in an email.
+fn link_params(email: &Email) -> (String, String, String) {
+    let link = email
+        .body
+        .lines()
+        .find(|line| line.starts_with("http"))
+        .expect("Email should contain a link");
+    let url = Url::parse(link).unwrap();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .unwrap()
+    };
+    (param("type"), param("email"), param("token"))
+}
+
+const VERIFY: &str = "mutation ($email: String!, $token: String!) {
+    verifyEmail(email: $email, token: $token) { id emailVerified }
+}";
+
+const SIGN_IN: &str = "mutation ($email: String!, $token: String!) {
+    signInWithMagicLink(email: $email, token: $token) {
+        sessionToken expires user { id emailVerified }
+    }
+}";
+
+#[actix_rt::test]
+async fn test_email_verification() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let mailer = Arc::new(RecordingMailer::default());
+    let user = insert_user(conn, "customer");
+    let request = "mutation { requestEmailVerification }";
+
+    let response = execute(&pool, &mailer, None, request, json!({})).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    let response = execute(&pool, &mailer, Some(&user), request, json!({})).await;
+    assert_eq!(response["data"]["requestEmailVerification"], true);
+    let sent = mailer.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(Some(&sent[0].to), user.email.as_ref());
+    let (kind, email, token) = link_params(&sent[0]);
+    assert_eq!(kind, "verify-email");
+
+    // Only a hash of the token is stored
+    let stored = verification_tokens::table
+        .filter(verification_tokens::identifier.eq(format!("verify-email:{}", email)))
+        .select(verification_tokens::token)
+        .load::<String>(conn)
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_ne!(stored[0], token);
+
+    let wrong = json!({ "email": email, "token": "0".repeat(64) });
+    let response = execute(&pool, &mailer, None, VERIFY, wrong).await;
+    assert_eq!(error_code(&response), "INVALID_TOKEN");
+    // Sign-in links cannot redeem verification tokens
+    let variables = json!({ "email": email, "token": token });
+    let response = execute(&pool, &mailer, None, SIGN_IN, variables.clone()).await;
+    assert_eq!(error_code(&response), "INVALID_TOKEN");
+
+    let response = execute(&pool, &mailer, None, VERIFY, variables.clone()).await;
+    assert_eq!(response["data"]["verifyEmail"]["id"], user.id);
+    assert!(response["data"]["verifyEmail"]["emailVerified"].is_string());
+
+    // Tokens are single-use
+    let response = execute(&pool, &mailer, None, VERIFY, variables).await;
+    assert_eq!(error_code(&response), "INVALID_TOKEN");
+
+    let response = execute(&pool, &mailer, Some(&user), request, json!({})).await;
+    assert_eq!(error_code(&response), "ALREADY_VERIFIED");
+    assert_eq!(mailer.sent().len(), 1);
+
+    cleanup(conn, &user);
+}
+
+#[actix_rt::test]
+async fn test_magic_link_sign_in() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let mailer = Arc::new(RecordingMailer::default());
+    let user = insert_user(conn, "customer");
+    let request = "mutation ($email: String!) { requestMagicLink(email: $email) }";
+
+    // Unknown addresses get the same answer, but no email
+    let unknown = json!({ "email": format!("{}@example.com", Uuid::new_v4()) });
+    let response = execute(&pool, &mailer, None, request, unknown).await;
+    assert_eq!(response["data"]["requestMagicLink"], true);
+    assert!(mailer.sent().is_empty());
+
+    let response = execute(&pool, &mailer, None, request, json!({ "email": "nope" })).await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    // Addresses match regardless of case, and a new link replaces the last one
+    let variables = json!({ "email": user.email.as_ref().unwrap().to_uppercase() });
+    execute(&pool, &mailer, None, request, variables.clone()).await;
+    let response = execute(&pool, &mailer, None, request, variables).await;
+    assert_eq!(response["data"]["requestMagicLink"], true);
+    let sent = mailer.sent();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(Some(&sent[1].to), user.email.as_ref());
+    let (_, email, replaced) = link_params(&sent[0]);
+    let (kind, _, token) = link_params(&sent[1]);
+    assert_eq!(kind, "sign-in");
+
+    let response = execute(
+        &pool,
+        &mailer,
+        None,
+        SIGN_IN,
+        json!({ "email": email, "token": replaced }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "INVALID_TOKEN");
+
+    let variables = json!({ "email": email, "token": token });
+    let response = execute(&pool, &mailer, None, SIGN_IN, variables.clone()).await;
+    let payload = &response["data"]["signInWithMagicLink"];
+    assert_eq!(payload["user"]["id"], user.id);
+    assert!(payload["user"]["emailVerified"].is_string());
+    let session_user = sessions::table
+        .filter(sessions::sessionToken.eq(payload["sessionToken"].as_str().unwrap()))
+        .select(sessions::userId)
+        .first::<i32>(conn)
+        .unwrap();
+    assert_eq!(session_user, user.id);
+
+    let response = execute(&pool, &mailer, None, SIGN_IN, variables).await;
+    assert_eq!(error_code(&response), "INVALID_TOKEN");
+
+    cleanup(conn, &user);
+}
+
+#[actix_rt::test]
+async fn test_expired_links_are_rejected() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let mailer = Arc::new(RecordingMailer::default());
+    let user = insert_user(conn, "customer");
+
+    execute(
+        &pool,
+        &mailer,
+        Some(&user),
+        "mutation { requestEmailVerification }",
+        json!({}),
+    )
+    .await;
+    let (_, email, token) = link_params(&mailer.sent()[0]);
+    diesel::update(verification_tokens::table)
+        .filter(verification_tokens::identifier.eq(format!("verify-email:{}", email)))
+        .set(verification_tokens::expires.eq(Utc::now() - Duration::minutes(1)))
+        .execute(conn)
+        .unwrap();
+
+    let variables = json!({ "email": email, "token": token });
+    let response = execute(&pool, &mailer, None, VERIFY, variables).await;
+    assert_eq!(error_code(&response), "INVALID_TOKEN");
+    let verified = users::table
+        .find(user.id)
+        .select(users::emailVerified)
+        .first::<Option<chrono::DateTime<Utc>>>(conn)
+        .unwrap();
+    assert!(verified.is_none());
+
+    cleanup(conn, &user);
+}
+
+#[actix_rt::test]
+async fn test_outbox_mailer_writes_eml_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let outbox = dir.path().join("outbox");
+    let from = parse_mailbox("PC Builder <no-reply@example.com>").unwrap();
+    let mailer = OutboxMailer::new(&outbox, from);
+
+    let email = Email {
+        to: "dana@example.com".to_string(),
+        subject: "Your sign-in link".to_string(),
+        body: "Sign in by opening this link.\n".to_string(),
+    };
+    mailer.send(&email).await.unwrap();
+
+    let files = std::fs::read_dir(&outbox)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].extension().unwrap(), "eml");
+    let message = std::fs::read_to_string(&files[0]).unwrap();
+    assert!(message.contains("To: dana@example.com"));
+    assert!(message.contains("Subject: Your sign-in link"));
+    assert!(message.contains("Sign in by opening this link."));
+
+    let invalid = Email {
+        to: "not an address".to_string(),
+        ..email
+    };
+    assert!(matches!(
+        mailer.send(&invalid).await,
+        Err(MailError::InvalidAddress(_))
+    ));
+}
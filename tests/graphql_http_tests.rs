@@ -0,0 +1,98 @@
+// tests/graphql_http_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{call_service, read_body_json, TestRequest};
+use rust_backend::settings::GraphQLSettings;
+use serde_json::{json, Value};
+
+#[actix_rt::test]
+async fn test_get_query() {
+    let app = setup_test_app!();
+
+    let request = TestRequest::get()
+        .uri("/graphql?query=%7B%20apiVersion%20%7D&variables=%7B%7D")
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = read_body_json(response).await;
+    assert_eq!(body, json!({ "data": { "apiVersion": "1.0" } }));
+}
+
+#[actix_rt::test]
+async fn test_get_rejects_mutations_and_invalid_variables() {
+    let app = setup_test_app!();
+
+    let request = TestRequest::get()
+        .uri("/graphql?query=mutation%20%7B%20apiVersion%20%7D")
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.headers().get(header::ALLOW).unwrap(), "POST");
+    let body: Value = read_body_json(response).await;
+    assert_eq!(
+        body["errors"][0]["extensions"]["code"],
+        "METHOD_NOT_ALLOWED"
+    );
+
+    let request = TestRequest::get()
+        .uri("/graphql?query=%7B%20apiVersion%20%7D&variables=not-json")
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = read_body_json(response).await;
+    assert_eq!(
+        body["errors"][0]["message"],
+        "Query parameter 'variables' must be a JSON object"
+    );
+}
+
+#[actix_rt::test]
+async fn test_batched_post() {
+    let app = setup_test_app!();
+
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!([
+            { "query": "{ apiVersion }" },
+            { "query": "query Version { apiVersion }", "operationName": "Version" },
+            { "query": "{ unknownField }" }
+        ]))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = read_body_json(response).await;
+    let responses = body.as_array().expect("batch response should be an array");
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0], json!({ "data": { "apiVersion": "1.0" } }));
+    assert_eq!(responses[1], json!({ "data": { "apiVersion": "1.0" } }));
+    assert!(responses[2]["errors"].is_array());
+}
+
+#[actix_rt::test]
+async fn test_batch_size_limit() {
+    let app = setup_test_app!(GraphQLSettings {
+        max_batch_size: 1,
+        ..GraphQLSettings::default()
+    });
+
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!([
+            { "query": "{ apiVersion }" },
+            { "query": "{ apiVersion }" }
+        ]))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let body: Value = read_body_json(response).await;
+    assert_eq!(
+        body["errors"][0]["message"],
+        "Batched request exceeds the maximum of 1 operations"
+    );
+}
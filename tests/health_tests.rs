@@ -0,0 +1,69 @@
+// tests/health_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::{http::StatusCode, test};
+use diesel::pg::PgConnection;
+use diesel::Connection;
+use diesel_migrations::MigrationHarness;
+use dotenv::dotenv;
+use rust_backend::migration::MIGRATIONS;
+use serde_json::Value;
+use std::env;
+
+/// Applies pending migrations so the readiness probe can succeed.
+fn run_migrations() {
+    dotenv().ok();
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    let mut conn =
+        PgConnection::establish(&database_url).expect("Failed to connect to test database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+}
+
+#[actix_rt::test]
+async fn test_healthz() {
+    let app = setup_test_app!();
+
+    let request = test::TestRequest::get().uri("/healthz").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = test::read_body(response).await;
+    assert_eq!(body, r#"{"status":"ok"}"#);
+}
+
+#[actix_rt::test]
+async fn test_readyz() {
+    run_migrations();
+    let app = setup_test_app!();
+
+    let request = test::TestRequest::get().uri("/readyz").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["status"], "ready");
+    assert_eq!(body["checks"]["database"], "ok");
+    assert_eq!(body["checks"]["migrations"], "ok");
+}
+
+#[actix_rt::test]
+async fn test_version() {
+    run_migrations();
+    let app = setup_test_app!();
+
+    let request = test::TestRequest::get().uri("/version").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    assert!(body["git_commit"].is_string());
+    assert!(
+        body["migration_version"].is_string(),
+        "Applied migration version should be reported: {}",
+        body
+    );
+}
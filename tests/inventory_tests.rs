@@ -0,0 +1,173 @@
+// tests/inventory_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::test::{call_service, read_body_json, TestRequest};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::parts::inventory;
+use rust_backend::graphql_schema::context::Context;
+use rust_backend::graphql_schema::queries::parts_queries::get_all_parts;
+use rust_backend::inventory::{fulfil, release, reserve, StockReservation};
+use rust_backend::types::errors::InventoryError;
+use serde_json::{json, Value};
+use utils::{cleanup, insert_inventory, insert_part, test_pool};
+
+/// Inserts a part, with an inventory row when `on_hand` is given, and returns its ID.
+fn insert_stocked_part(conn: &mut PgConnection, on_hand: Option<i32>) -> i32 {
+    let part_id = insert_part(conn, "100");
+    if let Some(on_hand) = on_hand {
+        insert_inventory(conn, part_id, on_hand);
+    }
+    part_id
+}
+
+fn stock(conn: &mut PgConnection, part_id: i32) -> (i32, i32) {
+    inventory::table
+        .find(part_id)
+        .select((inventory::quantity_on_hand, inventory::quantity_reserved))
+        .first(conn)
+        .expect("Failed to read inventory")
+}
+
+#[test]
+fn test_reserve_release_and_fulfil_stock() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let cpu = insert_stocked_part(conn, Some(5));
+    let memory = insert_stocked_part(conn, Some(2));
+    let reservations = [
+        StockReservation {
+            part_id: memory,
+            quantity: 2,
+        },
+        StockReservation {
+            part_id: cpu,
+            quantity: 1,
+        },
+    ];
+
+    reserve(conn, &reservations).unwrap();
+    assert_eq!(stock(conn, cpu), (5, 1));
+    assert_eq!(stock(conn, memory), (2, 2));
+
+    release(conn, &reservations).unwrap();
+    assert_eq!(stock(conn, memory), (2, 0));
+
+    reserve(conn, &reservations).unwrap();
+    fulfil(conn, &reservations).unwrap();
+    assert_eq!(stock(conn, cpu), (4, 0));
+    assert_eq!(stock(conn, memory), (0, 0));
+
+    cleanup(conn, &[], &[], &[cpu, memory]);
+}
+
+#[test]
+fn test_insufficient_stock_reserves_nothing() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let cpu = insert_stocked_part(conn, Some(5));
+    let untracked = insert_stocked_part(conn, None);
+    let reservations = [
+        StockReservation {
+            part_id: cpu,
+            quantity: 1,
+        },
+        StockReservation {
+            part_id: untracked,
+            quantity: 1,
+        },
+    ];
+
+    match reserve(conn, &reservations) {
+        Err(InventoryError::InsufficientStock {
+            part_id,
+            requested,
+            available,
+        }) => {
+            assert_eq!(part_id, untracked);
+            assert_eq!(requested, 1);
+            assert_eq!(available, 0);
+        }
+        other => panic!("Expected insufficient stock, got {:?}", other),
+    }
+    assert_eq!(stock(conn, cpu), (5, 0));
+
+    cleanup(conn, &[], &[], &[cpu, untracked]);
+}
+
+#[test]
+fn test_parts_in_stock_filter() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let stocked = insert_stocked_part(conn, Some(3));
+    let sold_out = insert_stocked_part(conn, Some(1));
+    let untracked = insert_stocked_part(conn, None);
+    reserve(
+        conn,
+        &[StockReservation {
+            part_id: sold_out,
+            quantity: 1,
+        }],
+    )
+    .unwrap();
+
+    let context = Context::new(pool.clone(), None);
+    let ids = |in_stock| {
+        get_all_parts(&context, None, None, in_stock)
+            .unwrap()
+            .into_iter()
+            .map(|part| part.id)
+            .collect::<Vec<_>>()
+    };
+
+    let in_stock = ids(Some(true));
+    assert!(in_stock.contains(&stocked));
+    assert!(!in_stock.contains(&sold_out));
+    assert!(!in_stock.contains(&untracked));
+
+    let out_of_stock = ids(Some(false));
+    assert!(!out_of_stock.contains(&stocked));
+    assert!(out_of_stock.contains(&sold_out));
+    assert!(out_of_stock.contains(&untracked));
+
+    cleanup(conn, &[], &[], &[stocked, sold_out, untracked]);
+}
+
+#[actix_rt::test]
+async fn test_part_stock_fields() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let stocked = insert_stocked_part(conn, Some(4));
+    let untracked = insert_stocked_part(conn, None);
+    let low = insert_stocked_part(conn, Some(2));
+    diesel::update(inventory::table.find(low))
+        .set(inventory::reorder_threshold.eq(2))
+        .execute(conn)
+        .unwrap();
+    let app = setup_test_app!();
+
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({
+            "query": "query ($a: Int!, $b: Int!, $c: Int!) {
+                a: part(partId: $a) { inStock availableQuantity needsReorder }
+                b: part(partId: $b) { inStock availableQuantity needsReorder }
+                c: part(partId: $c) { inStock availableQuantity needsReorder }
+            }",
+            "variables": { "a": stocked, "b": untracked, "c": low }
+        }))
+        .to_request();
+    let body: Value = read_body_json(call_service(&app, request).await).await;
+    assert_eq!(
+        body,
+        json!({ "data": {
+            "a": { "inStock": true, "availableQuantity": 4, "needsReorder": false },
+            "b": { "inStock": false, "availableQuantity": 0, "needsReorder": false },
+            "c": { "inStock": true, "availableQuantity": 2, "needsReorder": true }
+        }})
+    );
+
+    cleanup(conn, &[], &[], &[stocked, untracked, low]);
+}
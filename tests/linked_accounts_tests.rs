@@ -0,0 +1,139 @@
+// tests/linked_accounts_tests.rs
+
+mod utils;
+
+use chrono::Utc;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::users::{accounts, users};
+use rust_backend::models::auth::User;
+use serde_json::{json, Value};
+use utils::{cleanup, error_code, execute, test_pool};
+use uuid::Uuid;
+
+/// Inserts a user with an unverified email and an OAuth account per provider.
+fn insert_user(conn: &mut PgConnection, providers: &[&str]) -> User {
+    let user = utils::insert_user(conn, "customer");
+    for provider in providers {
+        diesel::insert_into(accounts::table)
+            .values((
+                accounts::userId.eq(user.id),
+                accounts::type_.eq("oauth"),
+                accounts::provider.eq(provider),
+                accounts::providerAccountId.eq(Uuid::new_v4().to_string()),
+                accounts::access_token.eq(Some("secret-access-token")),
+                accounts::refresh_token.eq(Some("secret-refresh-token")),
+                accounts::id_token.eq(Some("secret-id-token")),
+            ))
+            .execute(conn)
+            .expect("Failed to insert account");
+    }
+    user
+}
+
+fn providers(accounts: &Value) -> Vec<&str> {
+    accounts
+        .as_array()
+        .unwrap_or_else(|| panic!("Expected accounts, got {}", accounts))
+        .iter()
+        .map(|account| account["provider"].as_str().unwrap())
+        .collect()
+}
+
+const UNLINK: &str =
+    "mutation ($provider: String!) { unlinkAccount(provider: $provider) { provider } }";
+
+#[actix_rt::test]
+async fn test_linked_accounts_are_listed_for_their_owner_only() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, &["google", "github"]);
+    let other = insert_user(conn, &[]);
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        "{ me { linkedAccounts { provider type providerAccountId } } }",
+        json!({}),
+    )
+    .await;
+    let accounts = &response["data"]["me"]["linkedAccounts"];
+    assert_eq!(providers(accounts), ["github", "google"]);
+    assert_eq!(accounts[0]["type"], "oauth");
+    assert!(!response.to_string().contains("secret"));
+
+    let response = execute(
+        &pool,
+        Some(&other),
+        "query ($id: Int!) { userQuery { user(id: $id) { linkedAccounts { provider } } } }",
+        json!({ "id": user.id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    cleanup(conn, &[user.id, other.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_unlink_keeps_a_way_to_sign_in() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, &["google", "github"]);
+
+    let response = execute(&pool, None, UNLINK, json!({ "provider": "github" })).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    let response = execute(&pool, Some(&user), UNLINK, json!({ "provider": "github" })).await;
+    assert_eq!(providers(&response["data"]["unlinkAccount"]), ["google"]);
+
+    let response = execute(&pool, Some(&user), UNLINK, json!({ "provider": "github" })).await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let response = execute(&pool, Some(&user), UNLINK, json!({ "provider": "google" })).await;
+    assert_eq!(error_code(&response), "LAST_LOGIN_METHOD");
+
+    // A verified email address is another way to sign in
+    diesel::update(users::table.find(user.id))
+        .set(users::emailVerified.eq(Some(Utc::now())))
+        .execute(conn)
+        .unwrap();
+    let response = execute(&pool, Some(&user), UNLINK, json!({ "provider": "google" })).await;
+    assert_eq!(response["data"]["unlinkAccount"], json!([]));
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_schema_never_exposes_oauth_tokens() {
+    let pool = test_pool();
+    let response = execute(
+        &pool,
+        None,
+        "{ __schema { types { name fields { name } inputFields { name } } } }",
+        json!({}),
+    )
+    .await;
+
+    for ty in response["data"]["__schema"]["types"].as_array().unwrap() {
+        let fields = ty["fields"]
+            .as_array()
+            .into_iter()
+            .chain(ty["inputFields"].as_array())
+            .flatten();
+        for field in fields {
+            let name = field["name"]
+                .as_str()
+                .unwrap()
+                .to_lowercase()
+                .replace('_', "");
+            for secret in ["accesstoken", "refreshtoken", "idtoken"] {
+                assert!(
+                    !name.contains(secret),
+                    "{}.{} exposes an OAuth token",
+                    ty["name"],
+                    field["name"]
+                );
+            }
+        }
+    }
+}
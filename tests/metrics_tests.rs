@@ -0,0 +1,116 @@
+// tests/metrics_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::{http::StatusCode, test};
+use serial_test::serial;
+
+#[actix_rt::test]
+#[serial]
+async fn test_metrics_endpoint() {
+    let app = setup_test_app!();
+
+    let request = test::TestRequest::post()
+        .uri("/graphql")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(
+            r#"{"query": "query ApiVersion { apiVersion }", "operationName": "ApiVersion"}"#,
+        )
+        .to_request();
+    test::call_service(&app, request).await;
+
+    let request = test::TestRequest::get().uri("/metrics").to_request();
+    let response = test::call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = test::read_body(response).await;
+    let body = String::from_utf8(body.to_vec()).expect("Metrics should be UTF-8");
+    assert!(body.contains(r#"graphql_operation_duration_seconds_count{operation="ApiVersion"} 1"#));
+    assert!(body.contains(r#"db_pool_connections{state="idle"}"#));
+}
+
+#[actix_rt::test]
+async fn test_metrics_count_field_errors() {
+    let app = setup_test_app!();
+
+    // `parts` requires an authenticated user, so this returns a field error
+    let request = test::TestRequest::post()
+        .uri("/graphql")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"query": "{ parts { id } }"}"#)
+        .to_request();
+    test::call_service(&app, request).await;
+
+    let request = test::TestRequest::get().uri("/metrics").to_request();
+    let response = test::call_service(&app, request).await;
+    let body = test::read_body(response).await;
+    let body = String::from_utf8(body.to_vec()).expect("Metrics should be UTF-8");
+    assert!(body.contains(r#"graphql_errors_total{message="Unauthorized"}"#));
+}
+
+#[actix_rt::test]
+#[serial]
+async fn test_operation_labels_are_bounded() {
+    let rate_limits = rust_backend::settings::RateLimitSettings {
+        enabled: false,
+        ..Default::default()
+    };
+    let app = setup_test_app!(
+        rust_backend::settings::GraphQLSettings::default(),
+        rate_limits
+    );
+
+    for i in 0..150 {
+        let request = test::TestRequest::post()
+            .uri("/graphql")
+            .set_json(serde_json::json!({ "query": format!("query Flood{} {{ apiVersion }}", i) }))
+            .to_request();
+        test::call_service(&app, request).await;
+    }
+    // The operation name sent alongside the query is not used as a label
+    let request = test::TestRequest::post()
+        .uri("/graphql")
+        .set_json(serde_json::json!({
+            "query": "{ apiVersion }",
+            "operationName": "NotInTheDocument"
+        }))
+        .to_request();
+    test::call_service(&app, request).await;
+
+    let request = test::TestRequest::get().uri("/metrics").to_request();
+    let response = test::call_service(&app, request).await;
+    let body = test::read_body(response).await;
+    let body = String::from_utf8(body.to_vec()).expect("Metrics should be UTF-8");
+    let labels = body
+        .lines()
+        .filter(|line| line.starts_with("graphql_operation_duration_seconds_count"))
+        .count();
+    assert!(labels <= 102, "{} operation labels", labels);
+    assert!(body.contains(r#"graphql_operation_duration_seconds_count{operation="other"}"#));
+    assert!(!body.contains("NotInTheDocument"));
+}
+
+#[actix_rt::test]
+#[serial]
+async fn test_metrics_time_nested_fields() {
+    let app = setup_test_app!();
+
+    let request = test::TestRequest::post()
+        .uri("/graphql")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"query": "{ userQuery { users { id } } }"}"#)
+        .to_request();
+    test::call_service(&app, request).await;
+
+    let request = test::TestRequest::get().uri("/metrics").to_request();
+    let response = test::call_service(&app, request).await;
+    let body = test::read_body(response).await;
+    let body = String::from_utf8(body.to_vec()).expect("Metrics should be UTF-8");
+    assert!(body.contains(
+        r#"graphql_resolver_duration_seconds_count{field="userQuery",type="RootQuery"}"#
+    ));
+    assert!(
+        body.contains(r#"graphql_resolver_duration_seconds_count{field="users",type="UserQuery"}"#)
+    );
+}
@@ -1,9 +1,7 @@
 // tests/migration_tests.rs
 
-use diesel::pg::PgConnection;
-use diesel_migrations::MigrationHarness;
 use dotenv::dotenv;
-use rust_backend::migration::{generate_lock_key, run, AdvisoryLock, MIGRATIONS};
+use rust_backend::migration::{generate_lock_key, run, AdvisoryLock};
 use rust_backend::types::errors::MigrationError;
 use serial_test::serial;
 use std::env;
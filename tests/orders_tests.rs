@@ -0,0 +1,260 @@
+// tests/orders_tests.rs
+
+mod utils;
+
+use bigdecimal::BigDecimal;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::parts::{inventory, parts};
+use serde_json::json;
+use utils::{
+    cleanup, error_code, execute, insert_configuration, insert_inventory, insert_part, insert_user,
+    test_pool,
+};
+
+/// Inserts a part priced at `price` with `on_hand` units in stock.
+fn insert_stocked_part(conn: &mut PgConnection, price: &str, on_hand: i32) -> i32 {
+    let part_id = insert_part(conn, price);
+    insert_inventory(conn, part_id, on_hand);
+    part_id
+}
+
+fn stock(conn: &mut PgConnection, part_id: i32) -> (i32, i32) {
+    inventory::table
+        .find(part_id)
+        .select((inventory::quantity_on_hand, inventory::quantity_reserved))
+        .first(conn)
+        .expect("Failed to read inventory")
+}
+
+const ORDER_FIELDS: &str = "id status total items { partId quantity unitPrice lineTotal } statusHistory { status changedBy }";
+
+#[actix_rt::test]
+async fn test_checkout_lifecycle() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let customer = insert_user(conn, "customer");
+    let staff = insert_user(conn, "staff");
+    let cpu = insert_stocked_part(conn, "299.99", 5);
+    let memory = insert_stocked_part(conn, "50.00", 4);
+    let configuration_id = insert_configuration(conn, Some(customer.id), &[(cpu, 1), (memory, 2)]);
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        &format!(
+            "mutation ($id: Int!) {{ createOrder(configurationId: $id, notes: \"Quiet fans\") {{ {} notes }} }}",
+            ORDER_FIELDS
+        ),
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let order = &response["data"]["createOrder"];
+    assert_eq!(order["status"], "DRAFT", "{}", response);
+    assert_eq!(order["total"], "399.99");
+    assert_eq!(order["notes"], "Quiet fans");
+    assert_eq!(
+        order["items"],
+        json!([
+            { "partId": cpu, "quantity": 1, "unitPrice": "299.99", "lineTotal": "299.99" },
+            { "partId": memory, "quantity": 2, "unitPrice": "50.00", "lineTotal": "100.00" },
+        ])
+    );
+    let order_id = order["id"].as_i64().unwrap();
+
+    // Prices are frozen at order time
+    diesel::update(parts::table.find(cpu))
+        .set(parts::price.eq(Some(BigDecimal::from(999))))
+        .execute(conn)
+        .unwrap();
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        "mutation ($id: Int!) { submitOrder(id: $id) { status total } }",
+        json!({ "id": order_id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["submitOrder"],
+        json!({ "status": "SUBMITTED", "total": "399.99" })
+    );
+    assert_eq!(stock(conn, memory), (4, 2));
+
+    for status in ["BUILDING", "SHIPPED"] {
+        let response = execute(
+            &pool,
+            Some(&staff),
+            "mutation ($id: Int!, $status: OrderStatusType!) { updateOrderStatus(id: $id, status: $status) { status } }",
+            json!({ "id": order_id, "status": status }),
+        )
+        .await;
+        assert_eq!(response["data"]["updateOrderStatus"]["status"], status);
+        if status == "BUILDING" {
+            // Only staff can cancel an order being built
+            let response = execute(
+                &pool,
+                Some(&customer),
+                "mutation ($id: Int!) { cancelOrder(id: $id) { status } }",
+                json!({ "id": order_id }),
+            )
+            .await;
+            assert_eq!(error_code(&response), "INVALID_STATUS_TRANSITION");
+        }
+    }
+    assert_eq!(stock(conn, cpu), (4, 0));
+    assert_eq!(stock(conn, memory), (2, 0));
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        "{ myOrders { id status statusHistory { status changedBy } } }",
+        json!({}),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["myOrders"],
+        json!([{
+            "id": order_id,
+            "status": "SHIPPED",
+            "statusHistory": [
+                { "status": "DRAFT", "changedBy": customer.id },
+                { "status": "SUBMITTED", "changedBy": customer.id },
+                { "status": "BUILDING", "changedBy": staff.id },
+                { "status": "SHIPPED", "changedBy": staff.id },
+            ]
+        }])
+    );
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "{ orders(status: SHIPPED) { id } }",
+        json!({}),
+    )
+    .await;
+    assert!(response["data"]["orders"]
+        .as_array()
+        .unwrap()
+        .contains(&json!({ "id": order_id })));
+
+    cleanup(
+        conn,
+        &[customer.id, staff.id],
+        &[configuration_id],
+        &[cpu, memory],
+    );
+}
+
+#[actix_rt::test]
+async fn test_orders_are_restricted_to_their_owner_and_staff() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let customer = insert_user(conn, "customer");
+    let other = insert_user(conn, "customer");
+    let cpu = insert_stocked_part(conn, "100.00", 5);
+    let configuration_id = insert_configuration(conn, Some(customer.id), &[(cpu, 1)]);
+
+    let response = execute(&pool, None, "{ myOrders { id } }", json!({})).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    let create = "mutation ($id: Int!) { createOrder(configurationId: $id) { id } }";
+    let response = execute(
+        &pool,
+        Some(&other),
+        create,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        create,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let order_id = response["data"]["createOrder"]["id"].as_i64().unwrap();
+
+    let response = execute(
+        &pool,
+        Some(&other),
+        "query ($id: Int!) { order(id: $id) { id } }",
+        json!({ "id": order_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let response = execute(
+        &pool,
+        Some(&other),
+        "mutation ($id: Int!) { cancelOrder(id: $id) { id } }",
+        json!({ "id": order_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        "mutation ($id: Int!) { updateOrderStatus(id: $id, status: BUILDING) { id } }",
+        json!({ "id": order_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    let response = execute(&pool, Some(&customer), "{ orders { id } }", json!({})).await;
+    assert_eq!(error_code(&response), "FORBIDDEN");
+
+    cleanup(conn, &[customer.id, other.id], &[configuration_id], &[cpu]);
+}
+
+#[actix_rt::test]
+async fn test_cancelling_releases_stock_and_invalid_transitions_fail() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let customer = insert_user(conn, "customer");
+    let cpu = insert_stocked_part(conn, "100.00", 1);
+    let configuration_id = insert_configuration(conn, None, &[(cpu, 1)]);
+    let create = "mutation ($id: Int!) { createOrder(configurationId: $id) { id } }";
+    let submit = "mutation ($id: Int!) { submitOrder(id: $id) { status } }";
+    let cancel = "mutation ($id: Int!) { cancelOrder(id: $id) { status } }";
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        create,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let first = response["data"]["createOrder"]["id"].as_i64().unwrap();
+    let response = execute(
+        &pool,
+        Some(&customer),
+        create,
+        json!({ "id": configuration_id }),
+    )
+    .await;
+    let second = response["data"]["createOrder"]["id"].as_i64().unwrap();
+
+    execute(&pool, Some(&customer), submit, json!({ "id": first })).await;
+    assert_eq!(stock(conn, cpu), (1, 1));
+
+    // The only unit is reserved by the first order
+    let response = execute(&pool, Some(&customer), submit, json!({ "id": second })).await;
+    assert_eq!(error_code(&response), "INSUFFICIENT_STOCK");
+    assert_eq!(response["errors"][0]["extensions"]["available"], 0);
+
+    let response = execute(&pool, Some(&customer), cancel, json!({ "id": first })).await;
+    assert_eq!(response["data"]["cancelOrder"]["status"], "CANCELLED");
+    assert_eq!(stock(conn, cpu), (1, 0));
+
+    let response = execute(&pool, Some(&customer), cancel, json!({ "id": first })).await;
+    assert_eq!(error_code(&response), "INVALID_STATUS_TRANSITION");
+
+    let response = execute(&pool, Some(&customer), submit, json!({ "id": second })).await;
+    assert_eq!(response["data"]["submitOrder"]["status"], "SUBMITTED");
+
+    cleanup(conn, &[customer.id], &[configuration_id], &[cpu]);
+}
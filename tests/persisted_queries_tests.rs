@@ -0,0 +1,247 @@
+// tests/persisted_queries_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::test::{call_service, read_body_json, TestRequest};
+use diesel::pg::PgConnection;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::Connection;
+use diesel_migrations::MigrationHarness;
+use dotenv::dotenv;
+use rust_backend::migration::MIGRATIONS;
+use rust_backend::persisted_queries::{
+    resolve_query, PersistedQueryExtension, PersistedQueryStore,
+};
+use rust_backend::redaction::query_hash;
+use rust_backend::settings::PersistedQuerySettings;
+use rust_backend::types::errors::PersistedQueryError;
+use serde_json::{json, Value};
+use serial_test::serial;
+use std::env;
+use uuid::Uuid;
+
+/// Applies pending migrations so the `persisted_queries` table exists.
+fn run_migrations() -> String {
+    dotenv().ok();
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    let mut conn =
+        PgConnection::establish(&database_url).expect("Failed to connect to test database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    database_url
+}
+
+fn test_store() -> PersistedQueryStore {
+    let database_url = run_migrations();
+    let manager = ConnectionManager::<PgConnection>::new(database_url);
+    let pool = Pool::builder()
+        .max_size(2)
+        .build(manager)
+        .expect("Failed to create pool.");
+    PersistedQueryStore::new(pool, 10)
+}
+
+/// Returns a query whose hash has not been registered by earlier test runs.
+fn unique_query() -> String {
+    format!("query Apq_{} {{ apiVersion }}", Uuid::new_v4().simple())
+}
+
+fn extension(query: &str) -> PersistedQueryExtension {
+    PersistedQueryExtension {
+        version: 1,
+        sha256_hash: query_hash(query),
+    }
+}
+
+#[actix_rt::test]
+async fn test_automatic_persisted_query_flow() {
+    run_migrations();
+    let app = setup_test_app!();
+    let query = unique_query();
+    let persisted_query = json!({
+        "persistedQuery": { "version": 1, "sha256Hash": query_hash(&query) }
+    });
+
+    // An unknown hash asks the client to retry with the full query
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({ "extensions": persisted_query }))
+        .to_request();
+    let body: Value = read_body_json(call_service(&app, request).await).await;
+    assert_eq!(body["errors"][0]["message"], "PersistedQueryNotFound");
+    assert_eq!(
+        body["errors"][0]["extensions"]["code"],
+        "PERSISTED_QUERY_NOT_FOUND"
+    );
+
+    // Sending the query with its hash registers and runs it
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({ "query": query, "extensions": persisted_query }))
+        .to_request();
+    let body: Value = read_body_json(call_service(&app, request).await).await;
+    assert_eq!(body, json!({ "data": { "apiVersion": "1.0" } }));
+
+    // The hash alone now runs the registered query
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({ "extensions": persisted_query }))
+        .to_request();
+    let body: Value = read_body_json(call_service(&app, request).await).await;
+    assert_eq!(body, json!({ "data": { "apiVersion": "1.0" } }));
+}
+
+#[actix_rt::test]
+async fn test_persisted_query_hash_mismatch() {
+    run_migrations();
+    let app = setup_test_app!();
+
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({
+            "query": "{ apiVersion }",
+            "extensions": { "persistedQuery": { "version": 1, "sha256Hash": query_hash("{ other }") } }
+        }))
+        .to_request();
+    let body: Value = read_body_json(call_service(&app, request).await).await;
+    assert_eq!(
+        body["errors"][0]["message"],
+        "Provided sha256Hash does not match query"
+    );
+}
+
+#[test]
+#[serial]
+fn test_allowlist_only_mode() {
+    let store = test_store();
+    let settings = PersistedQuerySettings {
+        allowlist_only: true,
+        ..PersistedQuerySettings::default()
+    };
+    let query = unique_query();
+
+    // Unregistered queries are refused and not registered, with or without a hash
+    let result = resolve_query(
+        &store,
+        &settings,
+        Some(query.clone()),
+        Some(&extension(&query)),
+    );
+    assert!(matches!(result, Err(PersistedQueryError::NotAllowed)));
+    let result = resolve_query(&store, &settings, Some(query.clone()), None);
+    assert!(matches!(result, Err(PersistedQueryError::NotAllowed)));
+
+    // Queries registered by clients are not allowlisted
+    store.register(&query_hash(&query), &query);
+    let result = resolve_query(&store, &settings, None, Some(&extension(&query)));
+    assert!(matches!(result, Err(PersistedQueryError::NotFound)));
+    let result = resolve_query(&store, &settings, Some(query.clone()), None);
+    assert!(matches!(result, Err(PersistedQueryError::NotAllowed)));
+
+    // Allowlisted queries run whether sent in full or by hash
+    store
+        .allow(&query_hash(&query), &query, None)
+        .expect("Failed to allowlist query");
+    let result = resolve_query(&store, &settings, Some(query.clone()), None);
+    assert_eq!(result.unwrap(), query);
+    let result = resolve_query(&store, &settings, None, Some(&extension(&query)));
+    assert_eq!(result.unwrap(), query);
+}
+
+#[test]
+fn test_disabled_persisted_queries() {
+    let store = test_store();
+    let settings = PersistedQuerySettings {
+        enabled: false,
+        ..PersistedQuerySettings::default()
+    };
+
+    let result = resolve_query(&store, &settings, None, Some(&extension("{ a }")));
+    assert!(matches!(result, Err(PersistedQueryError::NotSupported)));
+    let result = resolve_query(&store, &settings, None, None);
+    assert!(matches!(result, Err(PersistedQueryError::MissingQuery)));
+}
+
+#[test]
+#[serial]
+fn test_load_manifest() {
+    let store = test_store();
+    let query = unique_query();
+    let path = env::temp_dir().join(format!("manifest-{}.json", Uuid::new_v4()));
+
+    let manifest = json!({
+        "format": "apollo-persisted-query-manifest",
+        "version": 1,
+        "operations": [
+            { "id": query_hash(&query), "name": "ApiVersion", "type": "query", "body": query }
+        ]
+    });
+    std::fs::write(&path, manifest.to_string()).expect("Failed to write manifest");
+    let loaded = store.load_manifest(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(loaded.unwrap(), 1);
+    assert_eq!(
+        store.get(&query_hash(&query)).unwrap().as_deref(),
+        Some(query.as_str())
+    );
+
+    // A new manifest replaces the allowlist, in memory and in the table
+    let replacement = unique_query();
+    let manifest = json!({
+        "operations": [{ "id": query_hash(&replacement), "body": replacement }]
+    });
+    std::fs::write(&path, manifest.to_string()).expect("Failed to write manifest");
+    let loaded = store.load_manifest(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+
+    assert_eq!(loaded.unwrap(), 1);
+    assert!(store.get_allowed(&query_hash(&query)).unwrap().is_none());
+    assert!(store
+        .get_allowed(&query_hash(&replacement))
+        .unwrap()
+        .is_some());
+    let other_instance = test_store();
+    assert!(other_instance
+        .get_allowed(&query_hash(&query))
+        .unwrap()
+        .is_none());
+    assert!(other_instance
+        .get_allowed(&query_hash(&replacement))
+        .unwrap()
+        .is_some());
+
+    let tampered = json!({
+        "operations": [{ "id": query_hash("{ a }"), "body": "{ b }" }]
+    });
+    std::fs::write(&path, tampered.to_string()).expect("Failed to write manifest");
+    let loaded = store.load_manifest(path.to_str().unwrap());
+    std::fs::remove_file(&path).ok();
+    assert!(matches!(
+        loaded,
+        Err(PersistedQueryError::ManifestError { .. })
+    ));
+}
+
+#[test]
+fn test_client_registrations_stay_in_memory() {
+    let store = test_store();
+    let queries = (0..11).map(|_| unique_query()).collect::<Vec<_>>();
+
+    for query in &queries[..10] {
+        store.register(&query_hash(query), query);
+    }
+    // Using the oldest registration keeps it over the next oldest
+    assert!(store.get(&query_hash(&queries[0])).unwrap().is_some());
+    store.register(&query_hash(&queries[10]), &queries[10]);
+
+    assert!(store.get(&query_hash(&queries[0])).unwrap().is_some());
+    assert!(store.get(&query_hash(&queries[1])).unwrap().is_none());
+    assert!(store.get(&query_hash(&queries[10])).unwrap().is_some());
+    // Nothing was written to the allowlist
+    assert!(store
+        .get_allowed(&query_hash(&queries[10]))
+        .unwrap()
+        .is_none());
+}
@@ -0,0 +1,122 @@
+// tests/profile_tests.rs
+
+mod utils;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::users::users;
+use rust_backend::models::auth::User;
+use serde_json::{json, Value};
+use utils::{cleanup, error_code, execute, test_pool};
+
+/// Inserts a customer with a bio.
+fn insert_user(conn: &mut PgConnection) -> User {
+    let user = utils::insert_user(conn, "customer");
+    diesel::update(users::table.find(user.id))
+        .set(users::bio.eq(Some("Builds quiet workstations")))
+        .get_result::<User>(conn)
+        .expect("Failed to set bio")
+}
+
+const UPDATE: &str = "mutation ($input: ProfileInput!) {
+    updateProfile(input: $input) { id name bio image }
+}";
+
+#[actix_rt::test]
+async fn test_me_returns_the_signed_in_user() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn);
+
+    let response = execute(&pool, Some(&user), "{ me { id name bio } }", json!({})).await;
+    assert_eq!(
+        response["data"]["me"],
+        json!({ "id": user.id, "name": "Test User", "bio": "Builds quiet workstations" })
+    );
+
+    let response = execute(&pool, None, "{ me { id } }", json!({})).await;
+    assert_eq!(response["data"]["me"], Value::Null);
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_update_profile() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn);
+    let other = insert_user(conn);
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "input": { "name": "  Dana Reyes ", "image": "https://example.com/dana.png" } }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["updateProfile"],
+        json!({
+            "id": user.id,
+            "name": "Dana Reyes",
+            "bio": "Builds quiet workstations",
+            "image": "https://example.com/dana.png",
+        })
+    );
+
+    // Explicit nulls and blank strings clear fields; omitted fields stay
+    let response = execute(
+        &pool,
+        Some(&user),
+        UPDATE,
+        json!({ "input": { "bio": null, "image": " " } }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["updateProfile"],
+        json!({ "id": user.id, "name": "Dana Reyes", "bio": null, "image": null })
+    );
+
+    let response = execute(&pool, Some(&user), UPDATE, json!({ "input": {} })).await;
+    assert_eq!(response["data"]["updateProfile"]["name"], "Dana Reyes");
+
+    // Only the caller's own record changes
+    let name = users::table
+        .find(other.id)
+        .select(users::name)
+        .first::<Option<String>>(conn)
+        .unwrap();
+    assert_eq!(name.as_deref(), Some("Test User"));
+
+    cleanup(conn, &[user.id, other.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_update_profile_validation() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn);
+
+    let response = execute(&pool, None, UPDATE, json!({ "input": { "name": "Anon" } })).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    for input in [
+        json!({ "name": "x".repeat(101) }),
+        json!({ "bio": "x".repeat(1001) }),
+        json!({ "image": "javascript:alert(1)" }),
+        json!({ "image": "not a url" }),
+        json!({ "image": "ftp://example.com/avatar.png" }),
+    ] {
+        let response = execute(&pool, Some(&user), UPDATE, json!({ "input": input })).await;
+        assert_eq!(error_code(&response), "BAD_REQUEST", "input: {}", input);
+    }
+
+    let stored = users::table
+        .find(user.id)
+        .select((users::name, users::image))
+        .first::<(Option<String>, Option<String>)>(conn)
+        .unwrap();
+    assert_eq!(stored, (Some("Test User".to_string()), None));
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
@@ -0,0 +1,217 @@
+// tests/query_limits_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::test::{call_service, read_body, read_body_json, TestRequest};
+use juniper::{InputValue, Variables};
+use rust_backend::graphql_schema::query_limits::{
+    analyze_query, check_query_limits, summarize_operation, QueryCost,
+};
+use rust_backend::graphql_schema::schema::create_schema;
+use rust_backend::settings::GraphQLSettings;
+use rust_backend::types::errors::QueryLimitError;
+use serde_json::Value;
+
+fn cost_of(query: &str, variables: &Variables) -> QueryCost {
+    let schema = create_schema();
+    analyze_query(&schema, query, None, variables, &GraphQLSettings::default())
+        .expect("query should be analyzed")
+}
+
+#[test]
+fn test_cost_uses_list_arguments_and_field_costs() {
+    let variables = Variables::new();
+
+    // parts (1) + 10 * (id (1) + manufacturer (5) + name (1))
+    let limited = cost_of(
+        "{ parts(limit: 10) { id manufacturer { name } } }",
+        &variables,
+    );
+    assert_eq!(
+        limited,
+        QueryCost {
+            depth: 3,
+            complexity: 71
+        }
+    );
+
+    // Without a limit the default list size of 100 applies
+    let unlimited = cost_of("{ parts { id manufacturer { name } } }", &variables);
+    assert_eq!(unlimited.complexity, 701);
+}
+
+#[test]
+fn test_cost_resolves_variables_and_fragments() {
+    let mut variables = Variables::new();
+    variables.insert("limit".to_string(), InputValue::scalar(2));
+
+    let cost = cost_of(
+        "query Parts($limit: Int) { parts(limit: $limit) { ...PartFields } }
+         fragment PartFields on PartGraphQL { id manufacturer { name } }",
+        &variables,
+    );
+    assert_eq!(
+        cost,
+        QueryCost {
+            depth: 3,
+            complexity: 15
+        }
+    );
+}
+
+/// Builds fragments `F0` to `F{n - 1}` on `type_name`, each spreading the
+/// next one twice, with the last one selecting `leaf`.
+fn doubled_fragment_chain(n: usize, type_name: &str, leaf: &str) -> String {
+    let mut fragments = String::new();
+    for i in 0..n - 1 {
+        fragments.push_str(&format!(
+            "fragment F{} on {} {{ ...F{} ...F{} }}\n",
+            i,
+            type_name,
+            i + 1,
+            i + 1
+        ));
+    }
+    fragments.push_str(&format!(
+        "fragment F{} on {} {{ {} }}\n",
+        n - 1,
+        type_name,
+        leaf
+    ));
+    fragments
+}
+
+#[test]
+fn test_repeated_fragment_spreads_are_measured_once() {
+    let schema = create_schema();
+    let chain = doubled_fragment_chain(40, "PartGraphQL", "id");
+
+    // Walking every spread would take 2^39 steps
+    let cost = cost_of(
+        &format!("{{ part(partId: 1) {{ ...F0 }} }}\n{}", chain),
+        &Variables::new(),
+    );
+    assert_eq!(
+        cost,
+        QueryCost {
+            depth: 2,
+            complexity: 1 + (1 << 39)
+        }
+    );
+
+    let chain = doubled_fragment_chain(40, "RootQuery", "apiVersion");
+    let summary = summarize_operation(&schema, &format!("{{ ...F0 }}\n{}", chain), None)
+        .expect("query should be summarized");
+    assert_eq!(summary.root_fields, vec!["apiVersion".to_string()]);
+}
+
+#[test]
+fn test_introspection_is_free_but_counts_depth() {
+    let cost = cost_of(
+        "{ __schema { types { name fields { name } } } }",
+        &Variables::new(),
+    );
+    assert_eq!(
+        cost,
+        QueryCost {
+            depth: 4,
+            complexity: 0
+        }
+    );
+}
+
+#[test]
+fn test_check_query_limits() {
+    let settings = GraphQLSettings {
+        max_depth: 3,
+        max_complexity: 100,
+        ..GraphQLSettings::default()
+    };
+
+    assert!(check_query_limits(
+        QueryCost {
+            depth: 3,
+            complexity: 100
+        },
+        &settings
+    )
+    .is_ok());
+    assert_eq!(
+        check_query_limits(
+            QueryCost {
+                depth: 4,
+                complexity: 1
+            },
+            &settings
+        ),
+        Err(QueryLimitError::TooDeep {
+            depth: 4,
+            max_depth: 3
+        })
+    );
+    assert_eq!(
+        check_query_limits(
+            QueryCost {
+                depth: 1,
+                complexity: 101
+            },
+            &settings
+        ),
+        Err(QueryLimitError::TooComplex {
+            complexity: 101,
+            max_complexity: 100
+        })
+    );
+
+    let unlimited = GraphQLSettings {
+        max_depth: 0,
+        max_complexity: 0,
+        ..GraphQLSettings::default()
+    };
+    assert!(check_query_limits(
+        QueryCost {
+            depth: 1000,
+            complexity: usize::MAX
+        },
+        &unlimited
+    )
+    .is_ok());
+}
+
+#[actix_rt::test]
+async fn test_graphql_rejects_query_over_limits() {
+    let app = setup_test_app!(GraphQLSettings {
+        max_depth: 2,
+        ..GraphQLSettings::default()
+    });
+
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"query": "{ parts(limit: 1) { manufacturer { name } } }"}"#)
+        .to_request();
+
+    let response = call_service(&app, request).await;
+    let body: Value = read_body_json(response).await;
+
+    assert_eq!(body["data"], Value::Null);
+    let error = &body["errors"][0];
+    assert_eq!(
+        error["message"],
+        "Query depth exceeds the maximum allowed depth of 2"
+    );
+    assert_eq!(error["extensions"]["code"], "QUERY_TOO_DEEP");
+    assert_eq!(error["extensions"]["depth"], 3);
+
+    // Queries within the limits still execute
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"query": "{ apiVersion }"}"#)
+        .to_request();
+
+    let response = call_service(&app, request).await;
+    let body = read_body(response).await;
+    assert_eq!(body, r#"{"data":{"apiVersion":"1.0"}}"#);
+}
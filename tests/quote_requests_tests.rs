@@ -0,0 +1,220 @@
+// tests/quote_requests_tests.rs
+
+mod utils;
+
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::quote_requests::quote_requests;
+use serde_json::{json, Value};
+use utils::{error_code, execute, insert_configuration, insert_user, test_pool};
+
+/// Deletes the quote requests, then the users and configurations.
+fn cleanup(
+    conn: &mut PgConnection,
+    quote_request_ids: &[i64],
+    user_ids: &[i32],
+    configuration_ids: &[i32],
+) {
+    let quote_request_ids = quote_request_ids
+        .iter()
+        .map(|&id| id as i32)
+        .collect::<Vec<_>>();
+    diesel::delete(quote_requests::table.filter(quote_requests::id.eq_any(quote_request_ids)))
+        .execute(conn)
+        .unwrap();
+    utils::cleanup(conn, user_ids, configuration_ids, &[]);
+}
+
+const SUBMIT: &str = "mutation ($input: QuoteRequestInput!) {
+    submitQuoteRequest(input: $input) {
+        id userId serviceSlug configurationId contactName contactEmail contactPhone company status
+    }
+}";
+
+fn quote_input(service_slug: &str, configuration_id: Option<i32>) -> Value {
+    json!({
+        "input": {
+            "serviceSlug": service_slug,
+            "configurationId": configuration_id,
+            "contactName": "  Dana Reyes ",
+            "contactEmail": "dana@example.com",
+            "contactPhone": "",
+            "company": "Reyes Visual Effects",
+            "requirements": "Four rendering workstations for Redshift.",
+        }
+    })
+}
+
+#[actix_rt::test]
+async fn test_submit_quote_requests() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let customer = insert_user(conn, "customer");
+    let own_configuration = insert_configuration(conn, Some(customer.id), &[]);
+    let anonymous_configuration = insert_configuration(conn, None, &[]);
+
+    let response = execute(&pool, None, "{ services { slug name } }", json!({})).await;
+    let slugs = response["data"]["services"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|service| service["slug"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(slugs, ["workstations", "scanning", "commercial", "gaming"]);
+
+    // Visitors can ask for a quote without signing in
+    let response = execute(
+        &pool,
+        None,
+        SUBMIT,
+        quote_input("workstations", Some(anonymous_configuration)),
+    )
+    .await;
+    let anonymous = &response["data"]["submitQuoteRequest"];
+    assert_eq!(anonymous["userId"], Value::Null);
+    assert_eq!(anonymous["contactName"], "Dana Reyes");
+    assert_eq!(anonymous["contactPhone"], Value::Null);
+    assert_eq!(anonymous["status"], "NEW");
+    let anonymous_id = anonymous["id"].as_i64().unwrap();
+
+    let response = execute(
+        &pool,
+        Some(&customer),
+        SUBMIT,
+        quote_input("scanning", Some(own_configuration)),
+    )
+    .await;
+    let signed_in = &response["data"]["submitQuoteRequest"];
+    assert_eq!(signed_in["userId"], customer.id);
+    assert_eq!(signed_in["configurationId"], own_configuration);
+    let signed_in_id = signed_in["id"].as_i64().unwrap();
+
+    // Other users' configurations cannot be attached
+    let response = execute(
+        &pool,
+        None,
+        SUBMIT,
+        quote_input("scanning", Some(own_configuration)),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let response = execute(&pool, None, SUBMIT, quote_input("mainframes", None)).await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    let mut invalid = quote_input("gaming", None);
+    invalid["input"]["contactEmail"] = json!("not-an-email");
+    let response = execute(&pool, None, SUBMIT, invalid).await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    let mut invalid = quote_input("gaming", None);
+    invalid["input"]["requirements"] = json!("   ");
+    let response = execute(&pool, None, SUBMIT, invalid).await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    cleanup(
+        conn,
+        &[anonymous_id, signed_in_id],
+        &[customer.id],
+        &[own_configuration, anonymous_configuration],
+    );
+}
+
+#[actix_rt::test]
+async fn test_staff_manage_quote_requests() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let customer = insert_user(conn, "customer");
+    let staff = insert_user(conn, "staff");
+
+    let response = execute(&pool, None, SUBMIT, quote_input("commercial", None)).await;
+    let id = response["data"]["submitQuoteRequest"]["id"]
+        .as_i64()
+        .unwrap();
+
+    for query in [
+        "{ quoteRequests { id } }",
+        "query ($id: Int!) { quoteRequest(id: $id) { id } }",
+    ] {
+        let response = execute(&pool, Some(&customer), query, json!({ "id": id })).await;
+        assert_eq!(error_code(&response), "FORBIDDEN");
+    }
+
+    let assign = "mutation ($id: Int!, $assignee: Int) {
+        assignQuoteRequest(id: $id, assigneeId: $assignee) { assignedTo }
+    }";
+    let response = execute(
+        &pool,
+        Some(&staff),
+        assign,
+        json!({ "id": id, "assignee": customer.id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        assign,
+        json!({ "id": id, "assignee": staff.id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["assignQuoteRequest"]["assignedTo"],
+        staff.id
+    );
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "mutation ($id: Int!) {
+            updateQuoteRequest(id: $id, status: CONTACTED, staffNotes: \"Called back\") {
+                status staffNotes
+            }
+        }",
+        json!({ "id": id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["updateQuoteRequest"],
+        json!({ "status": "CONTACTED", "staffNotes": "Called back" })
+    );
+
+    // Omitted fields are left as they are
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "mutation ($id: Int!) { updateQuoteRequest(id: $id, status: QUOTED) { status staffNotes } }",
+        json!({ "id": id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["updateQuoteRequest"],
+        json!({ "status": "QUOTED", "staffNotes": "Called back" })
+    );
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "query ($assignee: Int!) {
+            quoteRequests(status: QUOTED, assignedTo: $assignee) { id serviceSlug }
+        }",
+        json!({ "assignee": staff.id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["quoteRequests"],
+        json!([{ "id": id, "serviceSlug": "commercial" }])
+    );
+
+    let response = execute(
+        &pool,
+        Some(&staff),
+        "mutation { updateQuoteRequest(id: -1, status: LOST) { id } }",
+        json!({}),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    cleanup(conn, &[id], &[customer.id, staff.id], &[]);
+}
@@ -0,0 +1,254 @@
+// tests/rate_limit_tests.rs
+
+mod utils;
+
+use actix_web::dev::ServiceResponse;
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+use chrono::{Duration, Utc};
+use diesel::prelude::*;
+use rust_backend::api_keys;
+use rust_backend::diesel_schema::rate_limits::rate_limit_buckets;
+use rust_backend::diesel_schema::users::sessions;
+use rust_backend::models::api_keys::ApiKeyScope;
+use rust_backend::rate_limit::{MemoryStore, PostgresStore, RateLimitStore};
+use rust_backend::settings::{GraphQLSettings, RateLimit, RateLimitSettings};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use utils::{cleanup, insert_user, test_pool};
+use uuid::Uuid;
+
+/// Settings allowing `burst` requests per client, refilled at one a minute.
+fn settings(burst: u32) -> RateLimitSettings {
+    RateLimitSettings {
+        default: RateLimit {
+            burst,
+            per_minute: 1,
+        },
+        ..RateLimitSettings::default()
+    }
+}
+
+fn peer(address: &str) -> SocketAddr {
+    format!("{}:40000", address).parse().unwrap()
+}
+
+fn header_value<'a, B>(response: &'a ServiceResponse<B>, name: &str) -> Option<&'a str> {
+    response
+        .headers()
+        .get(name)
+        .map(|value| value.to_str().unwrap())
+}
+
+const QUERY: &str = "{ __typename }";
+
+#[actix_rt::test]
+async fn test_route_limit_refuses_requests_with_headers() {
+    let app = setup_test_app!(GraphQLSettings::default(), settings(2));
+    let request = |address: &str| {
+        test::TestRequest::post()
+            .uri("/graphql")
+            .peer_addr(peer(address))
+            .set_json(json!({ "query": QUERY }))
+            .to_request()
+    };
+
+    for remaining in ["1", "0"] {
+        let response = test::call_service(&app, request("198.51.100.1")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_value(&response, "RateLimit-Limit"), Some("2"));
+        assert_eq!(
+            header_value(&response, "RateLimit-Remaining"),
+            Some(remaining)
+        );
+        assert!(header_value(&response, "Retry-After").is_none());
+    }
+
+    let response = test::call_service(&app, request("198.51.100.1")).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header_value(&response, "RateLimit-Remaining"), Some("0"));
+    let reset: u64 = header_value(&response, "RateLimit-Reset")
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(reset > 60 && reset <= 120, "Unexpected reset {}", reset);
+    let retry_after: u64 = header_value(&response, header::RETRY_AFTER.as_str())
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(retry_after > 0 && retry_after <= 60);
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "RATE_LIMITED");
+
+    // Other addresses have buckets of their own
+    let response = test::call_service(&app, request("198.51.100.2")).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Health checks and metrics are exempt
+    for path in ["/healthz", "/healthz", "/metrics", "/metrics"] {
+        let req = test::TestRequest::get()
+            .uri(path)
+            .peer_addr(peer("198.51.100.1"))
+            .to_request();
+        let response = test::call_service(&app, req).await;
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(header_value(&response, "RateLimit-Limit").is_none());
+    }
+}
+
+#[actix_rt::test]
+async fn test_operation_and_route_limits() {
+    let mut settings = settings(100);
+    let one = RateLimit {
+        burst: 1,
+        per_minute: 1,
+    };
+    settings.routes.insert("/version".to_string(), one);
+    settings.operations.insert("__typename".to_string(), one);
+    let app = setup_test_app!(GraphQLSettings::default(), settings);
+    let address = peer("198.51.100.3");
+
+    let req = test::TestRequest::get()
+        .uri("/version")
+        .peer_addr(address)
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(header_value(&response, "RateLimit-Limit"), Some("1"));
+    let req = test::TestRequest::get()
+        .uri("/version")
+        .peer_addr(address)
+        .to_request();
+    let response = test::call_service(&app, req).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+
+    let request = |body: Value| {
+        test::TestRequest::post()
+            .uri("/graphql")
+            .peer_addr(address)
+            .set_json(body)
+            .to_request()
+    };
+    // The route bucket of /version is not the default bucket
+    let response = test::call_service(&app, request(json!({ "query": QUERY }))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = test::call_service(&app, request(json!({ "query": QUERY }))).await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(header_value(&response, "RateLimit-Limit"), Some("1"));
+    assert!(header_value(&response, "Retry-After").is_some());
+    let body: Value = test::read_body_json(response).await;
+    assert_eq!(body["errors"][0]["extensions"]["code"], "RATE_LIMITED");
+    assert!(body["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("__typename"));
+
+    // In a batch only the limited operation fails
+    let batch = json!([{ "query": "{ apiVersion }" }, { "query": QUERY }]);
+    let response = test::call_service(&app, request(batch)).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(response).await;
+    assert!(body[0].get("errors").is_none(), "Unexpected {}", body);
+    assert_eq!(body[1]["errors"][0]["extensions"]["code"], "RATE_LIMITED");
+}
+
+#[actix_rt::test]
+async fn test_users_and_api_keys_have_own_buckets() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let token = Uuid::new_v4().simple().to_string();
+    diesel::insert_into(sessions::table)
+        .values((
+            sessions::userId.eq(user.id),
+            sessions::sessionToken.eq(&token),
+            sessions::expires.eq(Utc::now() + Duration::days(1)),
+        ))
+        .execute(conn)
+        .unwrap();
+    let (_, secret) = api_keys::issue(
+        conn,
+        user.id,
+        user.id,
+        "Rate limits",
+        &[ApiKeyScope::CatalogRead],
+        None,
+    )
+    .unwrap();
+    let app = setup_test_app!(GraphQLSettings::default(), settings(1));
+    let call = async |authorization: Option<String>| -> StatusCode {
+        let mut req = test::TestRequest::post()
+            .uri("/graphql")
+            .peer_addr(peer("198.51.100.4"))
+            .set_json(json!({ "query": QUERY }));
+        if let Some(authorization) = authorization {
+            req = req.insert_header((header::AUTHORIZATION, authorization));
+        }
+        test::call_service(&app, req.to_request()).await.status()
+    };
+
+    assert_eq!(call(None).await, StatusCode::OK);
+    assert_eq!(call(None).await, StatusCode::TOO_MANY_REQUESTS);
+    // The same address signed in, or with a key, is counted separately
+    let bearer = Some(format!("Bearer {}", token));
+    assert_eq!(call(bearer.clone()).await, StatusCode::OK);
+    assert_eq!(call(bearer).await, StatusCode::TOO_MANY_REQUESTS);
+    let api_key = Some(format!("ApiKey {}", secret));
+    assert_eq!(call(api_key.clone()).await, StatusCode::OK);
+    assert_eq!(call(api_key).await, StatusCode::TOO_MANY_REQUESTS);
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_memory_store_refills_buckets() {
+    let store = MemoryStore::new();
+    // Refills a token every 100 milliseconds
+    let limit = RateLimit {
+        burst: 2,
+        per_minute: 600,
+    };
+
+    let first = store.acquire("client", limit).await.unwrap();
+    assert!(first.allowed);
+    assert_eq!(first.remaining, 1);
+    assert!(store.acquire("client", limit).await.unwrap().allowed);
+    let denied = store.acquire("client", limit).await.unwrap();
+    assert!(!denied.allowed);
+    assert_eq!(denied.remaining, 0);
+    assert_eq!(denied.retry_after, Some(1));
+    assert!(store.acquire("other", limit).await.unwrap().allowed);
+
+    actix_rt::time::sleep(std::time::Duration::from_millis(150)).await;
+    let refilled = store.acquire("client", limit).await.unwrap();
+    assert!(refilled.allowed);
+    assert_eq!(refilled.remaining, 0);
+}
+
+#[actix_rt::test]
+async fn test_postgres_store_shares_buckets_between_instances() {
+    let pool = test_pool();
+    let key = format!("test:{}", Uuid::new_v4());
+    let limit = RateLimit {
+        burst: 2,
+        per_minute: 1,
+    };
+    let instance = PostgresStore::new(pool.clone());
+    let other_instance = PostgresStore::new(pool.clone());
+
+    let first = instance.acquire(&key, limit).await.unwrap();
+    assert!(first.allowed);
+    assert_eq!(first.remaining, 1);
+    assert!(other_instance.acquire(&key, limit).await.unwrap().allowed);
+    let denied = instance.acquire(&key, limit).await.unwrap();
+    assert!(!denied.allowed);
+    assert_eq!(denied.remaining, 0);
+    assert!(matches!(denied.retry_after, Some(seconds) if seconds > 0 && seconds <= 60));
+
+    // Buckets that are not full yet are kept by the cleanup
+    let conn = &mut pool.get().unwrap();
+    rust_backend::rate_limit::delete_full_buckets(conn).unwrap();
+    let bucket = rate_limit_buckets::table.find(&key);
+    assert_eq!(bucket.count().get_result::<i64>(conn).unwrap(), 1);
+    diesel::delete(bucket).execute(conn).unwrap();
+}
@@ -0,0 +1,95 @@
+// tests/reference_cache_tests.rs
+
+mod utils;
+
+use diesel::prelude::*;
+use rust_backend::diesel_schema::parts::manufacturers;
+use rust_backend::graphql_schema::context::Context;
+use rust_backend::reference_cache::ReferenceCache;
+use std::sync::Arc;
+use std::time::Duration;
+use utils::{sync_ids, test_pool, DbPool};
+use uuid::Uuid;
+
+/// Inserts a manufacturer with a unique name and returns its ID.
+fn insert_manufacturer(pool: &DbPool) -> i32 {
+    let mut conn = pool.get().expect("Failed to get connection");
+    sync_ids(&mut conn, "manufacturers");
+    diesel::insert_into(manufacturers::table)
+        .values(manufacturers::name.eq(format!("Cache Test {}", Uuid::new_v4())))
+        .returning(manufacturers::id)
+        .get_result(&mut conn)
+        .expect("Failed to insert manufacturer")
+}
+
+fn rename_manufacturer(pool: &DbPool, manufacturer_id: i32, new_name: &str) {
+    let mut conn = pool.get().expect("Failed to get connection");
+    diesel::update(manufacturers::table.find(manufacturer_id))
+        .set(manufacturers::name.eq(new_name))
+        .execute(&mut conn)
+        .expect("Failed to rename manufacturer");
+}
+
+fn delete_manufacturer(pool: &DbPool, manufacturer_id: i32) {
+    let mut conn = pool.get().expect("Failed to get connection");
+    diesel::delete(manufacturers::table.find(manufacturer_id))
+        .execute(&mut conn)
+        .expect("Failed to delete manufacturer");
+}
+
+#[test]
+fn test_manufacturers_served_from_cache_until_invalidated() {
+    let pool = test_pool();
+    let manufacturer_id = insert_manufacturer(&pool);
+    let cache = ReferenceCache::new(pool.clone(), Duration::from_secs(300));
+
+    let cached = cache.manufacturer(manufacturer_id).unwrap().unwrap();
+    let new_name = format!("Renamed {}", Uuid::new_v4());
+    rename_manufacturer(&pool, manufacturer_id, &new_name);
+
+    // The snapshot is reused until it is invalidated
+    let stale = cache.manufacturer(manufacturer_id).unwrap().unwrap();
+    assert_eq!(stale.name, cached.name);
+
+    cache.invalidate_manufacturers();
+    let fresh = cache.manufacturer(manufacturer_id).unwrap().unwrap();
+    assert_eq!(fresh.name, new_name);
+
+    delete_manufacturer(&pool, manufacturer_id);
+}
+
+#[test]
+fn test_expired_snapshots_are_reloaded() {
+    let pool = test_pool();
+    let cache = ReferenceCache::new(pool.clone(), Duration::ZERO);
+    assert!(cache.category(-1).unwrap().is_none());
+
+    let manufacturer_id = insert_manufacturer(&pool);
+    let new_name = format!("Renamed {}", Uuid::new_v4());
+    cache.manufacturer(manufacturer_id).unwrap().unwrap();
+    rename_manufacturer(&pool, manufacturer_id, &new_name);
+    assert_eq!(
+        cache.manufacturer(manufacturer_id).unwrap().unwrap().name,
+        new_name
+    );
+
+    delete_manufacturer(&pool, manufacturer_id);
+}
+
+#[test]
+fn test_context_falls_back_to_database_for_new_rows() {
+    let pool = test_pool();
+    let cache = Arc::new(ReferenceCache::new(pool.clone(), Duration::from_secs(300)));
+    let context = Context::new(pool.clone(), None).with_reference_cache(Some(cache.clone()));
+
+    // Load the snapshot before the manufacturer exists
+    cache.manufacturer(-1).unwrap();
+    let manufacturer_id = insert_manufacturer(&pool);
+
+    let manufacturer = context
+        .get_manufacturer_by_id(manufacturer_id)
+        .expect("New manufacturers should be fetched from the database");
+    assert_eq!(manufacturer.id, manufacturer_id);
+
+    delete_manufacturer(&pool, manufacturer_id);
+}
@@ -0,0 +1,67 @@
+// tests/request_id_tests.rs
+
+use actix_web::{test, web, App, HttpResponse};
+use rust_backend::logger;
+use rust_backend::middleware::request_id::{RequestId, REQUEST_ID_HEADER};
+
+/// Echoes the request ID visible to code running inside the request.
+async fn echo_request_id() -> HttpResponse {
+    HttpResponse::Ok().body(logger::current_request_id().unwrap_or_default())
+}
+
+#[actix_rt::test]
+async fn test_incoming_request_id_is_propagated() {
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestId)
+            .route("/", web::get().to(echo_request_id)),
+    )
+    .await;
+
+    let request = test::TestRequest::get()
+        .uri("/")
+        .insert_header((REQUEST_ID_HEADER, "abc-123"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+
+    assert_eq!(
+        response.headers().get(REQUEST_ID_HEADER).unwrap(),
+        "abc-123"
+    );
+    let body = test::read_body(response).await;
+    assert_eq!(body, "abc-123");
+}
+
+#[actix_rt::test]
+async fn test_request_id_is_generated() {
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestId)
+            .route("/", web::get().to(echo_request_id)),
+    )
+    .await;
+
+    // Header values with spaces or control characters are replaced
+    let request = test::TestRequest::get()
+        .uri("/")
+        .insert_header((REQUEST_ID_HEADER, "not a valid id"))
+        .to_request();
+    let response = test::call_service(&app, request).await;
+
+    let header = response
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .expect("Response should carry a request ID")
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(
+        header.len(),
+        36,
+        "Generated ID should be a UUID: {}",
+        header
+    );
+
+    let body = test::read_body(response).await;
+    assert_eq!(body, header.as_bytes());
+}
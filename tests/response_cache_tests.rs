@@ -0,0 +1,144 @@
+// tests/response_cache_tests.rs
+
+#[macro_use]
+mod utils;
+
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{call_service, read_body_json, TestRequest};
+use diesel::pg::PgConnection;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::{Connection, RunQueryDsl};
+use diesel_migrations::MigrationHarness;
+use dotenv::dotenv;
+use rust_backend::migration::MIGRATIONS;
+use rust_backend::response_cache::{normalize_query, CacheScope, ResponseCache};
+use rust_backend::settings::ResponseCacheSettings;
+use serde_json::{json, Value};
+use std::env;
+
+/// Applies pending migrations so the `catalog_revision` table and triggers exist.
+fn run_migrations() -> String {
+    dotenv().ok();
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    let mut conn =
+        PgConnection::establish(&database_url).expect("Failed to connect to test database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    database_url
+}
+
+#[test]
+fn test_normalize_query() {
+    assert_eq!(
+        normalize_query("query  Parts {\n  parts(limit: 10, offset: 0) { id } # trailing\n}"),
+        "query Parts { parts(limit: 10 offset: 0) { id } }"
+    );
+    // String literals are left untouched
+    assert_eq!(
+        normalize_query(r#"{ part(name: "a,  # b") { id } }"#),
+        r#"{ part(name: "a,  # b") { id } }"#
+    );
+    assert_eq!(
+        normalize_query("{ a(s: \"\"\"x,  \"y\"\"\"\") }"),
+        "{ a(s: \"\"\"x,  \"y\"\"\"\") }"
+    );
+}
+
+#[test]
+fn test_cache_keys() {
+    let key = |query, variables: &Value, scope| ResponseCache::key(query, None, variables, scope);
+
+    // Formatting and absent variables do not change the key
+    assert_eq!(
+        key("{ parts { id } }", &Value::Null, CacheScope::Public),
+        key(
+            "{\n  parts {\n    id\n  }\n}",
+            &json!({}),
+            CacheScope::Public
+        )
+    );
+    assert_ne!(
+        key("{ parts { id } }", &json!({ "a": 1 }), CacheScope::Public),
+        key("{ parts { id } }", &json!({ "a": 2 }), CacheScope::Public)
+    );
+    assert_ne!(
+        key("{ parts { id } }", &Value::Null, CacheScope::Public),
+        key("{ parts { id } }", &Value::Null, CacheScope::User(1))
+    );
+}
+
+#[actix_rt::test]
+async fn test_etag_and_not_modified() {
+    run_migrations();
+    let app = setup_test_app!();
+
+    let request = TestRequest::get()
+        .uri("/graphql?query=%7B%20apiVersion%20%7D")
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let etag = response
+        .headers()
+        .get(header::ETAG)
+        .expect("cached responses should carry an ETag")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let cache_control = response.headers().get(header::CACHE_CONTROL).unwrap();
+    assert!(cache_control
+        .to_str()
+        .unwrap()
+        .starts_with("public, max-age="));
+    let body: Value = read_body_json(response).await;
+    assert_eq!(body, json!({ "data": { "apiVersion": "1.0" } }));
+
+    // The same query, formatted differently, is answered from the cache
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .insert_header((header::IF_NONE_MATCH, format!("W/{}", etag)))
+        .set_json(json!({ "query": "{\n  apiVersion\n}" }))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(response.headers().get(header::ETAG).unwrap(), etag.as_str());
+
+    // Responses with errors are not cached
+    let request = TestRequest::post()
+        .uri("/graphql")
+        .set_json(json!({ "query": "{ unknownField }" }))
+        .to_request();
+    let response = call_service(&app, request).await;
+    assert!(response.headers().get(header::ETAG).is_none());
+    assert_eq!(
+        response.headers().get(header::CACHE_CONTROL).unwrap(),
+        "no-store"
+    );
+}
+
+#[test]
+fn test_catalog_changes_invalidate_cache() {
+    let database_url = run_migrations();
+    let manager = ConnectionManager::<PgConnection>::new(database_url);
+    let pool = Pool::builder()
+        .max_size(2)
+        .build(manager)
+        .expect("Failed to create pool.");
+    let cache = ResponseCache::new(pool.clone(), ResponseCacheSettings::default());
+
+    // Nothing is cached until the catalog revision is known
+    let key = ResponseCache::key("{ parts { id } }", None, &Value::Null, CacheScope::Public);
+    assert!(cache.insert(key.clone(), json!({})).is_none());
+
+    let revision = cache.refresh_revision().expect("Failed to read revision");
+    cache.insert(key.clone(), json!({ "data": { "parts": [] } }));
+    assert!(cache.get(&key).is_some());
+
+    // Statement-level triggers bump the revision even when no rows change
+    let mut conn = pool.get().expect("Failed to get connection");
+    diesel::sql_query("UPDATE manufacturers SET name = name WHERE false")
+        .execute(&mut conn)
+        .expect("Failed to update manufacturers");
+
+    assert!(cache.refresh_revision().unwrap() > revision);
+    assert!(cache.get(&key).is_none());
+}
@@ -0,0 +1,282 @@
+// tests/sessions_tests.rs
+
+mod utils;
+
+use actix_web::cookie::Cookie;
+use actix_web::http::header;
+use actix_web::test;
+use chrono::{Duration, Utc};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::users::{sessions, verification_tokens};
+use rust_backend::graphql_schema::context::Context;
+use rust_backend::models::auth::{Session, User};
+use rust_backend::sessions::{self as user_sessions, ClientInfo};
+use rust_backend::verification;
+use serde_json::{json, Value};
+use utils::{cleanup, error_code, execute_in, insert_user, test_pool, DbPool};
+use uuid::Uuid;
+
+/// Inserts a session for `user` that expires after `ttl`.
+fn insert_session(conn: &mut PgConnection, user: &User, ttl: Duration) -> Session {
+    diesel::insert_into(sessions::table)
+        .values((
+            sessions::userId.eq(user.id),
+            sessions::sessionToken.eq(Uuid::new_v4().simple().to_string()),
+            sessions::expires.eq(Utc::now() + ttl),
+            sessions::user_agent.eq("SessionsTest/1.0"),
+        ))
+        .returning(Session::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert session")
+}
+
+/// Executes a GraphQL operation as `user`, made with session `session_id`.
+async fn execute(
+    pool: &DbPool,
+    user: Option<&User>,
+    session_id: Option<i32>,
+    query: &str,
+    variables: Value,
+) -> Value {
+    let context = Context::new(pool.clone(), user.cloned()).with_session(session_id);
+    execute_in(&context, query, variables).await
+}
+
+const MY_SESSIONS: &str = "{ mySessions { id current userAgent lastSeenAt } }";
+
+#[actix_rt::test]
+async fn test_session_token_authenticates_requests() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let session = insert_session(conn, &user, Duration::days(1));
+    let expired = insert_session(conn, &user, Duration::minutes(-1));
+    let app = setup_test_app!();
+    let query = json!({ "query": "{ me { id } }" });
+
+    let req = test::TestRequest::post()
+        .uri("/graphql")
+        .insert_header((
+            header::AUTHORIZATION,
+            format!("Bearer {}", session.session_token),
+        ))
+        .set_json(&query)
+        .to_request();
+    let response: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(response["data"]["me"]["id"], user.id);
+
+    let req = test::TestRequest::post()
+        .uri("/graphql")
+        .cookie(Cookie::new(
+            "next-auth.session-token",
+            session.session_token.clone(),
+        ))
+        .set_json(&query)
+        .to_request();
+    let response: Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(response["data"]["me"]["id"], user.id);
+
+    // Expired and unknown tokens leave the request anonymous
+    for token in [expired.session_token.as_str(), "not-a-session"] {
+        let req = test::TestRequest::post()
+            .uri("/graphql")
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+            .set_json(&query)
+            .to_request();
+        let response: Value = test::call_and_read_body_json(&app, req).await;
+        assert!(response["data"]["me"].is_null());
+    }
+
+    // The session the request was made with is flagged and its use recorded
+    let req = test::TestRequest::post()
+        .uri("/graphql")
+        .insert_header((
+            header::AUTHORIZATION,
+            format!("Bearer {}", session.session_token),
+        ))
+        .set_json(json!({ "query": MY_SESSIONS }))
+        .to_request();
+    let response: Value = test::call_and_read_body_json(&app, req).await;
+    let listed = response["data"]["mySessions"].as_array().unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], session.id);
+    assert_eq!(listed[0]["current"], true);
+    assert_eq!(listed[0]["userAgent"], "SessionsTest/1.0");
+    assert!(listed[0]["lastSeenAt"].is_string());
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_revoke_sessions() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let other_user = insert_user(conn, "customer");
+    let current = insert_session(conn, &user, Duration::days(1));
+    let laptop = insert_session(conn, &user, Duration::days(1));
+    let phone = insert_session(conn, &user, Duration::days(1));
+    let foreign = insert_session(conn, &other_user, Duration::days(1));
+    let revoke = "mutation ($id: Int!) { revokeSession(id: $id) { id current } }";
+    let revoke_others = "mutation { revokeAllOtherSessions { id current } }";
+
+    let response = execute(&pool, None, None, MY_SESSIONS, json!({})).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    // Sessions of other users cannot be revoked
+    let variables = json!({ "id": foreign.id });
+    let response = execute(&pool, Some(&user), Some(current.id), revoke, variables).await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let variables = json!({ "id": laptop.id });
+    let response = execute(&pool, Some(&user), Some(current.id), revoke, variables).await;
+    let remaining = response["data"]["revokeSession"].as_array().unwrap();
+    assert_eq!(remaining.len(), 2);
+    assert!(remaining.iter().all(|s| s["id"] != laptop.id));
+
+    // Without a session there is nothing to keep
+    let response = execute(&pool, Some(&user), None, revoke_others, json!({})).await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        Some(current.id),
+        revoke_others,
+        json!({}),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["revokeAllOtherSessions"],
+        json!([{ "id": current.id, "current": true }])
+    );
+    let phone_left = sessions::table
+        .find(phone.id)
+        .count()
+        .get_result::<i64>(conn)
+        .unwrap();
+    assert_eq!(phone_left, 0);
+    let foreign_left = sessions::table
+        .find(foreign.id)
+        .count()
+        .get_result::<i64>(conn)
+        .unwrap();
+    assert_eq!(foreign_left, 1);
+
+    cleanup(conn, &[user.id, other_user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_delete_expired_sessions_and_tokens() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let valid = insert_session(conn, &user, Duration::days(1));
+    let expired = insert_session(conn, &user, Duration::minutes(-1));
+    let identifier = format!("sign-in:{}", user.email.as_ref().unwrap());
+    diesel::insert_into(verification_tokens::table)
+        .values((
+            verification_tokens::identifier.eq(&identifier),
+            verification_tokens::token.eq(Uuid::new_v4().simple().to_string()),
+            verification_tokens::expires.eq(Utc::now() - Duration::minutes(1)),
+        ))
+        .execute(conn)
+        .unwrap();
+
+    let (deleted_sessions, deleted_tokens) = user_sessions::delete_expired(conn).unwrap();
+    assert!(deleted_sessions >= 1);
+    assert!(deleted_tokens >= 1);
+    let left = sessions::table
+        .filter(sessions::userId.eq(user.id))
+        .select(sessions::id)
+        .load::<i32>(conn)
+        .unwrap();
+    assert_eq!(left, vec![valid.id]);
+    assert_ne!(left[0], expired.id);
+    let tokens_left = verification_tokens::table
+        .filter(verification_tokens::identifier.eq(&identifier))
+        .count()
+        .get_result::<i64>(conn)
+        .unwrap();
+    assert_eq!(tokens_left, 0);
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_sign_in_records_client() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let email = user.email.clone().unwrap();
+    let (_, token) =
+        verification::request_sign_in(conn, &email, std::time::Duration::from_secs(60))
+            .unwrap()
+            .unwrap();
+    let client = ClientInfo {
+        user_agent: Some("Mozilla/5.0".to_string()),
+        ip_address: Some("203.0.113.7".to_string()),
+    };
+
+    let (_, session) = verification::sign_in(conn, &email, &token, &client).unwrap();
+    assert_eq!(session.user_agent.as_deref(), Some("Mozilla/5.0"));
+    assert_eq!(session.ip_address.as_deref(), Some("203.0.113.7"));
+    assert!(session.created_at <= Utc::now());
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_sessions_without_client_record_first_use() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    // NextAuth creates sessions without recording the client
+    let session = diesel::insert_into(sessions::table)
+        .values((
+            sessions::userId.eq(user.id),
+            sessions::sessionToken.eq(Uuid::new_v4().simple().to_string()),
+            sessions::expires.eq(Utc::now() + Duration::days(1)),
+        ))
+        .returning(Session::as_returning())
+        .get_result(conn)
+        .expect("Failed to insert session");
+    let app = setup_test_app!();
+
+    for user_agent in ["FirstClient/1.0", "SecondClient/2.0"] {
+        let req = test::TestRequest::post()
+            .uri("/graphql")
+            .peer_addr("198.51.100.9:40000".parse().unwrap())
+            .insert_header((header::USER_AGENT, user_agent))
+            .insert_header((
+                header::AUTHORIZATION,
+                format!("Bearer {}", session.session_token),
+            ))
+            .set_json(json!({ "query": "{ mySessions { userAgent ipAddress } }" }))
+            .to_request();
+        let response: Value = test::call_and_read_body_json(&app, req).await;
+        let listed = &response["data"]["mySessions"][0];
+        // Only the first client is recorded
+        assert_eq!(listed["userAgent"], "FirstClient/1.0");
+        assert_eq!(listed["ipAddress"], "198.51.100.9");
+    }
+
+    cleanup(conn, &[user.id], &[], &[]);
+}
+
+#[actix_rt::test]
+async fn test_client_info_trusts_proxy_headers_only_when_configured() {
+    let req = test::TestRequest::default()
+        .peer_addr("198.51.100.4:40000".parse().unwrap())
+        .insert_header(("X-Forwarded-For", "203.0.113.7"))
+        .insert_header((header::USER_AGENT, "Mozilla/5.0"))
+        .to_http_request();
+
+    let client = ClientInfo::from_request(&req, false);
+    assert_eq!(client.ip_address.as_deref(), Some("198.51.100.4"));
+    assert_eq!(client.user_agent.as_deref(), Some("Mozilla/5.0"));
+
+    let client = ClientInfo::from_request(&req, true);
+    assert_eq!(client.ip_address.as_deref(), Some("203.0.113.7"));
+}
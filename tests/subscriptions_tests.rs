@@ -0,0 +1,484 @@
+// tests/subscriptions_tests.rs
+
+mod utils;
+
+use actix_codec::Framed;
+use actix_http::ws::{Codec, Frame, Message};
+use actix_web::{guard, web, App, HttpServer};
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::sql_types::Integer;
+use diesel_migrations::MigrationHarness;
+use dotenv::dotenv;
+use futures::{SinkExt, StreamExt};
+use rust_backend::graphql_handler::graphql_handler;
+use rust_backend::graphql_schema::{context::Context, schema::create_schema};
+use rust_backend::graphql_ws::{graphql_ws_handler, is_websocket_upgrade};
+use rust_backend::migration::MIGRATIONS;
+use rust_backend::notifications::{
+    spawn_listener, ChangeEvent, ConfigurationAction, NotificationHub, PartPriceChanged,
+    PartStockChanged,
+};
+use rust_backend::persisted_queries::PersistedQueryStore;
+use rust_backend::rate_limit::RateLimiter;
+use rust_backend::response_cache::ResponseCache;
+use rust_backend::settings::{
+    GraphQLSettings, PersistedQuerySettings, RateLimit, RateLimitSettings, ResponseCacheSettings,
+    SubscriptionSettings,
+};
+use serde_json::{json, Value};
+use std::env;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use utils::sync_ids;
+
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn database_url() -> String {
+    dotenv().ok();
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    let mut conn =
+        PgConnection::establish(&database_url).expect("Failed to connect to test database");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    database_url
+}
+
+/// Starts a server on a random port whose subscriptions are fed by `hub`.
+fn start_server(hub: Arc<NotificationHub>) -> SocketAddr {
+    start_server_with(
+        hub,
+        RateLimitSettings::default(),
+        SubscriptionSettings::default(),
+    )
+}
+
+/// Starts a server like [`start_server`] with the given rate limit and
+/// subscription settings.
+fn start_server_with(
+    hub: Arc<NotificationHub>,
+    rate_limits: RateLimitSettings,
+    subscriptions: SubscriptionSettings,
+) -> SocketAddr {
+    let manager = ConnectionManager::<PgConnection>::new(database_url());
+    let pool = Pool::builder()
+        .max_size(4)
+        .build(manager)
+        .expect("Failed to create pool.");
+    let schema = web::Data::new(Arc::new(create_schema()));
+    let context = web::Data::new(Context::new(pool.clone(), None).with_notifications(Some(hub)));
+    let persisted_query_store = web::Data::new(PersistedQueryStore::new(pool.clone(), 10));
+    let rate_limiter = web::Data::new(RateLimiter::from_settings(&rate_limits, pool.clone()));
+    let response_cache = web::Data::new(ResponseCache::new(pool, ResponseCacheSettings::default()));
+    let subscriptions = web::Data::new(subscriptions);
+
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(schema.clone())
+            .app_data(context.clone())
+            .app_data(web::Data::new(GraphQLSettings::default()))
+            .app_data(persisted_query_store.clone())
+            .app_data(web::Data::new(PersistedQuerySettings::default()))
+            .app_data(response_cache.clone())
+            .app_data(rate_limiter.clone())
+            .app_data(subscriptions.clone())
+            .service(
+                web::resource("/graphql")
+                    .route(web::post().to(graphql_handler))
+                    .route(
+                        web::get()
+                            .guard(guard::fn_guard(is_websocket_upgrade))
+                            .to(graphql_ws_handler),
+                    ),
+            )
+    })
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .expect("Failed to bind test server");
+    let addr = server.addrs()[0];
+    actix_rt::spawn(server.run());
+    addr
+}
+
+/// Minimal WebSocket client speaking one GraphQL subprotocol.
+struct WsClient {
+    framed: Framed<TcpStream, Codec>,
+}
+
+impl WsClient {
+    async fn connect(addr: SocketAddr, protocol: &str) -> Self {
+        let mut stream = TcpStream::connect(addr).await.expect("Failed to connect");
+        let request = format!(
+            "GET /graphql HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\
+             Sec-WebSocket-Protocol: {}\r\n\r\n",
+            addr, protocol
+        );
+        stream.write_all(request.as_bytes()).await.unwrap();
+
+        // Read the handshake response byte by byte so no frame data is consumed
+        let mut head = Vec::new();
+        while !head.ends_with(b"\r\n\r\n") {
+            head.push(stream.read_u8().await.expect("Handshake failed"));
+        }
+        let head = String::from_utf8(head).unwrap();
+        assert!(head.starts_with("HTTP/1.1 101"), "{}", head);
+        assert!(head
+            .to_ascii_lowercase()
+            .contains(&format!("sec-websocket-protocol: {}", protocol)));
+
+        WsClient {
+            framed: Framed::new(stream, Codec::new().client_mode()),
+        }
+    }
+
+    async fn send(&mut self, message: Value) {
+        self.framed
+            .send(Message::Text(message.to_string().into()))
+            .await
+            .expect("Failed to send message");
+    }
+
+    /// Receives the next frame, skipping keep-alive messages.
+    async fn receive(&mut self) -> Frame {
+        loop {
+            let frame = timeout(RECEIVE_TIMEOUT, self.framed.next())
+                .await
+                .expect("Timed out waiting for a message")
+                .expect("Connection closed")
+                .expect("Invalid frame");
+            if let Frame::Text(bytes) = &frame {
+                let message: Value = serde_json::from_slice(bytes).unwrap();
+                if message["type"] == "ka" || message["type"] == "ping" {
+                    continue;
+                }
+            }
+            return frame;
+        }
+    }
+
+    async fn receive_json(&mut self) -> Value {
+        match self.receive().await {
+            Frame::Text(bytes) => serde_json::from_slice(&bytes).unwrap(),
+            frame => panic!("Expected a text frame, got {:?}", frame),
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_query_over_graphql_transport_ws() {
+    let addr = start_server(Arc::new(NotificationHub::new(16)));
+    let mut client = WsClient::connect(addr, "graphql-transport-ws").await;
+
+    client.send(json!({ "type": "connection_init" })).await;
+    assert_eq!(client.receive_json().await["type"], "connection_ack");
+
+    client
+        .send(json!({ "type": "ping", "payload": { "n": 1 } }))
+        .await;
+    assert_eq!(
+        client.receive_json().await,
+        json!({ "type": "pong", "payload": { "n": 1 } })
+    );
+
+    client
+        .send(json!({ "id": "1", "type": "subscribe", "payload": { "query": "{ apiVersion }" } }))
+        .await;
+    assert_eq!(
+        client.receive_json().await,
+        json!({ "id": "1", "type": "next", "payload": { "data": { "apiVersion": "1.0" } } })
+    );
+    assert_eq!(
+        client.receive_json().await,
+        json!({ "id": "1", "type": "complete" })
+    );
+}
+
+#[actix_rt::test]
+async fn test_operation_limits_apply_over_websocket() {
+    let mut rate_limits = RateLimitSettings::default();
+    rate_limits.operations.insert(
+        "apiVersion".to_string(),
+        RateLimit {
+            burst: 1,
+            per_minute: 1,
+        },
+    );
+    let addr = start_server_with(
+        Arc::new(NotificationHub::new(16)),
+        rate_limits,
+        SubscriptionSettings::default(),
+    );
+    let mut client = WsClient::connect(addr, "graphql-transport-ws").await;
+    client.send(json!({ "type": "connection_init" })).await;
+    assert_eq!(client.receive_json().await["type"], "connection_ack");
+
+    let subscribe = |id: &str| json!({ "id": id, "type": "subscribe", "payload": { "query": "{ apiVersion }" } });
+    client.send(subscribe("1")).await;
+    assert_eq!(client.receive_json().await["type"], "next");
+    assert_eq!(client.receive_json().await["type"], "complete");
+
+    client.send(subscribe("2")).await;
+    let message = client.receive_json().await;
+    assert_eq!(message["type"], "error", "Unexpected {}", message);
+    assert_eq!(message["id"], "2");
+    assert_eq!(message["payload"][0]["extensions"]["code"], "RATE_LIMITED");
+}
+
+#[actix_rt::test]
+async fn test_operations_per_connection_are_capped() {
+    let subscriptions = SubscriptionSettings {
+        max_operations: 1,
+        ..SubscriptionSettings::default()
+    };
+    let addr = start_server_with(
+        Arc::new(NotificationHub::new(16)),
+        RateLimitSettings::default(),
+        subscriptions,
+    );
+    let mut client = WsClient::connect(addr, "graphql-transport-ws").await;
+    client.send(json!({ "type": "connection_init" })).await;
+    assert_eq!(client.receive_json().await["type"], "connection_ack");
+
+    client
+        .send(json!({
+            "id": "prices",
+            "type": "subscribe",
+            "payload": { "query": "subscription { partPriceChanged(partId: 7) { partId } }" }
+        }))
+        .await;
+    let subscribe = |id: &str| json!({ "id": id, "type": "subscribe", "payload": { "query": "{ apiVersion }" } });
+    client.send(subscribe("1")).await;
+    let message = client.receive_json().await;
+    assert_eq!(message["type"], "error", "Unexpected {}", message);
+    assert_eq!(message["id"], "1");
+    assert_eq!(
+        message["payload"][0]["message"],
+        "Too many operations on this connection"
+    );
+
+    // Completing the subscription frees its slot
+    client
+        .send(json!({ "id": "prices", "type": "complete" }))
+        .await;
+    client.send(subscribe("2")).await;
+    let message = client.receive_json().await;
+    assert_eq!(message["type"], "next", "Unexpected {}", message);
+    assert_eq!(message["id"], "2");
+}
+
+#[actix_rt::test]
+async fn test_subscribe_before_init_closes_connection() {
+    let addr = start_server(Arc::new(NotificationHub::new(16)));
+    let mut client = WsClient::connect(addr, "graphql-transport-ws").await;
+
+    client
+        .send(json!({ "id": "1", "type": "subscribe", "payload": { "query": "{ apiVersion }" } }))
+        .await;
+    match client.receive().await {
+        Frame::Close(Some(reason)) => assert_eq!(u16::from(reason.code), 4401),
+        frame => panic!("Expected a close frame, got {:?}", frame),
+    }
+}
+
+#[actix_rt::test]
+async fn test_part_price_subscription_over_legacy_protocol() {
+    let hub = Arc::new(NotificationHub::new(16));
+    let addr = start_server(hub.clone());
+    let mut client = WsClient::connect(addr, "graphql-ws").await;
+
+    client.send(json!({ "type": "connection_init" })).await;
+    assert_eq!(client.receive_json().await["type"], "connection_ack");
+
+    let query =
+        "subscription ($id: Int!) { partPriceChanged(partId: $id) { partId oldPrice price } }";
+    client
+        .send(json!({
+            "id": "prices",
+            "type": "start",
+            "payload": { "query": query, "variables": { "id": 7 } }
+        }))
+        .await;
+
+    // Publish until the subscription has been registered and receives an event
+    let event = |part_id| {
+        ChangeEvent::PartPriceChanged(PartPriceChanged {
+            part_id,
+            old_price: Some("10.00".to_string()),
+            price: Some("12.50".to_string()),
+        })
+    };
+    let message = loop {
+        hub.publish(event(8));
+        hub.publish(event(7));
+        if let Ok(frame) = timeout(Duration::from_millis(100), client.receive()).await {
+            match frame {
+                Frame::Text(bytes) => break serde_json::from_slice::<Value>(&bytes).unwrap(),
+                frame => panic!("Expected a text frame, got {:?}", frame),
+            }
+        }
+    };
+    assert_eq!(
+        message,
+        json!({
+            "id": "prices",
+            "type": "data",
+            "payload": { "data": { "partPriceChanged": { "partId": 7, "oldPrice": "10.00", "price": "12.50" } } }
+        })
+    );
+
+    client.send(json!({ "id": "prices", "type": "stop" })).await;
+    loop {
+        let message = client.receive_json().await;
+        if message["type"] == "complete" {
+            assert_eq!(message["id"], "prices");
+            break;
+        }
+    }
+}
+
+#[actix_rt::test]
+async fn test_configuration_subscription_requires_existing_configuration() {
+    let addr = start_server(Arc::new(NotificationHub::new(16)));
+    let mut client = WsClient::connect(addr, "graphql-transport-ws").await;
+
+    client.send(json!({ "type": "connection_init" })).await;
+    assert_eq!(client.receive_json().await["type"], "connection_ack");
+
+    client
+        .send(json!({
+            "id": "1",
+            "type": "subscribe",
+            "payload": { "query": "subscription { configurationUpdated(id: -1) { configurationId action } }" }
+        }))
+        .await;
+    let message = client.receive_json().await;
+    assert_eq!(message["type"], "error");
+    assert_eq!(message["payload"][0]["message"], "Configuration not found");
+}
+
+#[derive(QueryableByName)]
+struct InsertedId {
+    #[diesel(sql_type = Integer)]
+    id: i32,
+}
+
+#[actix_rt::test]
+async fn test_database_triggers_publish_changes() {
+    let database_url = database_url();
+    let hub = Arc::new(NotificationHub::new(16));
+    spawn_listener(database_url.clone(), hub.clone()).expect("Failed to start listener");
+    for _ in 0..50 {
+        if hub.is_listening() {
+            break;
+        }
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert!(hub.is_listening(), "Listener did not connect");
+
+    let mut conn = PgConnection::establish(&database_url).unwrap();
+    sync_ids(&mut conn, "parts");
+    let part = diesel::sql_query(
+        "INSERT INTO parts (name, model, price) VALUES ('Notify Test', 'NT-1', 10.00) RETURNING id",
+    )
+    .get_result::<InsertedId>(&mut conn)
+    .unwrap();
+    let configuration =
+        diesel::sql_query("INSERT INTO configurations (name) VALUES ('Notify Test') RETURNING id")
+            .get_result::<InsertedId>(&mut conn)
+            .unwrap();
+
+    let mut prices = Box::pin(hub.part_price_changes(part.id));
+    let mut stock = Box::pin(hub.part_stock_changes(part.id));
+    let mut configurations = Box::pin(hub.configuration_updates(configuration.id));
+
+    diesel::sql_query(format!(
+        "UPDATE parts SET price = 12.50 WHERE id = {}",
+        part.id
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    let change = timeout(RECEIVE_TIMEOUT, prices.next())
+        .await
+        .expect("Timed out waiting for the price change")
+        .unwrap();
+    assert_eq!(change.old_price.as_deref(), Some("10.00"));
+    assert_eq!(change.price.as_deref(), Some("12.50"));
+
+    diesel::sql_query(format!(
+        "INSERT INTO inventory (part_id, quantity_on_hand, quantity_reserved) VALUES ({}, 5, 2)",
+        part.id
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    let change = timeout(RECEIVE_TIMEOUT, stock.next())
+        .await
+        .expect("Timed out waiting for the stock change")
+        .unwrap();
+    assert_eq!(change.available_quantity, 3);
+
+    diesel::sql_query(format!(
+        "INSERT INTO configuration_parts (configuration_id, part_id) VALUES ({}, {})",
+        configuration.id, part.id
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    let change = timeout(RECEIVE_TIMEOUT, configurations.next())
+        .await
+        .expect("Timed out waiting for the configuration change")
+        .unwrap();
+    assert_eq!(change.action, ConfigurationAction::Updated);
+
+    diesel::sql_query(format!(
+        "DELETE FROM configuration_parts WHERE configuration_id = {}",
+        configuration.id
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    diesel::sql_query(format!(
+        "DELETE FROM configurations WHERE id = {}",
+        configuration.id
+    ))
+    .execute(&mut conn)
+    .unwrap();
+    diesel::sql_query(format!("DELETE FROM parts WHERE id = {}", part.id))
+        .execute(&mut conn)
+        .unwrap();
+}
+
+#[test]
+fn test_parse_change_events() {
+    let event = ChangeEvent::parse(
+        "configuration_updated",
+        r#"{"configurationId": 3, "action": "DELETED"}"#,
+    )
+    .unwrap();
+    match event {
+        ChangeEvent::ConfigurationUpdated(change) => {
+            assert_eq!(change.configuration_id, 3);
+            assert_eq!(change.action, ConfigurationAction::Deleted);
+        }
+        other => panic!("Unexpected event {:?}", other),
+    }
+
+    let event = ChangeEvent::parse(
+        "part_stock_changed",
+        r#"{"partId": 4, "availableQuantity": 0}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        event,
+        ChangeEvent::PartStockChanged(PartStockChanged {
+            part_id: 4,
+            available_quantity: 0,
+        })
+    );
+
+    assert!(ChangeEvent::parse("part_price_changed", "not json").is_err());
+    assert!(ChangeEvent::parse("unknown", "{}").is_err());
+}
@@ -1,14 +1,232 @@
 // tests/utils.rs
 
+// Each test crate uses only some of these helpers
+#![allow(dead_code)]
+
+use bigdecimal::BigDecimal;
+use diesel::pg::PgConnection;
+use diesel::prelude::*;
+use diesel::r2d2::{ConnectionManager, Pool};
+use diesel_migrations::MigrationHarness;
+use dotenv::dotenv;
+use juniper::http::GraphQLRequest;
+use rust_backend::diesel_schema::configurations::{configuration_parts, configurations};
+use rust_backend::diesel_schema::orders::orders;
+use rust_backend::diesel_schema::parts::{
+    cpu_specs, gpu_specs, inventory, memory_specs, parts, storage_specs,
+};
+use rust_backend::diesel_schema::users::{accounts, sessions, users};
+use rust_backend::graphql_schema::context::Context;
+use rust_backend::graphql_schema::schema::create_schema;
+use rust_backend::migration::MIGRATIONS;
+use rust_backend::models::auth::User;
+use serde_json::Value;
+use std::env;
+use std::str::FromStr;
+use uuid::Uuid;
+
+pub type DbPool = Pool<ConnectionManager<PgConnection>>;
+
+/// Connects to `TEST_DATABASE_URL` and runs pending migrations.
+pub fn test_pool() -> DbPool {
+    dotenv().ok();
+    let database_url = env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must be set");
+    let manager = ConnectionManager::<PgConnection>::new(database_url);
+    let pool = Pool::builder()
+        .max_size(2)
+        .build(manager)
+        .expect("Failed to create pool.");
+    pool.get()
+        .expect("Failed to get connection")
+        .run_pending_migrations(MIGRATIONS)
+        .expect("Failed to run migrations");
+    pool
+}
+
+/// Inserts a user with a unique, unverified email address and the given role.
+pub fn insert_user(conn: &mut PgConnection, role: &str) -> User {
+    diesel::insert_into(users::table)
+        .values((
+            users::name.eq("Test User"),
+            users::email.eq(format!("{}@example.com", Uuid::new_v4())),
+            users::role.eq(role),
+        ))
+        .get_result::<User>(conn)
+        .expect("Failed to insert user")
+}
+
+/// Moves the ID sequence of `table` past its largest ID. The data import
+/// inserts explicit IDs, leaving the sequences behind.
+pub fn sync_ids(conn: &mut PgConnection, table: &str) {
+    diesel::sql_query(format!(
+        "SELECT setval(pg_get_serial_sequence('{0}', 'id'), \
+         GREATEST((SELECT MAX(id) FROM {0}), 1))",
+        table
+    ))
+    .execute(conn)
+    .unwrap_or_else(|e| panic!("Failed to sync {} IDs: {}", table, e));
+}
+
+/// Inserts a part priced at `price` and returns its ID.
+pub fn insert_part(conn: &mut PgConnection, price: &str) -> i32 {
+    sync_ids(conn, "parts");
+    diesel::insert_into(parts::table)
+        .values((
+            parts::name.eq(format!("Test Part {}", Uuid::new_v4())),
+            parts::model.eq("TP-1"),
+            parts::price.eq(Some(BigDecimal::from_str(price).unwrap())),
+        ))
+        .returning(parts::id)
+        .get_result(conn)
+        .expect("Failed to insert part")
+}
+
+/// Puts `on_hand` units of a part in stock.
+pub fn insert_inventory(conn: &mut PgConnection, part_id: i32, on_hand: i32) {
+    diesel::insert_into(inventory::table)
+        .values((
+            inventory::part_id.eq(part_id),
+            inventory::quantity_on_hand.eq(on_hand),
+        ))
+        .execute(conn)
+        .expect("Failed to insert inventory");
+}
+
+/// Inserts a configuration with the given `(part_id, quantity)` lines and
+/// returns its ID.
+pub fn insert_configuration(
+    conn: &mut PgConnection,
+    owner: Option<i32>,
+    lines: &[(i32, i32)],
+) -> i32 {
+    let configuration_id = diesel::insert_into(configurations::table)
+        .values((
+            configurations::name.eq("Test build"),
+            configurations::user_id.eq(owner),
+        ))
+        .returning(configurations::id)
+        .get_result(conn)
+        .expect("Failed to insert configuration");
+    for (part_id, quantity) in lines {
+        diesel::insert_into(configuration_parts::table)
+            .values((
+                configuration_parts::configuration_id.eq(configuration_id),
+                configuration_parts::part_id.eq(part_id),
+                configuration_parts::quantity.eq(quantity),
+            ))
+            .execute(conn)
+            .expect("Failed to insert configuration part");
+    }
+    configuration_id
+}
+
+/// Deletes the users with their orders, sessions, accounts and
+/// configurations, then the other configurations and the parts with their
+/// specs. Rows that cascade, such as wishlists and stock, go with them.
+pub fn cleanup(
+    conn: &mut PgConnection,
+    user_ids: &[i32],
+    configuration_ids: &[i32],
+    part_ids: &[i32],
+) {
+    diesel::delete(orders::table.filter(orders::user_id.eq_any(user_ids)))
+        .execute(conn)
+        .unwrap();
+    let mut configuration_ids = configuration_ids.to_vec();
+    configuration_ids.extend(
+        configurations::table
+            .filter(configurations::user_id.eq_any(user_ids))
+            .select(configurations::id)
+            .load::<i32>(conn)
+            .unwrap(),
+    );
+    diesel::delete(
+        configuration_parts::table
+            .filter(configuration_parts::configuration_id.eq_any(&configuration_ids)),
+    )
+    .execute(conn)
+    .unwrap();
+    diesel::delete(configurations::table.filter(configurations::id.eq_any(&configuration_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(sessions::table.filter(sessions::userId.eq_any(user_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(accounts::table.filter(accounts::userId.eq_any(user_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(users::table.filter(users::id.eq_any(user_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(cpu_specs::table.filter(cpu_specs::part_id.eq_any(part_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(gpu_specs::table.filter(gpu_specs::part_id.eq_any(part_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(memory_specs::table.filter(memory_specs::part_id.eq_any(part_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(storage_specs::table.filter(storage_specs::part_id.eq_any(part_ids)))
+        .execute(conn)
+        .unwrap();
+    diesel::delete(parts::table.filter(parts::id.eq_any(part_ids)))
+        .execute(conn)
+        .unwrap();
+}
+
+/// Executes a GraphQL operation as `user` and returns the JSON response.
+pub async fn execute(pool: &DbPool, user: Option<&User>, query: &str, variables: Value) -> Value {
+    execute_in(&Context::new(pool.clone(), user.cloned()), query, variables).await
+}
+
+/// Executes a GraphQL operation in `context` and returns the JSON response.
+pub async fn execute_in(context: &Context, query: &str, variables: Value) -> Value {
+    let schema = create_schema();
+    let request = GraphQLRequest::new(
+        query.to_string(),
+        None,
+        Some(serde_json::from_value(variables).unwrap()),
+    );
+    serde_json::to_value(request.execute(&schema, context).await).unwrap()
+}
+
+/// Returns the code of the first error in a GraphQL response.
+pub fn error_code(response: &Value) -> &str {
+    response["errors"][0]["extensions"]["code"]
+        .as_str()
+        .unwrap_or_else(|| panic!("Expected an error, got {}", response))
+}
+
 #[macro_export]
 macro_rules! setup_test_app {
-    () => {{
-        use actix_web::{test, web, App};
+    () => {
+        setup_test_app!(rust_backend::settings::GraphQLSettings::default())
+    };
+    ($graphql_settings:expr) => {
+        setup_test_app!(
+            $graphql_settings,
+            rust_backend::settings::RateLimitSettings::default()
+        )
+    };
+    ($graphql_settings:expr, $rate_limit_settings:expr) => {{
+        use actix_web::{guard, test, web, App};
         use diesel::r2d2::{ConnectionManager, Pool};
         use diesel::PgConnection;
         use dotenv::dotenv;
-        use rust_backend::graphql_handler::graphql_handler;
+        use rust_backend::graphql_handler::{graphql_get_handler, graphql_handler};
         use rust_backend::graphql_schema::{context::Context, schema::create_schema};
+        use rust_backend::graphql_ws::{graphql_ws_handler, is_websocket_upgrade};
+        use rust_backend::health_handler::{healthz, readyz, version};
+        use rust_backend::metrics::metrics_handler;
+        use rust_backend::middleware::auth::Authentication;
+        use rust_backend::middleware::rate_limit::RateLimiting;
+        use rust_backend::persisted_queries::PersistedQueryStore;
+        use rust_backend::rate_limit::RateLimiter;
+        use rust_backend::response_cache::ResponseCache;
+        use rust_backend::settings::{
+            PersistedQuerySettings, ResponseCacheSettings, SubscriptionSettings,
+        };
         use std::env;
         use std::sync::Arc;
 
@@ -19,7 +237,18 @@ macro_rules! setup_test_app {
         let pool = Pool::builder()
             .build(manager)
             .expect("Failed to create pool.");
-        let context = web::Data::new(Context::new(pool, None));
+        let persisted_query_store = web::Data::new(PersistedQueryStore::new(pool.clone(), 100));
+        // Caching stays disabled when the catalog revision table has not been migrated
+        let response_cache = web::Data::new(ResponseCache::new(
+            pool.clone(),
+            ResponseCacheSettings::default(),
+        ));
+        response_cache.refresh_revision().ok();
+        let context = web::Data::new(Context::new(pool.clone(), None));
+        let rate_limiter = web::Data::new(RateLimiter::from_settings(
+            &$rate_limit_settings,
+            pool.clone(),
+        ));
 
         // Create GraphQL schema
         let schema = Arc::new(create_schema());
@@ -27,13 +256,30 @@ macro_rules! setup_test_app {
         // Initialize the Actix Web application
         test::init_service(
             App::new()
+                .wrap(RateLimiting::new(rate_limiter.clone().into_inner()))
+                .wrap(Authentication::new(pool))
                 .app_data(web::Data::new(schema.clone()))
                 .app_data(context.clone())
+                .app_data(web::Data::new($graphql_settings))
+                .app_data(persisted_query_store)
+                .app_data(web::Data::new(PersistedQuerySettings::default()))
+                .app_data(response_cache)
+                .app_data(rate_limiter)
+                .app_data(web::Data::new(SubscriptionSettings::default()))
                 .service(
                     web::resource("/graphql")
-                        .guard(actix_web::guard::Post())
-                        .to(graphql_handler),
-                ),
+                        .route(web::post().to(graphql_handler))
+                        .route(
+                            web::get()
+                                .guard(guard::fn_guard(is_websocket_upgrade))
+                                .to(graphql_ws_handler),
+                        )
+                        .route(web::get().to(graphql_get_handler)),
+                )
+                .service(web::resource("/healthz").to(healthz))
+                .service(web::resource("/readyz").to(readyz))
+                .service(web::resource("/version").to(version))
+                .service(web::resource("/metrics").to(metrics_handler)),
         )
         .await
     }};
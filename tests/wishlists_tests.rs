@@ -0,0 +1,217 @@
+// tests/wishlists_tests.rs
+
+mod utils;
+
+use chrono::Utc;
+use diesel::prelude::*;
+use rust_backend::diesel_schema::parts::parts;
+use serde_json::json;
+use utils::{cleanup, error_code, execute, insert_part, insert_user, test_pool};
+
+const ADD: &str = "mutation ($partId: Int!, $wishlistId: Int, $name: String) {
+    addToWishlist(partId: $partId, wishlistId: $wishlistId, name: $name) {
+        id name items { part { id name } }
+    }
+}";
+
+const WISHLISTS: &str = "{ wishlists { id name items { part { id } } } }";
+
+#[actix_rt::test]
+async fn test_save_parts_to_named_wishlists() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let user = insert_user(conn, "customer");
+    let (cpu, gpu) = (insert_part(conn, "99.99"), insert_part(conn, "99.99"));
+
+    // Without a wishlist the part goes to the default one, created on demand
+    let response = execute(&pool, Some(&user), ADD, json!({ "partId": cpu })).await;
+    let default = &response["data"]["addToWishlist"];
+    assert_eq!(default["name"], "Wishlist", "Unexpected {}", response);
+    let default_id = default["id"].as_i64().unwrap();
+
+    // Saving the same part again leaves the list unchanged
+    let response = execute(
+        &pool,
+        Some(&user),
+        ADD,
+        json!({ "partId": cpu, "wishlistId": default_id }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["addToWishlist"]["items"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        ADD,
+        json!({ "partId": gpu, "name": "  Upgrades " }),
+    )
+    .await;
+    assert_eq!(response["data"]["addToWishlist"]["name"], "Upgrades");
+    let response = execute(
+        &pool,
+        Some(&user),
+        ADD,
+        json!({ "partId": cpu, "name": "Upgrades" }),
+    )
+    .await;
+    let upgrades = &response["data"]["addToWishlist"];
+    let upgrades_id = upgrades["id"].as_i64().unwrap();
+    // Most recently saved first
+    let saved = upgrades["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["part"]["id"].as_i64().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(saved, vec![cpu as i64, gpu as i64]);
+
+    let response = execute(&pool, Some(&user), WISHLISTS, json!({})).await;
+    let names = response["data"]["wishlists"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|list| list["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["Wishlist", "Upgrades"]);
+
+    let remove = "mutation ($wishlistId: Int!, $partId: Int!) {
+        removeFromWishlist(wishlistId: $wishlistId, partId: $partId) { items { part { id } } }
+    }";
+    let response = execute(
+        &pool,
+        Some(&user),
+        remove,
+        json!({ "wishlistId": upgrades_id, "partId": gpu }),
+    )
+    .await;
+    assert_eq!(
+        response["data"]["removeFromWishlist"]["items"],
+        json!([{ "part": { "id": cpu } }])
+    );
+
+    let response = execute(
+        &pool,
+        Some(&user),
+        "mutation ($id: Int!) { deleteWishlist(id: $id) }",
+        json!({ "id": default_id }),
+    )
+    .await;
+    assert_eq!(response["data"]["deleteWishlist"], true);
+    let response = execute(&pool, Some(&user), WISHLISTS, json!({})).await;
+    assert_eq!(response["data"]["wishlists"].as_array().unwrap().len(), 1);
+
+    // Both a wishlist and a name, or a blank name, are refused
+    let response = execute(
+        &pool,
+        Some(&user),
+        ADD,
+        json!({ "partId": cpu, "wishlistId": upgrades_id, "name": "Upgrades" }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+    let response = execute(
+        &pool,
+        Some(&user),
+        ADD,
+        json!({ "partId": cpu, "name": " " }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    cleanup(conn, &[user.id], &[], &[cpu, gpu]);
+}
+
+#[actix_rt::test]
+async fn test_wishlists_are_private_and_convert_to_configurations() {
+    let pool = test_pool();
+    let conn = &mut pool.get().unwrap();
+    let (owner, other) = (insert_user(conn, "customer"), insert_user(conn, "customer"));
+    let (cpu, gpu) = (insert_part(conn, "99.99"), insert_part(conn, "99.99"));
+
+    let response = execute(&pool, None, WISHLISTS, json!({})).await;
+    assert_eq!(error_code(&response), "UNAUTHENTICATED");
+
+    execute(&pool, Some(&owner), ADD, json!({ "partId": gpu })).await;
+    let response = execute(&pool, Some(&owner), ADD, json!({ "partId": cpu })).await;
+    let wishlist_id = response["data"]["addToWishlist"]["id"].as_i64().unwrap();
+
+    // Other users can neither see nor change the wishlist
+    let response = execute(&pool, Some(&other), WISHLISTS, json!({})).await;
+    assert_eq!(response["data"]["wishlists"], json!([]));
+    let response = execute(
+        &pool,
+        Some(&other),
+        ADD,
+        json!({ "partId": cpu, "wishlistId": wishlist_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    // Discontinued parts cannot be saved and are left out of configurations
+    diesel::update(parts::table.find(gpu))
+        .set(parts::deleted_at.eq(Some(Utc::now())))
+        .execute(conn)
+        .unwrap();
+    let response = execute(&pool, Some(&owner), ADD, json!({ "partId": gpu })).await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+
+    let convert = "mutation ($wishlistId: Int!, $name: String) {
+        createConfigurationFromWishlist(wishlistId: $wishlistId, name: $name) {
+            id userId name parts { part { id } quantity }
+        }
+    }";
+    let response = execute(
+        &pool,
+        Some(&other),
+        convert,
+        json!({ "wishlistId": wishlist_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "NOT_FOUND");
+    let response = execute(
+        &pool,
+        Some(&owner),
+        convert,
+        json!({ "wishlistId": wishlist_id, "name": "First build" }),
+    )
+    .await;
+    let configuration = &response["data"]["createConfigurationFromWishlist"];
+    assert_eq!(
+        configuration["name"], "First build",
+        "Unexpected {}",
+        response
+    );
+    assert_eq!(configuration["userId"], owner.id);
+    assert_eq!(
+        configuration["parts"],
+        json!([{ "part": { "id": cpu }, "quantity": 1 }])
+    );
+
+    // The wishlist is kept, and one without available parts cannot be converted
+    let remove = "mutation ($wishlistId: Int!, $partId: Int!) {
+        removeFromWishlist(wishlistId: $wishlistId, partId: $partId) { id }
+    }";
+    execute(
+        &pool,
+        Some(&owner),
+        remove,
+        json!({ "wishlistId": wishlist_id, "partId": cpu }),
+    )
+    .await;
+    let response = execute(
+        &pool,
+        Some(&owner),
+        convert,
+        json!({ "wishlistId": wishlist_id }),
+    )
+    .await;
+    assert_eq!(error_code(&response), "BAD_REQUEST");
+
+    cleanup(conn, &[owner.id, other.id], &[], &[cpu, gpu]);
+}